@@ -1,14 +1,557 @@
-#![feature(test)]
+//! Criterion benchmarks: `cargo bench`, or `cargo bench -- <filter>` to run only
+//! the benchmarks whose names match the regex `<filter>`.
 
-include!("../examples/counting_game.rs");
+extern crate criterion;
+extern crate mcts;
 
-extern crate test;
-use self::test::Bencher;
+#[path = "../tests/common/mod.rs"]
+mod common;
 
-#[bench]
-fn counting_game(b: &mut Bencher) {
-    b.iter(|| {
-        let mut mcts = MCTSManager::new(CountingGame(0), MyMCTS, MyEvaluator,UCTPolicy::new(1e6), ());
-        mcts.playout_n(100_000);
+use common::*;
+use criterion::{criterion_group, criterion_main, BatchSize, Bencher, Criterion};
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::time::Duration;
+
+// Expansion heavy: every playout adds a node to a single deep line. Only the
+// playouts are timed: the manager is made, and with `reserve` its nodes allocated,
+// before, and the tree is freed after.
+fn counting_game(b: &mut Bencher, reserve: bool, playouts: u32, threads: usize) {
+    b.iter_batched(
+        || {
+            let mut mcts = counting_manager(1e6);
+            if reserve {
+                mcts.reserve(playouts as u64);
+            }
+            mcts
+        },
+        |mut mcts| {
+            if threads == 1 {
+                mcts.playout_n(playouts as usize);
+            } else {
+                mcts.playout_n_parallel(playouts, threads);
+            }
+            mcts
+        },
+        BatchSize::PerIteration,
+    );
+}
+
+// CountingMCTS reading parent visits from the cache refreshed every `interval`
+// visits, or summing them with 0.
+#[derive(Default)]
+struct CachedCountingMCTS {
+    interval: usize,
+}
+
+impl MCTS for CachedCountingMCTS {
+    type State = CountingGame;
+    type Eval = CountingEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn virtual_loss(&self) -> f64 {
+        500.0
+    }
+    fn cached_visits_interval(&self) -> usize {
+        self.interval
+    }
+}
+
+// A low exploration constant sends most playouts far down the adding line.
+fn deep_counting_game(interval: usize, threads: usize) {
+    let mut mcts = MCTSManager::new(
+        CountingGame(0),
+        CachedCountingMCTS { interval },
+        CountingEvaluator,
+        UCTPolicy::new(10.0),
+        (),
+    );
+    mcts.playout_n_parallel(50_000, threads);
+}
+
+// Selection heavy: the 42 nodes of the game are all created within the first few
+// hundred playouts, so the rest only walk the tree.
+fn fill_game(threads: usize) {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(6, 3),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    if threads == 1 {
+        mcts.playout_n(20_000);
+    } else {
+        mcts.playout_n_parallel(20_000, threads);
+    }
+}
+
+// Per-move overhead: a 1ms search on 4 workers, whose time past 1ms is spent
+// starting and stopping them.
+fn one_millisecond<S: Spawner>(spawner: &S) {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(6, 3),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    mcts.playout_parallel_for_on(Duration::from_millis(1), 4, spawner);
+}
+
+// Evaluator heavy: every new state is scored by 16 random fills to the end.
+struct RolloutEvaluator;
+
+fn rollouts<R: SelectionRng>(state: &FillGame, rng: &mut R) -> f64 {
+    let mut total = 0;
+    for _ in 0..16 {
+        let mut state = state.clone();
+        while let Some(mov) = rng.select_by_key(state.available_moves().into_iter(), |_| 0.0) {
+            state.make_move(&mov).unwrap();
+        }
+        total += state.score();
+    }
+    total as f64 / 16.0
+}
+
+impl Evaluator<RolloutMCTS> for RolloutEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<RolloutMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let score = match handle {
+            Some(mut handle) => rollouts(state, &mut handle.thread_data().policy_data),
+            None => rollouts(state, &mut PolicyRng::new(0)),
+        };
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &f64,
+        _: SearchHandle<RolloutMCTS>,
+    ) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct RolloutMCTS;
+
+impl MCTS for RolloutMCTS {
+    type State = FillGame;
+    type Eval = RolloutEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+fn fill_game_rollouts() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(32, 16),
+        RolloutMCTS,
+        RolloutEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n(2_000);
+}
+
+const BOARD_BYTES: usize = 1024;
+const PLAYABLE: usize = 16;
+
+// A kilobyte of board with only a few playable cells, so hashing dominates the lookups.
+#[derive(Clone, Hash)]
+struct BigBoard {
+    cells: [u8; BOARD_BYTES],
+    filled: u8,
+}
+
+impl Default for BigBoard {
+    fn default() -> Self {
+        Self {
+            cells: [0; BOARD_BYTES],
+            filled: 0,
+        }
+    }
+}
+
+fn zobrist_key(cell: usize) -> u64 {
+    let mut z = (cell as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl GameState for BigBoard {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &Self::Player {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        if self.filled == 4 {
+            return vec![];
+        }
+        (0..PLAYABLE).filter(|&i| self.cells[i] == 0).collect()
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.cells[*mov] = 1;
+        self.filled += 1;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+impl IncrementallyHashable for BigBoard {
+    fn full_hash(&self) -> u64 {
+        (0..PLAYABLE)
+            .filter(|&i| self.cells[i] != 0)
+            .fold(0, |h, i| h ^ zobrist_key(i))
+    }
+    fn hash_after(&self, current: u64, mov: &usize) -> u64 {
+        current ^ zobrist_key(*mov)
+    }
+}
+
+struct BoardEvaluator;
+
+impl<Spec: MCTS<State = BigBoard, TreePolicy = UCTPolicy<()>>> Evaluator<Spec> for BoardEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &BigBoard,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<Spec>>,
+    ) -> (Vec<()>, usize) {
+        let score = (0..PLAYABLE).filter(|&i| state.cells[i] != 0).sum();
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &BigBoard, evaln: &usize, _: SearchHandle<Spec>) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct FullHashMCTS;
+
+impl MCTS for FullHashMCTS {
+    type State = BigBoard;
+    type Eval = BoardEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+#[derive(Default)]
+struct IncrementalHashMCTS;
+
+impl MCTS for IncrementalHashMCTS {
+    type State = BigBoard;
+    type Eval = BoardEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = IncrementalTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+fn big_board_full_hash() {
+    let mut mcts = MCTSManager::new(
+        BigBoard::default(),
+        FullHashMCTS,
+        BoardEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1 << 16),
+    );
+    mcts.playout_n(10_000);
+}
+
+fn big_board_incremental_hash() {
+    let mut mcts = MCTSManager::new(
+        BigBoard::default(),
+        IncrementalHashMCTS,
+        BoardEvaluator,
+        UCTPolicy::new(5.0),
+        IncrementalTable::new(1 << 16),
+    );
+    mcts.playout_n(10_000);
+}
+
+// A player with an identity, as in a game server: cloning it allocates.
+#[derive(Clone, PartialEq)]
+struct Seat {
+    id: u64,
+    name: String,
+}
+
+// Two seats take turns adding 1 or 2 to a total, up to 40 plies; the second seat
+// wants the total to be even.
+#[derive(Clone)]
+struct SeatGame {
+    seats: std::sync::Arc<[Seat; 2]>,
+    plies: u8,
+    total: u32,
+}
+
+impl Default for SeatGame {
+    fn default() -> Self {
+        let seat = |id: u64| Seat {
+            id,
+            name: format!("seat {}", id),
+        };
+        Self {
+            seats: std::sync::Arc::new([seat(1), seat(2)]),
+            plies: 0,
+            total: 0,
+        }
+    }
+}
+
+impl GameState for SeatGame {
+    type Move = u32;
+    type Player = Seat;
+    type MoveList = Vec<u32>;
+
+    fn current_player(&self) -> &Seat {
+        &self.seats[self.plies as usize % 2]
+    }
+    fn available_moves(&self) -> Vec<u32> {
+        if self.plies == 40 {
+            vec![]
+        } else {
+            vec![1, 2]
+        }
+    }
+    fn make_move(&mut self, mov: &u32) -> Result<(), ()> {
+        self.total += mov;
+        self.plies += 1;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<Seat> {
+        None
+    }
+}
+
+struct SeatEvaluator;
+
+impl Evaluator<SeatMCTS> for SeatEvaluator {
+    type StateEvaluation = u32;
+
+    fn evaluate_new_state(
+        &self,
+        state: &SeatGame,
+        moves: &Vec<u32>,
+        _: Option<SearchHandle<SeatMCTS>>,
+    ) -> (Vec<()>, u32) {
+        (vec![(); moves.len()], state.total)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &u32, player: &Seat) -> f64 {
+        if evaln.is_multiple_of(2) == (player.id == 2) {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+    fn evaluate_existing_state(&self, _: &SeatGame, evaln: &u32, _: SearchHandle<SeatMCTS>) -> u32 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct SeatMCTS;
+
+impl MCTS for SeatMCTS {
+    type State = SeatGame;
+    type Eval = SeatEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+fn seat_game() {
+    let mut mcts = MCTSManager::new(
+        SeatGame::default(),
+        SeatMCTS,
+        SeatEvaluator,
+        UCTPolicy::new(1.0),
+        (),
+    );
+    mcts.playout_n(20_000);
+}
+
+fn connect_four() {
+    let mut mcts = connect_four_manager(ConnectFour::new(), 20_000);
+    mcts.seed(Some(1));
+    mcts.playout_n(20_000);
+}
+
+// FillGame with uniform priors, for `AlphaGoPolicy`.
+struct PriorEvaluator;
+
+impl Evaluator<PriorMCTS> for PriorEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<PriorMCTS>>,
+    ) -> (Vec<f64>, usize) {
+        (vec![1.0 / moves.len() as f64; moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &usize,
+        _: SearchHandle<PriorMCTS>,
+    ) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct PriorMCTS;
+
+impl MCTS for PriorMCTS {
+    type State = FillGame;
+    type Eval = PriorEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+}
+
+// PUCT scores of the root's moves, whose visits are on both sides of the end of the
+// policy's reciprocal table.
+fn alphago_scores(mcts: &MCTSManager<PriorMCTS>) -> f64 {
+    let root = mcts.tree().root_node();
+    let parent_visits = root.moves().map(|x| x.visits()).sum();
+    root.moves()
+        .map(|x| {
+            mcts.tree_policy()
+                .score_child(x, std::hint::black_box(parent_visits))
+        })
+        .sum()
+}
+
+// Whole searches take tens of milliseconds, so each group takes fewer samples than
+// criterion's default 100.
+fn searches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("expansion");
+    group.sample_size(10);
+    group.bench_function("counting_game", |b| counting_game(b, false, 100_000, 1));
+    group.bench_function("warm_up_unreserved", |b| counting_game(b, false, 10_000, 1));
+    group.bench_function("warm_up_reserved", |b| counting_game(b, true, 10_000, 1));
+    group.finish();
+
+    let mut group = c.benchmark_group("selection");
+    group.sample_size(10);
+    group.bench_function("fill_game", |b| b.iter(|| fill_game(1)));
+    group.finish();
+
+    let mut group = c.benchmark_group("evaluator");
+    group.sample_size(10);
+    group.bench_function("fill_game_rollouts", |b| b.iter(fill_game_rollouts));
+    group.finish();
+
+    let mut group = c.benchmark_group("players");
+    group.sample_size(10);
+    group.bench_function("seat_game", |b| b.iter(seat_game));
+    group.finish();
+
+    let mut group = c.benchmark_group("games");
+    group.sample_size(10);
+    group.bench_function("connect_four", |b| b.iter(connect_four));
+    group.finish();
+
+    let mut group = c.benchmark_group("hashing");
+    group.sample_size(10);
+    group.bench_function("big_board_full_hash", |b| b.iter(big_board_full_hash));
+    group.bench_function("big_board_incremental_hash", |b| {
+        b.iter(big_board_incremental_hash)
+    });
+    group.finish();
+}
+
+fn policy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("policy");
+    let mut mcts = MCTSManager::new(
+        FillGame::new(16, 4),
+        PriorMCTS,
+        PriorEvaluator,
+        AlphaGoPolicy::new(1.0),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n(2_000);
+    group.bench_function("alphago_score_child", |b| b.iter(|| alphago_scores(&mcts)));
+    group.finish();
+}
+
+fn parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parallel");
+    group.sample_size(10);
+    group.bench_function("counting_game_1_thread", |b| {
+        counting_game(b, false, 50_000, 1)
+    });
+    group.bench_function("counting_game_4_threads", |b| {
+        counting_game(b, false, 50_000, 4)
+    });
+    group.bench_function("fill_game_4_threads", |b| b.iter(|| fill_game(4)));
+    group.bench_function("deep_counting_game_16_threads", |b| {
+        b.iter(|| deep_counting_game(0, 16))
     });
+    group.bench_function("deep_counting_game_16_threads_cached_visits", |b| {
+        b.iter(|| deep_counting_game(8, 16))
+    });
+    group.bench_function("1ms_spawned_threads", |b| {
+        b.iter(|| one_millisecond(&SpawnThreads))
+    });
+    #[cfg(feature = "rayon")]
+    {
+        // `cargo bench --features rayon`
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+        group.bench_function("1ms_rayon_pool", |b| b.iter(|| one_millisecond(&pool)));
+    }
+    group.finish();
 }
+
+criterion_group!(benches, searches, policy, parallel);
+criterion_main!(benches);
@@ -0,0 +1,65 @@
+//! Plays a game of Connect Four against itself, searching for a fixed time per
+//! move and printing each move's statistics.
+//!
+//! ```text
+//! cargo run --release --example connect_four [milliseconds per move]
+//! ```
+//!
+//! The game, its bitboards and their Zobrist hashing live in
+//! `tests/common/connect_four.rs`, which the tests and benchmarks share.
+
+#[allow(dead_code)]
+#[path = "../tests/common/connect_four.rs"]
+mod connect_four;
+
+use connect_four::*;
+use mcts::*;
+use std::time::Duration;
+
+// Enough for the default half second per move; a fuller table keeps fewer
+// transpositions.
+const TABLE_NODES: usize = 1 << 20;
+
+fn main() {
+    let millis = std::env::args()
+        .nth(1)
+        .map(|x| x.parse().expect("milliseconds per move"))
+        .unwrap_or(500);
+    let mut state = ConnectFour::new();
+    println!("{}", state);
+    while !state.is_terminal() {
+        // The crate can't keep the subtree below the move played, so every move
+        // starts a new search.
+        let mut mcts = connect_four_manager(state.clone(), TABLE_NODES);
+        let summary = mcts.playout_for(Duration::from_millis(millis));
+        let mov = mcts.best_move().expect("the game isn't over");
+        let stats = mcts
+            .root_move_stats_detailed()
+            .into_iter()
+            .find(|x| *x.move_info.get_move() == mov)
+            .unwrap();
+        println!(
+            "ply {}: {} plays column {} ({} visits, mean {:.3}); {} playouts, {:.0}/sec, {:?}",
+            state.plies() + 1,
+            if *state.current_player() == 0 {
+                'X'
+            } else {
+                'O'
+            },
+            mov,
+            stats.visits,
+            stats.mean,
+            summary.playouts,
+            summary.playouts_per_second(),
+            summary.stop_reason,
+        );
+        println!("  expects {:?}", mcts.principal_variation(8));
+        state.make_move(&mov).unwrap();
+        println!("{}", state);
+    }
+    match state.get_winner() {
+        Some(0) => println!("X wins"),
+        Some(_) => println!("O wins"),
+        None => println!("Draw"),
+    }
+}
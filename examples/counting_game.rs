@@ -1,7 +1,6 @@
 extern crate mcts;
 
-use mcts::tree_policy::*;
-use mcts::*;
+use mcts::prelude::*;
 
 #[derive(Clone, Hash, Default)]
 struct CountingGame(i64);
@@ -17,8 +16,8 @@ impl GameState for CountingGame {
     type Player = ();
     type MoveList = Vec<Self::Move>;
 
-    fn current_player(&self) -> Self::Player {
-        ()
+    fn current_player(&self) -> &Self::Player {
+        &()
     }
 
     fn available_moves(&self) -> Vec<Self::Move> {
@@ -95,8 +94,15 @@ impl MCTS for MyMCTS {
 
 fn main() {
     let game = CountingGame(0);
-    let mut mcts = MCTSManager::new(game, MyMCTS, MyEvaluator, UCTPolicy::new(5.0), ());
-    mcts.playout_n(100000);
+    // Two moves per position, 100,000 playouts.
+    let params = SearchPreset::Balanced.params(2, 100_000);
+    let mut mcts = MCTSManager::builder(MyMCTS)
+        .state(game)
+        .evaluator(MyEvaluator)
+        .preset(&params)
+        .transposition_table(())
+        .build();
+    mcts.playout_n(params.playouts as usize);
     let pv: Vec<_> = mcts
         .principal_variation_states(10)
         .into_iter()
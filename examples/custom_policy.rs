@@ -0,0 +1,119 @@
+//! A tree policy written outside the crate, using only its public API: greedy on the
+//! mean reward, except that moves are tried once each before any is repeated.
+
+extern crate mcts;
+
+use mcts::prelude::*;
+
+/// Write a three-digit number, one digit at a time. The score is the number, so
+/// the best line is 9, 9, 9.
+#[derive(Clone, Default)]
+struct Digits(Vec<u8>);
+
+impl GameState for Digits {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.0.len() == 3 {
+            vec![]
+        } else {
+            (0..10).collect()
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct DigitsEvaluator;
+
+impl Evaluator<DigitsMCTS> for DigitsEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Digits,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<DigitsMCTS>>,
+    ) -> (Vec<()>, f64) {
+        // Digits still to be written count as 4.5, their mean.
+        let mut value = 0.0;
+        for i in 0..3 {
+            value = value * 10.0 + state.0.get(i).map_or(4.5, |&x| x as f64);
+        }
+        (vec![(); moves.len()], value)
+    }
+    fn evaluate_existing_state(&self, _: &Digits, evaln: &f64, _: SearchHandle<DigitsMCTS>) -> f64 {
+        *evaln
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+}
+
+struct GreedyPolicy;
+
+impl<Spec: MCTS<TreePolicy = Self>> TreePolicy<Spec> for GreedyPolicy {
+    type MoveEvaluation = ();
+    type ThreadLocalData = PolicyRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<Spec>,
+    ) -> &'a MoveInfo<Spec>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
+    {
+        // PolicyRng takes the highest key and breaks ties at random.
+        handle
+            .thread_data()
+            .policy_data
+            .select_by_key(moves, |mov| mov.mean_reward_or(f64::INFINITY))
+            .unwrap()
+    }
+
+    fn score_child(&self, child: &MoveInfo<Spec>, _parent_visits: u64) -> f64 {
+        child.mean_reward_or(f64::INFINITY)
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<PolicyRng> {
+        Some(PolicyRng::new(seed))
+    }
+}
+
+#[derive(Default)]
+struct DigitsMCTS;
+
+impl MCTS for DigitsMCTS {
+    type State = Digits;
+    type Eval = DigitsEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = GreedyPolicy;
+    type TranspositionTable = ();
+}
+
+fn main() {
+    let mut mcts = MCTSManager::new(
+        Digits::default(),
+        DigitsMCTS,
+        DigitsEvaluator,
+        GreedyPolicy,
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n(1000);
+    let pv = mcts.principal_variation(3);
+    println!("Principal variation: {:?}", pv);
+    assert_eq!(pv, vec![9, 9, 9]);
+}
@@ -0,0 +1,201 @@
+//! A knapsack puzzle where each node caches an upper bound on the value still
+//! reachable from it, and a custom tree policy uses the bound to stop exploring
+//! children that can't beat the best value seen.
+
+extern crate mcts;
+
+use mcts::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// (weight, value)
+const ITEMS: [(u32, u32); 10] = [
+    (12, 24),
+    (7, 13),
+    (11, 23),
+    (8, 15),
+    (9, 16),
+    (6, 11),
+    (5, 9),
+    (14, 26),
+    (4, 7),
+    (3, 5),
+];
+const CAPACITY: u32 = 30;
+
+#[derive(Clone, Default)]
+struct Knapsack {
+    taken: [bool; ITEMS.len()],
+    weight: u32,
+    value: u32,
+}
+
+impl Knapsack {
+    /// The value of the fractional relaxation: no packing of the remaining items can
+    /// do better.
+    fn upper_bound(&self) -> f64 {
+        let mut items: Vec<_> = (0..ITEMS.len())
+            .filter(|&i| !self.taken[i])
+            .map(|i| ITEMS[i])
+            .collect();
+        items.sort_by(|a, b| (b.1 * a.0).cmp(&(a.1 * b.0)));
+        let mut room = (CAPACITY - self.weight) as f64;
+        let mut bound = self.value as f64;
+        for (weight, value) in items {
+            let fraction = (room / weight as f64).min(1.0);
+            bound += fraction * value as f64;
+            room -= fraction * weight as f64;
+            if room <= 0.0 {
+                break;
+            }
+        }
+        bound
+    }
+}
+
+impl GameState for Knapsack {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        (0..ITEMS.len())
+            .filter(|&i| !self.taken[i] && self.weight + ITEMS[i].0 <= CAPACITY)
+            .collect()
+    }
+    fn make_move(&mut self, &mov: &usize) -> Result<(), ()> {
+        self.taken[mov] = true;
+        self.weight += ITEMS[mov].0;
+        self.value += ITEMS[mov].1;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+#[derive(Clone)]
+struct Evaluation {
+    /// The value of a random packing from here.
+    rollout: f64,
+    upper_bound: f64,
+}
+
+struct KnapsackEvaluator;
+
+impl Evaluator<KnapsackMCTS> for KnapsackEvaluator {
+    type StateEvaluation = Evaluation;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Knapsack,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<KnapsackMCTS>>,
+    ) -> (Vec<()>, Evaluation) {
+        let mut end = state.clone();
+        let mut rng = match handle {
+            Some(mut handle) => handle.thread_data().policy_data.clone(),
+            None => PolicyRng::new(0),
+        };
+        while let Some(mov) = rng.select_by_key(end.available_moves().into_iter(), |_| 0.0) {
+            end.make_move(&mov).unwrap();
+        }
+        let evaluation = Evaluation {
+            rollout: end.value as f64,
+            upper_bound: state.upper_bound(),
+        };
+        (vec![(); moves.len()], evaluation)
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &Knapsack,
+        evaln: &Evaluation,
+        _: SearchHandle<KnapsackMCTS>,
+    ) -> Evaluation {
+        evaln.clone()
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &Evaluation, _: &()) -> f64 {
+        evaln.rollout
+    }
+    // Computed once, when the node is created, and read by the policy at every visit.
+    fn make_node_data(&self, evaln: &Evaluation) -> f64 {
+        evaln.upper_bound
+    }
+}
+
+/// UCT, except that a child whose upper bound is no better than the best rollout
+/// seen so far is never chosen while another child remains.
+struct BoundedPolicy {
+    exploration_constant: f64,
+}
+
+impl TreePolicy<KnapsackMCTS> for BoundedPolicy {
+    type MoveEvaluation = ();
+    type ThreadLocalData = PolicyRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<KnapsackMCTS>,
+    ) -> &'a MoveInfo<KnapsackMCTS>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<KnapsackMCTS>> + Clone,
+    {
+        let parent_visits: u64 = moves.clone().map(|x| x.visits()).sum();
+        let best_seen = BEST.load(Ordering::Relaxed) as f64;
+        handle
+            .thread_data()
+            .policy_data
+            .select_by_key(moves, |mov| {
+                if let Some(child) = mov.child() {
+                    if *child.data() <= best_seen {
+                        return f64::MIN;
+                    }
+                }
+                if mov.visits() == 0 {
+                    return f64::INFINITY;
+                }
+                let explore = ((parent_visits as f64).ln() / mov.visits() as f64).sqrt();
+                mov.avg_reward() + self.exploration_constant * explore
+            })
+            .unwrap()
+    }
+}
+
+#[derive(Default)]
+struct KnapsackMCTS;
+
+impl MCTS for KnapsackMCTS {
+    type State = Knapsack;
+    type Eval = KnapsackEvaluator;
+    type NodeData = f64;
+    type ExtraThreadData = ();
+    type TreePolicy = BoundedPolicy;
+    type TranspositionTable = ();
+
+    fn after_backpropagation(&self, _: &[&MoveInfo<Self>], evaln: &Evaluation) {
+        BEST.fetch_max(evaln.rollout as u64, Ordering::Relaxed);
+    }
+}
+
+/// The best rollout value found so far.
+static BEST: AtomicU64 = AtomicU64::new(0);
+
+fn main() {
+    let policy = BoundedPolicy {
+        exploration_constant: 10.0,
+    };
+    let mut mcts = MCTSManager::builder(KnapsackMCTS)
+        .state(Knapsack::default())
+        .evaluator(KnapsackEvaluator)
+        .policy(policy)
+        .transposition_table(())
+        .seed(0)
+        .build();
+    mcts.playout_n(10_000);
+    println!("Best packing found: {}", BEST.load(Ordering::Relaxed));
+    println!("Root bound: {}", mcts.tree().root_node().data());
+    println!("Principal variation: {:?}", mcts.principal_variation(10));
+}
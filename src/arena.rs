@@ -0,0 +1,822 @@
+//! Playing matches between two agents, for example two configurations of a search.
+
+use super::*;
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// Something that chooses moves. Closures `FnMut(&S, u64) -> Option<S::Move>` are
+/// agents, as is `MCTSAgent`.
+pub trait Agent<S: GameState> {
+    /// The move to make in `state`, or `None` to stop the game. `seed` differs
+    /// between moves and games, and repeats when a match is replayed with the same seed.
+    fn choose_move(&mut self, state: &S, seed: u64) -> Option<S::Move>;
+
+    /// Like `choose_move`, searching within `budget` rather than the agent's own when
+    /// one is given, and reporting on the move. The harness calls this.
+    ///
+    /// By default this times `choose_move`, ignoring `budget`, and reports no
+    /// playouts or value.
+    fn play_move(&mut self, state: &S, seed: u64, _budget: Option<Budget>) -> MoveReport<S::Move> {
+        let start = clock::Instant::now();
+        let mov = self.choose_move(state, seed);
+        MoveReport {
+            mov,
+            time: start.elapsed(),
+            playouts: 0,
+            root_value: None,
+            budget: None,
+            by_a: false,
+        }
+    }
+}
+
+impl<S: GameState, F: FnMut(&S, u64) -> Option<S::Move>> Agent<S> for F {
+    fn choose_move(&mut self, state: &S, seed: u64) -> Option<S::Move> {
+        self(state, seed)
+    }
+}
+
+/// How long `MCTSAgent` searches for each move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Budget {
+    Playouts(usize),
+    Time(Duration),
+}
+
+/// An agent that runs a fresh single-threaded search for every move and plays the
+/// best move.
+pub struct MCTSAgent<Spec, F> {
+    make_manager: F,
+    budget: Budget,
+    _spec: PhantomData<fn() -> Spec>,
+}
+
+impl<Spec: MCTS, F: FnMut(Spec::State) -> MCTSManager<Spec>> MCTSAgent<Spec, F> {
+    /// `make_manager` builds the manager that searches from the given state.
+    pub fn new(make_manager: F, budget: Budget) -> Self {
+        Self {
+            make_manager,
+            budget,
+            _spec: PhantomData,
+        }
+    }
+}
+
+impl<Spec, F> Agent<Spec::State> for MCTSAgent<Spec, F>
+where
+    Spec: MCTS,
+    ThreadData<Spec>: Default,
+    F: FnMut(Spec::State) -> MCTSManager<Spec>,
+{
+    fn choose_move(&mut self, state: &Spec::State, seed: u64) -> Option<Move<Spec>> {
+        self.play_move(state, seed, None).mov
+    }
+
+    fn play_move(
+        &mut self,
+        state: &Spec::State,
+        seed: u64,
+        budget: Option<Budget>,
+    ) -> MoveReport<Move<Spec>> {
+        let start = clock::Instant::now();
+        let budget = budget.unwrap_or(self.budget);
+        let mut manager = (self.make_manager)(state.clone());
+        manager.seed(Some(seed));
+        let summary = match budget {
+            Budget::Playouts(n) => manager.playout_n(n),
+            Budget::Time(duration) => manager.playout_for(duration),
+        };
+        MoveReport {
+            mov: manager.best_move(),
+            time: start.elapsed(),
+            playouts: summary.playouts,
+            root_value: manager.root_value().map(|x| x.mean),
+            budget: Some(budget),
+            by_a: false,
+        }
+    }
+}
+
+/// What went into one move of a match.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveReport<M> {
+    /// The chosen move; `None` when the agent stopped the game.
+    pub mov: Option<M>,
+    /// Time spent choosing the move, searching included.
+    pub time: Duration,
+    pub playouts: u64,
+    /// The value of the position for the player to move, as estimated by the search.
+    pub root_value: Option<f64>,
+    /// The budget the move was searched with, if the agent has one.
+    pub budget: Option<Budget>,
+    /// Whether the first agent of the match made the move. Filled in by the harness.
+    pub by_a: bool,
+}
+
+impl<M> MoveReport<M> {
+    /// How far the move went over its budget: time beyond a `Budget::Time`, zero
+    /// otherwise. A timed search always overruns a little, as it finishes the playout
+    /// in progress and then picks its move.
+    pub fn overrun(&self) -> Duration {
+        match self.budget {
+            Some(Budget::Time(duration)) => self.time.saturating_sub(duration),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Whether the move went over its budget by more than `tolerance`, or ran more
+    /// playouts than a `Budget::Playouts`.
+    pub fn exceeded_budget(&self, tolerance: Duration) -> bool {
+        match self.budget {
+            Some(Budget::Playouts(n)) => self.playouts > n as u64,
+            Some(Budget::Time(_)) => self.overrun() > tolerance,
+            None => false,
+        }
+    }
+}
+
+/// The moves of one game of a match.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameReport<M> {
+    /// Whether the first agent of the match moved first.
+    pub a_first: bool,
+    pub moves: Vec<MoveReport<M>>,
+}
+
+impl<M> GameReport<M> {
+    pub fn summary(&self) -> TimeSummary {
+        TimeSummary::of(&self.moves)
+    }
+}
+
+/// Timing of a set of moves; see `GameReport::summary` and `MatchResult::summary`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TimeSummary {
+    pub moves: usize,
+    pub playouts: u64,
+    pub total: Duration,
+    /// Zero with no moves.
+    pub mean: Duration,
+    pub median: Duration,
+    /// The time that 90% of the moves took at most.
+    pub p90: Duration,
+    pub max: Duration,
+    /// The largest `MoveReport::overrun`.
+    pub max_overrun: Duration,
+}
+
+impl TimeSummary {
+    pub fn of<'a, M: 'a, I: IntoIterator<Item = &'a MoveReport<M>>>(reports: I) -> Self {
+        let mut times = Vec::new();
+        let mut result = TimeSummary::default();
+        for report in reports {
+            times.push(report.time);
+            result.playouts += report.playouts;
+            result.total += report.time;
+            result.max_overrun = result.max_overrun.max(report.overrun());
+        }
+        if times.is_empty() {
+            return result;
+        }
+        times.sort();
+        // Nearest rank.
+        let percentile = |p: usize| times[(times.len() * p).div_ceil(100).max(1) - 1];
+        result.moves = times.len();
+        result.mean = result.total / times.len() as u32;
+        result.median = percentile(50);
+        result.p90 = percentile(90);
+        result.max = times[times.len() - 1];
+        result
+    }
+}
+
+/// Wins, draws and losses from one agent's point of view.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+
+impl Tally {
+    pub fn games(&self) -> u64 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Wins plus half the draws, as a fraction of the games. NaN with no games.
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + 0.5 * self.draws as f64) / self.games() as f64
+    }
+
+    fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+    }
+}
+
+impl std::ops::Add for Tally {
+    type Output = Tally;
+    fn add(self, other: Tally) -> Tally {
+        Tally {
+            wins: self.wins + other.wins,
+            draws: self.draws + other.draws,
+            losses: self.losses + other.losses,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Outcome {
+    fn points(self) -> usize {
+        match self {
+            Outcome::Win => 2,
+            Outcome::Draw => 1,
+            Outcome::Loss => 0,
+        }
+    }
+}
+
+/// The result of `play_match`, from the first agent's (A's) point of view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchResult<M> {
+    /// Games where A moved first.
+    pub a_first: Tally,
+    /// Games where B moved first.
+    pub b_first: Tally,
+    /// With alternating colors, games come in pairs that share a seed. `pairs[k]`
+    /// counts the pairs where A scored `k / 2` points, for pentanomial SPRT. An
+    /// unfinished last pair isn't counted.
+    pub pairs: [u64; 5],
+    /// Every game, in the order played.
+    pub games: Vec<GameReport<M>>,
+}
+
+impl<M> Default for MatchResult<M> {
+    fn default() -> Self {
+        Self {
+            a_first: Tally::default(),
+            b_first: Tally::default(),
+            pairs: [0; 5],
+            games: Vec::new(),
+        }
+    }
+}
+
+impl<M> MatchResult<M> {
+    pub fn total(&self) -> Tally {
+        self.a_first + self.b_first
+    }
+
+    /// Every move of the match, in the order played.
+    pub fn moves(&self) -> impl Iterator<Item = &MoveReport<M>> {
+        self.games.iter().flat_map(|x| &x.moves)
+    }
+
+    /// The timing of every move of the match.
+    pub fn summary(&self) -> TimeSummary {
+        TimeSummary::of(self.moves())
+    }
+
+    /// The timing of the moves made by agent A (`a` set) or B.
+    pub fn summary_of(&self, a: bool) -> TimeSummary {
+        TimeSummary::of(self.moves().filter(|x| x.by_a == a))
+    }
+}
+
+/// Options for `play_match_with`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MatchOptions {
+    /// Play the games in pairs with the same seed, B moving first in the second game
+    /// of each pair.
+    pub alternate_colors: bool,
+    pub seed: u64,
+    /// The budget passed to `Agent::play_move` for A's moves.
+    pub budget_a: Option<Budget>,
+    /// The budget passed to `Agent::play_move` for B's moves.
+    pub budget_b: Option<Budget>,
+}
+
+/// Plays `games` games from `S::default()` between `agent_a` and `agent_b`; see
+/// `play_match_from`.
+pub fn play_match<S, A, B>(
+    agent_a: &mut A,
+    agent_b: &mut B,
+    games: usize,
+    alternate_colors: bool,
+) -> MatchResult<S::Move>
+where
+    S: GameState + Default,
+    A: Agent<S>,
+    B: Agent<S>,
+{
+    play_match_from(&S::default(), agent_a, agent_b, games, alternate_colors, 0)
+}
+
+/// Plays `games` games from `start` between `agent_a` and `agent_b`, each searching
+/// with its own budget; see `play_match_with`.
+pub fn play_match_from<S, A, B>(
+    start: &S,
+    agent_a: &mut A,
+    agent_b: &mut B,
+    games: usize,
+    alternate_colors: bool,
+    seed: u64,
+) -> MatchResult<S::Move>
+where
+    S: GameState,
+    A: Agent<S>,
+    B: Agent<S>,
+{
+    let options = MatchOptions {
+        alternate_colors,
+        seed,
+        ..Default::default()
+    };
+    play_match_with(start, agent_a, agent_b, games, &options)
+}
+
+/// Plays `games` games from `start` between `agent_a` and `agent_b`, recording a
+/// `MoveReport` for every move.
+///
+/// A moves first unless `options.alternate_colors` is set. A game ends when the
+/// state is terminal or an agent has no move, and the winner is decided by
+/// `get_winner`. Players are told apart by `current_player`, so this is for
+/// two-player games.
+pub fn play_match_with<S, A, B>(
+    start: &S,
+    agent_a: &mut A,
+    agent_b: &mut B,
+    games: usize,
+    options: &MatchOptions,
+) -> MatchResult<S::Move>
+where
+    S: GameState,
+    A: Agent<S>,
+    B: Agent<S>,
+{
+    let alternate_colors = options.alternate_colors;
+    let mut result = MatchResult::default();
+    let mut pair_points = 0;
+    for game in 0..games {
+        let (a_first, pair) = if alternate_colors {
+            (game.is_multiple_of(2), game / 2)
+        } else {
+            (true, game)
+        };
+        let pair_seed = (pair as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let game_seed = options.seed.wrapping_add(pair_seed);
+        let a = (&mut *agent_a, options.budget_a);
+        let b = (&mut *agent_b, options.budget_b);
+        let (outcome, moves) = if a_first {
+            play_game(start, a, b, game_seed, true)
+        } else {
+            let (outcome, moves) = play_game(start, b, a, game_seed, false);
+            let outcome = match outcome {
+                Outcome::Win => Outcome::Loss,
+                Outcome::Draw => Outcome::Draw,
+                Outcome::Loss => Outcome::Win,
+            };
+            (outcome, moves)
+        };
+        result.games.push(GameReport { a_first, moves });
+        if a_first {
+            result.a_first.add(outcome);
+        } else {
+            result.b_first.add(outcome);
+        }
+        if alternate_colors {
+            pair_points += outcome.points();
+            if !a_first {
+                result.pairs[pair_points] += 1;
+                pair_points = 0;
+            }
+        }
+    }
+    result
+}
+
+/// Plays one game between agents with their budgets, returning the outcome for
+/// `first` and the report of every move. `first_is_a` fills in `MoveReport::by_a`.
+fn play_game<S, A, B>(
+    start: &S,
+    first: (&mut A, Option<Budget>),
+    second: (&mut B, Option<Budget>),
+    seed: u64,
+    first_is_a: bool,
+) -> (Outcome, Vec<MoveReport<S::Move>>)
+where
+    S: GameState,
+    A: Agent<S>,
+    B: Agent<S>,
+{
+    let mut state = start.clone();
+    let first_player = state.current_player().clone();
+    let mut ply = 0u64;
+    let mut moves = Vec::new();
+    while !state.is_terminal() {
+        let move_seed = seed.wrapping_add(ply);
+        let by_first = *state.current_player() == first_player;
+        let mut report = if by_first {
+            first.0.play_move(&state, move_seed, first.1)
+        } else {
+            second.0.play_move(&state, move_seed, second.1)
+        };
+        report.by_a = by_first == first_is_a;
+        let mov = report.mov.clone();
+        moves.push(report);
+        match mov {
+            Some(mov) => {
+                if state.make_move(&mov).is_err() {
+                    break;
+                }
+            }
+            None => break,
+        }
+        ply += 1;
+    }
+    let outcome = match state.get_winner() {
+        Some(winner) if winner == first_player => Outcome::Win,
+        Some(_) => Outcome::Loss,
+        None => Outcome::Draw,
+    };
+    (outcome, moves)
+}
+
+/// The result of `strength_test`, from the first agent's (A's) point of view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrengthReport {
+    pub tally: Tally,
+    /// As `MatchResult::pairs`, over every opening.
+    pub pairs: [u64; 5],
+    /// The one-sided p-value of the sign test on the pairs: how likely A would be
+    /// to win at least as many of the decided pairs as it did if it were no
+    /// stronger than B. See `sign_test`.
+    pub p_value: f64,
+}
+
+impl StrengthReport {
+    /// The pairs won by A and by B: those where one scored more than the other.
+    pub fn decided_pairs(&self) -> (u64, u64) {
+        (self.pairs[3] + self.pairs[4], self.pairs[0] + self.pairs[1])
+    }
+
+    /// Whether A is stronger than B at significance level `alpha`, as in
+    /// `assert!(report.a_is_stronger(0.01))`.
+    pub fn a_is_stronger(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// Tests whether `agent_a` is stronger than `agent_b`: plays `pairs` pairs of
+/// games from each of `openings`, with colors swapped within each pair, and
+/// applies `sign_test` to the pairs. Pairs rather than games are counted, as the
+/// two games of a pair start from the same opening and aren't independent; the
+/// pairs are, given that each opening's games get seeds of their own, derived
+/// from `seed`. Like `play_match_with`, this is for two-player games.
+pub fn strength_test<S, A, B>(
+    openings: &[S],
+    agent_a: &mut A,
+    agent_b: &mut B,
+    pairs: usize,
+    seed: u64,
+) -> StrengthReport
+where
+    S: GameState,
+    A: Agent<S>,
+    B: Agent<S>,
+{
+    let mut tally = Tally::default();
+    let mut pair_counts = [0; 5];
+    for (i, opening) in openings.iter().enumerate() {
+        let options = MatchOptions {
+            alternate_colors: true,
+            seed: splitmix64(seed ^ i as u64),
+            ..Default::default()
+        };
+        let result = play_match_with(opening, agent_a, agent_b, 2 * pairs, &options);
+        tally = tally + result.total();
+        for (x, y) in pair_counts.iter_mut().zip(result.pairs) {
+            *x += y;
+        }
+    }
+    let won = pair_counts[3] + pair_counts[4];
+    let lost = pair_counts[0] + pair_counts[1];
+    StrengthReport {
+        tally,
+        pairs: pair_counts,
+        p_value: sign_test(won, lost),
+    }
+}
+
+/// The one-sided p-value of `wins` against `losses`, ties left out: the
+/// probability of at least `wins` heads in `wins + losses` tosses of a fair coin.
+/// 1 when there are no wins.
+pub fn sign_test(wins: u64, losses: u64) -> f64 {
+    if wins == 0 {
+        return 1.0;
+    }
+    let n = wins + losses;
+    // The logs of the binomial coefficients from `wins` up, summed without
+    // overflowing however many games there are.
+    let mut ln_choose = 0.0;
+    let mut terms = Vec::new();
+    for k in 0..=n {
+        if k >= wins {
+            terms.push(ln_choose);
+        }
+        if k < n {
+            ln_choose += ((n - k) as f64).ln() - ((k + 1) as f64).ln();
+        }
+    }
+    let max = terms.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let sum: f64 = terms.iter().map(|x| (x - max).exp()).sum();
+    (max + sum.ln() - n as f64 * std::f64::consts::LN_2)
+        .exp()
+        .min(1.0)
+}
+
+/// One position from a self-play game, as a training example. With the `serde`
+/// feature it's serializable when the state and its moves are.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "S: serde::Serialize, S::Move: serde::Serialize",
+        deserialize = "S: serde::Deserialize<'de>, S::Move: serde::Deserialize<'de>"
+    ))
+)]
+pub struct TrainingSample<S: GameState> {
+    pub state: S,
+    /// The root moves and their share of the root visits.
+    pub policy: Vec<(S::Move, f64)>,
+    /// The result of the game for the player to move in `state`: 1 for a win, -1 for
+    /// a loss and 0 for a draw.
+    pub outcome: f64,
+}
+
+/// Options for `generate_self_play_game`.
+#[derive(Clone, Debug, Default)]
+pub struct SelfPlayOptions {
+    /// Seeds the searches and the move sampling.
+    pub seed: u64,
+    /// The player to move resigns when no root move has a mean reward above this.
+    pub resign_threshold: Option<f64>,
+    /// The game is a draw after this many moves.
+    pub max_moves: Option<usize>,
+}
+
+/// Plays a game from `Spec::State::default()` against itself and returns a sample for
+/// every position where a move was made.
+///
+/// Each move gets a fresh search from `make_manager`, running
+/// `playouts_per_move(ply)` playouts. The move is then sampled in proportion to
+/// `visits^(1 / temperature_schedule(ply))`, or is the most visited move when the
+/// temperature is 0. Outcomes are filled in once the game is over; a resignation
+/// counts as a loss for the player who resigned.
+pub fn generate_self_play_game<Spec, F, T, P>(
+    make_manager: F,
+    temperature_schedule: T,
+    playouts_per_move: P,
+    options: &SelfPlayOptions,
+) -> Vec<TrainingSample<Spec::State>>
+where
+    Spec: MCTS,
+    ThreadData<Spec>: Default,
+    F: FnMut(Spec::State) -> MCTSManager<Spec>,
+    T: Fn(usize) -> f64,
+    P: Fn(usize) -> usize,
+{
+    let (samples, _) = self_play_game(
+        make_manager,
+        temperature_schedule,
+        playouts_per_move,
+        options,
+        |_, _| {},
+    );
+    samples
+}
+
+/// `generate_self_play_game`, steered away from the openings of earlier games: before
+/// each search in the first `penalty.plies()` plies, `LineRepetitionPenalty::apply`
+/// lowers the priors of the root moves that would repeat them. The game's moves are
+/// added to `penalty` once it's over, so generating games in a loop with the same
+/// `penalty` spreads them over more openings.
+pub fn generate_varied_self_play_game<Spec, F, T, P>(
+    make_manager: F,
+    temperature_schedule: T,
+    playouts_per_move: P,
+    options: &SelfPlayOptions,
+    penalty: &mut LineRepetitionPenalty<Move<Spec>>,
+) -> Vec<TrainingSample<Spec::State>>
+where
+    Spec: MCTS,
+    ThreadData<Spec>: Default,
+    F: FnMut(Spec::State) -> MCTSManager<Spec>,
+    T: Fn(usize) -> f64,
+    P: Fn(usize) -> usize,
+    MoveEvaluation<Spec>: UpdatableMoveEvaluation<Value = f64>,
+    Move<Spec>: PartialEq,
+{
+    let (samples, line) = self_play_game(
+        make_manager,
+        temperature_schedule,
+        playouts_per_move,
+        options,
+        |manager, played| {
+            penalty.apply(manager.tree(), played);
+        },
+    );
+    penalty.add_line(&line);
+    samples
+}
+
+/// Plays a self-play game, calling `before_search` with each fresh manager and the
+/// moves played so far, and returns the samples and the moves.
+fn self_play_game<Spec, F, T, P, H>(
+    mut make_manager: F,
+    temperature_schedule: T,
+    playouts_per_move: P,
+    options: &SelfPlayOptions,
+    mut before_search: H,
+) -> (Vec<TrainingSample<Spec::State>>, Vec<Move<Spec>>)
+where
+    Spec: MCTS,
+    ThreadData<Spec>: Default,
+    F: FnMut(Spec::State) -> MCTSManager<Spec>,
+    T: Fn(usize) -> f64,
+    P: Fn(usize) -> usize,
+    H: FnMut(&MCTSManager<Spec>, &[Move<Spec>]),
+{
+    let mut state = Spec::State::default();
+    let mut samples = Vec::new();
+    let mut line = Vec::new();
+    let mut players = Vec::new();
+    let mut resigned = None;
+    let mut rng = WeightedRng::new(options.seed);
+    while !state.is_terminal() {
+        let ply = samples.len();
+        if options.max_moves.is_some_and(|max| ply >= max) {
+            break;
+        }
+        let mut manager = make_manager(state.clone());
+        manager.seed(Some(options.seed.wrapping_add(ply as u64)));
+        before_search(&manager, &line);
+        manager.playout_n(playouts_per_move(ply));
+        let moves: Vec<_> = manager.tree().root_node().moves().collect();
+        let total: u64 = moves.iter().map(|x| x.visits()).sum();
+        if total == 0 {
+            break;
+        }
+        if let Some(threshold) = options.resign_threshold {
+            let best = moves
+                .iter()
+                .filter_map(|x| x.mean_reward())
+                .fold(f64::NEG_INFINITY, f64::max);
+            if best < threshold {
+                resigned = Some(state.current_player().clone());
+                break;
+            }
+        }
+        let policy: Vec<_> = moves
+            .iter()
+            .map(|x| (x.get_move().clone(), x.visits() as f64 / total as f64))
+            .collect();
+        let chosen = sample_move(&moves, temperature_schedule(ply), &mut rng).clone();
+        players.push(state.current_player().clone());
+        samples.push(TrainingSample {
+            state: state.clone(),
+            policy,
+            outcome: 0.0,
+        });
+        if state.make_move(&chosen).is_err() {
+            break;
+        }
+        line.push(chosen);
+    }
+    let winner = state.get_winner();
+    for (sample, player) in samples.iter_mut().zip(players) {
+        sample.outcome = match (&resigned, &winner) {
+            (Some(loser), _) if *loser == player => -1.0,
+            (Some(_), _) => 1.0,
+            (None, Some(winner)) if *winner == player => 1.0,
+            (None, Some(_)) => -1.0,
+            (None, None) => 0.0,
+        };
+    }
+    (samples, line)
+}
+
+/// The openings of earlier games, for `generate_varied_self_play_game` to steer new
+/// games away from. Within the first `plies` plies, a move that would continue an
+/// opening already played has its prior lowered by `penalty` times the share of
+/// the earlier games that continued that way. The search still visits it if it
+/// turns out to be good, unlike excluding it.
+#[derive(Clone, Debug)]
+pub struct LineRepetitionPenalty<M> {
+    /// The first `plies` moves of each earlier game.
+    lines: Vec<Vec<M>>,
+    plies: usize,
+    penalty: f64,
+}
+
+impl<M: Clone + PartialEq> LineRepetitionPenalty<M> {
+    pub fn new(plies: usize, penalty: f64) -> Self {
+        assert!(
+            penalty >= 0.0,
+            "line repetition penalty is {} (must not be negative)",
+            penalty
+        );
+        Self {
+            lines: Vec::new(),
+            plies,
+            penalty,
+        }
+    }
+
+    /// Adds an earlier game, of which only the first `plies` moves count.
+    pub fn add_line(&mut self, moves: &[M]) {
+        let len = moves.len().min(self.plies);
+        self.lines.push(moves[..len].to_vec());
+    }
+
+    /// The number of games added.
+    pub fn lines(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn plies(&self) -> usize {
+        self.plies
+    }
+
+    /// How much `apply` lowers the prior of `mov` after the moves `played`: 0 from
+    /// ply `plies` on, and otherwise `penalty` times the share of the earlier games
+    /// that began with `played` followed by `mov`.
+    pub fn penalty(&self, played: &[M], mov: &M) -> f64 {
+        if played.len() >= self.plies || self.lines.is_empty() {
+            return 0.0;
+        }
+        let repeats = self
+            .lines
+            .iter()
+            .filter(|line| line.starts_with(played) && line.get(played.len()) == Some(mov))
+            .count();
+        self.penalty * repeats as f64 / self.lines.len() as f64
+    }
+
+    /// Lowers the prior of each of the root moves of `tree`, whose root is reached
+    /// by the moves `played`, by its `penalty`, through
+    /// `MoveInfo::update_move_evaluation`. Priors don't go below 0. Returns the
+    /// number of moves whose prior was lowered.
+    pub fn apply<Spec>(&self, tree: &SearchTree<Spec>, played: &[M]) -> usize
+    where
+        Spec: MCTS,
+        Spec::State: GameState<Move = M>,
+        MoveEvaluation<Spec>: UpdatableMoveEvaluation<Value = f64>,
+    {
+        let root = tree.root_node();
+        let mut lowered = 0;
+        for info in root.moves().chain(root.excluded_moves()) {
+            let penalty = self.penalty(played, info.get_move());
+            if penalty > 0.0 {
+                let prior = info.move_evaluation().load();
+                info.update_move_evaluation((prior - penalty).max(0.0));
+                lowered += 1;
+            }
+        }
+        lowered
+    }
+}
+
+/// Picks a move in proportion to `visits^(1 / temperature)`: at a temperature of
+/// one, the same draw as `MCTSManager::sample_move_weighted` with `RootKey::Visits`.
+fn sample_move<'a, Spec: MCTS>(
+    moves: &[&'a MoveInfo<Spec>],
+    temperature: f64,
+    rng: &mut WeightedRng,
+) -> &'a Move<Spec> {
+    if temperature <= 0.0 {
+        let mut best = moves[0];
+        for x in moves {
+            if x.visits() > best.visits() {
+                best = x;
+            }
+        }
+        return best.get_move();
+    }
+    // Relative to the most visited move, so that low temperatures don't overflow.
+    let max = moves.iter().map(|x| x.visits()).max().unwrap() as f64;
+    let key = |x: &&MoveInfo<Spec>| (x.visits() as f64 / max).powf(1.0 / temperature);
+    rng.select_by_key(moves.iter().cloned(), key)
+        .unwrap()
+        .get_move()
+}
@@ -1,22 +1,154 @@
-use std;
+//! Every atomic the crate uses comes from here, so the orderings that matter are
+//! in one place. Statistics are updated with relaxed operations: a reader may see
+//! them a little out of date, but never torn. Pointers to nodes, which other
+//! threads dereference, go through `PublishedPtr`.
+//!
+//! The few stronger orderings are model checked by `tests/loom.rs`. Built with
+//! `--cfg loom`, the atomics they're on are loom's, which only work inside
+//! `loom::model`, so nothing but those tests can run in that build.
 
-#[cfg(not(any(target_pointer_width = "64", feature = "nightly")))]
-compile_error!("If you aren't compiling for 64-bit, you must use the nightly compiler.");
+#[cfg(loom)]
+use loom::sync::atomic::AtomicPtr as CheckedAtomicPtr;
+#[cfg(not(loom))]
+use std::sync::atomic::AtomicPtr as CheckedAtomicPtr;
 
-pub type AtomicF64 = atomic_float::AtomicF64;
+/// An `AtomicU64` whose orderings `tests/loom.rs` checks.
+#[cfg(not(loom))]
+pub type CheckedAtomicU64 = std::sync::atomic::AtomicU64;
+#[cfg(loom)]
+pub type CheckedAtomicU64 = loom::sync::atomic::AtomicU64;
 
-#[cfg(target_pointer_width = "64")]
-pub type AtomicU64 = std::sync::atomic::AtomicUsize;
-#[cfg(not(target_pointer_width = "64"))]
-pub type AtomicU64 = std::sync::atomic::AtomicU64;
+/// An `AtomicUsize` whose orderings `tests/loom.rs` checks.
+#[cfg(not(loom))]
+pub type CheckedAtomicUsize = std::sync::atomic::AtomicUsize;
+#[cfg(loom)]
+pub type CheckedAtomicUsize = loom::sync::atomic::AtomicUsize;
+
+/// A float that is stored as `f32` with the `f32_stats` feature and as `f64`
+/// otherwise, and is always read and written as `f64`. Under loom it's an `f64`
+/// on a checked atomic. As an `f32`, a sum stops changing once it is about 2^24
+/// times what is added to it.
+#[derive(Debug)]
+pub struct AtomicStat(AtomicStatRepr);
+
+#[cfg(all(not(loom), not(feature = "f32_stats")))]
+type AtomicStatRepr = atomic_float::AtomicF64;
+#[cfg(all(not(loom), feature = "f32_stats"))]
+type AtomicStatRepr = atomic_float::AtomicF32;
+#[cfg(loom)]
+type AtomicStatRepr = CheckedAtomicF64;
+
+#[cfg(any(loom, not(feature = "f32_stats")))]
+fn narrow(x: f64) -> f64 {
+    x
+}
+#[cfg(all(not(loom), feature = "f32_stats"))]
+fn narrow(x: f64) -> f32 {
+    x as f32
+}
+
+#[cfg(any(loom, not(feature = "f32_stats")))]
+fn widen(x: f64) -> f64 {
+    x
+}
+#[cfg(all(not(loom), feature = "f32_stats"))]
+fn widen(x: f32) -> f64 {
+    x as f64
+}
+
+/// `atomic_float::AtomicF64` on loom's `AtomicU64`.
+#[cfg(loom)]
+#[derive(Debug)]
+struct CheckedAtomicF64(CheckedAtomicU64);
 
-#[cfg(target_pointer_width = "64")]
-pub type FakeU64 = usize;
-#[cfg(not(target_pointer_width = "64"))]
-pub type FakeU64 = u64;
+#[cfg(loom)]
+impl CheckedAtomicF64 {
+    fn new(x: f64) -> Self {
+        CheckedAtomicF64(CheckedAtomicU64::new(x.to_bits()))
+    }
+    fn load(&self, order: Ordering) -> f64 {
+        f64::from_bits(self.0.load(order))
+    }
+    fn store(&self, x: f64, order: Ordering) {
+        self.0.store(x.to_bits(), order);
+    }
+    fn fetch_add(&self, x: f64, order: Ordering) -> f64 {
+        let previous = self
+            .0
+            .fetch_update(order, Ordering::Relaxed, |bits| {
+                Some((f64::from_bits(bits) + x).to_bits())
+            })
+            .unwrap();
+        f64::from_bits(previous)
+    }
+    fn fetch_sub(&self, x: f64, order: Ordering) -> f64 {
+        self.fetch_add(-x, order)
+    }
+}
 
+impl AtomicStat {
+    pub fn new(x: f64) -> Self {
+        AtomicStat(AtomicStatRepr::new(narrow(x)))
+    }
+    pub fn load(&self, order: Ordering) -> f64 {
+        widen(self.0.load(order))
+    }
+    pub fn store(&self, x: f64, order: Ordering) {
+        self.0.store(narrow(x), order);
+    }
+    pub fn fetch_add(&self, x: f64, order: Ordering) -> f64 {
+        widen(self.0.fetch_add(narrow(x), order))
+    }
+    pub fn fetch_sub(&self, x: f64, order: Ordering) -> f64 {
+        widen(self.0.fetch_sub(narrow(x), order))
+    }
+}
+
+/// A pointer that starts null and is set, once, to an object that other threads
+/// may then read through it.
+///
+/// Publishing is a release and loading an acquire, so whatever was written to the
+/// object before it was published is visible to a thread that loads the pointer.
+/// With relaxed operations a thread could follow the pointer to a node whose
+/// moves it doesn't see yet.
+#[derive(Debug)]
+pub struct PublishedPtr<T>(CheckedAtomicPtr<T>);
+
+impl<T> Default for PublishedPtr<T> {
+    fn default() -> Self {
+        PublishedPtr(CheckedAtomicPtr::new(std::ptr::null_mut()))
+    }
+}
+
+impl<T> PublishedPtr<T> {
+    pub fn new(ptr: *mut T) -> Self {
+        PublishedPtr(CheckedAtomicPtr::new(ptr))
+    }
+    pub fn load(&self) -> *mut T {
+        self.0.load(Ordering::Acquire)
+    }
+    /// Sets the pointer if it is still null, or returns the pointer another thread
+    /// published first.
+    pub fn publish(&self, ptr: *mut T) -> Result<(), *mut T> {
+        self.0
+            .compare_exchange(
+                std::ptr::null_mut(),
+                ptr,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .map(|_| ())
+    }
+    /// Points at `ptr`, whatever was published before.
+    pub fn replace(&self, ptr: *mut T) {
+        self.0.store(ptr, Ordering::Release);
+    }
+}
+
+pub type AtomicU32 = std::sync::atomic::AtomicU32;
+pub type AtomicU64 = std::sync::atomic::AtomicU64;
 pub type AtomicPtr<T> = std::sync::atomic::AtomicPtr<T>;
 pub type AtomicBool = std::sync::atomic::AtomicBool;
 pub type AtomicIsize = std::sync::atomic::AtomicIsize;
 pub type AtomicUsize = std::sync::atomic::AtomicUsize;
-pub use std::sync::atomic::Ordering;
+pub use std::sync::atomic::{fence, Ordering};
@@ -0,0 +1,211 @@
+use super::*;
+
+/// A piece of an `MCTSManagerBuilder` that hasn't been given yet.
+pub struct Missing;
+
+/// Builds an `MCTSManager` from named pieces, starting from `MCTSManager::builder`:
+///
+/// ```text
+/// MCTSManager::builder(MyMCTS)
+///     .state(game)
+///     .evaluator(MyEvaluator)
+///     .policy(UCTPolicy::new(0.5))
+///     .transposition_table(ApproxTable::new(1024))
+///     .seed(42)
+///     .build()
+/// ```
+///
+/// The state, evaluator, policy and transposition table are required: `build` only
+/// exists once all four have been given, so leaving one out is a compile error.
+pub struct MCTSManagerBuilder<
+    Spec: MCTS,
+    State = Missing,
+    Eval = Missing,
+    Policy = Missing,
+    Table = Missing,
+> {
+    spec: Spec,
+    state: State,
+    eval: Eval,
+    policy: Policy,
+    table: Table,
+    seed: Option<u64>,
+    max_nodes: Option<usize>,
+    min_root_visits: Option<u64>,
+    reserve: Option<u64>,
+    history: Option<Arc<HistoryTable<Move<Spec>>>>,
+    shared_stats: Option<Arc<dyn SharedStats<Move<Spec>>>>,
+}
+
+impl<Spec: MCTS> MCTSManagerBuilder<Spec> {
+    pub fn new(spec: Spec) -> Self {
+        Self {
+            spec,
+            state: Missing,
+            eval: Missing,
+            policy: Missing,
+            table: Missing,
+            seed: None,
+            max_nodes: None,
+            min_root_visits: None,
+            reserve: None,
+            history: None,
+            shared_stats: None,
+        }
+    }
+}
+
+impl<Spec: MCTS, State, Eval, Policy, Table> MCTSManagerBuilder<Spec, State, Eval, Policy, Table> {
+    pub fn state(
+        self,
+        state: Spec::State,
+    ) -> MCTSManagerBuilder<Spec, Spec::State, Eval, Policy, Table> {
+        MCTSManagerBuilder {
+            spec: self.spec,
+            state,
+            eval: self.eval,
+            policy: self.policy,
+            table: self.table,
+            seed: self.seed,
+            max_nodes: self.max_nodes,
+            min_root_visits: self.min_root_visits,
+            reserve: self.reserve,
+            history: self.history,
+            shared_stats: self.shared_stats,
+        }
+    }
+
+    pub fn evaluator(
+        self,
+        eval: Spec::Eval,
+    ) -> MCTSManagerBuilder<Spec, State, Spec::Eval, Policy, Table> {
+        MCTSManagerBuilder {
+            spec: self.spec,
+            state: self.state,
+            eval,
+            policy: self.policy,
+            table: self.table,
+            seed: self.seed,
+            max_nodes: self.max_nodes,
+            min_root_visits: self.min_root_visits,
+            reserve: self.reserve,
+            history: self.history,
+            shared_stats: self.shared_stats,
+        }
+    }
+
+    pub fn policy(
+        self,
+        policy: Spec::TreePolicy,
+    ) -> MCTSManagerBuilder<Spec, State, Eval, Spec::TreePolicy, Table> {
+        MCTSManagerBuilder {
+            spec: self.spec,
+            state: self.state,
+            eval: self.eval,
+            policy,
+            table: self.table,
+            seed: self.seed,
+            max_nodes: self.max_nodes,
+            min_root_visits: self.min_root_visits,
+            reserve: self.reserve,
+            history: self.history,
+            shared_stats: self.shared_stats,
+        }
+    }
+
+    pub fn transposition_table(
+        self,
+        table: Spec::TranspositionTable,
+    ) -> MCTSManagerBuilder<Spec, State, Eval, Policy, Spec::TranspositionTable> {
+        MCTSManagerBuilder {
+            spec: self.spec,
+            state: self.state,
+            eval: self.eval,
+            policy: self.policy,
+            table,
+            seed: self.seed,
+            max_nodes: self.max_nodes,
+            min_root_visits: self.min_root_visits,
+            reserve: self.reserve,
+            history: self.history,
+            shared_stats: self.shared_stats,
+        }
+    }
+
+    /// See `MCTSManager::seed`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// See `MCTSManager::max_nodes`.
+    pub fn max_nodes(mut self, limit: usize) -> Self {
+        self.max_nodes = Some(limit);
+        self
+    }
+
+    /// See `MCTSManager::min_root_visits`.
+    pub fn min_root_visits(mut self, visits: u64) -> Self {
+        self.min_root_visits = Some(visits);
+        self
+    }
+
+    /// See `MCTSManager::reserve`.
+    pub fn reserve(mut self, playouts_hint: u64) -> Self {
+        self.reserve = Some(playouts_hint);
+        self
+    }
+
+    /// Uses `params`: their `UCTPolicy` as the policy, and their playout budget for
+    /// `reserve`. The transposition table, if any, still has to be given; make it
+    /// with `params.table_size` entries.
+    pub fn preset<MV>(
+        self,
+        params: &SearchParams,
+    ) -> MCTSManagerBuilder<Spec, State, Eval, Spec::TreePolicy, Table>
+    where
+        Spec: MCTS<TreePolicy = tree_policy::UCTPolicy<MV>>,
+    {
+        self.policy(params.uct_policy()).reserve(params.playouts)
+    }
+
+    /// See `MCTSManager::set_history_table`.
+    pub fn history_table(mut self, table: Arc<HistoryTable<Move<Spec>>>) -> Self {
+        self.history = Some(table);
+        self
+    }
+
+    /// See `MCTSManager::set_shared_stats`.
+    pub fn shared_stats(mut self, stats: Arc<dyn SharedStats<Move<Spec>>>) -> Self {
+        self.shared_stats = Some(stats);
+        self
+    }
+}
+
+impl<Spec: MCTS>
+    MCTSManagerBuilder<Spec, Spec::State, Spec::Eval, Spec::TreePolicy, Spec::TranspositionTable>
+where
+    ThreadData<Spec>: Default,
+{
+    pub fn build(self) -> MCTSManager<Spec> {
+        let mut manager =
+            MCTSManager::new(self.state, self.spec, self.eval, self.policy, self.table);
+        manager.seed(self.seed);
+        if let Some(limit) = self.max_nodes {
+            manager.max_nodes(limit);
+        }
+        if let Some(visits) = self.min_root_visits {
+            manager.min_root_visits(visits);
+        }
+        if let Some(playouts_hint) = self.reserve {
+            manager.reserve(playouts_hint);
+        }
+        if self.history.is_some() {
+            manager.set_history_table(self.history);
+        }
+        if self.shared_stats.is_some() {
+            manager.set_shared_stats(self.shared_stats);
+        }
+        manager
+    }
+}
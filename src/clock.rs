@@ -0,0 +1,74 @@
+//! The clock the search is timed with.
+//!
+//! By default this is `std::time::Instant`, which isn't available on
+//! `wasm32-unknown-unknown`. There, install a clock with `set_clock`, for example
+//! one reading `performance.now()`. Without the `wasm` feature a clock installed
+//! with `set_clock` replaces `Instant` too.
+
+use super::atomics::{fence, AtomicPtr, Ordering};
+use std::time::Duration;
+
+static CLOCK: AtomicPtr<()> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Times every subsequent search with `clock`, which returns the time since some
+/// fixed point and must never go backwards.
+pub fn set_clock(clock: fn() -> Duration) {
+    CLOCK.store(clock as *mut (), Ordering::Release);
+}
+
+/// Goes back to the default clock.
+pub fn clear_clock() {
+    CLOCK.store(std::ptr::null_mut(), Ordering::Release);
+}
+
+/// Whether searches can be timed: a clock is installed, or `Instant` is available.
+pub fn has_clock() -> bool {
+    cfg!(not(feature = "wasm")) || !CLOCK.load(Ordering::Relaxed).is_null()
+}
+
+/// A point in time, as read from the current clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Instant(Duration);
+
+impl Instant {
+    pub(crate) fn now() -> Self {
+        let clock = CLOCK.load(Ordering::Relaxed);
+        if !clock.is_null() {
+            fence(Ordering::Acquire);
+            let clock: fn() -> Duration = unsafe { std::mem::transmute(clock) };
+            return Instant(clock());
+        }
+        Instant(default_clock())
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Self::now().0.saturating_sub(self.0)
+    }
+}
+
+impl std::ops::Add<Duration> for Instant {
+    type Output = Instant;
+    fn add(self, duration: Duration) -> Instant {
+        Instant(self.0 + duration)
+    }
+}
+
+impl std::ops::Sub for Instant {
+    type Output = Duration;
+    fn sub(self, earlier: Instant) -> Duration {
+        self.0.saturating_sub(earlier.0)
+    }
+}
+
+/// Time since the first call.
+#[cfg(not(feature = "wasm"))]
+fn default_clock() -> Duration {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START.get_or_init(std::time::Instant::now).elapsed()
+}
+
+/// Without a clock, time stands still.
+#[cfg(feature = "wasm")]
+fn default_clock() -> Duration {
+    Duration::ZERO
+}
@@ -0,0 +1,170 @@
+use super::*;
+use std::fmt;
+
+/// Options for `SearchTree::diff`.
+#[derive(Clone, Debug)]
+pub struct DiffOptions {
+    /// Number of plies below the root to compare.
+    pub max_depth: usize,
+    /// Moves with fewer visits than this in both trees are left out, along with
+    /// their subtrees.
+    pub min_visits: u64,
+    /// Number of plies of the principal variations to compare.
+    pub pv_depth: usize,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            min_visits: 1,
+            pv_depth: 64,
+        }
+    }
+}
+
+/// The statistics of one move in two searches.
+pub struct MoveDiff<Spec: MCTS> {
+    /// The moves from the root, ending with this one.
+    pub path: Vec<Move<Spec>>,
+    /// Visits in the first and second tree. A move missing from a tree has no visits.
+    pub visits: (u64, u64),
+    /// Mean reward in the first and second tree, NaN when unvisited.
+    pub mean: (f64, f64),
+}
+
+impl<Spec: MCTS> MoveDiff<Spec> {
+    pub fn visit_delta(&self) -> i64 {
+        self.visits.1 as i64 - self.visits.0 as i64
+    }
+
+    pub fn mean_delta(&self) -> f64 {
+        self.mean.1 - self.mean.0
+    }
+}
+
+/// The result of `SearchTree::diff`.
+pub struct TreeDiff<Spec: MCTS> {
+    /// Moves in depth-first order, each node's moves in the first tree's order
+    /// followed by any that only the second tree has.
+    pub moves: Vec<MoveDiff<Spec>>,
+    /// The first ply at which the principal variations differ, or `None` if they're
+    /// the same up to `DiffOptions::pv_depth`.
+    pub pv_divergence: Option<usize>,
+}
+
+impl<Spec: MCTS> SearchTree<Spec>
+where
+    Move<Spec>: PartialEq,
+{
+    /// Compares this search with `other`, matching moves by equality.
+    ///
+    /// Both trees should have the same root state. The comparison is most useful
+    /// between seeded searches, so that only the change under test makes a difference.
+    pub fn diff(&self, other: &SearchTree<Spec>, options: &DiffOptions) -> TreeDiff<Spec> {
+        let mut moves = Vec::new();
+        let mut path = Vec::new();
+        diff_nodes(
+            Some(self.root_node()),
+            Some(other.root_node()),
+            &mut path,
+            &mut moves,
+            options,
+        );
+        let ours = self.principal_variation(options.pv_depth);
+        let theirs = other.principal_variation(options.pv_depth);
+        let same = ours
+            .iter()
+            .zip(theirs.iter())
+            .take_while(|(a, b)| a.get_move() == b.get_move())
+            .count();
+        let pv_divergence = if same == ours.len() && same == theirs.len() {
+            None
+        } else {
+            Some(same)
+        };
+        TreeDiff {
+            moves,
+            pv_divergence,
+        }
+    }
+}
+
+fn diff_nodes<Spec: MCTS>(
+    a: Option<NodeHandle<Spec>>,
+    b: Option<NodeHandle<Spec>>,
+    path: &mut Vec<Move<Spec>>,
+    result: &mut Vec<MoveDiff<Spec>>,
+    options: &DiffOptions,
+) where
+    Move<Spec>: PartialEq,
+{
+    if path.len() >= options.max_depth {
+        return;
+    }
+    let a_moves: Vec<&MoveInfo<Spec>> = a.iter().flat_map(|x| x.moves()).collect();
+    let b_moves: Vec<&MoveInfo<Spec>> = b.iter().flat_map(|x| x.moves()).collect();
+    let only_b = b_moves
+        .iter()
+        .filter(|x| !a_moves.iter().any(|y| y.get_move() == x.get_move()))
+        .map(|x| (None, Some(*x)));
+    let pairs: Vec<_> = a_moves
+        .iter()
+        .map(|x| {
+            let other = b_moves.iter().find(|y| y.get_move() == x.get_move());
+            (Some(*x), other.cloned())
+        })
+        .chain(only_b)
+        .collect();
+    for (x, y) in pairs {
+        let visits = |m: Option<&MoveInfo<Spec>>| m.map(|m| m.visits()).unwrap_or(0);
+        let mean = |m: Option<&MoveInfo<Spec>>| m.and_then(|m| m.mean_reward()).unwrap_or(f64::NAN);
+        if visits(x) < options.min_visits && visits(y) < options.min_visits {
+            continue;
+        }
+        let mov = x.or(y).unwrap().get_move().clone();
+        path.push(mov);
+        result.push(MoveDiff {
+            path: path.clone(),
+            visits: (visits(x), visits(y)),
+            mean: (mean(x), mean(y)),
+        });
+        diff_nodes(
+            x.and_then(|m| m.child()),
+            y.and_then(|m| m.child()),
+            path,
+            result,
+            options,
+        );
+        path.pop();
+    }
+}
+
+impl<Spec: MCTS> fmt::Display for TreeDiff<Spec>
+where
+    Move<Spec>: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pv_divergence {
+            Some(ply) => writeln!(f, "principal variations differ at ply {}", ply)?,
+            None => writeln!(f, "principal variations agree")?,
+        }
+        for x in &self.moves {
+            let depth = x.path.len() - 1;
+            writeln!(
+                f,
+                "{:indent$}{:?}: visits {} -> {} ({:+}), mean {:.4} -> {:.4} ({:+.4})",
+                "",
+                x.path[depth],
+                x.visits.0,
+                x.visits.1,
+                x.visit_delta(),
+                x.mean.0,
+                x.mean.1,
+                x.mean_delta(),
+                indent = depth * 2
+            )?;
+        }
+        Ok(())
+    }
+}
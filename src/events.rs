@@ -0,0 +1,93 @@
+//! Lightweight instrumentation of the search, compiled in with the `events` feature.
+//!
+//! Install a sink with `set_event_sink` to receive a `SearchEvent` for every
+//! interesting thing the search does. When no sink is installed, emitting an event
+//! is a single relaxed load; events never allocate.
+//!
+//! With the `tracing` feature, which turns this one on, every event is also
+//! reported to `tracing` under the target `mcts`, and each playout runs in a
+//! `playout` span at the trace level. Events are named: `expansion` and
+//! `transposition_hit` at the trace level, `cycle_detected` and
+//! `weighted_fallback` at debug, and `search.progress`, with the playouts so far
+//! and the playouts per second, at info. Playouts have their span instead of an
+//! event.
+
+use super::atomics::{fence, AtomicPtr, Ordering};
+use std::time::Duration;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SearchEvent {
+    /// A playout finished after descending `depth` plies.
+    Playout { depth: usize },
+    /// A new node was created and added to the tree.
+    Expansion,
+    /// A child was found in the transposition table instead of being created.
+    TranspositionHit,
+    /// A playout ran into a node already on its path.
+    CycleDetected { depth: usize },
+    /// Emitted periodically by the `MCTSManager` search loops.
+    Progress { playouts: u64, elapsed: Duration },
+    /// A `WeightedRng` was given keys that make no distribution, and chose among
+    /// `options` elements by its fallback.
+    WeightedFallback { options: usize },
+}
+
+static SINK: AtomicPtr<()> = AtomicPtr::new(std::ptr::null_mut());
+
+/// Sends every subsequent event to `sink`, from whichever thread produces it.
+pub fn set_event_sink(sink: fn(&SearchEvent)) {
+    SINK.store(sink as *mut (), Ordering::Release);
+}
+
+/// Stops sending events.
+pub fn clear_event_sink() {
+    SINK.store(std::ptr::null_mut(), Ordering::Release);
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline]
+pub(crate) fn emit<F: FnOnce() -> SearchEvent>(make_event: F) {
+    let sink = SINK.load(Ordering::Relaxed);
+    if !sink.is_null() {
+        fence(Ordering::Acquire);
+        let sink: fn(&SearchEvent) = unsafe { std::mem::transmute(sink) };
+        sink(&make_event());
+    }
+}
+
+// Events are a few words on the stack, so making them for `tracing`, which
+// decides cheaply whether anyone's listening, costs no allocation.
+#[cfg(feature = "tracing")]
+#[inline]
+pub(crate) fn emit<F: FnOnce() -> SearchEvent>(make_event: F) {
+    let event = make_event();
+    let sink = SINK.load(Ordering::Relaxed);
+    if !sink.is_null() {
+        fence(Ordering::Acquire);
+        let sink: fn(&SearchEvent) = unsafe { std::mem::transmute(sink) };
+        sink(&event);
+    }
+    trace(&event);
+}
+
+#[cfg(feature = "tracing")]
+fn trace(event: &SearchEvent) {
+    use tracing::{event, Level};
+    match *event {
+        SearchEvent::Playout { .. } => (),
+        SearchEvent::Expansion => event!(name: "expansion", target: "mcts", Level::TRACE, {}),
+        SearchEvent::TranspositionHit => {
+            event!(name: "transposition_hit", target: "mcts", Level::TRACE, {})
+        }
+        SearchEvent::CycleDetected { depth } => {
+            event!(name: "cycle_detected", target: "mcts", Level::DEBUG, depth)
+        }
+        SearchEvent::Progress { playouts, elapsed } => {
+            let nps = playouts as f64 / elapsed.as_secs_f64();
+            event!(name: "search.progress", target: "mcts", Level::INFO, playouts, nps)
+        }
+        SearchEvent::WeightedFallback { options } => {
+            event!(name: "weighted_fallback", target: "mcts", Level::DEBUG, options)
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use super::*;
+use std::fmt;
+
+/// One of the root's moves in a `MoveExplanation`. Values are for the player to move
+/// at the root.
+pub struct ExplainedMove<Spec: MCTS> {
+    pub mov: Move<Spec>,
+    pub visits: u64,
+    /// NaN for unvisited moves.
+    pub mean: f64,
+    /// `TreePolicy::move_prior` of the move's evaluation, 0 for policies without
+    /// priors.
+    pub prior: f64,
+    /// The score the tree policy gives the move now, NaN for policies that don't
+    /// say; see `TreePolicy::score_child`.
+    pub policy_score: f64,
+    /// Whether the move is excluded from the search; see `MoveInfo::is_excluded`.
+    pub excluded: bool,
+    /// The moves the principal variation would take after this one.
+    pub continuation: Vec<Move<Spec>>,
+}
+
+/// The result of `SearchTree::explain_best_move`.
+pub struct MoveExplanation<Spec: MCTS> {
+    /// The best move, as `MCTSManager::best_move` chooses it, followed by its most
+    /// visited rivals. Empty before any playouts.
+    pub moves: Vec<ExplainedMove<Spec>>,
+    /// The visits of all of the root's moves.
+    pub root_visits: u64,
+    /// The root's moves that `MCTS::max_children` discarded, which no search tried.
+    pub pruned_moves: usize,
+}
+
+impl<Spec: MCTS> MoveExplanation<Spec> {
+    pub fn best(&self) -> Option<&ExplainedMove<Spec>> {
+        self.moves.first()
+    }
+    pub fn rivals(&self) -> &[ExplainedMove<Spec>] {
+        self.moves.get(1..).unwrap_or(&[])
+    }
+}
+
+impl<Spec: MCTS> SearchTree<Spec> {
+    /// Gathers what the search knows about its best move and the `rivals` most
+    /// visited of the other root moves, ties going to the earlier move, each with
+    /// the first `plies` moves of its continuation. For debugging a search, or
+    /// showing why it chose a move.
+    pub fn explain_best_move(&self, rivals: usize, plies: usize) -> MoveExplanation<Spec> {
+        let root = self.root_node();
+        let stats = self.root_move_stats_detailed();
+        let root_visits = stats.iter().map(|x| x.visits).sum();
+        let explain = |x: &DetailedMoveStats<Spec>| ExplainedMove {
+            mov: x.move_info.get_move().clone(),
+            visits: x.visits,
+            mean: x.mean,
+            prior: self.tree_policy().move_prior(x.move_info.move_evaluation()),
+            policy_score: x.policy_score,
+            excluded: x.excluded,
+            continuation: x
+                .move_info
+                .child()
+                .map(|child| self.line_from(child, plies, 1))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|x| x.get_move().clone())
+                .collect(),
+        };
+        let mut moves = Vec::new();
+        let best = self.principal_variation(1).first().copied();
+        if let Some(best) = best {
+            let (best, mut others): (Vec<_>, Vec<_>) =
+                stats.iter().partition(|x| std::ptr::eq(x.move_info, best));
+            others.sort_by_key(|x| std::cmp::Reverse(x.visits));
+            moves.extend(best.into_iter().chain(others).take(rivals + 1).map(explain));
+        }
+        MoveExplanation {
+            moves,
+            root_visits,
+            pruned_moves: root.pruned_moves(),
+        }
+    }
+}
+
+impl<Spec: MCTS> fmt::Display for MoveExplanation<Spec>
+where
+    Move<Spec>: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.moves.is_empty() {
+            return writeln!(f, "no move searched yet");
+        }
+        write!(f, "{} visits", self.root_visits)?;
+        if self.pruned_moves > 0 {
+            write!(f, ", {} moves pruned", self.pruned_moves)?;
+        }
+        writeln!(f, "; values for the player to move")?;
+        // Policies without priors give every move 0.
+        let priors = self.moves.iter().any(|x| x.prior != 0.0);
+        for (i, x) in self.moves.iter().enumerate() {
+            let label = if i == 0 { "best " } else { "rival" };
+            write!(
+                f,
+                "{} {:?}: {} visits ({:.1}%), mean {:+.3}",
+                label,
+                x.mov,
+                x.visits,
+                100.0 * x.visits as f64 / self.root_visits as f64,
+                x.mean,
+            )?;
+            if priors {
+                write!(f, ", prior {:.3}", x.prior)?;
+            }
+            if !x.policy_score.is_nan() {
+                write!(f, ", score {:.3}", x.policy_score)?;
+            }
+            if x.excluded {
+                write!(f, ", excluded")?;
+            }
+            if !x.continuation.is_empty() {
+                write!(f, ", then {:?}", x.continuation)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
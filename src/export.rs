@@ -0,0 +1,516 @@
+//! Exporting the search tree for inspection by other tools.
+
+use super::*;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// Options for `SearchTree::write_dot`.
+#[derive(Clone, Debug)]
+pub struct DotOptions {
+    /// Number of plies below the root to include.
+    pub max_depth: usize,
+    /// Moves with fewer visits than this are left out, along with their subtrees.
+    pub min_visits: u64,
+    /// Label edges with the move, its visits and its mean reward.
+    pub edge_labels: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            min_visits: 1,
+            edge_labels: true,
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The moves of `node` in a deterministic order (by their `Debug` representation).
+pub(crate) fn sorted_moves<'a, Spec: MCTS>(node: &NodeHandle<'a, Spec>) -> Vec<&'a MoveInfo<Spec>>
+where
+    Move<Spec>: Debug,
+{
+    let mut moves: Vec<_> = node
+        .moves()
+        .map(|x| (format!("{:?}", x.get_move()), x))
+        .collect();
+    moves.sort_by(|a, b| a.0.cmp(&b.0));
+    moves.into_iter().map(|x| x.1).collect()
+}
+
+impl<Spec: MCTS> SearchTree<Spec>
+where
+    Move<Spec>: Debug,
+{
+    /// Writes the tree in Graphviz DOT format.
+    ///
+    /// Nodes shared through the transposition table are written once, with one
+    /// in-edge per parent. Children are written in order of their `Debug`
+    /// representation, so the output of two similar searches can be diffed.
+    pub fn write_dot<W: Write>(&self, out: &mut W, options: &DotOptions) -> io::Result<()> {
+        let mut ids: HashMap<*const (), usize> = HashMap::new();
+        let root = self.root_node();
+        ids.insert(root.into_raw(), 0);
+        let mut stack = vec![(root, 0)];
+        writeln!(out, "digraph mcts {{")?;
+        while let Some((node, depth)) = stack.pop() {
+            let id = ids[&node.into_raw()];
+            let visits: u64 = node.moves().map(|x| x.visits()).sum();
+            writeln!(out, "  n{} [label=\"{}\"];", id, visits)?;
+            if depth >= options.max_depth {
+                continue;
+            }
+            let mut to_visit = Vec::new();
+            for mov in sorted_moves(&node) {
+                if mov.visits() < options.min_visits {
+                    continue;
+                }
+                let child = match mov.child() {
+                    Some(child) => child,
+                    None => continue,
+                };
+                let next_id = ids.len();
+                let child_id = *ids.entry(child.into_raw()).or_insert_with(|| {
+                    to_visit.push((child, depth + 1));
+                    next_id
+                });
+                if options.edge_labels {
+                    writeln!(
+                        out,
+                        "  n{} -> n{} [label=\"{}\\n{} visits\\n{:.3} mean\"];",
+                        id,
+                        child_id,
+                        escape(&format!("{:?}", mov.get_move())),
+                        mov.visits(),
+                        mov.avg_reward()
+                    )?;
+                } else {
+                    writeln!(out, "  n{} -> n{};", id, child_id)?;
+                }
+            }
+            stack.extend(to_visit.into_iter().rev());
+        }
+        writeln!(out, "}}")
+    }
+}
+
+/// Options for `SearchTree::write_json`.
+#[cfg(feature = "json")]
+#[derive(Clone, Debug)]
+pub struct JsonOptions {
+    /// Number of plies below the root to include.
+    pub max_depth: usize,
+    /// Moves with fewer visits than this are left out, along with their subtrees.
+    pub min_visits: u64,
+}
+
+#[cfg(feature = "json")]
+impl Default for JsonOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            min_visits: 1,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(feature = "json")]
+fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        format!("{}", x)
+    } else {
+        "null".into()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<Spec: MCTS> SearchTree<Spec>
+where
+    Move<Spec>: Debug,
+    MoveEvaluation<Spec>: Debug,
+{
+    /// Writes the tree as JSON, streaming it to `out` as it goes.
+    ///
+    /// The root is `{ "visits": ..., "children": [...] }` and every child is
+    /// `{ "move": ..., "visits": ..., "mean": ..., "prior": ..., "children": [...] }`.
+    /// Moves and priors are written as their `Debug` strings. Nodes shared through the
+    /// transposition table are written once per parent, and a node whose path passes
+    /// through it already is written without its children, so cycles end.
+    pub fn write_json<W: Write>(&self, out: &mut W, options: &JsonOptions) -> io::Result<()> {
+        let root = self.root_node();
+        let visits: u64 = root.moves().map(|x| x.visits()).sum();
+        write!(out, "{{\"visits\":{},\"children\":", visits)?;
+        let mut ancestors = vec![root.into_raw()];
+        write_json_children(out, &root, &mut ancestors, options)?;
+        writeln!(out, "}}")
+    }
+}
+
+#[cfg(feature = "json")]
+fn write_json_children<Spec: MCTS, W: Write>(
+    out: &mut W,
+    node: &NodeHandle<Spec>,
+    ancestors: &mut Vec<*const ()>,
+    options: &JsonOptions,
+) -> io::Result<()>
+where
+    Move<Spec>: Debug,
+    MoveEvaluation<Spec>: Debug,
+{
+    write!(out, "[")?;
+    // `ancestors` holds the root too, so it is one longer than the depth of `node`.
+    if ancestors.len() <= options.max_depth {
+        let mut first = true;
+        for mov in sorted_moves(node) {
+            if mov.visits() < options.min_visits {
+                continue;
+            }
+            if !first {
+                write!(out, ",")?;
+            }
+            first = false;
+            write!(
+                out,
+                "{{\"move\":{},\"visits\":{},\"mean\":{},\"prior\":{},\"children\":",
+                json_string(&format!("{:?}", mov.get_move())),
+                mov.visits(),
+                json_number(mov.avg_reward()),
+                json_string(&format!("{:?}", mov.move_evaluation())),
+            )?;
+            match mov.child() {
+                Some(child) if !ancestors.contains(&child.into_raw()) => {
+                    ancestors.push(child.into_raw());
+                    write_json_children(out, &child, ancestors, options)?;
+                    ancestors.pop();
+                }
+                _ => write!(out, "[]")?,
+            }
+            write!(out, "}}")?;
+        }
+    }
+    write!(out, "]")
+}
+
+/// A node of the tree, as produced by `SearchTree::iter_nodes`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeRecord<M> {
+    /// The moves from the root to the node; empty for the root.
+    pub path: Vec<M>,
+    pub depth: usize,
+    /// The visits of the move leading to the node. For the root,
+    /// `SearchTree::root_visits`.
+    pub visits: u64,
+    /// The mean reward of the move leading to the node, NaN when unvisited. For the
+    /// root, the mean over its moves, for the player to move there.
+    pub mean: f64,
+    /// `TreePolicy::move_prior` of the move leading to the node, NaN for the root.
+    pub prior: f64,
+}
+
+/// See `SearchTree::iter_nodes`.
+pub struct NodeRecords<'a, Spec: 'a + MCTS> {
+    tree: &'a SearchTree<Spec>,
+    min_visits: u64,
+    root_pending: bool,
+    /// Moves left to visit, with the depth of the node they lead to. The next is on
+    /// top.
+    stack: Vec<(usize, &'a MoveInfo<Spec>)>,
+    path: Vec<&'a Move<Spec>>,
+    /// The nodes along `path`, to stop at cycles through the transposition table.
+    ancestors: Vec<*const ()>,
+}
+
+impl<'a, Spec: MCTS> NodeRecords<'a, Spec> {
+    fn push_children(&mut self, node: NodeHandle<'a, Spec>, depth: usize) {
+        let start = self.stack.len();
+        let min_visits = self.min_visits;
+        let moves = node.moves().filter(|x| x.visits() >= min_visits);
+        self.stack.extend(moves.map(|x| (depth, x)));
+        self.stack[start..].reverse();
+    }
+}
+
+impl<'a, Spec: MCTS> Iterator for NodeRecords<'a, Spec> {
+    type Item = NodeRecord<&'a Move<Spec>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.root_pending {
+            self.root_pending = false;
+            self.push_children(self.tree.root_node(), 1);
+            let moves: u64 = self.tree.root_node().moves().map(|x| x.visits()).sum();
+            return Some(NodeRecord {
+                path: Vec::new(),
+                depth: 0,
+                visits: self.tree.root_visits(),
+                mean: self.tree.root_sum_rewards() / moves as f64,
+                prior: f64::NAN,
+            });
+        }
+        let (depth, mov) = self.stack.pop()?;
+        self.path.truncate(depth - 1);
+        self.path.push(mov.get_move());
+        self.ancestors.truncate(depth - 1);
+        let child = mov.child();
+        let ptr = child.as_ref().map_or(std::ptr::null(), |x| x.into_raw());
+        let cycle = ptr == self.tree.root_node().into_raw() || self.ancestors.contains(&ptr);
+        self.ancestors.push(ptr);
+        if let (Some(child), false) = (child, cycle) {
+            self.push_children(child, depth + 1);
+        }
+        Some(NodeRecord {
+            path: self.path.clone(),
+            depth,
+            visits: mov.visits(),
+            mean: mov.avg_reward(),
+            prior: self.tree.tree_policy().move_prior(mov.move_evaluation()),
+        })
+    }
+}
+
+impl<Spec: MCTS> SearchTree<Spec> {
+    /// Every node reached through moves with at least `min_visits` visits, starting
+    /// with the root, in depth-first order with children in node order. For bulk
+    /// export: the walk keeps its own stack, so deep trees can't overflow the
+    /// thread's. Nodes shared through the transposition table are produced once per
+    /// path leading to them, and a node whose path passes through it already is
+    /// produced without its children.
+    ///
+    /// The paths borrow their moves from the tree, so walking it clones none.
+    pub fn iter_nodes(&self, min_visits: u64) -> NodeRecords<'_, Spec> {
+        NodeRecords {
+            tree: self,
+            min_visits,
+            root_pending: true,
+            stack: Vec::new(),
+            path: Vec::new(),
+            ancestors: Vec::new(),
+        }
+    }
+}
+
+/// The first bytes of a `SearchTree::write_dump` file.
+pub const DUMP_MAGIC: &[u8; 8] = b"MCTSDUMP";
+/// The format version written by `SearchTree::write_dump`.
+pub const DUMP_VERSION: u8 = 1;
+
+fn write_varint(out: &mut Vec<u8>, mut x: u64) {
+    while x >= 0x80 {
+        out.push(x as u8 | 0x80);
+        x >>= 7;
+    }
+    out.push(x as u8);
+}
+
+impl<Spec: MCTS> SearchTree<Spec> {
+    /// Writes the tree in a compact binary format for external visualizers, read back
+    /// by `TreeDump::read`. `encode` turns a move into the bytes stored for it.
+    ///
+    /// Nodes are reached through moves with at least `min_visits` visits, in
+    /// breadth-first order starting with the root. A node shared through the
+    /// transposition table gets its children under the first parent reaching it, and
+    /// is a leaf under the others.
+    ///
+    /// All numbers are little-endian; varints are LEB128. The file is:
+    ///
+    /// - `DUMP_MAGIC` and the `DUMP_VERSION` byte;
+    /// - the move table: a varint count, then each move as a varint length and bytes;
+    /// - a varint node count, then the root's visits as a varint and mean as an `f32`;
+    /// - every other node as its parent index minus the previous node's parent index,
+    ///   its move's index in the move table and its visits, all varints, then its
+    ///   mean reward as an `f32`.
+    ///
+    /// Parent indices never decrease in breadth-first order, so a node typically
+    /// takes 7 to 9 bytes. The nodes are buffered before anything is written.
+    pub fn write_dump<W, E>(&self, out: &mut W, min_visits: u64, mut encode: E) -> io::Result<()>
+    where
+        W: Write,
+        E: FnMut(&Move<Spec>) -> Vec<u8>,
+    {
+        let mut move_table: Vec<Vec<u8>> = Vec::new();
+        let mut move_ids: HashMap<Vec<u8>, u64> = HashMap::new();
+        let mut seen = std::collections::HashSet::new();
+        let mut queue = std::collections::VecDeque::new();
+        let mut nodes = Vec::new();
+        let mut count = 1u64;
+        let mut prev_parent = 0;
+        let root = self.root_node();
+        let root_moves: u64 = root.moves().map(|x| x.visits()).sum();
+        let root_mean = self.root_sum_rewards() / root_moves as f64;
+        seen.insert(root.into_raw());
+        queue.push_back((root, 0u64));
+        while let Some((node, index)) = queue.pop_front() {
+            for mov in node.moves().filter(|x| x.visits() >= min_visits) {
+                let bytes = encode(mov.get_move());
+                let id = match move_ids.get(&bytes) {
+                    Some(&id) => id,
+                    None => {
+                        let id = move_table.len() as u64;
+                        move_ids.insert(bytes.clone(), id);
+                        move_table.push(bytes);
+                        id
+                    }
+                };
+                write_varint(&mut nodes, index - prev_parent);
+                write_varint(&mut nodes, id);
+                write_varint(&mut nodes, mov.visits());
+                nodes.extend_from_slice(&(mov.avg_reward() as f32).to_le_bytes());
+                prev_parent = index;
+                if let Some(child) = mov.child() {
+                    if seen.insert(child.into_raw()) {
+                        queue.push_back((child, count));
+                    }
+                }
+                count += 1;
+            }
+        }
+        let mut header = Vec::new();
+        header.extend_from_slice(DUMP_MAGIC);
+        header.push(DUMP_VERSION);
+        write_varint(&mut header, move_table.len() as u64);
+        for bytes in &move_table {
+            write_varint(&mut header, bytes.len() as u64);
+            header.extend_from_slice(bytes);
+        }
+        write_varint(&mut header, count);
+        write_varint(&mut header, self.root_visits());
+        header.extend_from_slice(&(root_mean as f32).to_le_bytes());
+        out.write_all(&header)?;
+        out.write_all(&nodes)
+    }
+}
+
+/// A node of a `TreeDump`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DumpNode {
+    /// The index of the parent node; `None` for the root.
+    pub parent: Option<usize>,
+    /// The index in `TreeDump::moves` of the move leading to the node; `None` for the
+    /// root.
+    pub mov: Option<usize>,
+    pub visits: u64,
+    pub mean: f32,
+}
+
+/// A tree written by `SearchTree::write_dump`, and the reference reader for the
+/// format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TreeDump {
+    /// The encoded moves.
+    pub moves: Vec<Vec<u8>>,
+    /// The nodes in breadth-first order, the root first.
+    pub nodes: Vec<DumpNode>,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_byte<R: Read>(input: &mut R) -> io::Result<u8> {
+    let mut byte = [0];
+    input.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_varint<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut result = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = read_byte(input)?;
+        result |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn read_f32<R: Read>(input: &mut R) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+impl TreeDump {
+    /// Reads a tree written by `SearchTree::write_dump`.
+    pub fn read<R: Read>(input: &mut R) -> io::Result<TreeDump> {
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != DUMP_MAGIC {
+            return Err(invalid("not a tree dump"));
+        }
+        if read_byte(input)? != DUMP_VERSION {
+            return Err(invalid("unsupported tree dump version"));
+        }
+        let move_count = read_varint(input)?;
+        let mut moves = Vec::new();
+        for _ in 0..move_count {
+            let len = read_varint(input)?;
+            let mut bytes = Vec::new();
+            input.by_ref().take(len).read_to_end(&mut bytes)?;
+            if bytes.len() as u64 != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            moves.push(bytes);
+        }
+        let node_count = read_varint(input)?;
+        if node_count == 0 {
+            return Err(invalid("a tree dump has a root"));
+        }
+        let mut nodes = vec![DumpNode {
+            parent: None,
+            mov: None,
+            visits: read_varint(input)?,
+            mean: read_f32(input)?,
+        }];
+        let mut parent = 0u64;
+        for index in 1..node_count {
+            parent = parent
+                .checked_add(read_varint(input)?)
+                .filter(|&x| x < index)
+                .ok_or_else(|| invalid("parent index out of range"))?;
+            let mov = read_varint(input)?;
+            if mov >= move_count {
+                return Err(invalid("move index out of range"));
+            }
+            nodes.push(DumpNode {
+                parent: Some(parent as usize),
+                mov: Some(mov as usize),
+                visits: read_varint(input)?,
+                mean: read_f32(input)?,
+            });
+        }
+        Ok(TreeDump { moves, nodes })
+    }
+
+    /// The encoded move leading to `node`; `None` for the root.
+    pub fn move_bytes(&self, node: usize) -> Option<&[u8]> {
+        self.nodes[node].mov.map(|x| &self.moves[x][..])
+    }
+
+    /// The indices of the children of `node`. Takes time linear in the number of
+    /// nodes.
+    pub fn children(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        let nodes = self.nodes.iter().enumerate();
+        nodes.filter(move |x| x.1.parent == Some(node)).map(|x| x.0)
+    }
+}
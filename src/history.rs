@@ -0,0 +1,104 @@
+use super::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Statistics for every move, wherever in the tree it was made: how often it was
+/// on a playout's path and the rewards it got there, for the player who made it.
+///
+/// Give one to `MCTSManager::set_history_table` and the search updates it during
+/// backpropagation; `HistoryPolicy` reads it to choose between children with few
+/// visits of their own. The table is shared through an `Arc`, so it can outlive a
+/// manager and carry over to the search for the next move of the game.
+///
+/// Moves are stored by hash in a fixed number of entries. Moves whose hashes collide
+/// share an entry, which only blurs the statistics of both.
+pub struct HistoryTable<M> {
+    entries: Box<[HistoryEntry]>,
+    mask: usize,
+    hash: fn(&M) -> u64,
+}
+
+struct HistoryEntry {
+    visits: AtomicU64,
+    sum_rewards: AtomicStat,
+}
+
+fn hash_move<M: Hash>(mov: &M) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    mov.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<M: Hash> HistoryTable<M> {
+    /// A table with `size` entries, rounded up to a power of 2.
+    pub fn new(size: usize) -> Self {
+        let capacity = size
+            .max(1)
+            .checked_next_power_of_two()
+            .expect("history table size is too large");
+        let entries = (0..capacity)
+            .map(|_| HistoryEntry {
+                visits: AtomicU64::new(0),
+                sum_rewards: AtomicStat::new(0.0),
+            })
+            .collect();
+        Self {
+            entries,
+            mask: capacity - 1,
+            hash: hash_move::<M>,
+        }
+    }
+}
+
+impl<M> HistoryTable<M> {
+    fn entry(&self, mov: &M) -> &HistoryEntry {
+        &self.entries[(self.hash)(mov) as usize & self.mask]
+    }
+    /// Adds a visit to `mov` with `reward`, for the player who made it.
+    pub fn record(&self, mov: &M, reward: f64) {
+        let entry = self.entry(mov);
+        entry.sum_rewards.fetch_add(reward, Ordering::Relaxed);
+        entry.visits.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn visits(&self, mov: &M) -> u64 {
+        self.entry(mov).visits.load(Ordering::Relaxed)
+    }
+    /// The mean reward of `mov`, or `None` if it hasn't been visited.
+    pub fn mean(&self, mov: &M) -> Option<f64> {
+        let entry = self.entry(mov);
+        let visits = entry.visits.load(Ordering::Relaxed);
+        if visits == 0 {
+            None
+        } else {
+            Some(entry.sum_rewards.load(Ordering::Relaxed) / visits as f64)
+        }
+    }
+    /// Forgets everything.
+    pub fn clear(&self) {
+        for entry in self.entries.iter() {
+            entry.visits.store(0, Ordering::Relaxed);
+            entry.sum_rewards.store(0.0, Ordering::Relaxed);
+        }
+    }
+    /// The number of entries.
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// Statistics kept by the caller over a whole game or match, outliving managers.
+/// Give one to `MCTSManager::set_shared_stats` and `record` is called during
+/// backpropagation for every move on a playout's path, with the reward for the
+/// player who made it. Searches without one skip it, at the cost of a branch.
+///
+/// `record` runs on the search threads, so it must be cheap and thread-safe. Keep
+/// an `Arc` to the concrete table to read it back.
+pub trait SharedStats<M>: Send + Sync {
+    fn record(&self, mov: &M, reward: f64);
+}
+
+impl<M: Send + Sync> SharedStats<M> for HistoryTable<M> {
+    fn record(&self, mov: &M, reward: f64) {
+        HistoryTable::record(self, mov, reward)
+    }
+}
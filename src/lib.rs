@@ -3,9 +3,7 @@
 //! It is still under development and the documentation isn't good. However, the following example may be helpful:
 //!
 //! ```
-//! use mcts::*;
-//! use mcts::tree_policy::*;
-//! use mcts::transposition_table::*;
+//! use mcts::prelude::*;
 //!
 //! // A really simple game. There's one player and one number. In each move the player can
 //! // increase or decrease the number. The player's score is the number.
@@ -26,8 +24,8 @@
 //!     type Player = ();
 //!     type MoveList = Vec<Move>;
 //!
-//!     fn current_player(&self) -> Self::Player {
-//!         ()
+//!     fn current_player(&self) -> &Self::Player {
+//!         &()
 //!     }
 //!     fn available_moves(&self) -> Vec<Move> {
 //!         let x = self.0;
@@ -37,11 +35,12 @@
 //!             vec![Move::Add, Move::Sub]
 //!         }
 //!     }
-//!     fn make_move(&mut self, mov: &Self::Move) {
+//!     fn make_move(&mut self, mov: &Self::Move) -> Result<(), ()> {
 //!         match *mov {
 //!             Move::Add => self.0 += 1,
 //!             Move::Sub => self.0 -= 1,
 //!         }
+//!         Ok(())
 //!     }
 //!     fn get_winner(&self) -> Option<Self::Player> {
 //!         let x = self.0;
@@ -108,14 +107,55 @@
 extern crate crossbeam;
 extern crate smallvec;
 
+macro_rules! emit_event {
+    ($event:expr) => {
+        #[cfg(feature = "events")]
+        $crate::events::emit(|| $event);
+    };
+}
+
+pub mod arena;
 mod atomics;
+mod builder;
+pub mod clock;
+mod diff;
+#[cfg(feature = "events")]
+pub mod events;
+mod explain;
+pub mod export;
+mod history;
+mod live;
+pub mod nmcs;
+pub mod nrpa;
+pub mod prelude;
+mod preset;
 mod search_tree;
+mod snapshot;
+mod spawn;
+mod stop;
+mod summary;
+mod tactics;
 pub mod transposition_table;
 pub mod tree_policy;
+mod validate;
+mod zero_sum;
 
+pub use builder::*;
+pub use diff::*;
+pub use explain::*;
+pub use history::*;
+pub use live::*;
+pub use preset::*;
 pub use search_tree::*;
+pub use snapshot::*;
+pub use spawn::*;
+pub use stop::*;
+pub use summary::*;
+pub use tactics::*;
 use transposition_table::*;
 use tree_policy::*;
+pub use validate::*;
+pub use zero_sum::*;
 
 use atomics::*;
 use std::fmt::Debug;
@@ -128,22 +168,102 @@ pub trait MCTS: Sized {
     type Eval: Evaluator<Self>;
     type TreePolicy: TreePolicy<Self>;
     type NodeData: Default + Sync + Send;
+    /// Use `()` for no table. Hashing requirements belong to the table, so states
+    /// only need to implement `Hash` when used with a table like `ApproxTable`.
     type TranspositionTable: TranspositionTable<Self>;
     type ExtraThreadData;
 
     fn virtual_loss(&self) -> f64 {
         0.0
     }
+    /// How a playout in progress discourages other threads from following it.
+    /// Defaults to `VirtualLossMode::Reward(self.virtual_loss())`. Overridden by
+    /// `TreePolicy::virtual_loss`, when the policy has one.
+    fn virtual_loss_mode(&self) -> VirtualLossMode {
+        VirtualLossMode::Reward(self.virtual_loss())
+    }
     fn visits_before_expansion(&self) -> u64 {
         1
     }
+    /// The most playouts that may be going through a move at once. A move with this
+    /// many playouts in flight is saturated: selection passes over it and takes the
+    /// best of its siblings instead, unless they're all saturated. This keeps threads
+    /// from piling into one branch where virtual loss isn't enough to spread them.
+    ///
+    /// The limit is soft: threads that choose at the same moment can all pass it.
+    /// Playouts that avoid a saturated move are counted in
+    /// `SearchSummary::saturated_selections`. Defaults to unlimited, which doesn't
+    /// keep count of playouts in flight at all.
+    fn max_in_flight(&self) -> usize {
+        usize::MAX
+    }
+    /// With `k > 0`, each node keeps a copy of its visit count that's refreshed
+    /// whenever it's `k` or more visits behind, and tree policies read it through
+    /// `SearchHandle::cached_visits` instead of summing the visits of the node's
+    /// children before scoring them on every step down. The exploration term hardly
+    /// changes with a count a few visits stale, and selection goes over the children
+    /// once instead of twice.
+    /// Backpropagation still updates the exact counters, so the statistics a search
+    /// reports are unaffected. Defaults to 0, which always sums.
+    fn cached_visits_interval(&self) -> usize {
+        0
+    }
     fn node_limit(&self) -> usize {
-        std::usize::MAX
+        usize::MAX
+    }
+    /// Whether a new node should exclude `mov`, one of `state`'s available moves,
+    /// after the evaluator has evaluated it. For games whose move generator returns
+    /// moves that may turn out to be illegal, when checking is too slow to do in
+    /// `available_moves`.
+    ///
+    /// Excluded moves keep their evaluations but are set aside: the tree policy never
+    /// sees them, so they get no visits and are never in the principal variation.
+    /// See `NodeHandle::excluded_moves`. Defaults to excluding nothing.
+    fn exclude_move(&self, _state: &Self::State, _mov: &Move<Self>) -> bool {
+        false
     }
+    /// Whether searches that return a `SearchSummary`, and chunked searches, stop at
+    /// once when the root has only one move to choose from (see
+    /// `SearchTree::forced_root_move`), with `StopReason::ForcedMove`. Defaults to
+    /// true; turn it off to keep searching the tree below a forced move, say to
+    /// reuse it after the move is made.
+    fn stop_on_forced_move(&self) -> bool {
+        true
+    }
+    /// The most children a new node keeps: the moves with the highest
+    /// `TreePolicy::move_prior`, plus `random_extra_children` of the others chosen at
+    /// random. The rest are discarded and never searched. Defaults to unlimited.
+    fn max_children(&self) -> usize {
+        usize::MAX
+    }
+    /// The number of moves, beyond the `max_children` with the best priors, that a
+    /// new node keeps at random so that bad priors can't hide a move forever.
+    ///
+    /// The choice depends only on the search's master seed (see
+    /// `MCTSManager::seed`) and the moves leading to the node, so it's the same
+    /// whichever thread expands the node (with a transposition table, a node reached
+    /// by several paths uses the first path taken). Thread data made outside the
+    /// manager uses its policy data instead, and the root a fixed seed. Defaults to 0.
+    fn random_extra_children(&self) -> usize {
+        0
+    }
+    /// The bytes a copy of `state` takes, for transposition tables that keep copies
+    /// of states, like `ExactTable`, to account for them. Defaults to the size of
+    /// the state type; states that own heap memory should add it.
+    fn state_size_hint(&self, _state: &Self::State) -> usize {
+        std::mem::size_of::<Self::State>()
+    }
+    /// The estimate `MCTSManager::reserve` uses to turn playouts into nodes.
+    fn expected_nodes_per_playout(&self) -> f64 {
+        1.0
+    }
+    /// The move the principal variation takes among `children`. Defaults to the most
+    /// visited move that isn't excluded, or the most visited move when all are.
     fn select_child_after_search<'a>(&self, children: &'a [MoveInfo<Self>]) -> &'a MoveInfo<Self> {
-        children
-            .into_iter()
+        let selectable = children.iter().filter(|child| !child.is_excluded());
+        selectable
             .max_by_key(|child| child.visits())
+            .or_else(|| children.iter().max_by_key(|child| child.visits()))
             .unwrap()
     }
     /// `playout` panics when this length is exceeded. Defaults to one million.
@@ -151,6 +271,49 @@ pub trait MCTS: Sized {
         1_000_000
     }
     fn on_backpropagation(&self, _evaln: &StateEvaluation<Self>, _handle: SearchHandle<Self>) {}
+    /// Called once per playout, after its evaluation has been applied to every node.
+    /// `path` holds the moves selected from the root to the leaf. This runs on the
+    /// search threads, so it should be cheap.
+    fn after_backpropagation(&self, _path: &[&MoveInfo<Self>], _evaln: &StateEvaluation<Self>) {}
+    /// Called when a new node's `is_terminal()` and `available_moves()` disagree, with
+    /// the moves from the root to its state. A state with no moves that isn't terminal
+    /// is only a mismatch when `no_moves_behaviour` returns `Panic` for it, and is
+    /// reported just before the panic; the other behaviours settle what it is. States
+    /// with moves are checked only in builds with debug assertions, since it costs a
+    /// call to `is_terminal`. Every mismatch is also counted in
+    /// `SearchSummary::terminality_mismatches`.
+    ///
+    /// Does nothing by default. To see the states, when they implement `Debug`:
+    ///
+    /// ```ignore
+    /// fn on_terminality_mismatch(&self, state: &State, path: &[Move], mismatch: TerminalityMismatch) {
+    ///     eprintln!("{}", mismatch.describe(state, path));
+    /// }
+    /// ```
+    fn on_terminality_mismatch(
+        &self,
+        _state: &Self::State,
+        _path: &[Move<Self>],
+        _mismatch: TerminalityMismatch,
+    ) {
+    }
+    /// Consulted when `available_moves()` is empty but `is_terminal()` is false, or
+    /// when `exclude_move` excludes every move.
+    fn no_moves_behaviour(&self, _state: &Self::State) -> NoMovesBehaviour<Self> {
+        NoMovesBehaviour::Terminal
+    }
+    /// Lets backpropagation call `interpret_evaluation_for_player` less often.
+    fn evaluation_symmetry(&self) -> EvaluationSymmetry {
+        EvaluationSymmetry::General
+    }
+    /// What to do when `interpret_evaluation_for_player` returns NaN or an infinity.
+    fn non_finite_rewards(&self) -> NonFiniteRewards {
+        NonFiniteRewards::PanicInDebug
+    }
+    /// What to do when `make_move` fails for the move selected during tree descent.
+    fn move_error_behaviour(&self) -> MoveErrorBehaviour {
+        MoveErrorBehaviour::Panic
+    }
     fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
         if std::mem::size_of::<Self::TranspositionTable>() == 0 {
             CycleBehaviour::Ignore
@@ -163,6 +326,59 @@ pub trait MCTS: Sized {
 pub struct ThreadData<Spec: MCTS> {
     pub policy_data: TreePolicyThreadData<Spec>,
     pub extra_data: Spec::ExtraThreadData,
+    /// The moves selected from the root in the current playout. Kept here so that
+    /// playouts reuse its allocation.
+    path: Vec<Move<Spec>>,
+    /// A hash of the indices of the moves in `path`, each in its node.
+    path_hash: u64,
+    /// When the search running on this thread has to stop, if it's timed.
+    deadline: Option<clock::Instant>,
+    /// The seed of the search running on this thread, if it's seeded.
+    seed: Option<u64>,
+    /// The state of the generator for `SearchTree::set_root_noise`, seeded from
+    /// entropy on first use unless the search set it.
+    noise: Option<u64>,
+}
+
+impl<Spec: MCTS> ThreadData<Spec> {
+    pub fn new(policy_data: TreePolicyThreadData<Spec>, extra_data: Spec::ExtraThreadData) -> Self {
+        Self {
+            policy_data,
+            extra_data,
+            path: Vec::new(),
+            path_hash: 0,
+            deadline: None,
+            seed: None,
+            noise: None,
+        }
+    }
+    pub(crate) fn path(&self) -> &[Move<Spec>] {
+        &self.path
+    }
+    pub(crate) fn clear_path(&mut self) {
+        self.path.clear();
+        self.path_hash = 0;
+    }
+    /// Adds `mov`, at `index` in its node, to the path.
+    pub(crate) fn push_path(&mut self, mov: &Move<Spec>, index: usize) {
+        self.path.push(mov.clone());
+        self.path_hash = splitmix64(self.path_hash ^ index as u64);
+    }
+    /// A seed for the randomness of expanding the node at the end of the path. It
+    /// depends only on the search's seed and the path, not on the thread, so that
+    /// whichever thread expands a node does it the same way.
+    pub(crate) fn expansion_seed(&self) -> Option<u64> {
+        self.seed.map(|seed| splitmix64(seed ^ self.path_hash))
+    }
+    pub(crate) fn deadline(&self) -> Option<clock::Instant> {
+        self.deadline
+    }
+    /// The next number for the root noise of this thread.
+    pub(crate) fn next_noise(&mut self) -> u64 {
+        let state = self.noise.get_or_insert_with(random_seed);
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        splitmix64(*state)
+    }
 }
 
 impl<Spec: MCTS> Default for ThreadData<Spec>
@@ -171,10 +387,7 @@ where
     Spec::ExtraThreadData: Default,
 {
     fn default() -> Self {
-        Self {
-            policy_data: Default::default(),
-            extra_data: Default::default(),
-        }
+        Self::new(Default::default(), Default::default())
     }
 }
 
@@ -182,7 +395,9 @@ pub type MoveEvaluation<Spec> = <<Spec as MCTS>::TreePolicy as TreePolicy<Spec>>
 pub type StateEvaluation<Spec> = <<Spec as MCTS>::Eval as Evaluator<Spec>>::StateEvaluation;
 pub type Move<Spec> = <<Spec as MCTS>::State as GameState>::Move;
 pub type MoveList<Spec> = <<Spec as MCTS>::State as GameState>::MoveList;
-pub type Player<Spec> = <<Spec as MCTS>::State as GameState>::Player;
+pub type PlayerOf<Spec> = <<Spec as MCTS>::State as GameState>::Player;
+#[deprecated(note = "renamed to `PlayerOf`, which doesn't clash with games' own `Player` types")]
+pub type Player<Spec> = PlayerOf<Spec>;
 pub type TreePolicyThreadData<Spec> =
     <<Spec as MCTS>::TreePolicy as TreePolicy<Spec>>::ThreadLocalData;
 
@@ -191,18 +406,46 @@ pub trait GameState: Clone {
     type Player: Sync + Send + Clone + PartialEq;
     type MoveList: std::iter::IntoIterator<Item = Self::Move>;
 
-    fn current_player(&self) -> Self::Player;
+    /// The player to move. It's borrowed so that player types that are costly to
+    /// clone, such as ones with names or ids, aren't cloned at every ply of a
+    /// playout: the search clones each distinct player once per playout. Games that
+    /// compute the player rather than store it can return a reference to a constant,
+    /// like `&()` or `if black { &Color::Black } else { &Color::White }`.
+    fn current_player(&self) -> &Self::Player;
     fn available_moves(&self) -> Self::MoveList;
+    #[allow(clippy::result_unit_err)]
     fn make_move(&mut self, mov: &Self::Move) -> Result<(), ()>;
     fn get_winner(&self) -> Option<Self::Player>;
     fn is_terminal(&self) -> bool {
         self.available_moves().into_iter().next().is_none()
     }
+    /// Children are stored in decreasing order of this hint (ties keep the order of
+    /// `available_moves`). Combined with `TieBreak::FirstMove`, this decides which
+    /// unvisited children are tried first.
+    fn move_order_hint(&self, _mov: &Self::Move) -> i32 {
+        0
+    }
+}
+
+/// A game state that can update its hash as moves are made, e.g. with Zobrist hashing.
+/// Use it with `IncrementalTable` to avoid hashing whole states during the search.
+pub trait IncrementallyHashable: GameState {
+    /// The hash of this state, computed from scratch.
+    fn full_hash(&self) -> u64;
+    /// The hash of the state after `mov` is made, given that this state's hash is `current`.
+    fn hash_after(&self, current: u64, mov: &Self::Move) -> u64;
 }
 
 pub trait Evaluator<Spec: MCTS> {
     type StateEvaluation: Sync + Send + Clone;
 
+    /// Evaluates a state the search hasn't seen, and each of its moves.
+    ///
+    /// `handle` is `Some` for every state reached by a playout, and its `path_moves`
+    /// lead from the root state to `state`. It is `None` for the root state, which is
+    /// evaluated when the tree is created and again by
+    /// `SearchTree::refresh_root_evaluation`, and for the states given to
+    /// `SearchTree::preload_states`, which no playout has reached.
     fn evaluate_new_state(
         &self,
         state: &Spec::State,
@@ -220,8 +463,70 @@ pub trait Evaluator<Spec: MCTS> {
     fn interpret_evaluation_for_player(
         &self,
         evaluation: &Self::StateEvaluation,
-        player: &Player<Spec>,
+        player: &PlayerOf<Spec>,
     ) -> f64;
+
+    /// The `NodeData` of a node whose state was just evaluated to `evaluation`. Called
+    /// once for each node, right after `evaluate_new_state`, and never for a state
+    /// found in the transposition table. The default is `NodeData::default()`.
+    ///
+    /// Policies read it through `MoveInfo::child` and `NodeHandle::data`, and
+    /// evaluators and hooks through `SearchHandle::node_data`.
+    fn make_node_data(&self, _evaluation: &Self::StateEvaluation) -> Spec::NodeData {
+        Default::default()
+    }
+
+    /// Called once for every node `evaluate_new_state` evaluated, with its
+    /// evaluation, when the node is freed. Resources kept for the node outside the
+    /// tree can be released here; since there's no `self`, the evaluation has to say
+    /// where they are. Clones of it passed to `evaluate_existing_state` or
+    /// backpropagation aren't reported.
+    ///
+    /// Nodes are only freed when no search is running: when the tree is dropped or
+    /// reset, on the thread doing so. Nodes discarded during a search, because
+    /// another thread expanded the same move first or the transposition table
+    /// already had the state, are kept until then.
+    fn on_node_dropped(_evaluation: &Self::StateEvaluation) {}
+}
+
+/// Shares an evaluator, and whatever it caches, between managers: a spec whose `Eval`
+/// is `Arc<E>` can be given a clone of the same `Arc` every time a manager is made.
+impl<Spec: MCTS, E: Evaluator<Spec>> Evaluator<Spec> for Arc<E> {
+    type StateEvaluation = E::StateEvaluation;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Spec::State,
+        moves: &MoveList<Spec>,
+        handle: Option<SearchHandle<Spec>>,
+    ) -> (Vec<MoveEvaluation<Spec>>, Self::StateEvaluation) {
+        (**self).evaluate_new_state(state, moves, handle)
+    }
+
+    fn evaluate_existing_state(
+        &self,
+        state: &Spec::State,
+        existing_evaln: &Self::StateEvaluation,
+        handle: SearchHandle<Spec>,
+    ) -> Self::StateEvaluation {
+        (**self).evaluate_existing_state(state, existing_evaln, handle)
+    }
+
+    fn interpret_evaluation_for_player(
+        &self,
+        evaluation: &Self::StateEvaluation,
+        player: &PlayerOf<Spec>,
+    ) -> f64 {
+        (**self).interpret_evaluation_for_player(evaluation, player)
+    }
+
+    fn make_node_data(&self, evaluation: &Self::StateEvaluation) -> Spec::NodeData {
+        (**self).make_node_data(evaluation)
+    }
+
+    fn on_node_dropped(evaluation: &Self::StateEvaluation) {
+        E::on_node_dropped(evaluation)
+    }
 }
 
 pub struct MCTSManager<Spec: MCTS> {
@@ -229,27 +534,52 @@ pub struct MCTSManager<Spec: MCTS> {
     // thread local data when we have no asynchronous workers
     single_threaded_tld: Option<ThreadData<Spec>>,
     print_on_playout_error: bool,
+    seed: Option<u64>,
+    resign_patience: usize,
+    resign_streak: usize,
+    last_search_degraded: bool,
+    chunked: Option<ChunkedSearch>,
+}
+
+/// A search run in chunks by `MCTSManager::playout_chunk`.
+struct ChunkedSearch {
+    checkpoint: Checkpoint,
+    target: Option<u64>,
+    playouts: u64,
+    /// Time spent inside `playout_chunk`, leaving out the time between chunks.
+    busy: Duration,
 }
 
+/// How many consecutive hopeless `should_resign` queries it takes to resign, unless
+/// changed with `MCTSManager::resign_patience`.
+pub const DEFAULT_RESIGN_PATIENCE: usize = 3;
+
 impl<Spec: MCTS + Sync> MCTSManager<Spec>
 where
     ThreadData<Spec>: Default,
     Spec::Eval: Sync,
 {
-    unsafe fn spawn_worker_thread(&self, stop_signal: Arc<AtomicBool>) -> JoinHandle<()> {
+    unsafe fn spawn_worker_thread(
+        &self,
+        stop_signal: Arc<AtomicBool>,
+        seed: u64,
+        thread_index: usize,
+    ) -> JoinHandle<()> {
         let search_tree = &self.search_tree;
         let print_on_playout_error = self.print_on_playout_error;
         crossbeam::spawn_unsafe(move || {
-            let mut tld = Default::default();
+            let mut tld = thread_data(search_tree, seed, thread_index);
             loop {
-                if stop_signal.load(Ordering::SeqCst) {
+                // Relaxed: the flag guards no data, and the tree is only read
+                // again after the thread is joined, which orders everything.
+                if stop_signal.load(Ordering::Relaxed) {
                     break;
                 }
                 if !search_tree.playout(&mut tld) {
                     if print_on_playout_error {
                         eprintln!(
                             "Node limit of {} reached. Halting search.",
-                            search_tree.spec().node_limit()
+                            search_tree.node_limit()
                         );
                     }
                     break;
@@ -260,10 +590,11 @@ where
     pub fn playout_parallel_async<'a>(&'a mut self, num_threads: usize) -> AsyncSearch<'a, Spec> {
         assert!(num_threads != 0);
         let stop_signal = Arc::new(AtomicBool::new(false));
+        let seed = self.master_seed();
         let threads = (0..num_threads)
-            .map(|_| {
+            .map(|i| {
                 let stop_signal = stop_signal.clone();
-                unsafe { self.spawn_worker_thread(stop_signal) }
+                unsafe { self.spawn_worker_thread(stop_signal, seed, i) }
             })
             .collect();
         AsyncSearch {
@@ -276,10 +607,11 @@ where
         assert!(num_threads != 0);
         let self_box = Box::new(self);
         let stop_signal = Arc::new(AtomicBool::new(false));
+        let seed = self_box.master_seed();
         let threads = (0..num_threads)
-            .map(|_| {
+            .map(|i| {
                 let stop_signal = stop_signal.clone();
-                unsafe { self_box.spawn_worker_thread(stop_signal) }
+                unsafe { self_box.spawn_worker_thread(stop_signal, seed, i) }
             })
             .collect();
         AsyncSearchOwned {
@@ -288,32 +620,115 @@ where
             threads,
         }
     }
-    pub fn playout_parallel_for(&mut self, duration: Duration, num_threads: usize) {
-        let search = self.playout_parallel_async(num_threads);
-        std::thread::sleep(duration);
-        search.halt();
+    /// Runs playouts on `num_threads` threads until `duration` has passed, as
+    /// measured by the `clock` module. Each thread checks the time before every
+    /// playout, so it stops at most one playout late. Evaluators that take long can
+    /// cut that short by checking `SearchHandle::deadline_exceeded`.
+    ///
+    /// The summary's `thread_playouts` has each thread's number of playouts.
+    ///
+    /// # Panics
+    ///
+    /// With the `wasm` feature, if no clock has been installed with
+    /// `clock::set_clock`.
+    pub fn playout_parallel_for(
+        &mut self,
+        duration: Duration,
+        num_threads: usize,
+    ) -> SearchSummary {
+        self.playout_parallel_for_on(duration, num_threads, &SpawnThreads)
+    }
+    /// `playout_parallel_for` with its `workers` run by `spawner`, such as a
+    /// `rayon::ThreadPool` with the `rayon` feature, instead of on threads spawned
+    /// for the search. The summary's `thread_playouts` has each worker's number of
+    /// playouts.
+    pub fn playout_parallel_for_on<S: Spawner + ?Sized>(
+        &mut self,
+        duration: Duration,
+        workers: usize,
+        spawner: &S,
+    ) -> SearchSummary {
+        assert!(workers != 0);
+        assert!(
+            clock::has_clock(),
+            "playout_parallel_for needs a clock; install one with mcts::clock::set_clock"
+        );
+        let checkpoint = Checkpoint::new(&self.search_tree);
+        if let Some(summary) = self.forced_move_search(&checkpoint) {
+            return summary;
+        }
+        let deadline = clock::Instant::now() + duration;
+        let search_tree = &self.search_tree;
+        let print_on_playout_error = self.print_on_playout_error;
+        let seed = self.master_seed();
+        let thread_playouts: Vec<_> = (0..workers).map(|_| AtomicU64::new(0)).collect();
+        spawner.run(workers, &|i| {
+            let mut tld = thread_data(search_tree, seed, i);
+            tld.deadline = Some(deadline);
+            let mut playouts = 0;
+            while clock::Instant::now() < deadline {
+                if !search_tree.playout(&mut tld) {
+                    if print_on_playout_error {
+                        eprintln!(
+                            "Node limit of {} reached. Halting search.",
+                            search_tree.node_limit()
+                        );
+                    }
+                    break;
+                }
+                playouts += 1;
+            }
+            thread_playouts[i].store(playouts, Ordering::Relaxed);
+        });
+        let mut summary = self.finish_search(&checkpoint);
+        summary.thread_playouts = thread_playouts
+            .into_iter()
+            .map(|x| x.into_inner())
+            .collect();
+        summary
+    }
+    pub fn playout_n_parallel(&mut self, n: u32, num_threads: usize) -> SearchSummary {
+        self.playout_n_parallel_on(n, num_threads, &SpawnThreads)
     }
-    pub fn playout_n_parallel(&mut self, n: u32, num_threads: usize) {
+    /// `playout_n_parallel` with its `workers` run by `spawner`, such as a
+    /// `rayon::ThreadPool` with the `rayon` feature, instead of on threads spawned
+    /// for the search. Each worker's thread data is made when it starts and dropped
+    /// when it returns, seeded as the thread with its index would be, so the pool
+    /// keeps nothing from one search to the next.
+    pub fn playout_n_parallel_on<S: Spawner + ?Sized>(
+        &mut self,
+        n: u32,
+        workers: usize,
+        spawner: &S,
+    ) -> SearchSummary {
+        let checkpoint = Checkpoint::new(&self.search_tree);
         if n == 0 {
-            return;
+            return self.finish_search(&checkpoint);
         }
-        assert!(num_threads != 0);
+        if let Some(summary) = self.forced_move_search(&checkpoint) {
+            return summary;
+        }
+        assert!(workers != 0);
+        self.search_tree.enable_root_floor(Some(n as u64));
         let counter = AtomicIsize::new(n as isize);
         let search_tree = &self.search_tree;
-        crossbeam::scope(|scope| {
-            for _ in 0..num_threads {
-                scope.spawn(|| {
-                    let mut tld = Default::default();
-                    loop {
-                        let count = counter.fetch_sub(1, Ordering::SeqCst);
-                        if count <= 0 {
-                            break;
-                        }
-                        search_tree.playout(&mut tld);
-                    }
-                });
+        let progress = &ProgressReporter::start();
+        let seed = self.master_seed();
+        spawner.run(workers, &|i| {
+            let mut tld = thread_data(search_tree, seed, i);
+            loop {
+                // Each count is taken once whatever the ordering, as
+                // read-modify-writes are atomic on their own.
+                let count = counter.fetch_sub(1, Ordering::Relaxed);
+                if count <= 0 {
+                    break;
+                }
+                search_tree.playout(&mut tld);
+                progress.report(n as u64 + 1 - count as u64);
             }
         });
+        search_tree.enable_root_floor(None);
+        self.finish_search(&checkpoint)
     }
     pub fn perf_test<F>(&mut self, num_threads: usize, mut f: F)
     where
@@ -324,7 +739,7 @@ where
             let n1 = search.manager.search_tree.num_nodes();
             std::thread::sleep(Duration::from_secs(1));
             let n2 = search.manager.search_tree.num_nodes();
-            let diff = if n2 > n1 { n2 - n1 } else { 0 };
+            let diff = n2.saturating_sub(n1);
             f(diff);
         }
     }
@@ -352,18 +767,250 @@ where
             search_tree,
             single_threaded_tld,
             print_on_playout_error: true,
+            seed: None,
+            resign_patience: DEFAULT_RESIGN_PATIENCE,
+            resign_streak: 0,
+            last_search_degraded: false,
+            chunked: None,
         }
     }
 
+    /// See `MCTSManagerBuilder`.
+    pub fn builder(spec: Spec) -> MCTSManagerBuilder<Spec> {
+        MCTSManagerBuilder::new(spec)
+    }
+
+    /// Sets the master seed that searches derive their threads' seeds from: thread
+    /// `i` gets `splitmix64(seed ^ i)` for its `TreePolicy::seeded_thread_data`.
+    /// With `None`, each search draws a new master seed from entropy. Policies that
+    /// can't be seeded get `Default` thread data either way.
+    pub fn seed(&mut self, seed: Option<u64>) -> &mut Self {
+        self.seed = seed;
+        self.single_threaded_tld = None;
+        self
+    }
+
+    fn master_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(random_seed)
+    }
+
+    /// Overrides `MCTS::node_limit`.
+    pub fn max_nodes(&mut self, limit: usize) -> &mut Self {
+        self.search_tree.set_node_limit(limit);
+        self
+    }
+
+    /// Visits every root move `visits` times before the tree policy chooses at the
+    /// root; see `SearchTree::set_min_root_visits`. `playout_n` and
+    /// `playout_n_parallel` skip this when they have fewer playouts than that takes.
+    /// Such playouts are counted in `SearchSummary::floor_visits`.
+    pub fn min_root_visits(&mut self, visits: u64) -> &mut Self {
+        self.search_tree.set_min_root_visits(visits);
+        self
+    }
+
+    /// With probability `epsilon`, each playout takes a uniformly random root move
+    /// instead of the tree policy's; see `SearchTree::set_root_noise`.
+    pub fn root_noise(&mut self, epsilon: f64) -> &mut Self {
+        self.search_tree.set_root_noise(epsilon);
+        self
+    }
+
+    /// Allocates the nodes that `playouts_hint` playouts are expected to create
+    /// (see `MCTS::expected_nodes_per_playout`) and grows the transposition table to
+    /// match, so that the search doesn't pay for it as it goes. See
+    /// `SearchTree::reserve` and `memory_stats`.
+    pub fn reserve(&mut self, playouts_hint: u64) -> &mut Self {
+        let per_playout = self.search_tree.spec().expected_nodes_per_playout();
+        let nodes = (playouts_hint as f64 * per_playout).ceil();
+        self.search_tree.reserve(nodes as usize);
+        self
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        self.search_tree.memory_stats()
+    }
+
+    /// The value of the root position for the player to move there: the mean of
+    /// the root's moves' rewards, weighted by their visits, with its standard error
+    /// under the `reward_variance` feature. `None` before any playouts, or when the
+    /// root has no moves.
+    ///
+    /// The rewards of the moves that were explored rather than chosen are part of the
+    /// mean, so it approaches the value of best play from below as the search goes on.
+    pub fn root_value(&self) -> Option<ValueEstimate> {
+        self.search_tree.root_value()
+    }
+
+    /// A view of the root's statistics that other threads can read while the search
+    /// runs; see `LiveStats`. Take it before starting the workers: it follows this
+    /// manager's tree, across moves of the manager but not across `reset`.
+    pub fn live_stats(&self) -> LiveStats<Move<Spec>> {
+        self.search_tree.live_stats()
+    }
+
+    /// Whether the last of `playout_n`, `playout_for`, `playout_n_parallel`,
+    /// `playout_parallel_for` and chunked searches was cut short by the node limit; see
+    /// `SearchSummary::expansion_halted`. Its statistics are usable, but weaker than
+    /// its budget would have made them, which resignation and training data filters
+    /// may want to take into account.
+    pub fn last_search_degraded(&self) -> bool {
+        self.last_search_degraded
+    }
+
+    fn finish_search(&mut self, checkpoint: &Checkpoint) -> SearchSummary {
+        let summary = checkpoint.finish(&self.search_tree);
+        self.last_search_degraded = summary.expansion_halted;
+        summary
+    }
+
+    /// Whether the root has a forced move that `MCTS::stop_on_forced_move` says not
+    /// to search.
+    fn stops_on_forced_move(&self) -> bool {
+        self.search_tree.spec().stop_on_forced_move()
+            && self.search_tree.forced_root_move().is_some()
+    }
+
+    /// The summary of a search that stops before it starts, because the root has a
+    /// forced move, or `None` if the search should go ahead.
+    fn forced_move_search(&mut self, checkpoint: &Checkpoint) -> Option<SearchSummary> {
+        if !self.stops_on_forced_move() {
+            return None;
+        }
+        if self.search_tree.forced_root_move().unwrap().visits() == 0 {
+            // So that `best_move` has a visited move to report.
+            self.playout();
+        }
+        let mut summary = self.finish_search(checkpoint);
+        summary.stop_reason = StopReason::ForcedMove;
+        Some(summary)
+    }
+
+    /// Whether the position is hopeless for the player to move: `root_value` has been
+    /// below `-threshold`, after at least `min_playouts` playouts, for this query and
+    /// the ones before it, `resign_patience` queries in a row. A single query can be
+    /// fooled by noise; several, made as the search goes on, rarely are.
+    ///
+    /// Any other query, including one made with fewer than `min_playouts` playouts,
+    /// starts the count again. Rewards must be on a scale where `threshold` makes
+    /// sense, such as -1 to 1.
+    pub fn should_resign(&mut self, threshold: f64, min_playouts: u64) -> bool {
+        let hopeless = match self.root_value() {
+            Some(value) => value.visits >= min_playouts && value.mean < -threshold,
+            None => false,
+        };
+        if hopeless {
+            self.resign_streak += 1;
+        } else {
+            self.resign_streak = 0;
+        }
+        self.resign_streak >= self.resign_patience
+    }
+
+    /// Sets how many consecutive hopeless queries `should_resign` waits for. Starts
+    /// as `DEFAULT_RESIGN_PATIENCE`.
+    pub fn resign_patience(&mut self, queries: usize) -> &mut Self {
+        self.resign_patience = queries.max(1);
+        self
+    }
+
+    /// The number of consecutive hopeless `should_resign` queries so far.
+    pub fn resign_streak(&self) -> usize {
+        self.resign_streak
+    }
+
+    /// Carries a streak over, for example from the manager that searched the
+    /// previous position of the game.
+    pub fn set_resign_streak(&mut self, streak: usize) -> &mut Self {
+        self.resign_streak = streak;
+        self
+    }
+
+    /// Updates `table` during backpropagation, for `HistoryPolicy` to read. Pass the
+    /// same table to the manager for the next move to keep what was learned.
+    pub fn set_history_table(&mut self, table: Option<Arc<HistoryTable<Move<Spec>>>>) -> &mut Self {
+        self.search_tree.set_history_table(table);
+        self
+    }
+
+    pub fn history_table(&self) -> Option<&Arc<HistoryTable<Move<Spec>>>> {
+        self.search_tree.history_table()
+    }
+
+    /// Updates `stats` during backpropagation. Unlike a history table, it belongs to
+    /// the caller and is only written to; pass it to every manager of a game to
+    /// gather statistics over all of its searches.
+    pub fn set_shared_stats(
+        &mut self,
+        stats: Option<Arc<dyn SharedStats<Move<Spec>>>>,
+    ) -> &mut Self {
+        self.search_tree.set_shared_stats(stats);
+        self
+    }
+
+    pub fn shared_stats(&self) -> Option<&Arc<dyn SharedStats<Move<Spec>>>> {
+        self.search_tree.shared_stats()
+    }
+
     pub fn print_on_playout_error(&mut self, v: bool) -> &mut Self {
         self.print_on_playout_error = v;
         self
     }
 
+    /// Records the move indices and evaluation of the most recent playouts (1024 by
+    /// default; see `record_playouts_with_capacity`), for use with `replay`.
+    pub fn record_playouts(&mut self, v: bool) -> &mut Self {
+        self.record_playouts_with_capacity(v, 1024)
+    }
+
+    pub fn record_playouts_with_capacity(&mut self, v: bool, capacity: usize) -> &mut Self {
+        self.search_tree.record_playouts(v, capacity);
+        self
+    }
+
+    /// Times the phases of every 64th playout on each thread, so that
+    /// `SearchSummary::phase_breakdown` is available.
+    pub fn profile_phases(&mut self, v: bool) -> &mut Self {
+        self.profile_phases_with_interval(v, 64)
+    }
+
+    pub fn profile_phases_with_interval(&mut self, v: bool, interval: usize) -> &mut Self {
+        self.search_tree
+            .profile_phases(if v { interval.max(1) } else { 0 });
+        self
+    }
+
+    /// Samples the root's best move and value every 64th playout, keeping the last
+    /// 32 samples, for `stability_report`.
+    pub fn track_stability(&mut self, v: bool) -> &mut Self {
+        self.track_stability_with(v, 64, 32)
+    }
+
+    pub fn track_stability_with(&mut self, v: bool, interval: u64, capacity: usize) -> &mut Self {
+        self.search_tree
+            .track_stability(if v { interval.max(1) } else { 0 }, capacity);
+        self
+    }
+
+    /// How often the best move changed, and how much the root value varied, over
+    /// the samples kept by `track_stability`. A search that still flips between
+    /// moves hasn't settled, and may be worth extending.
+    pub fn stability_report(&self) -> StabilityReport {
+        StabilityReport::new(self.search_tree.stability_samples())
+    }
+
+    pub fn recorded_playouts(&self) -> Vec<PlayoutRecord<Spec>> {
+        self.search_tree.recorded_playouts()
+    }
+
+    pub fn replay(&self, record: &PlayoutRecord<Spec>) -> Option<Spec::State> {
+        self.search_tree.replay(record)
+    }
+
     pub fn playout(&mut self) {
         // Avoid overhead of thread creation
         if self.single_threaded_tld.is_none() {
-            self.single_threaded_tld = Some(Default::default());
+            self.single_threaded_tld = Some(thread_data(&self.search_tree, self.master_seed(), 0));
         }
         self.search_tree
             .playout(self.single_threaded_tld.as_mut().unwrap());
@@ -373,39 +1020,476 @@ where
             self.playout();
         }
     }
-    pub fn playout_n(&mut self, n: usize) {
-        for _ in 0..n {
+    /// Like `playout_n`, but stops early once `stop` is met, with
+    /// `StopReason::Condition`. The condition is checked before the first playout
+    /// and then every `StopWhen::interval` playouts.
+    pub fn playout_n_until(&mut self, n: usize, stop: &StopWhen) -> SearchSummary {
+        let checkpoint = Checkpoint::new(&self.search_tree);
+        if n > 0 {
+            if let Some(summary) = self.forced_move_search(&checkpoint) {
+                return summary;
+            }
+        }
+        let progress = ProgressReporter::start();
+        self.search_tree.enable_root_floor(Some(n as u64));
+        let mut met = false;
+        for i in 0..n {
+            if (i as u64).is_multiple_of(stop.interval()) && stop.is_met(&self.search_tree) {
+                met = true;
+                break;
+            }
+            self.playout();
+            progress.report(i as u64 + 1);
+        }
+        self.search_tree.enable_root_floor(None);
+        let mut summary = self.finish_search(&checkpoint);
+        if met {
+            summary.stop_reason = StopReason::Condition;
+        }
+        summary
+    }
+    pub fn playout_n(&mut self, n: usize) -> SearchSummary {
+        let checkpoint = Checkpoint::new(&self.search_tree);
+        if n > 0 {
+            if let Some(summary) = self.forced_move_search(&checkpoint) {
+                return summary;
+            }
+        }
+        let progress = ProgressReporter::start();
+        self.search_tree.enable_root_floor(Some(n as u64));
+        for i in 0..n {
             self.playout();
+            progress.report(i as u64 + 1);
         }
+        self.search_tree.enable_root_floor(None);
+        self.finish_search(&checkpoint)
+    }
+    /// Runs playouts on the current thread until `duration` has passed, as measured
+    /// by the `clock` module.
+    ///
+    /// # Panics
+    ///
+    /// With the `wasm` feature, if no clock has been installed with
+    /// `clock::set_clock`.
+    pub fn playout_for(&mut self, duration: Duration) -> SearchSummary {
+        assert!(
+            clock::has_clock(),
+            "playout_for needs a clock; install one with mcts::clock::set_clock"
+        );
+        let checkpoint = Checkpoint::new(&self.search_tree);
+        if let Some(summary) = self.forced_move_search(&checkpoint) {
+            return summary;
+        }
+        let progress = ProgressReporter::start();
+        let deadline = clock::Instant::now() + duration;
+        if self.single_threaded_tld.is_none() {
+            self.single_threaded_tld = Some(thread_data(&self.search_tree, self.master_seed(), 0));
+        }
+        self.single_threaded_tld.as_mut().unwrap().deadline = Some(deadline);
+        let mut playouts = 0;
+        while clock::Instant::now() < deadline {
+            self.playout();
+            playouts += 1;
+            progress.report(playouts);
+        }
+        self.single_threaded_tld.as_mut().unwrap().deadline = None;
+        self.finish_search(&checkpoint)
     }
 
-    pub fn principal_variation_info(&self, num_moves: usize) -> Vec<MoveInfoHandle<Spec>> {
+    /// Starts a chunked search (see `playout_chunk`) aiming for `target` playouts, or
+    /// no target, dropping the progress of any chunked search before it.
+    pub fn chunk_target(&mut self, target: Option<u64>) -> &mut Self {
+        self.chunked = Some(ChunkedSearch {
+            checkpoint: Checkpoint::new(&self.search_tree),
+            target,
+            playouts: 0,
+            busy: Duration::ZERO,
+        });
+        self
+    }
+
+    /// Runs playouts on the current thread until `max_playouts` have run,
+    /// `max_duration` has passed, the target set with `chunk_target` is reached or
+    /// the tree reaches its node limit, whichever comes first. For event loops that
+    /// can neither block for a whole search nor run it on other threads: each call
+    /// returns quickly, and the next one carries on where it left off, so a search
+    /// run in chunks plays out exactly like one `playout_n` call.
+    ///
+    /// Starts a chunked search without a target if none is running. Its summary
+    /// accumulates over the chunks; see `chunked_summary`.
+    ///
+    /// # Panics
+    ///
+    /// With the `wasm` feature, if no clock has been installed with
+    /// `clock::set_clock`.
+    pub fn playout_chunk(&mut self, max_playouts: usize, max_duration: Duration) -> ChunkResult {
+        assert!(
+            clock::has_clock(),
+            "playout_chunk needs a clock; install one with mcts::clock::set_clock"
+        );
+        if self.chunked.is_none() {
+            self.chunk_target(None);
+        }
+        let start = clock::Instant::now();
+        if self.single_threaded_tld.is_none() {
+            self.single_threaded_tld = Some(thread_data(&self.search_tree, self.master_seed(), 0));
+        }
+        let forced_move = self.stops_on_forced_move();
+        let max_playouts = if forced_move {
+            // At most the one playout `best_move` needs.
+            (self.search_tree.forced_root_move().unwrap().visits() == 0) as usize
+        } else {
+            max_playouts
+        };
+        let chunked = self.chunked.as_mut().unwrap();
+        let left = chunked
+            .target
+            .map_or(u64::MAX, |x| x.saturating_sub(chunked.playouts));
+        let max_playouts = left.min(max_playouts as u64);
+        let tld = self.single_threaded_tld.as_mut().unwrap();
+        self.search_tree.enable_root_floor(chunked.target);
+        let mut playouts = 0;
+        let mut node_limit_reached = false;
+        while playouts < max_playouts && start.elapsed() < max_duration {
+            if !self.search_tree.playout(tld) {
+                node_limit_reached = true;
+                break;
+            }
+            playouts += 1;
+        }
+        self.search_tree.enable_root_floor(None);
+        chunked.playouts += playouts;
+        chunked.busy += start.elapsed();
+        ChunkResult {
+            playouts,
+            total_playouts: chunked.playouts,
+            target_reached: chunked.target.is_some_and(|x| chunked.playouts >= x),
+            node_limit_reached,
+            forced_move,
+        }
+    }
+
+    /// The summary of the chunked search so far, with `elapsed` the time spent in
+    /// `playout_chunk`. Anything else searched from this manager since the chunked
+    /// search started is counted too. `None` if no chunked search is running.
+    pub fn chunked_summary(&self) -> Option<SearchSummary> {
+        let chunked = self.chunked.as_ref()?;
+        let mut summary = chunked.checkpoint.finish(&self.search_tree);
+        summary.elapsed = chunked.busy;
+        Some(summary)
+    }
+
+    /// Ends the chunked search, returning its summary, which `last_search_degraded`
+    /// then reports on. `None` if no chunked search is running.
+    pub fn finish_chunks(&mut self) -> Option<SearchSummary> {
+        let summary = self.chunked_summary()?;
+        self.chunked = None;
+        self.last_search_degraded = summary.expansion_halted;
+        Some(summary)
+    }
+
+    pub fn principal_variation_info(&self, num_moves: usize) -> Vec<MoveInfoHandle<'_, Spec>> {
         self.search_tree.principal_variation(num_moves)
     }
+    /// See `SearchTree::principal_variation`. Empty before any playouts or when the
+    /// root has no moves.
     pub fn principal_variation(&self, num_moves: usize) -> Vec<Move<Spec>> {
+        self.principal_variation_refs(num_moves)
+            .into_iter()
+            .cloned()
+            .collect()
+    }
+    /// `principal_variation` without cloning its moves: they're borrowed from the
+    /// tree, for as long as the manager is.
+    pub fn principal_variation_refs(&self, num_moves: usize) -> Vec<&Move<Spec>> {
         self.search_tree
             .principal_variation(num_moves)
             .into_iter()
             .map(|x| x.get_move())
-            .map(|x| x.clone())
             .collect()
     }
+    /// See `SearchTree::principal_variation_reliable`. Each move comes with its visit
+    /// count, so that callers can show how far the line is to be trusted.
+    pub fn principal_variation_reliable(
+        &self,
+        max_depth: usize,
+        min_visits: u64,
+    ) -> Vec<(Move<Spec>, u64)> {
+        self.search_tree
+            .principal_variation_reliable(max_depth, min_visits)
+            .into_iter()
+            .map(|x| (x.get_move().clone(), x.visits()))
+            .collect()
+    }
+    /// See `SearchTree::annotated_principal_variation`. For reviewing a search: a
+    /// large negative `delta` shows where the line turned against the player moving.
+    pub fn annotated_principal_variation(&self, num_moves: usize) -> Vec<AnnotatedPly<'_, Spec>> {
+        self.search_tree.annotated_principal_variation(num_moves)
+    }
+    /// See `SearchTree::annotated_principal_variation_with`.
+    pub fn annotated_principal_variation_with(
+        &self,
+        num_moves: usize,
+        kind: NodeValueKind,
+    ) -> Vec<AnnotatedPly<'_, Spec>> {
+        self.search_tree
+            .annotated_principal_variation_with(num_moves, kind)
+    }
+    /// The root state followed by the states along `principal_variation`. Empty,
+    /// without the root state, when the principal variation is empty.
     pub fn principal_variation_states(&self, num_moves: usize) -> Vec<Spec::State> {
-        let moves = self.principal_variation(num_moves);
+        self.states_along(self.principal_variation_refs(num_moves))
+    }
+    /// The root state followed by the states along `principal_variation_reliable`.
+    pub fn principal_variation_states_reliable(
+        &self,
+        max_depth: usize,
+        min_visits: u64,
+    ) -> Vec<Spec::State> {
+        let moves = self
+            .search_tree
+            .principal_variation_reliable(max_depth, min_visits);
+        self.states_along(moves.into_iter().map(|x| x.get_move()).collect())
+    }
+    fn states_along(&self, moves: Vec<&Move<Spec>>) -> Vec<Spec::State> {
+        if moves.is_empty() {
+            return Vec::new();
+        }
         let mut states = vec![self.search_tree.root_state().clone()];
         for mov in moves {
             let mut state = states[states.len() - 1].clone();
-            if state.make_move(&mov).is_ok() {
+            if state.make_move(mov).is_ok() {
                 states.push(state);
             }
         }
         states
     }
+    pub fn root_move_stats_detailed(&self) -> Vec<DetailedMoveStats<'_, Spec>> {
+        self.search_tree.root_move_stats_detailed()
+    }
+    /// See `SearchTree::root_move_stats_ref`.
+    pub fn root_move_stats_ref(&self) -> Vec<RootMoveStats<'_, Move<Spec>>> {
+        self.search_tree.root_move_stats_ref()
+    }
+    /// See `SearchTree::root_move_stats_with`.
+    pub fn root_move_stats_with(&self, kind: NodeValueKind) -> Vec<RootMoveStats<'_, Move<Spec>>> {
+        self.search_tree.root_move_stats_with(kind)
+    }
+    /// The number of the root's moves that `MCTS::max_children` discarded. They
+    /// don't appear in `root_move_stats_detailed`.
+    pub fn root_pruned_moves(&self) -> usize {
+        self.search_tree.root_node().pruned_moves()
+    }
+    /// Excludes the root move equal to `mov` from the search, keeping its
+    /// statistics, as `NodeHandle::exclude_child` does. Returns false if no root move
+    /// is equal to it or it was already excluded.
+    pub fn exclude_root_move(&mut self, mov: &Move<Spec>) -> bool
+    where
+        Move<Spec>: PartialEq,
+    {
+        let root = self.search_tree.root_node();
+        match root.moves().position(|x| x.get_move() == mov) {
+            Some(index) => root.exclude_child(index),
+            None => false,
+        }
+    }
+    /// Makes the root move equal to `mov` selectable again, as
+    /// `NodeHandle::include_child` does. Returns false if no root move is equal to
+    /// it or it wasn't excluded.
+    pub fn include_root_move(&mut self, mov: &Move<Spec>) -> bool
+    where
+        Move<Spec>: PartialEq,
+    {
+        let root = self.search_tree.root_node();
+        match root.moves().position(|x| x.get_move() == mov) {
+            Some(index) => root.include_child(index),
+            None => false,
+        }
+    }
+    /// See `SearchTree::preload_states`.
+    pub fn preload_states(&mut self, states: &[Spec::State]) -> PreloadReport {
+        if self.single_threaded_tld.is_none() {
+            self.single_threaded_tld = Some(thread_data(&self.search_tree, self.master_seed(), 0));
+        }
+        self.search_tree
+            .preload_states(states, self.single_threaded_tld.as_mut().unwrap())
+    }
     pub fn tree(&self) -> &SearchTree<Spec> {
         &self.search_tree
     }
+    pub fn spec(&self) -> &Spec {
+        self.search_tree.spec()
+    }
+    pub fn evaluator(&self) -> &Spec::Eval {
+        self.search_tree.evaluator()
+    }
+    pub fn tree_policy(&self) -> &Spec::TreePolicy {
+        self.search_tree.tree_policy()
+    }
+    pub fn root_state(&self) -> &Spec::State {
+        self.search_tree.root_state()
+    }
+    /// See `SearchTree::refresh_root_evaluation`.
+    pub fn refresh_root_evaluation(&mut self) -> usize
+    where
+        MoveEvaluation<Spec>: UpdatableMoveEvaluation,
+        Move<Spec>: PartialEq,
+    {
+        self.search_tree.refresh_root_evaluation()
+    }
+    /// `SearchTree::explain_best_move`, with the three most visited rivals and three
+    /// plies of each continuation. Its `Display` prints a line per move.
+    pub fn explain_best_move(&self) -> MoveExplanation<Spec> {
+        self.search_tree.explain_best_move(3, 3)
+    }
     pub fn best_move(&self) -> Option<Move<Spec>> {
-        self.principal_variation(1).get(0).map(|x| x.clone())
+        self.principal_variation_refs(1).first().map(|&x| x.clone())
+    }
+    /// Draws one of the root's moves at random, with probability proportional to
+    /// `key`, using a `WeightedRng` seeded with `seed`. Unlike `best_move`, this
+    /// gives varied but reasonable play, for example for sparring opponents.
+    /// `None` when the root has no moves, or, for `RootKey::Mean`, no visited moves.
+    pub fn sample_move_weighted(&self, key: RootKey, seed: u64) -> Option<Move<Spec>> {
+        let policy = self.search_tree.tree_policy();
+        let root = self.search_tree.root_node();
+        let candidates = root
+            .moves()
+            .filter(|x| key != RootKey::Mean || x.visits() > 0);
+        WeightedRng::new(seed)
+            .select_by_key(candidates, |x| match key {
+                RootKey::Visits => x.visits() as f64,
+                RootKey::Mean => x.avg_reward(),
+                RootKey::Prior => policy.move_prior(x.move_evaluation()),
+            })
+            .map(|x| x.get_move().clone())
+    }
+    /// Checks `best_move` with `budget` more playouts on the current thread, all
+    /// spent on the two most visited root moves: the others are excluded from
+    /// selection for the duration. The report says how the visits between the two
+    /// moved and whether `best_move` changed. The playouts stay in the tree, but
+    /// aren't a search of their own: `last_search_degraded` still reports on the
+    /// search before, and a single candidate is searched all the same.
+    pub fn verify_best_move(&mut self, budget: usize) -> VerificationReport<Move<Spec>> {
+        let best_before = self.best_root_index();
+        let (before, exclusions): (Vec<(usize, u64)>, _) = {
+            let moves: Vec<_> = self.search_tree.root_node().moves().collect();
+            let mut ranked: Vec<usize> = (0..moves.len())
+                .filter(|&i| !moves[i].is_excluded())
+                .collect();
+            ranked.sort_by_key(|&i| std::cmp::Reverse(moves[i].visits()));
+            ranked.truncate(2);
+            let keep: Vec<_> = ranked.iter().map(|&i| moves[i]).collect();
+            let before = ranked.iter().map(|&i| (i, moves[i].visits())).collect();
+            (before, self.search_tree.exclude_root_moves_except(&keep))
+        };
+        let mut tld = match self.single_threaded_tld.take() {
+            Some(tld) => tld,
+            None => thread_data(&self.search_tree, self.master_seed(), 0),
+        };
+        let checkpoint = Checkpoint::new(&self.search_tree);
+        for _ in 0..budget {
+            self.search_tree.playout(&mut tld);
+        }
+        let playouts = checkpoint.finish(&self.search_tree).playouts;
+        self.single_threaded_tld = Some(tld);
+        drop(exclusions);
+        let moves: Vec<_> = self.search_tree.root_node().moves().collect();
+        let total_before: u64 = before.iter().map(|x| x.1).sum();
+        let total_after: u64 = before.iter().map(|x| moves[x.0].visits()).sum();
+        let candidates = before
+            .iter()
+            .map(|&(i, visits_before)| VerifiedMove {
+                mov: moves[i].get_move().clone(),
+                visits_before,
+                visits_after: moves[i].visits(),
+                share_before: visits_before as f64 / total_before as f64,
+                share_after: moves[i].visits() as f64 / total_after as f64,
+            })
+            .collect();
+        let best_after = self.best_root_index();
+        VerificationReport {
+            candidates,
+            playouts,
+            best_before: best_before.map(|i| moves[i].get_move().clone()),
+            best_after: best_after.map(|i| moves[i].get_move().clone()),
+            changed: best_before != best_after,
+        }
+    }
+    /// The index among the root's moves of the first move of the principal
+    /// variation.
+    fn best_root_index(&self) -> Option<usize> {
+        let best = *self.principal_variation_info(1).first()?;
+        self.search_tree
+            .root_node()
+            .moves()
+            .position(|x| std::ptr::eq(x, best))
+    }
+    /// `best_move`, checked for blunders too shallow for a small search to see: the
+    /// move is rejected if the opponent can answer it by winning at once, and the
+    /// next most visited root move is checked instead. See `TacticalCheck`.
+    pub fn best_move_checked(&self, check: &TacticalCheck) -> TacticalReport<Move<Spec>> {
+        check.check(&self.search_tree)
+    }
+    /// Like `best_move`, but says why there isn't one.
+    pub fn try_best_move(&self) -> Result<Move<Spec>, BestMoveError> {
+        let root = self.search_tree.root_node();
+        if root.moves().next().is_none() {
+            return if self.search_tree.root_state().is_terminal() {
+                Err(BestMoveError::TerminalRoot)
+            } else {
+                Err(BestMoveError::NoLegalMoves)
+            };
+        }
+        self.best_move().ok_or(BestMoveError::NoPlayouts)
+    }
+
+    /// Plays `moves` from the root, as when loading a game record, leaving the
+    /// manager ready to search the final position. Each ply keeps the subtree
+    /// below its move when there is one (see `SearchTree::advance_root`); from the
+    /// first that doesn't, the rest are played on a fresh tree. Fails, leaving the
+    /// manager as it was, if a move can't be made.
+    pub fn replay_moves(&mut self, moves: &[Move<Spec>]) -> Result<ReplaySummary, ReplayError>
+    where
+        Move<Spec>: PartialEq,
+    {
+        let mut state = self.search_tree.root_state().clone();
+        for (index, mov) in moves.iter().enumerate() {
+            if state.is_terminal() {
+                return Err(ReplayError::GameOver { index });
+            }
+            if state.make_move(mov).is_err() {
+                return Err(ReplayError::IllegalMove { index });
+            }
+        }
+        let mut summary = ReplaySummary {
+            plies: moves.len(),
+            retained_plies: 0,
+        };
+        if moves.is_empty() {
+            return Ok(summary);
+        }
+        self.single_threaded_tld = None;
+        self.resign_streak = 0;
+        self.last_search_degraded = false;
+        self.chunked = None;
+        for (ply, mov) in moves.iter().enumerate() {
+            let index = self
+                .search_tree
+                .root_node()
+                .moves()
+                .position(|x| x.get_move() == mov);
+            let kept = index.map(|i| self.search_tree.advance_root(i));
+            if kept == Some(Ok(true)) {
+                summary.retained_plies += 1;
+                continue;
+            }
+            // A move without a subtree already rebuilt the tree after it.
+            if kept != Some(Ok(false)) || ply + 1 < moves.len() {
+                self.search_tree.set_root(state);
+            }
+            break;
+        }
+        Ok(summary)
     }
 
     pub fn reset(self) -> Self {
@@ -413,8 +1497,155 @@ where
             search_tree: self.search_tree.reset(),
             print_on_playout_error: self.print_on_playout_error,
             single_threaded_tld: None,
+            seed: self.seed,
+            resign_patience: self.resign_patience,
+            resign_streak: 0,
+            last_search_degraded: false,
+            chunked: None,
+        }
+    }
+
+    /// Takes the manager apart. The spec, evaluator, policy, root state and
+    /// transposition table are owned by the tree: `SearchTree::into_parts` takes
+    /// them out of it.
+    pub fn into_parts(self) -> ManagerParts<Spec> {
+        ManagerParts {
+            search_tree: self.search_tree,
+            seed: self.seed,
+            print_on_playout_error: self.print_on_playout_error,
+            resign_patience: self.resign_patience,
+            resign_streak: self.resign_streak,
+        }
+    }
+
+    /// Puts a manager back together from `into_parts`, keeping the tree and its
+    /// statistics.
+    pub fn from_parts(parts: ManagerParts<Spec>) -> Self {
+        Self {
+            search_tree: parts.search_tree,
+            single_threaded_tld: None,
+            print_on_playout_error: parts.print_on_playout_error,
+            seed: parts.seed,
+            resign_patience: parts.resign_patience.max(1),
+            resign_streak: parts.resign_streak,
+            last_search_degraded: false,
+            chunked: None,
+        }
+    }
+}
+
+/// Everything an `MCTSManager` owns, from `MCTSManager::into_parts`.
+pub struct ManagerParts<Spec: MCTS> {
+    pub search_tree: SearchTree<Spec>,
+    pub seed: Option<u64>,
+    pub print_on_playout_error: bool,
+    /// See `MCTSManager::resign_patience`.
+    pub resign_patience: usize,
+    /// See `MCTSManager::resign_streak`.
+    pub resign_streak: usize,
+}
+
+/// Crate internals for the model checks in `tests/loom.rs`.
+#[cfg(loom)]
+#[doc(hidden)]
+pub mod loom_checked {
+    use super::*;
+    pub use atomics::PublishedPtr;
+
+    /// Updates a `LiveStats` view as playouts through the root do.
+    pub struct LiveRecorder(Arc<live::LiveRoot<usize>>);
+
+    impl LiveRecorder {
+        /// A root with the moves `0..moves`, none visited yet.
+        pub fn new(moves: usize) -> Self {
+            let moves = (0..moves).map(|x| (x, 0, 0.0));
+            Self(Arc::new(live::LiveRoot::new(moves, 0)))
+        }
+
+        pub fn record(&self, index: usize, reward: f64) {
+            self.0.record(Some(index), reward);
+        }
+
+        pub fn stats(&self) -> LiveStats<usize> {
+            self.0.handle()
+        }
+    }
+
+    /// The statistics of one move, updated as playouts through it update them.
+    pub struct MoveStats(AtomicNodeStats);
+
+    impl Default for MoveStats {
+        fn default() -> Self {
+            Self(AtomicNodeStats::new())
+        }
+    }
+
+    impl MoveStats {
+        /// A playout going through the move, taking its virtual loss.
+        pub fn down(&self, virtual_loss: VirtualLossMode) {
+            self.0.down(virtual_loss);
+        }
+
+        /// The playout's reward coming back, and its virtual loss given back.
+        pub fn up(&self, virtual_loss: VirtualLossMode, reward: f64) {
+            self.0.up(virtual_loss, reward);
+        }
+
+        pub fn visits(&self) -> u64 {
+            self.0.visits.load(Ordering::Relaxed) as u64
         }
+
+        pub fn sum_rewards(&self) -> f64 {
+            self.0.sum_evaluations.load(Ordering::Relaxed)
+        }
+    }
+
+    /// Inserts `value` under `hash` as `TranspositionTable::insert` does, returning
+    /// the value another thread got in first, if any.
+    pub fn table_insert<'a, K, V>(
+        table: &'a ApproxQuadraticProbingHashTable<K, V>,
+        hash: u64,
+        value: &'a V,
+    ) -> Option<&'a V> {
+        table.insert_hash(hash, value)
+    }
+
+    pub fn table_lookup<K, V>(
+        table: &ApproxQuadraticProbingHashTable<K, V>,
+        hash: u64,
+    ) -> Option<&V> {
+        table.lookup_hash(hash)
+    }
+}
+
+/// Thread data for the search thread with index `thread_index`, seeded from the
+/// search's master seed `seed`.
+fn thread_data<Spec: MCTS>(
+    search_tree: &SearchTree<Spec>,
+    seed: u64,
+    thread_index: usize,
+) -> ThreadData<Spec>
+where
+    ThreadData<Spec>: Default,
+{
+    let mut tld: ThreadData<Spec> = Default::default();
+    let seeded = search_tree
+        .tree_policy()
+        .seeded_thread_data(splitmix64(seed ^ thread_index as u64));
+    if let Some(policy_data) = seeded {
+        tld.policy_data = policy_data;
     }
+    tld.seed = Some(seed);
+    tld.noise = Some(splitmix64(splitmix64(seed ^ thread_index as u64)));
+    tld
+}
+
+/// Scrambles `x`, as the SplitMix64 generator does to its state.
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
 }
 
 // https://stackoverflow.com/questions/26998485/rust-print-format-number-with-thousand-separator
@@ -429,6 +1660,35 @@ fn thousands_separate(x: usize) -> String {
     String::from_utf8(result).unwrap()
 }
 
+#[cfg(feature = "events")]
+const PROGRESS_INTERVAL: u64 = 1 << 10;
+
+/// Emits `SearchEvent::Progress` every `PROGRESS_INTERVAL` playouts when the `events`
+/// feature is enabled, and compiles to nothing otherwise.
+struct ProgressReporter {
+    #[cfg(feature = "events")]
+    start: clock::Instant,
+}
+
+impl ProgressReporter {
+    fn start() -> Self {
+        Self {
+            #[cfg(feature = "events")]
+            start: clock::Instant::now(),
+        }
+    }
+    #[inline]
+    fn report(&self, _playouts: u64) {
+        #[cfg(feature = "events")]
+        if _playouts.is_multiple_of(PROGRESS_INTERVAL) {
+            events::emit(|| events::SearchEvent::Progress {
+                playouts: _playouts,
+                elapsed: self.start.elapsed(),
+            });
+        }
+    }
+}
+
 #[must_use]
 pub struct AsyncSearch<'a, Spec: 'a + MCTS> {
     manager: &'a mut MCTSManager<Spec>,
@@ -445,7 +1705,7 @@ impl<'a, Spec: MCTS> AsyncSearch<'a, Spec> {
 
 impl<'a, Spec: MCTS> Drop for AsyncSearch<'a, Spec> {
     fn drop(&mut self) {
-        self.stop_signal.store(true, Ordering::SeqCst);
+        self.stop_signal.store(true, Ordering::Relaxed);
         drain_join_unwrap(&mut self.threads);
     }
 }
@@ -459,7 +1719,7 @@ pub struct AsyncSearchOwned<Spec: MCTS> {
 
 impl<Spec: MCTS> AsyncSearchOwned<Spec> {
     fn stop_threads(&mut self) {
-        self.stop_signal.store(true, Ordering::SeqCst);
+        self.stop_signal.store(true, Ordering::Relaxed);
         drain_join_unwrap(&mut self.threads);
     }
     pub fn halt(mut self) -> MCTSManager<Spec> {
@@ -501,3 +1761,238 @@ pub enum CycleBehaviour<Spec: MCTS> {
     PanicWhenCycleDetected,
     UseThisEvalWhenCycleDetected(StateEvaluation<Spec>),
 }
+
+/// What to do with a state that has no available moves but isn't terminal.
+///
+/// `GameState::is_terminal` defaults to "there are no available moves", so this only
+/// comes into play for games that override it, such as games where a player without
+/// a legal move passes and play continues.
+///
+/// Consecutive passes are the game's responsibility: `is_terminal` should become true
+/// once they end the game. If they don't, the search is only bounded by
+/// `max_playout_length` and the configured `cycle_behaviour`.
+///
+/// For games where such a state is a bug, `Panic` and `SkipPlayout` help find it.
+pub enum NoMovesBehaviour<Spec: MCTS> {
+    /// Treat the state as terminal.
+    Terminal,
+    /// Give the node a single child for this move, with this move evaluation.
+    Pass(Move<Spec>, MoveEvaluation<Spec>),
+    /// Panic, with this description of the state in the message.
+    /// See `NoMovesBehaviour::panic_with_state`.
+    Panic(String),
+    /// Keep the state out of the search: playouts that reach it are abandoned
+    /// without backpropagating anything, and undo their virtual loss. The move
+    /// leading to it stays unvisited, so policies that favour unvisited moves, like
+    /// `UCTPolicy`, will keep choosing it and waste playouts.
+    SkipPlayout,
+}
+
+impl<Spec: MCTS> NoMovesBehaviour<Spec> {
+    /// `Panic` with the state's `Debug` representation.
+    pub fn panic_with_state(state: &Spec::State) -> Self
+    where
+        Spec::State: Debug,
+    {
+        NoMovesBehaviour::Panic(format!("{:?}", state))
+    }
+}
+
+/// How a state's `is_terminal()` and `available_moves()` disagree. See
+/// `MCTS::on_terminality_mismatch`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminalityMismatch {
+    /// `available_moves()` is empty but `is_terminal()` is false, and
+    /// `MCTS::no_moves_behaviour` returned `Panic` for the state.
+    NoMovesNotTerminal,
+    /// `is_terminal()` is true but `available_moves()` isn't empty.
+    TerminalWithMoves,
+}
+
+impl TerminalityMismatch {
+    /// A message with the state's `Debug` representation and the moves leading to it.
+    pub fn describe<S: Debug, M: Debug>(self, state: &S, path: &[M]) -> String {
+        format!("{} after moves {:?} for state {:?}", self, path, state)
+    }
+}
+
+impl std::fmt::Display for TerminalityMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TerminalityMismatch::NoMovesNotTerminal => {
+                write!(f, "available_moves() is empty but is_terminal() is false")
+            }
+            TerminalityMismatch::TerminalWithMoves => {
+                write!(f, "is_terminal() is true but available_moves() isn't empty")
+            }
+        }
+    }
+}
+
+/// How `Evaluator::interpret_evaluation_for_player` relates the players' rewards.
+/// See `MCTS::evaluation_symmetry`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EvaluationSymmetry {
+    /// Interpret the evaluation for every node on the path.
+    #[default]
+    General,
+    /// Every player gets the same reward, so it's interpreted once per playout.
+    Constant,
+    /// There are two players and one's reward is the negation of the other's, so
+    /// the evaluation is interpreted once per playout.
+    ZeroSumTwoPlayer,
+}
+
+/// See `MCTS::non_finite_rewards`. A NaN reward would otherwise make every mean
+/// and score above it NaN for the rest of the search.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NonFiniteRewards {
+    /// Panic in debug builds; backpropagate the reward unchecked in release builds.
+    PanicInDebug,
+    Panic,
+    /// Backpropagate this value instead. Substitutions are counted in
+    /// `SearchSummary::non_finite_rewards`.
+    Substitute(f64),
+}
+
+/// See `MCTS::move_error_behaviour`. Every failed move is counted in
+/// `SearchSummary::move_errors`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveErrorBehaviour {
+    Panic,
+    /// Mark the move excluded, so it's never selected again, and select another
+    /// move at the same node. This assumes a failed `make_move` leaves the state
+    /// unchanged. A node whose moves are all excluded is evaluated as a leaf.
+    /// See `MoveInfo::is_excluded`.
+    SkipAndExclude,
+    /// Abandon the playout without backpropagating anything, undoing its virtual
+    /// loss. The move may be selected again.
+    AbortPlayout,
+}
+
+/// See `MCTS::virtual_loss_mode`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VirtualLossMode {
+    /// Subtract this from a move's rewards while a playout through it is in progress.
+    Reward(f64),
+    /// Also add this many visits while a playout through it is in progress. This
+    /// is the usual formulation, and works with rewards of any scale. Other
+    /// playouts' virtual visits count towards `MCTS::visits_before_expansion`.
+    VisitsAndReward(u64, f64),
+}
+
+impl VirtualLossMode {
+    fn visits(self) -> usize {
+        match self {
+            VirtualLossMode::Reward(_) => 0,
+            VirtualLossMode::VisitsAndReward(visits, _) => visits as usize,
+        }
+    }
+
+    fn reward(self) -> f64 {
+        match self {
+            VirtualLossMode::Reward(reward) => reward,
+            VirtualLossMode::VisitsAndReward(_, reward) => reward,
+        }
+    }
+}
+
+/// The weight of each root move for `MCTSManager::sample_move_weighted`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RootKey {
+    Visits,
+    /// The mean reward, for the player to move at the root. Unvisited moves are
+    /// left out, and if any mean is negative, every mean is shifted up so that the
+    /// lowest is zero, which leaves the worst move out too.
+    Mean,
+    /// `TreePolicy::move_prior`.
+    Prior,
+}
+
+/// See `MCTSManager::verify_best_move`.
+#[derive(Clone, Debug)]
+pub struct VerificationReport<M> {
+    /// The root moves the verification searched, most visited first at the start.
+    pub candidates: Vec<VerifiedMove<M>>,
+    /// The playouts of the verification, which may be fewer than the budget when
+    /// some were abandoned.
+    pub playouts: u64,
+    pub best_before: Option<M>,
+    pub best_after: Option<M>,
+    /// Whether `best_move` is no longer the move it was.
+    pub changed: bool,
+}
+
+/// One of the moves of a `VerificationReport`. Shares are of the visits of both
+/// candidates together, NaN when neither has any.
+#[derive(Clone, Debug)]
+pub struct VerifiedMove<M> {
+    pub mov: M,
+    pub visits_before: u64,
+    pub visits_after: u64,
+    pub share_before: f64,
+    pub share_after: f64,
+}
+
+impl<M> VerifiedMove<M> {
+    /// The visits the verification gave the move.
+    pub fn verification_visits(&self) -> u64 {
+        self.visits_after - self.visits_before
+    }
+}
+
+/// Why `MCTSManager::try_best_move` found no move.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BestMoveError {
+    /// None of the root's moves have been visited yet.
+    NoPlayouts,
+    /// The game is over at the root.
+    TerminalRoot,
+    /// The root has no moves but isn't terminal, and `MCTS::no_moves_behaviour`
+    /// didn't supply one.
+    NoLegalMoves,
+}
+
+impl std::fmt::Display for BestMoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BestMoveError::NoPlayouts => write!(f, "no playouts have been run"),
+            BestMoveError::TerminalRoot => write!(f, "the root state is terminal"),
+            BestMoveError::NoLegalMoves => write!(f, "the root state has no legal moves"),
+        }
+    }
+}
+
+impl std::error::Error for BestMoveError {}
+
+/// What `MCTSManager::replay_moves` did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// The moves played.
+    pub plies: usize,
+    /// The moves, from the first, after which the tree kept what earlier searches
+    /// learnt about the position.
+    pub retained_plies: usize,
+}
+
+/// Why `MCTSManager::replay_moves` couldn't play its moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// `make_move` failed for the move at `index`.
+    IllegalMove { index: usize },
+    /// The game was over before the move at `index`.
+    GameOver { index: usize },
+}
+
+impl std::fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ReplayError::IllegalMove { index } => write!(f, "move {} is illegal", index),
+            ReplayError::GameOver { index } => {
+                write!(f, "the game is over before move {}", index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
@@ -0,0 +1,122 @@
+//! A read-only view of a running search, for polling from other threads.
+
+use super::*;
+
+/// The statistics of the root's moves, kept up to date by the search, for other
+/// threads to read while it runs, for example a GUI showing the best move so far.
+/// From `MCTSManager::live_stats`; clones share the statistics.
+///
+/// The view only reads its own atomics and never follows a pointer into the tree,
+/// so it can't see a node that is still being created. Each playout adds to its
+/// root move's statistics and then, with release ordering, to the playout count,
+/// which the view reads first, with acquire ordering. The moves therefore account
+/// for at least the playouts reported. Reads aren't a snapshot, though: a playout
+/// finishing meanwhile can be counted in its move but not in `playouts`, and a
+/// move's visits and rewards can be read between its updates.
+pub struct LiveStats<M> {
+    root: Arc<LiveRoot<M>>,
+}
+
+impl<M> Clone for LiveStats<M> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+/// One root move, as read from `LiveStats`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiveMoveStats<M> {
+    pub mov: M,
+    /// Finished playouts through the move. Unlike `MoveInfo::visits`, these never
+    /// include virtual visits.
+    pub visits: u64,
+    pub sum_rewards: f64,
+}
+
+impl<M> LiveMoveStats<M> {
+    /// `None` without visits.
+    pub fn mean_reward(&self) -> Option<f64> {
+        (self.visits > 0).then(|| self.sum_rewards / self.visits as f64)
+    }
+}
+
+impl<M: Clone> LiveStats<M> {
+    /// The playouts through the root so far, those of earlier searches included.
+    pub fn playouts(&self) -> u64 {
+        self.root.playouts.load(Ordering::Acquire)
+    }
+
+    /// Every root move, in node order. The moves `MCTS::exclude_move` set aside
+    /// aren't root moves, so they're left out; moves excluded later, with
+    /// `MCTSManager::exclude_root_move`, are still listed, with the statistics they
+    /// had then.
+    pub fn root_stats(&self) -> Vec<LiveMoveStats<M>> {
+        // Read first, to order the reads of the moves after it.
+        self.playouts();
+        let moves = self.root.moves.iter();
+        moves
+            .map(|x| LiveMoveStats {
+                mov: x.mov.clone(),
+                visits: x.visits.load(Ordering::Relaxed),
+                sum_rewards: x.sum_rewards.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// The most visited root move, the first of them on ties. `None` before any
+    /// playout through a move.
+    pub fn best_move(&self) -> Option<M> {
+        let mut best: Option<LiveMoveStats<M>> = None;
+        for x in self.root_stats() {
+            if x.visits > best.as_ref().map_or(0, |b| b.visits) {
+                best = Some(x);
+            }
+        }
+        best.map(|x| x.mov)
+    }
+}
+
+pub(crate) struct LiveRoot<M> {
+    moves: Vec<LiveMove<M>>,
+    playouts: CheckedAtomicU64,
+}
+
+struct LiveMove<M> {
+    mov: M,
+    visits: CheckedAtomicU64,
+    sum_rewards: AtomicStat,
+}
+
+impl<M> LiveRoot<M> {
+    /// Starts from the root's `moves`, with their visits and summed rewards, and
+    /// `playouts` through the root.
+    pub(crate) fn new<I: IntoIterator<Item = (M, u64, f64)>>(moves: I, playouts: u64) -> Self {
+        let moves = moves.into_iter();
+        LiveRoot {
+            moves: moves
+                .map(|(mov, visits, sum_rewards)| LiveMove {
+                    mov,
+                    visits: CheckedAtomicU64::new(visits),
+                    sum_rewards: AtomicStat::new(sum_rewards),
+                })
+                .collect(),
+            playouts: CheckedAtomicU64::new(playouts),
+        }
+    }
+
+    /// Counts a playout through the root move at `index`, or one ending at the root
+    /// when `None`.
+    pub(crate) fn record(&self, index: Option<usize>, reward: f64) {
+        if let Some(x) = index.and_then(|i| self.moves.get(i)) {
+            x.visits.fetch_add(1, Ordering::Relaxed);
+            x.sum_rewards.fetch_add(reward, Ordering::Relaxed);
+        }
+        self.playouts.fetch_add(1, Ordering::Release);
+    }
+
+    pub(crate) fn handle(self: &Arc<Self>) -> LiveStats<M> {
+        LiveStats { root: self.clone() }
+    }
+}
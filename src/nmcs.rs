@@ -0,0 +1,159 @@
+//! Nested Monte Carlo Search, for deterministic single-player puzzles.
+//!
+//! At level 0 a search is a single rollout. At level `n` it plays the puzzle out one
+//! move at a time: every move available is tried with a level `n - 1` search from
+//! the state it leads to, and the move played is the next one of the best sequence
+//! found so far, at this level, from any of those searches. So a level never ends
+//! worse than the best sequence it has seen.
+//!
+//! There is no search tree, and no averaging: only the best score counts, which is
+//! what puzzles want and what UCT's mean rewards are bad at. Each level multiplies
+//! the cost by roughly the number of moves in a game times the branching factor, so
+//! levels above 2 or 3 are rarely affordable.
+
+use super::*;
+use tree_policy::{PolicyRng, SelectionRng};
+
+/// A game with one player and a score to maximize when it ends.
+pub trait Puzzle: GameState {
+    /// The score of a finished game. Higher is better.
+    fn score(&self) -> f64;
+}
+
+/// Chooses the moves of the rollouts at level 0.
+///
+/// `PolicyRng` chooses uniformly at random, and closures
+/// `FnMut(&S, &[S::Move]) -> usize` are rollout policies too.
+pub trait RolloutPolicy<S: GameState> {
+    /// The index of the move to make in `state`. `moves` is never empty.
+    fn choose_move(&mut self, state: &S, moves: &[S::Move]) -> usize;
+}
+
+impl<S: GameState> RolloutPolicy<S> for PolicyRng {
+    fn choose_move(&mut self, _: &S, moves: &[S::Move]) -> usize {
+        self.select_by_key(0..moves.len(), |_| 0.0).unwrap()
+    }
+}
+
+impl<S: GameState, F: FnMut(&S, &[S::Move]) -> usize> RolloutPolicy<S> for F {
+    fn choose_move(&mut self, state: &S, moves: &[S::Move]) -> usize {
+        self(state, moves)
+    }
+}
+
+/// Runs a level `level` search from `state` and returns the best sequence of moves
+/// it found, played to the end of the game, with its score.
+///
+/// Above level 0, moves that `make_move` rejects are skipped; a rollout ends at one.
+/// The puzzle must end: a rollout runs until there are no moves left.
+///
+/// ```
+/// use mcts::nmcs::*;
+/// use mcts::tree_policy::PolicyRng;
+/// use mcts::GameState;
+///
+/// // Pick 4 of the digits 0 to 9, in increasing order; the score is their sum.
+/// #[derive(Clone)]
+/// struct Digits(Vec<u8>);
+///
+/// impl GameState for Digits {
+///     type Move = u8;
+///     type Player = ();
+///     type MoveList = Vec<u8>;
+///
+///     fn current_player(&self) -> &() {
+///         &()
+///     }
+///     fn available_moves(&self) -> Vec<u8> {
+///         if self.0.len() == 4 {
+///             return vec![];
+///         }
+///         let next = self.0.last().map_or(0, |&x| x + 1);
+///         (next..10).collect()
+///     }
+///     fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+///         self.0.push(*mov);
+///         Ok(())
+///     }
+///     fn get_winner(&self) -> Option<()> {
+///         None
+///     }
+/// }
+///
+/// impl Puzzle for Digits {
+///     fn score(&self) -> f64 {
+///         self.0.iter().map(|&x| x as f64).sum()
+///     }
+/// }
+///
+/// let (moves, score) = nested_search(Digits(vec![]), 2, &mut PolicyRng::new(0));
+/// assert_eq!(moves, vec![6, 7, 8, 9]);
+/// assert_eq!(score, 30.0);
+/// ```
+pub fn nested_search<S, R>(state: S, level: u32, rollout_policy: &mut R) -> (Vec<S::Move>, f64)
+where
+    S: Puzzle,
+    R: RolloutPolicy<S>,
+{
+    if level == 0 {
+        return rollout(state, rollout_policy);
+    }
+    let mut state = state;
+    let mut played: Vec<S::Move> = Vec::new();
+    // The best complete sequence from the starting state, which always begins with
+    // the moves played so far.
+    let mut best: Option<(Vec<S::Move>, f64)> = None;
+    loop {
+        let moves: Vec<S::Move> = state.available_moves().into_iter().collect();
+        for mov in &moves {
+            let mut child = state.clone();
+            if child.make_move(mov).is_err() {
+                continue;
+            }
+            let (rest, score) = nested_search(child, level - 1, rollout_policy);
+            if best.as_ref().is_none_or(|x| score > x.1) {
+                let mut sequence = played.clone();
+                sequence.push(mov.clone());
+                sequence.extend(rest);
+                best = Some((sequence, score));
+            }
+        }
+        let next = match &best {
+            Some((sequence, _)) if sequence.len() > played.len() => sequence[played.len()].clone(),
+            _ => break,
+        };
+        if state.make_move(&next).is_err() {
+            break;
+        }
+        played.push(next);
+    }
+    match best {
+        Some(best) if best.0.len() == played.len() => best,
+        _ => {
+            let score = state.score();
+            (played, score)
+        }
+    }
+}
+
+fn rollout<S, R>(mut state: S, rollout_policy: &mut R) -> (Vec<S::Move>, f64)
+where
+    S: Puzzle,
+    R: RolloutPolicy<S>,
+{
+    let mut played = Vec::new();
+    loop {
+        let moves: Vec<S::Move> = state.available_moves().into_iter().collect();
+        if moves.is_empty() {
+            break;
+        }
+        let index = rollout_policy.choose_move(&state, &moves);
+        let mov = moves[index].clone();
+        if state.make_move(&mov).is_err() {
+            break;
+        }
+        played.push(mov);
+    }
+    let score = state.score();
+    (played, score)
+}
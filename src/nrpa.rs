@@ -0,0 +1,184 @@
+//! Nested Rollout Policy Adaptation, for deterministic single-player puzzles.
+//!
+//! The rollouts are guided by a policy: a weight for every move code, where the
+//! code of a move in a state is whatever the caller's `move_code` function makes of
+//! it. A rollout chooses each move with probability proportional to the exponential
+//! of its weight. A level `n` search runs `iterations` level `n - 1` searches, and
+//! after each one moves the policy towards the best sequence found so far at this
+//! level, so later searches sample near it.
+//!
+//! A search of level `levels` runs `iterations.pow(levels)` rollouts.
+
+use super::*;
+use nmcs::Puzzle;
+use std::collections::HashMap;
+use tree_policy::PolicyRng;
+
+type Policy = HashMap<u64, f64>;
+
+/// Runs a level `levels` search from `state` and returns the best sequence of moves
+/// found, played to the end of the game, with its score.
+///
+/// `alpha` is the learning rate of the policy; 1.0 is the usual choice. Moves with
+/// the same code share a weight, so codes should tell apart the moves that matter,
+/// for example by including the position a move is made in. Rollouts end at a move
+/// `make_move` rejects, as well as when there are no moves left.
+///
+/// ```
+/// use mcts::nmcs::Puzzle;
+/// use mcts::nrpa::nrpa_search;
+/// use mcts::tree_policy::PolicyRng;
+/// use mcts::GameState;
+///
+/// // Pick 4 of the digits 0 to 9, in increasing order; the score is their sum.
+/// #[derive(Clone)]
+/// struct Digits(Vec<u8>);
+///
+/// impl GameState for Digits {
+///     type Move = u8;
+///     type Player = ();
+///     type MoveList = Vec<u8>;
+///
+///     fn current_player(&self) -> &() {
+///         &()
+///     }
+///     fn available_moves(&self) -> Vec<u8> {
+///         if self.0.len() == 4 {
+///             return vec![];
+///         }
+///         let next = self.0.last().map_or(0, |&x| x + 1);
+///         (next..10).collect()
+///     }
+///     fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+///         self.0.push(*mov);
+///         Ok(())
+///     }
+///     fn get_winner(&self) -> Option<()> {
+///         None
+///     }
+/// }
+///
+/// impl Puzzle for Digits {
+///     fn score(&self) -> f64 {
+///         self.0.iter().map(|&x| x as f64).sum()
+///     }
+/// }
+///
+/// let move_code = |state: &Digits, mov: &u8| (state.0.len() * 10) as u64 + *mov as u64;
+/// let mut rng = PolicyRng::new(0);
+/// let (moves, score) = nrpa_search(Digits(vec![]), 2, 20, 1.0, move_code, &mut rng);
+/// assert_eq!(moves, vec![6, 7, 8, 9]);
+/// assert_eq!(score, 30.0);
+/// ```
+pub fn nrpa_search<S, F>(
+    state: S,
+    levels: u32,
+    iterations: usize,
+    alpha: f64,
+    move_code: F,
+    rng: &mut PolicyRng,
+) -> (Vec<S::Move>, f64)
+where
+    S: Puzzle,
+    F: Fn(&S, &S::Move) -> u64,
+{
+    let search = Search {
+        root: state,
+        iterations,
+        alpha,
+        move_code,
+    };
+    search.nrpa(levels, Policy::new(), rng)
+}
+
+struct Search<S, F> {
+    root: S,
+    iterations: usize,
+    alpha: f64,
+    move_code: F,
+}
+
+impl<S, F> Search<S, F>
+where
+    S: Puzzle,
+    F: Fn(&S, &S::Move) -> u64,
+{
+    fn nrpa(&self, level: u32, mut policy: Policy, rng: &mut PolicyRng) -> (Vec<S::Move>, f64) {
+        if level == 0 {
+            return self.rollout(&policy, rng);
+        }
+        let mut best: Option<(Vec<S::Move>, f64)> = None;
+        for _ in 0..self.iterations {
+            let (sequence, score) = self.nrpa(level - 1, policy.clone(), rng);
+            // Ties replace the best, which keeps the policy moving on plateaus.
+            if best.as_ref().is_none_or(|x| score >= x.1) {
+                best = Some((sequence, score));
+            }
+            if let Some((sequence, _)) = &best {
+                policy = self.adapt(&policy, sequence);
+            }
+        }
+        best.unwrap_or_else(|| self.rollout(&policy, rng))
+    }
+
+    /// Weights of the codes of `moves` in `state`, and their sum.
+    fn weights(&self, policy: &Policy, state: &S, moves: &[S::Move]) -> (Vec<(u64, f64)>, f64) {
+        let weights: Vec<(u64, f64)> = moves
+            .iter()
+            .map(|mov| {
+                let code = (self.move_code)(state, mov);
+                (code, policy.get(&code).cloned().unwrap_or(0.0).exp())
+            })
+            .collect();
+        let total = weights.iter().map(|x| x.1).sum();
+        (weights, total)
+    }
+
+    fn rollout(&self, policy: &Policy, rng: &mut PolicyRng) -> (Vec<S::Move>, f64) {
+        let mut state = self.root.clone();
+        let mut played = Vec::new();
+        loop {
+            let moves: Vec<S::Move> = state.available_moves().into_iter().collect();
+            if moves.is_empty() {
+                break;
+            }
+            let (weights, total) = self.weights(policy, &state, &moves);
+            let mut target = rng.next_f64() * total;
+            let mut index = moves.len() - 1;
+            for (i, (_, weight)) in weights.iter().enumerate() {
+                if target < *weight {
+                    index = i;
+                    break;
+                }
+                target -= weight;
+            }
+            let mov = moves[index].clone();
+            if state.make_move(&mov).is_err() {
+                break;
+            }
+            played.push(mov);
+        }
+        let score = state.score();
+        (played, score)
+    }
+
+    /// Moves the policy towards choosing `sequence`: each move's weight goes up by
+    /// `alpha`, and the weights of all the moves it was chosen from go down by
+    /// `alpha` times the probability the policy gave them.
+    fn adapt(&self, policy: &Policy, sequence: &[S::Move]) -> Policy {
+        let mut adapted = policy.clone();
+        let mut state = self.root.clone();
+        for mov in sequence {
+            let moves: Vec<S::Move> = state.available_moves().into_iter().collect();
+            let (weights, total) = self.weights(policy, &state, &moves);
+            *adapted.entry((self.move_code)(&state, mov)).or_insert(0.0) += self.alpha;
+            for (code, weight) in weights {
+                *adapted.entry(code).or_insert(0.0) -= self.alpha * weight / total;
+            }
+            if state.make_move(mov).is_err() {
+                break;
+            }
+        }
+        adapted
+    }
+}
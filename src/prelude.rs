@@ -0,0 +1,24 @@
+//! The types needed to set up and run a search, for a single glob import:
+//!
+//! ```
+//! use mcts::prelude::*;
+//! ```
+//!
+//! The `Move<Spec>` alias is left out because games usually have a `Move` type of
+//! their own; it's still available as `mcts::Move`.
+
+pub use super::{
+    CycleBehaviour, Evaluator, GameState, HistoryTable, IncrementallyHashable, MCTSManager,
+    MCTSManagerBuilder, MoveEvaluation, MoveInfo, MoveList, NodeHandle, PlayerOf, SearchHandle,
+    SearchParams, SearchPreset, SearchSummary, StateEvaluation, ThreadData, TreePolicyThreadData,
+    TwoPlayerZeroSumSpec, ValueEstimate, WinDrawLossEvaluator, MCTS,
+};
+
+pub use super::transposition_table::{
+    ApproxTable, ExactTable, IncrementalTable, TranspositionTable,
+};
+
+pub use super::tree_policy::{
+    AlphaGoPolicy, AtomicPrior, HistoryPolicy, PolicyRng, PriorAnnealing, PriorWithValue,
+    ProgressiveUnpruning, SelectionRng, TieBreak, TreePolicy, UCTPolicy, WeightedRng,
+};
@@ -0,0 +1,90 @@
+use super::*;
+use tree_policy::UCTPolicy;
+
+/// Starting points for a search's parameters, for when there's no time to tune
+/// them. Each gives an exploration constant for `UCTPolicy` with adaptive
+/// exploration (see `UCTPolicy::with_adaptive_exploration`), so the constant doesn't
+/// depend on the scale of the rewards, a virtual loss, and a transposition table
+/// size. See `SearchPreset::params`.
+///
+/// Tuning still pays: these are meant to be reasonable everywhere, not best
+/// anywhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SearchPreset {
+    /// Little exploration, for games where short forcing lines decide the result
+    /// and the search should dig into the most promising move.
+    Tactical,
+    #[default]
+    Balanced,
+    /// Much exploration, for games with many moves of similar value, or evaluators
+    /// too noisy to trust early.
+    Exploratory,
+}
+
+impl SearchPreset {
+    /// The dimensionless exploration constant.
+    pub fn exploration_constant(self) -> f64 {
+        match self {
+            SearchPreset::Tactical => 0.5,
+            SearchPreset::Balanced => 1.0,
+            SearchPreset::Exploratory => 2.0,
+        }
+    }
+
+    /// Virtual visits with no reward, which work whatever the scale of the rewards.
+    /// More exploratory presets spread parallel searches further.
+    pub fn virtual_loss_mode(self) -> VirtualLossMode {
+        match self {
+            SearchPreset::Tactical => VirtualLossMode::VisitsAndReward(1, 0.0),
+            SearchPreset::Balanced => VirtualLossMode::VisitsAndReward(2, 0.0),
+            SearchPreset::Exploratory => VirtualLossMode::VisitsAndReward(3, 0.0),
+        }
+    }
+
+    /// The parameters for a search of about `playouts` playouts in a game whose
+    /// positions have about `branching_factor` moves.
+    pub fn params(self, branching_factor: usize, playouts: u64) -> SearchParams {
+        SearchParams {
+            preset: self,
+            exploration_constant: self.exploration_constant(),
+            virtual_loss_mode: self.virtual_loss_mode(),
+            table_size: table_size(branching_factor, playouts),
+            playouts,
+        }
+    }
+}
+
+/// A search creates at most a node per playout, plus the root's children when its
+/// moves are tried in turn. The table is kept at most two thirds full, as
+/// `ApproxQuadraticProbingHashTable::enough_to_hold` does.
+fn table_size(branching_factor: usize, playouts: u64) -> usize {
+    let nodes = (playouts as usize).saturating_add(branching_factor);
+    nodes
+        .saturating_mul(3)
+        .div_ceil(2)
+        .max(1)
+        .checked_next_power_of_two()
+        .unwrap_or(usize::MAX / 2 + 1)
+}
+
+/// The parameters chosen by `SearchPreset::params`. Give them to
+/// `MCTSManagerBuilder::preset`, or use them piece by piece.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SearchParams {
+    pub preset: SearchPreset,
+    pub exploration_constant: f64,
+    pub virtual_loss_mode: VirtualLossMode,
+    /// The entries for an `ApproxTable` or `ExactTable`, a power of 2.
+    pub table_size: usize,
+    /// The playout budget the parameters were chosen for.
+    pub playouts: u64,
+}
+
+impl SearchParams {
+    /// A `UCTPolicy` with adaptive exploration and the preset's virtual loss.
+    pub fn uct_policy<MV>(&self) -> UCTPolicy<MV> {
+        UCTPolicy::new(self.exploration_constant)
+            .with_adaptive_exploration(true)
+            .with_virtual_loss(self.virtual_loss_mode)
+    }
+}
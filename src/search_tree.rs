@@ -1,63 +1,263 @@
-#![cfg_attr(feature = "nightly", feature(integer_atomics))]
-
-use std;
-
 use super::*;
 use atomics::*;
 use smallvec::SmallVec;
+use std::collections::VecDeque;
 use std::fmt;
 use std::fmt::{Debug, Display, Formatter};
-use std::ptr::{null, null_mut};
-use std::sync::Mutex;
+use std::mem::MaybeUninit;
+use std::ptr::null_mut;
+use std::sync::{Mutex, OnceLock};
 
-use tree_policy::TreePolicy;
+#[cfg(feature = "events")]
+use events::SearchEvent;
+use tree_policy::{TreePolicy, UpdatableMoveEvaluation};
+
+const DEFAULT_RECORDING_CAPACITY: usize = 1024;
+/// The depth up to which a playout's path is kept off the heap.
+const LARGE_DEPTH: usize = 64;
 
 /// You're not intended to use this class (use an `MCTSManager` instead),
 /// but you can use it if you want to manage the threads yourself.
 pub struct SearchTree<Spec: MCTS> {
     root_node: SearchNode<Spec>,
     root_state: Spec::State,
+    root_hash: Option<u64>,
     tree_policy: Spec::TreePolicy,
     table: Spec::TranspositionTable,
     eval: Spec::Eval,
     manager: Spec,
 
     num_nodes: AtomicUsize,
+    /// Visits the root had when `advance_root` made it the root, which the depth
+    /// histogram doesn't count.
+    carried_root_visits: u64,
+    /// See `total_playouts`, `total_nodes_created` and `total_evaluations`.
+    playouts: AtomicU64,
+    nodes_created: AtomicU64,
+    evaluations: AtomicU64,
     orphaned: Mutex<Vec<Box<SearchNode<Spec>>>>,
+    /// Nodes made by `preload_states`, which no move owns.
+    preloaded: Vec<Box<SearchNode<Spec>>>,
     transposition_table_hits: AtomicUsize,
     delayed_transposition_table_hits: AtomicUsize,
     expansion_contention_events: AtomicUsize,
+    non_finite_rewards: AtomicUsize,
+    terminality_mismatches: AtomicUsize,
+    move_errors: AtomicUsize,
+    saturated_selections: AtomicUsize,
+    selection_fallbacks: AtomicUsize,
+    halted_playouts: AtomicUsize,
+    /// See `live_stats`.
+    live: OnceLock<Arc<LiveRoot<Move<Spec>>>>,
+    /// See `set_min_root_visits`.
+    min_root_visits: AtomicU64,
+    root_floor_enabled: AtomicBool,
+    floor_visits: AtomicUsize,
+    /// See `set_root_noise`. The bits of an `f64`.
+    root_noise: AtomicU64,
+    noisy_root_choices: AtomicUsize,
+    depth_histogram: [AtomicUsize; DEPTH_BUCKETS],
+    phase_interval: AtomicUsize,
+    phase_nanos: [AtomicU64; PHASES],
+    recording: AtomicBool,
+    recording_capacity: AtomicUsize,
+    records: Mutex<VecDeque<PlayoutRecord<Spec>>>,
+    /// See `track_stability`.
+    stability_interval: AtomicU64,
+    stability_capacity: AtomicUsize,
+    stability: Mutex<VecDeque<StabilitySample>>,
+    node_limit: AtomicUsize,
+    /// Allocations made by `reserve`, used before allocating new nodes.
+    reserved_nodes: ReservedNodes<SearchNode<Spec>>,
+    nodes_reserved: AtomicUsize,
+    history: Option<Arc<HistoryTable<Move<Spec>>>>,
+    shared_stats: Option<Arc<dyn SharedStats<Move<Spec>>>>,
+}
+
+/// The components a `SearchTree` owns, from `SearchTree::into_parts`.
+pub struct TreeParts<Spec: MCTS> {
+    pub spec: Spec,
+    pub evaluator: Spec::Eval,
+    pub tree_policy: Spec::TreePolicy,
+    pub root_state: Spec::State,
+    pub transposition_table: Spec::TranspositionTable,
+}
+
+/// One playout, as recorded by `SearchTree::record_playouts`.
+pub struct PlayoutRecord<Spec: MCTS> {
+    /// The index of the chosen move in each node along the path, starting at the root.
+    pub move_indices: Vec<usize>,
+    /// The evaluation that was backpropagated.
+    pub evaluation: StateEvaluation<Spec>,
+    pub thread: std::thread::ThreadId,
+}
+
+impl<Spec: MCTS> Clone for PlayoutRecord<Spec> {
+    fn clone(&self) -> Self {
+        Self {
+            move_indices: self.move_indices.clone(),
+            evaluation: self.evaluation.clone(),
+            thread: self.thread,
+        }
+    }
+}
+
+impl<Spec: MCTS> Debug for PlayoutRecord<Spec>
+where
+    StateEvaluation<Spec>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("PlayoutRecord")
+            .field("move_indices", &self.move_indices)
+            .field("evaluation", &self.evaluation)
+            .field("thread", &self.thread)
+            .finish()
+    }
+}
+
+/// A snapshot of a move's statistics, as returned by `NodeHandle::children`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NodeStats {
+    pub visits: u64,
+    pub sum_rewards: f64,
+}
+
+impl NodeStats {
+    /// The mean reward, NaN when unvisited.
+    pub fn mean(&self) -> f64 {
+        self.sum_rewards / self.visits as f64
+    }
 }
 
+/// The statistics of a node or a move. `flags` sits next to the sums, so that a
+/// move's `MoveFlags` fill the room an f32 sum leaves before the next 8 bytes;
+/// nodes have none.
 #[derive(Debug)]
-struct NodeStats {
-    visits: AtomicUsize,
-    sum_evaluations: AtomicF64,
+pub(crate) struct AtomicNodeStats<Flags = ()> {
+    pub(crate) visits: CheckedAtomicUsize,
+    pub(crate) sum_evaluations: AtomicStat,
+    #[cfg(feature = "reward_variance")]
+    pub(crate) sum_squared_evaluations: AtomicStat,
+    pub(crate) flags: Flags,
+}
+
+/// A move's in-flight count, up to 2^30, and flags, in one word. Every access is
+/// relaxed: the count is kept with read-modify-writes, which see every earlier one
+/// whatever their ordering, and no data is published with the flags.
+#[derive(Debug, Default)]
+pub(crate) struct MoveFlags(AtomicU32);
+
+impl MoveFlags {
+    /// Whether dropping the move drops its child.
+    const OWNED: u32 = 1 << 31;
+    /// Whether the move is excluded from selection.
+    const EXCLUDED: u32 = 1 << 30;
+    const IN_FLIGHT: u32 = Self::EXCLUDED - 1;
+
+    fn in_flight(&self) -> usize {
+        (self.0.load(Ordering::Relaxed) & Self::IN_FLIGHT) as usize
+    }
+    fn enter(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+    fn leave(&self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+    fn get(&self, flag: u32) -> bool {
+        self.0.load(Ordering::Relaxed) & flag != 0
+    }
+    /// Sets or clears `flag`, returning whether it was set.
+    fn set(&self, flag: u32, value: bool) -> bool {
+        let old = if value {
+            self.0.fetch_or(flag, Ordering::Relaxed)
+        } else {
+            self.0.fetch_and(!flag, Ordering::Relaxed)
+        };
+        old & flag != 0
+    }
 }
 
+/// A move from a node, with its move evaluation, its statistics and the child it
+/// leads to. Tree policies choose between these, so the read accessors are what an
+/// external `TreePolicy` has to work with. The statistics are for the player who
+/// makes the move, and are read without locking, so during a search they may
+/// include playouts still in progress.
 pub struct MoveInfo<Spec: MCTS> {
     mov: Move<Spec>,
     move_evaluation: MoveEvaluation<Spec>,
-    child: AtomicPtr<SearchNode<Spec>>,
-    owned: AtomicBool,
-    stats: NodeStats,
+    /// Other threads follow it into the child, so it's published with release
+    /// ordering and loaded with acquire; see `PublishedPtr`.
+    child: PublishedPtr<SearchNode<Spec>>,
+    /// Relaxed, as every statistic; see the `atomics` module. Its `MoveFlags` hold:
+    ///
+    /// - whether dropping the move drops `child`. Set by the thread that published
+    ///   the child and only read with the tree to itself, when dropping it or, with
+    ///   no search running, to validate or print it; the join or `&mut` that made
+    ///   that so orders the accesses.
+    /// - whether the move is excluded. A playout that reads it late only takes the
+    ///   move once more.
+    /// - the playouts going through the move, kept under `MCTS::max_in_flight`.
+    stats: AtomicNodeStats<MoveFlags>,
 }
 
 pub struct SearchNode<Spec: MCTS> {
     moves: Vec<MoveInfo<Spec>>,
     data: Spec::NodeData,
     evaln: StateEvaluation<Spec>,
-    stats: NodeStats,
+    stats: AtomicNodeStats,
+    /// Set by `NoMovesBehaviour::SkipPlayout`.
+    skip_playouts: bool,
+    /// The number of moves `MCTS::max_children` discarded.
+    pruned_moves: usize,
+    /// The moves `MCTS::exclude_move` excluded, which are never selected.
+    excluded_moves: Vec<MoveInfo<Spec>>,
+    /// The number of playouts that ended at this node, for `SearchTree::validate`.
+    evaluations: AtomicUsize,
+    /// The number of `moves` excluded from selection: under
+    /// `MoveErrorBehaviour::SkipAndExclude`, or by `NodeHandle::exclude_child`.
+    num_excluded: AtomicUsize,
+    /// The node's visits as of the last refresh, under `MCTS::cached_visits_interval`.
+    cached_visits: AtomicUsize,
 }
 
 impl<Spec: MCTS> SearchNode<Spec> {
-    fn new(moves: Vec<MoveInfo<Spec>>, evaln: StateEvaluation<Spec>) -> Self {
+    fn new(moves: Vec<MoveInfo<Spec>>, evaln: StateEvaluation<Spec>, data: Spec::NodeData) -> Self {
         Self {
             moves,
-            data: Default::default(),
+            data,
             evaln,
-            stats: NodeStats::new(),
+            stats: AtomicNodeStats::new(),
+            skip_playouts: false,
+            pruned_moves: 0,
+            excluded_moves: Vec::new(),
+            evaluations: AtomicUsize::new(0),
+            num_excluded: AtomicUsize::new(0),
+            cached_visits: AtomicUsize::new(0),
+        }
+    }
+
+    /// Excludes one of `moves` from selection, returning false if it already was.
+    fn exclude(&self, choice: &MoveInfo<Spec>) -> bool {
+        let newly = !choice.stats.flags.set(MoveFlags::EXCLUDED, true);
+        if newly {
+            self.num_excluded.fetch_add(1, Ordering::Relaxed);
+        }
+        newly
+    }
+
+    /// Returns the move to selection, returning false if it wasn't excluded.
+    fn include(&self, choice: &MoveInfo<Spec>) -> bool {
+        let newly = choice.stats.flags.set(MoveFlags::EXCLUDED, false);
+        if newly {
+            self.num_excluded.fetch_sub(1, Ordering::Relaxed);
         }
+        newly
+    }
+
+    /// The index of `choice` in `moves`.
+    fn move_index(&self, choice: &MoveInfo<Spec>) -> usize {
+        (choice as *const MoveInfo<Spec> as usize - self.moves.as_ptr() as usize)
+            / std::mem::size_of::<MoveInfo<Spec>>()
     }
 }
 
@@ -66,9 +266,8 @@ impl<Spec: MCTS> MoveInfo<Spec> {
         MoveInfo {
             mov,
             move_evaluation,
-            child: AtomicPtr::default(),
-            stats: NodeStats::new(),
-            owned: AtomicBool::new(false),
+            child: PublishedPtr::default(),
+            stats: AtomicNodeStats::new(),
         }
     }
 
@@ -76,25 +275,115 @@ impl<Spec: MCTS> MoveInfo<Spec> {
         &self.mov
     }
 
+    /// Whether the move is never selected: `MCTS::exclude_move` excluded it, its
+    /// `make_move` failed under `MoveErrorBehaviour::SkipAndExclude`, or
+    /// `NodeHandle::exclude_child` excluded it. Excluded moves keep their statistics,
+    /// but are left out of the principal variation.
+    pub fn is_excluded(&self) -> bool {
+        self.stats.flags.get(MoveFlags::EXCLUDED)
+    }
+
+    /// What `Evaluator::evaluate_new_state` said about the move, such as its prior.
     pub fn move_evaluation(&self) -> &MoveEvaluation<Spec> {
         &self.move_evaluation
     }
 
+    /// Replaces the move evaluation, for example the prior, while the search may be
+    /// running.
+    pub fn update_move_evaluation(
+        &self,
+        value: <MoveEvaluation<Spec> as UpdatableMoveEvaluation>::Value,
+    ) where
+        MoveEvaluation<Spec>: UpdatableMoveEvaluation,
+    {
+        self.move_evaluation.store(value);
+    }
+
+    /// The playouts through this move, including virtual visits of playouts in
+    /// progress under `VirtualLossMode::VisitsAndReward`.
     pub fn visits(&self) -> u64 {
         self.stats.visits.load(Ordering::Relaxed) as u64
     }
 
+    /// The playouts going through this move right now. Only counted when
+    /// `MCTS::max_in_flight` is limited; always 0 otherwise.
+    pub fn in_flight(&self) -> usize {
+        self.stats.flags.in_flight()
+    }
+
+    /// Whether this move's child was created for it, rather than found in the
+    /// transposition table.
+    pub(crate) fn owns_child(&self) -> bool {
+        self.stats.flags.get(MoveFlags::OWNED)
+    }
+
+    /// The sum of the rewards of the playouts through this move, less any virtual
+    /// loss of playouts in progress.
     pub fn sum_rewards(&self) -> f64 {
-        self.stats.sum_evaluations.load(Ordering::Relaxed) as f64
+        self.stats.sum_evaluations.load(Ordering::Relaxed)
+    }
+
+    /// The mean reward, or `None` before the first visit.
+    pub fn mean_reward(&self) -> Option<f64> {
+        let visits = self.visits();
+        if visits == 0 {
+            None
+        } else {
+            Some(self.sum_rewards() / visits as f64)
+        }
+    }
+
+    /// The mean reward, or `default` before the first visit.
+    pub fn mean_reward_or(&self, default: f64) -> f64 {
+        self.mean_reward().unwrap_or(default)
     }
 
+    /// The mean reward, NaN before the first visit.
     pub fn avg_reward(&self) -> f64 {
-        self.sum_rewards() / self.visits() as f64
+        self.mean_reward_or(f64::NAN)
+    }
+
+    /// `visits` and `sum_rewards` together.
+    pub fn stats(&self) -> NodeStats {
+        NodeStats {
+            visits: self.visits(),
+            sum_rewards: self.sum_rewards(),
+        }
+    }
+
+    /// The sum of the squares of the rewards, for `reward_variance`.
+    #[cfg(feature = "reward_variance")]
+    pub fn sum_squared_rewards(&self) -> f64 {
+        self.stats.sum_squared_evaluations.load(Ordering::Relaxed)
+    }
+
+    /// The sample variance of the rewards, or `None` with fewer than two visits.
+    #[cfg(feature = "reward_variance")]
+    pub fn reward_variance(&self) -> Option<f64> {
+        let n = self.visits();
+        if n < 2 {
+            return None;
+        }
+        let sum = self.sum_rewards();
+        let sum_squares = self.sum_squared_rewards();
+        let n = n as f64;
+        Some(((sum_squares - sum * sum / n) / (n - 1.0)).max(0.0))
     }
 
-    pub fn child(&self) -> Option<NodeHandle<Spec>> {
-        let ptr = self.child.load(Ordering::Relaxed);
-        if ptr == null_mut() {
+    /// A 95% confidence interval on the mean reward, using the normal approximation.
+    /// `None` with fewer than two visits.
+    #[cfg(feature = "reward_variance")]
+    pub fn confidence_interval(&self) -> Option<(f64, f64)> {
+        let variance = self.reward_variance()?;
+        let mean = self.mean_reward()?;
+        let half_width = 1.96 * (variance / self.visits() as f64).sqrt();
+        Some((mean - half_width, mean + half_width))
+    }
+
+    /// The node this move leads to, or `None` until a playout has expanded it.
+    pub fn child(&self) -> Option<NodeHandle<'_, Spec>> {
+        let ptr = self.child.load();
+        if ptr.is_null() {
             None
         } else {
             unsafe { Some(NodeHandle { node: &*ptr }) }
@@ -107,7 +396,7 @@ where
     Move<Spec>: Display,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let own_str = if self.owned.load(Ordering::Relaxed) {
+        let own_str = if self.stats.flags.get(MoveFlags::OWNED) {
             ""
         } else {
             " [child pointer is alias]"
@@ -133,7 +422,7 @@ where
     Move<Spec>: Debug,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let own_str = if self.owned.load(Ordering::Relaxed) {
+        let own_str = if self.stats.flags.get(MoveFlags::OWNED) {
             ""
         } else {
             " [child pointer is alias]"
@@ -155,39 +444,197 @@ where
     }
 }
 
+impl<Spec: MCTS> Drop for SearchNode<Spec> {
+    fn drop(&mut self) {
+        Spec::Eval::on_node_dropped(&self.evaln);
+    }
+}
+
 impl<Spec: MCTS> Drop for MoveInfo<Spec> {
     fn drop(&mut self) {
-        if !self.owned.load(Ordering::SeqCst) {
+        // The tree is being dropped, so the search threads have been joined.
+        if !self.stats.flags.get(MoveFlags::OWNED) {
             return;
         }
-        let ptr = self.child.load(Ordering::SeqCst);
-        if ptr != null_mut() {
+        let ptr = self.child.load();
+        if !ptr.is_null() {
             unsafe {
-                Box::from_raw(ptr);
+                drop(Box::from_raw(ptr));
             }
         }
     }
 }
 
 fn create_node<Spec: MCTS>(
+    manager: &Spec,
     eval: &Spec::Eval,
     policy: &Spec::TreePolicy,
     state: &Spec::State,
-    handle: Option<SearchHandle<Spec>>,
+    mut handle: Option<SearchHandle<Spec>>,
+    timer: &mut PhaseTimer,
+    terminality_mismatches: &AtomicUsize,
 ) -> SearchNode<Spec> {
     let moves = state.available_moves();
-    let (move_eval, state_eval) = eval.evaluate_new_state(&state, &moves, handle);
+    let outer = timer.switch(Phase::Evaluate);
+    let (move_eval, state_eval) =
+        eval.evaluate_new_state(state, &moves, handle.as_mut().map(|x| x.reborrow()));
+    timer.switch(outer);
+    let evaluated = (moves, move_eval, state_eval);
+    build_node(
+        manager,
+        eval,
+        policy,
+        state,
+        evaluated,
+        handle,
+        terminality_mismatches,
+    )
+}
+
+/// The node for `state`, from its available moves and what
+/// `Evaluator::evaluate_new_state` said about them.
+fn build_node<Spec: MCTS>(
+    manager: &Spec,
+    eval: &Spec::Eval,
+    policy: &Spec::TreePolicy,
+    state: &Spec::State,
+    evaluated: (
+        MoveList<Spec>,
+        Vec<MoveEvaluation<Spec>>,
+        StateEvaluation<Spec>,
+    ),
+    mut handle: Option<SearchHandle<Spec>>,
+    terminality_mismatches: &AtomicUsize,
+) -> SearchNode<Spec> {
+    let (moves, move_eval, state_eval) = evaluated;
+    let data = eval.make_node_data(&state_eval);
     policy.validate_evaluations(&move_eval);
-    let moves = moves
+    let mut moves: Vec<_> = moves
         .into_iter()
-        .zip(move_eval.into_iter())
+        .zip(move_eval)
         .map(|(m, e)| MoveInfo::new(m, e))
         .collect();
-    SearchNode::new(moves, state_eval)
+    // Checking a state with moves costs a call to is_terminal, so only debug builds
+    // do it.
+    let terminal = if moves.is_empty() || cfg!(debug_assertions) {
+        Some(state.is_terminal())
+    } else {
+        None
+    };
+    let no_moves = moves.is_empty();
+    let report_mismatch = |handle: &Option<SearchHandle<Spec>>, mismatch| {
+        terminality_mismatches.fetch_add(1, Ordering::Relaxed);
+        let path = handle.as_ref().map_or(&[][..], |x| x.path_moves());
+        manager.on_terminality_mismatch(state, path, mismatch);
+    };
+    if terminal == Some(true) && !no_moves {
+        report_mismatch(&handle, TerminalityMismatch::TerminalWithMoves);
+    }
+    let excluded: Vec<bool> = moves
+        .iter()
+        .map(|x| manager.exclude_move(state, &x.mov))
+        .collect();
+    let excluded_moves = if excluded.contains(&true) {
+        let mut excluded = excluded.into_iter();
+        let (excluded, kept): (Vec<MoveInfo<Spec>>, _) =
+            moves.into_iter().partition(|_| excluded.next().unwrap());
+        moves = kept;
+        for x in &excluded {
+            x.stats.flags.set(MoveFlags::EXCLUDED, true);
+        }
+        excluded
+    } else {
+        Vec::new()
+    };
+    let pruned_moves = if moves.len() > manager.max_children() {
+        match handle.as_mut() {
+            Some(handle) => match handle
+                .tld
+                .expansion_seed()
+                .and_then(|seed| policy.seeded_thread_data(seed))
+            {
+                Some(mut rng) => prune_moves(manager, policy, &mut moves, &mut rng),
+                None => prune_moves(
+                    manager,
+                    policy,
+                    &mut moves,
+                    &mut handle.thread_data().policy_data,
+                ),
+            },
+            None => {
+                let mut rng = policy.seeded_thread_data(0).unwrap_or_default();
+                prune_moves(manager, policy, &mut moves, &mut rng)
+            }
+        }
+    } else {
+        0
+    };
+    moves.sort_by_key(|x| std::cmp::Reverse(state.move_order_hint(&x.mov)));
+    if moves.is_empty() && !terminal.unwrap_or_else(|| state.is_terminal()) {
+        match manager.no_moves_behaviour(state) {
+            NoMovesBehaviour::Terminal => (),
+            NoMovesBehaviour::Pass(mov, evaln) => moves.push(MoveInfo::new(mov, evaln)),
+            NoMovesBehaviour::Panic(description) => {
+                // `Terminal`, `Pass` and `SkipPlayout` settle what the state is, so only
+                // a state the spec refuses to handle is a mismatch.
+                if no_moves {
+                    report_mismatch(&handle, TerminalityMismatch::NoMovesNotTerminal);
+                }
+                panic!(
+                    "available_moves() is empty or excluded but is_terminal() is false for state {}",
+                    description
+                )
+            }
+            NoMovesBehaviour::SkipPlayout => {
+                let mut node = SearchNode::new(moves, state_eval, data);
+                node.skip_playouts = true;
+                node.excluded_moves = excluded_moves;
+                return node;
+            }
+        }
+    }
+    let mut node = SearchNode::new(moves, state_eval, data);
+    node.pruned_moves = pruned_moves;
+    node.excluded_moves = excluded_moves;
+    node
+}
+
+/// Keeps the `max_children` moves with the best priors and `random_extra_children`
+/// of the others, in their original order, and returns how many were discarded.
+fn prune_moves<Spec: MCTS>(
+    manager: &Spec,
+    policy: &Spec::TreePolicy,
+    moves: &mut Vec<MoveInfo<Spec>>,
+    rng: &mut TreePolicyThreadData<Spec>,
+) -> usize {
+    let max_children = manager.max_children().max(1);
+    let prior = |i: usize| {
+        let prior = policy.move_prior(&moves[i].move_evaluation);
+        if prior.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            prior
+        }
+    };
+    let mut order: Vec<usize> = (0..moves.len()).collect();
+    order.sort_by(|&a, &b| prior(b).partial_cmp(&prior(a)).unwrap());
+    let mut keep = vec![false; moves.len()];
+    for &i in &order[..max_children] {
+        keep[i] = true;
+    }
+    let mut rest = order.split_off(max_children);
+    for _ in 0..manager.random_extra_children().min(rest.len()) {
+        let chosen = rng.select_by_key(0..rest.len(), |_| 0.0).unwrap();
+        keep[rest.swap_remove(chosen)] = true;
+    }
+    let before = moves.len();
+    let mut keep = keep.into_iter();
+    moves.retain(|_| keep.next().unwrap());
+    before - moves.len()
 }
 
 fn is_cycle<T>(past: &[&T], current: &T) -> bool {
-    past.iter().any(|x| *x as *const T == current as *const T)
+    past.iter().any(|x| std::ptr::eq(*x, current))
 }
 
 impl<Spec: MCTS> SearchTree<Spec> {
@@ -198,245 +645,1389 @@ impl<Spec: MCTS> SearchTree<Spec> {
         eval: Spec::Eval,
         table: Spec::TranspositionTable,
     ) -> Self {
-        let root_node = create_node(&eval, &tree_policy, &state, None);
+        let terminality_mismatches = AtomicUsize::new(0);
+        let root_node = create_node(
+            &manager,
+            &eval,
+            &tree_policy,
+            &state,
+            None,
+            &mut PhaseTimer::default(),
+            &terminality_mismatches,
+        );
+        let root_hash = table.root_hash(&state);
+        let node_limit = manager.node_limit();
         Self {
             root_state: state,
+            root_hash,
             root_node,
             manager,
             tree_policy,
             eval,
             table,
             num_nodes: 1.into(),
+            carried_root_visits: 0,
+            playouts: 0.into(),
+            nodes_created: 0.into(),
+            evaluations: 0.into(),
             orphaned: Mutex::new(Vec::new()),
+            preloaded: Vec::new(),
             transposition_table_hits: 0.into(),
             delayed_transposition_table_hits: 0.into(),
             expansion_contention_events: 0.into(),
+            non_finite_rewards: 0.into(),
+            terminality_mismatches,
+            move_errors: 0.into(),
+            saturated_selections: 0.into(),
+            selection_fallbacks: 0.into(),
+            halted_playouts: 0.into(),
+            live: OnceLock::new(),
+            min_root_visits: AtomicU64::new(0),
+            root_floor_enabled: AtomicBool::new(true),
+            floor_visits: 0.into(),
+            root_noise: AtomicU64::new(0.0f64.to_bits()),
+            noisy_root_choices: 0.into(),
+            depth_histogram: std::array::from_fn(|_| AtomicUsize::new(0)),
+            phase_interval: AtomicUsize::new(0),
+            phase_nanos: std::array::from_fn(|_| AtomicU64::new(0)),
+            recording: AtomicBool::new(false),
+            recording_capacity: AtomicUsize::new(DEFAULT_RECORDING_CAPACITY),
+            records: Mutex::new(VecDeque::new()),
+            stability_interval: AtomicU64::new(0),
+            stability_capacity: AtomicUsize::new(0),
+            stability: Mutex::new(VecDeque::new()),
+            node_limit: AtomicUsize::new(node_limit),
+            reserved_nodes: ReservedNodes::default(),
+            nodes_reserved: AtomicUsize::new(0),
+            history: None,
+            shared_stats: None,
         }
     }
 
-    pub fn reset(self) -> Self {
-        Self::new(
+    pub fn reset(mut self) -> Self {
+        let node_limit = self.node_limit();
+        // The old nodes are freed when `self` goes out of scope.
+        self.table.clear();
+        let mut result = Self::new(
             self.root_state,
             self.manager,
             self.tree_policy,
             self.eval,
             self.table,
-        )
+        );
+        result.set_node_limit(node_limit);
+        result.set_min_root_visits(self.min_root_visits.into_inner());
+        result.set_root_noise(f64::from_bits(self.root_noise.into_inner()));
+        result.track_stability(
+            self.stability_interval.into_inner(),
+            self.stability_capacity.into_inner(),
+        );
+        result.record_playouts(
+            self.recording.into_inner(),
+            self.recording_capacity.into_inner(),
+        );
+        result.profile_phases(self.phase_interval.into_inner());
+        result.history = self.history;
+        result.shared_stats = self.shared_stats;
+        result
     }
 
-    pub fn spec(&self) -> &Spec {
-        &self.manager
+    /// Takes the tree apart, freeing its nodes. The transposition table is cleared,
+    /// as its entries point to them.
+    pub fn into_parts(mut self) -> TreeParts<Spec> {
+        self.table.clear();
+        TreeParts {
+            spec: self.manager,
+            evaluator: self.eval,
+            tree_policy: self.tree_policy,
+            root_state: self.root_state,
+            transposition_table: self.table,
+        }
     }
 
-    pub fn num_nodes(&self) -> usize {
-        self.num_nodes.load(Ordering::SeqCst)
+    /// Makes `state` the root, discarding the whole tree, like `reset` but in place.
+    /// The settings are kept, as are the counters of the tree's whole life such as
+    /// `total_playouts`.
+    pub fn set_root(&mut self, state: Spec::State) {
+        self.table.clear();
+        self.preloaded.clear();
+        self.root_node = create_node(
+            &self.manager,
+            &self.eval,
+            &self.tree_policy,
+            &state,
+            None,
+            &mut PhaseTimer::default(),
+            &self.terminality_mismatches,
+        );
+        self.root_hash = self.table.root_hash(&state);
+        self.root_state = state;
+        *self.num_nodes.get_mut() = 1;
+        self.carried_root_visits = 0;
+        self.forget_root();
     }
 
-    #[inline(never)]
-    pub fn playout(&self, tld: &mut ThreadData<Spec>) -> bool {
-        const LARGE_DEPTH: usize = 64;
-        let sentinel = IncreaseSentinel::new(&self.num_nodes);
-        if sentinel.num_nodes >= self.manager.node_limit() {
-            return false;
-        }
+    /// Plays the root move at `index` and makes the position after it the root.
+    /// The subtree below the move is kept, with its statistics, when the move has
+    /// a child of its own; otherwise the tree is rebuilt as by `set_root`. Trees
+    /// with a transposition table are always rebuilt, as nodes in the subtree may
+    /// be shared with the discarded ones. Returns whether the subtree was kept, or
+    /// an error if the move can't be made.
+    ///
+    /// # Panics
+    ///
+    /// If `index` isn't the index of one of the root's moves.
+    #[allow(clippy::result_unit_err)]
+    pub fn advance_root(&mut self, index: usize) -> Result<bool, ()> {
+        let choice = &self.root_node.moves[index];
         let mut state = self.root_state.clone();
-        let mut path: SmallVec<[&MoveInfo<Spec>; LARGE_DEPTH]> = SmallVec::new();
-        let mut node_path: SmallVec<[&SearchNode<Spec>; LARGE_DEPTH]> = SmallVec::new();
-        let mut players: SmallVec<[Player<Spec>; LARGE_DEPTH]> = SmallVec::new();
-        let mut did_we_create = false;
-        let mut node = &self.root_node;
-        loop {
-            if node.moves.is_empty() {
-                break;
-            }
-            if path.len() >= self.manager.max_playout_length() {
-                break;
-            }
-            let choice = self
-                .tree_policy
-                .choose_child(node.moves.iter(), self.make_handle(node, tld));
-            choice.stats.down(&self.manager);
-            players.push(state.current_player());
-            path.push(choice);
-            assert!(path.len() <= self.manager.max_playout_length(),
-                "playout length exceeded maximum of {} (maybe the transposition table is creating an infinite loop?)",
-                self.manager.max_playout_length());
-            if state.make_move(&choice.mov).is_err() {
-                break;
-            }
-
-            let (new_node, new_did_we_create) = self.descend(&state, choice, node, tld);
-            node = new_node;
-            did_we_create = new_did_we_create;
-            match self.manager.cycle_behaviour() {
-                CycleBehaviour::Ignore => (),
-                CycleBehaviour::PanicWhenCycleDetected => {
-                    if is_cycle(&node_path, node) {
-                        panic!("cycle detected! you should do one of the following:\n- make states acyclic\n- remove transposition table\n- change cycle_behaviour()");
-                    }
-                }
-                CycleBehaviour::UseCurrentEvalWhenCycleDetected => {
-                    if is_cycle(&node_path, node) {
-                        break;
-                    }
-                }
-                CycleBehaviour::UseThisEvalWhenCycleDetected(e) => {
-                    if is_cycle(&node_path, node) {
-                        self.finish_playout(&path, &node_path, &players, tld, &e);
-                        return true;
-                    }
+        state.make_move(&choice.mov)?;
+        let child = choice.child.load();
+        let keep = std::mem::size_of::<Spec::TranspositionTable>() == 0
+            && choice.owns_child()
+            && !child.is_null();
+        if !keep {
+            self.set_root(state);
+            return Ok(false);
+        }
+        // The old root gives up the child, then drops the rest of the tree.
+        choice.stats.flags.set(MoveFlags::OWNED, false);
+        self.root_node = *unsafe { Box::from_raw(child) };
+        self.root_hash = self.table.root_hash(&state);
+        self.root_state = state;
+        let mut nodes = 1;
+        let mut stack = vec![&self.root_node];
+        while let Some(node) = stack.pop() {
+            for mov in node.moves.iter().chain(&node.excluded_moves) {
+                let child = mov.child.load();
+                if mov.owns_child() && !child.is_null() {
+                    nodes += 1;
+                    stack.push(unsafe { &*child });
                 }
-            };
-            node_path.push(node);
-            node.stats.down(&self.manager);
-            if node.stats.visits.load(Ordering::Relaxed) as u64
-                <= self.manager.visits_before_expansion()
-            {
-                break;
             }
         }
-        let new_evaln = if did_we_create {
+        *self.num_nodes.get_mut() = nodes;
+        self.carried_root_visits = self.root_visits();
+        self.forget_root();
+        Ok(true)
+    }
+
+    /// Drops what was kept about the old root's moves.
+    fn forget_root(&mut self) {
+        self.live = OnceLock::new();
+        for x in &self.depth_histogram {
+            x.store(0, Ordering::Relaxed);
+        }
+        self.records.get_mut().unwrap().clear();
+        self.stability.get_mut().unwrap().clear();
+    }
+
+    pub(crate) fn carried_root_visits(&self) -> u64 {
+        self.carried_root_visits
+    }
+
+    /// The number of nodes at which playouts stop. Starts as `MCTS::node_limit`.
+    pub fn node_limit(&self) -> usize {
+        self.node_limit.load(Ordering::Relaxed)
+    }
+
+    pub fn set_node_limit(&self, limit: usize) {
+        self.node_limit.store(limit, Ordering::Relaxed);
+    }
+
+    /// The visits every root move gets before the tree policy chooses at the root.
+    pub fn min_root_visits(&self) -> u64 {
+        self.min_root_visits.load(Ordering::Relaxed)
+    }
+
+    /// Until every root move that isn't excluded has `visits` visits, playouts take
+    /// the root move with the fewest visits, the first of them on ties, instead of
+    /// asking the tree policy. This visits them in turn, so that no legal move ends
+    /// a small search unvisited. Virtual visits of playouts in progress count, so
+    /// parallel searches under `VirtualLossMode::Reward` may overshoot a little.
+    /// Zero, the default, turns this off.
+    pub fn set_min_root_visits(&self, visits: u64) {
+        self.min_root_visits.store(visits, Ordering::Relaxed);
+    }
+
+    /// Turns `min_root_visits` off for a search of `budget` playouts too small to
+    /// give every root move its visits, or back on for `None`.
+    pub(crate) fn enable_root_floor(&self, budget: Option<u64>) {
+        let floor = self.min_root_visits() * self.selectable_root_moves() as u64;
+        let enabled = budget.is_none_or(|budget| budget >= floor);
+        self.root_floor_enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// The root's only selectable move, when it has exactly one: the game allows no
+    /// other, or `MCTS::exclude_move`, `MCTS::max_children` or failed moves took
+    /// the others out.
+    pub fn forced_root_move(&self) -> Option<MoveInfoHandle<'_, Spec>> {
+        let mut moves = self.root_node.moves.iter().filter(|x| !x.is_excluded());
+        let only = moves.next()?;
+        if moves.next().is_some() {
             None
         } else {
-            Some(self.eval.evaluate_existing_state(
-                &state,
-                &node.evaln,
-                self.make_handle(node, tld),
-            ))
-        };
-
-        let evaln = new_evaln.as_ref().unwrap_or(&node.evaln);
+            Some(only)
+        }
+    }
 
-        self.finish_playout(&path, &node_path, &players, tld, evaln);
-        true
+    fn selectable_root_moves(&self) -> usize {
+        let root = &self.root_node;
+        root.moves.len() - root.num_excluded.load(Ordering::Relaxed)
     }
 
-    fn descend<'a, 'b>(
-        &'a self,
-        state: &Spec::State,
-        choice: &MoveInfo<Spec>,
-        current_node: &'b SearchNode<Spec>,
-        tld: &'b mut ThreadData<Spec>,
-    ) -> (&'a SearchNode<Spec>, bool) {
-        let child = choice.child.load(Ordering::Relaxed) as *const _;
-        if child != null() {
-            return unsafe { (&*child, false) };
+    /// The root move to visit under `min_root_visits`, if any.
+    fn floor_choice(&self) -> Option<&MoveInfo<Spec>> {
+        let floor = self.min_root_visits();
+        if floor == 0 || !self.root_floor_enabled.load(Ordering::Relaxed) {
+            return None;
         }
-        if let Some(node) = self
-            .table
-            .lookup(state, self.make_handle(current_node, tld))
-        {
-            let child = choice
-                .child
-                .compare_exchange(
-                    null_mut(),
-                    node as *const _ as *mut _,
-                    Ordering::Relaxed,
-                    Ordering::Relaxed,
-                )
-                .unwrap_or_else(|x| x) as *const _;
-            if child == null() {
-                self.transposition_table_hits
-                    .fetch_add(1, Ordering::Relaxed);
-                return (node, false);
-            } else {
-                return unsafe { (&*child, false) };
-            }
+        let moves = self.root_node.moves.iter().filter(|x| !x.is_excluded());
+        let choice = moves.min_by_key(|x| x.visits())?;
+        if choice.visits() < floor {
+            Some(choice)
+        } else {
+            None
         }
-        let created = create_node(
-            &self.eval,
-            &self.tree_policy,
-            state,
-            Some(self.make_handle(current_node, tld)),
-        );
-        let created = Box::into_raw(Box::new(created));
-        let other_child = choice
-            .child
-            .compare_exchange(null_mut(), created, Ordering::Relaxed, Ordering::Relaxed)
-            .unwrap_or_else(|x| x);
-        if other_child != null_mut() {
-            self.expansion_contention_events
-                .fetch_add(1, Ordering::Relaxed);
-            unsafe {
-                Box::from_raw(created);
-                return (&*other_child, false);
-            }
+    }
+
+    /// The probability that a playout takes a random root move.
+    pub fn root_noise(&self) -> f64 {
+        f64::from_bits(self.root_noise.load(Ordering::Relaxed))
+    }
+
+    /// With probability `epsilon`, clamped to `[0, 1]`, each playout takes a root
+    /// move chosen uniformly among those not excluded instead of asking the tree
+    /// policy, which still chooses below the root. Unlike noise mixed into the
+    /// priors, it leaves the move evaluations alone. The random numbers come from
+    /// each thread's own generator, seeded from the search's master seed, so seeded
+    /// single-threaded searches repeat. `min_root_visits` takes precedence. Zero,
+    /// the default, turns this off.
+    pub fn set_root_noise(&self, epsilon: f64) {
+        let epsilon = if epsilon.is_nan() {
+            0.0
+        } else {
+            epsilon.clamp(0.0, 1.0)
+        };
+        self.root_noise.store(epsilon.to_bits(), Ordering::Relaxed);
+    }
+
+    /// The root move to visit under `set_root_noise`, if this playout draws one.
+    fn noise_choice(&self, tld: &mut ThreadData<Spec>) -> Option<&MoveInfo<Spec>> {
+        let epsilon = self.root_noise();
+        if epsilon == 0.0 {
+            return None;
         }
-        if let Some(existing) = self.table.insert(
-            state,
-            unsafe { &*created },
-            self.make_handle(current_node, tld),
-        ) {
-            self.delayed_transposition_table_hits
-                .fetch_add(1, Ordering::Relaxed);
-            let existing_ptr = existing as *const _ as *mut _;
-            choice.child.store(existing_ptr, Ordering::Relaxed);
-            self.orphaned
-                .lock()
-                .unwrap()
-                .push(unsafe { Box::from_raw(created) });
-            return (existing, false);
+        // 53 random bits, uniform in [0, 1).
+        let draw = (tld.next_noise() >> 11) as f64 / (1u64 << 53) as f64;
+        if draw >= epsilon {
+            return None;
         }
-        choice.owned.store(true, Ordering::Relaxed);
-        self.num_nodes.fetch_add(1, Ordering::Relaxed);
-        unsafe { (&*created, true) }
+        let selectable = self.selectable_root_moves();
+        if selectable == 0 {
+            return None;
+        }
+        let index = tld.next_noise() % selectable as u64;
+        let mut moves = self.root_node.moves.iter().filter(|x| !x.is_excluded());
+        moves.nth(index as usize)
     }
 
-    fn finish_playout(
+    /// The playouts that took a random root move because of `set_root_noise`.
+    pub fn noisy_root_choices(&self) -> usize {
+        self.noisy_root_choices.load(Ordering::Relaxed)
+    }
+
+    /// Excludes every root move but `keep` from selection until the returned guard
+    /// is dropped, even by a panic. Only call it with no search running.
+    pub(crate) fn exclude_root_moves_except(
         &self,
-        path: &[&MoveInfo<Spec>],
-        node_path: &[&SearchNode<Spec>],
-        players: &[Player<Spec>],
-        tld: &mut ThreadData<Spec>,
-        evaln: &StateEvaluation<Spec>,
-    ) {
-        for ((move_info, player), node) in
-            path.iter().zip(players.iter()).zip(node_path.iter()).rev()
-        {
-            let evaln_value = self.eval.interpret_evaluation_for_player(evaln, player);
-            node.stats.up(&self.manager, evaln_value);
-            move_info.stats.replace(&node.stats);
-            unsafe {
-                self.manager.on_backpropagation(
-                    &evaln,
-                    self.make_handle(&*move_info.child.load(Ordering::Relaxed), tld),
-                );
-            }
-        }
-        self.manager
-            .on_backpropagation(&evaln, self.make_handle(&self.root_node, tld));
+        keep: &[&MoveInfo<Spec>],
+    ) -> RootExclusions<'_, Spec> {
+        let root = &self.root_node;
+        let keep = |x: &MoveInfo<Spec>| keep.iter().any(|&k| std::ptr::eq(k, x));
+        let indices = (0..root.moves.len())
+            .filter(|&i| !keep(&root.moves[i]) && root.exclude(&root.moves[i]))
+            .collect();
+        RootExclusions { root, indices }
     }
 
-    fn make_handle<'a>(
-        &'a self,
-        node: &'a SearchNode<Spec>,
-        tld: &'a mut ThreadData<Spec>,
-    ) -> SearchHandle<'a, Spec> {
-        SearchHandle {
-            node,
-            tld,
-            manager: &self.manager,
-        }
+    /// The playouts that took a root move because of `min_root_visits`.
+    pub fn floor_visits(&self) -> usize {
+        self.floor_visits.load(Ordering::Relaxed)
     }
 
-    pub fn root_state(&self) -> &Spec::State {
-        &self.root_state
+    pub fn spec(&self) -> &Spec {
+        &self.manager
     }
-    pub fn root_node(&self) -> NodeHandle<Spec> {
-        NodeHandle {
-            node: &self.root_node,
-        }
+
+    pub fn tree_policy(&self) -> &Spec::TreePolicy {
+        &self.tree_policy
     }
 
-    pub fn principal_variation(&self, num_moves: usize) -> Vec<MoveInfoHandle<Spec>> {
-        let mut result = Vec::new();
-        let mut crnt = &self.root_node;
-        while crnt.moves.len() != 0 && result.len() < num_moves {
-            let choice = self.manager.select_child_after_search(&crnt.moves);
-            result.push(choice);
-            let child = choice.child.load(Ordering::SeqCst) as *const SearchNode<Spec>;
-            if child == null() {
-                break;
+    pub fn evaluator(&self) -> &Spec::Eval {
+        &self.eval
+    }
+
+    pub fn transposition_table(&self) -> &Spec::TranspositionTable {
+        &self.table
+    }
+
+    /// The table updated during backpropagation, if any. See `HistoryTable`.
+    pub fn history_table(&self) -> Option<&Arc<HistoryTable<Move<Spec>>>> {
+        self.history.as_ref()
+    }
+
+    pub fn set_history_table(&mut self, table: Option<Arc<HistoryTable<Move<Spec>>>>) {
+        self.history = table;
+    }
+
+    /// The caller's statistics updated during backpropagation, if any. See
+    /// `SharedStats`.
+    pub fn shared_stats(&self) -> Option<&Arc<dyn SharedStats<Move<Spec>>>> {
+        self.shared_stats.as_ref()
+    }
+
+    pub fn set_shared_stats(&mut self, stats: Option<Arc<dyn SharedStats<Move<Spec>>>>) {
+        self.shared_stats = stats;
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        // Only compared with the node limit, which playouts in progress can
+        // overshoot anyway, and counted in summaries once the threads are joined.
+        self.num_nodes.load(Ordering::Relaxed)
+    }
+
+    /// The playouts finished since the tree was created, over every search. Unlike
+    /// `SearchSummary::playouts` it doesn't start again with each search, and it
+    /// can be read at any time, even during a search.
+    pub fn total_playouts(&self) -> u64 {
+        self.playouts.load(Ordering::Relaxed)
+    }
+
+    /// The nodes playouts have added to the tree since it was created, not counting
+    /// the root. Nodes lost to a transposition found by another thread aren't
+    /// counted.
+    pub fn total_nodes_created(&self) -> u64 {
+        self.nodes_created.load(Ordering::Relaxed)
+    }
+
+    /// The states playouts have evaluated from scratch since the tree was created:
+    /// the calls to `Evaluator::evaluate_new_state`, and the evaluations given to
+    /// `apply_evaluations`. The root's evaluation isn't counted.
+    pub fn total_evaluations(&self) -> u64 {
+        self.evaluations.load(Ordering::Relaxed)
+    }
+
+    /// Allocates room for `nodes` more nodes up front, capped by the node limit, and
+    /// asks the transposition table to make room for them too. Only the nodes
+    /// themselves are allocated ahead; their move lists are still allocated as they
+    /// are created.
+    pub fn reserve(&mut self, nodes: usize) {
+        let num_nodes = self.num_nodes();
+        let room = self.node_limit().saturating_sub(num_nodes);
+        let nodes = nodes.min(room.saturating_sub(self.reserved_nodes.left()));
+        self.reserved_nodes.add(nodes);
+        let left = self.reserved_nodes.left();
+        *self.nodes_reserved.get_mut() += nodes;
+        self.table.reserve(num_nodes + left);
+    }
+
+    /// The number of playouts through the root: those that made a move from it plus
+    /// those that ended at it, when it's terminal.
+    pub fn root_visits(&self) -> u64 {
+        let moves: u64 = self.root_node.moves.iter().map(|x| x.visits()).sum();
+        moves + self.root_node.evaluations.load(Ordering::Relaxed) as u64
+    }
+
+    /// The sum of the rewards of the root's moves, for the player to move at the
+    /// root. Every other node's rewards are for the player who moved into it, like
+    /// the rewards of the move leading there, but the root has no such move, so it
+    /// takes the perspective of the player choosing between its moves. Playouts
+    /// ending at a terminal root add nothing.
+    pub fn root_sum_rewards(&self) -> f64 {
+        self.root_node.moves.iter().map(|x| x.sum_rewards()).sum()
+    }
+
+    /// See `MCTSManager::root_value`.
+    pub fn root_value(&self) -> Option<ValueEstimate> {
+        let moves = &self.root_node.moves;
+        let visits: u64 = moves.iter().map(|x| x.visits()).sum();
+        if visits == 0 {
+            return None;
+        }
+        let sum: f64 = moves.iter().map(|x| x.sum_rewards()).sum();
+        let n = visits as f64;
+        #[cfg(feature = "reward_variance")]
+        let std_error = if visits == 1 {
+            f64::INFINITY
+        } else {
+            let sum_squares: f64 = moves.iter().map(|x| x.sum_squared_rewards()).sum();
+            let variance = ((sum_squares - sum * sum / n) / (n - 1.0)).max(0.0);
+            (variance / n).sqrt()
+        };
+        Some(ValueEstimate {
+            mean: sum / n,
+            #[cfg(feature = "reward_variance")]
+            std_error,
+            visits,
+        })
+    }
+
+    pub fn memory_stats(&self) -> MemoryStats {
+        let reserved = self.nodes_reserved.load(Ordering::Relaxed);
+        MemoryStats {
+            nodes: self.num_nodes(),
+            nodes_reserved: reserved,
+            reserved_nodes_used: reserved - self.reserved_nodes.left(),
+            bytes_per_move: std::mem::size_of::<MoveInfo<Spec>>(),
+            state_storage: self.table.state_storage(),
+        }
+    }
+
+    fn allocate_node(&self, node: SearchNode<Spec>) -> *mut SearchNode<Spec> {
+        match self.reserved_nodes.take() {
+            Some(slot) => Box::into_raw(Box::write(slot, node)),
+            None => Box::into_raw(Box::new(node)),
+        }
+    }
+
+    /// The depths of all playouts from the current root so far.
+    pub fn depth_histogram(&self) -> DepthHistogram {
+        DepthHistogram::from_atomics(&self.depth_histogram)
+    }
+
+    #[inline(never)]
+    pub fn playout(&self, tld: &mut ThreadData<Spec>) -> bool {
+        let end = self.run_playout(tld, false);
+        self.gather_fallbacks(tld);
+        match end {
+            PlayoutEnd::Halted => false,
+            PlayoutEnd::Finished => true,
+            PlayoutEnd::Pending(_) => unreachable!(),
+        }
+    }
+
+    /// Runs playouts until `max` of them are done or waiting for an evaluation, and
+    /// returns the ones waiting: those that stopped at a move whose node doesn't
+    /// exist yet. The rest end inside the tree and are backpropagated as usual.
+    /// Stops early when the node limit is reached.
+    ///
+    /// The waiting playouts keep their virtual loss until their leaves are passed to
+    /// `apply_evaluations`, or dropped. Each leaf holds a place under the node limit
+    /// until then too. Two leaves can stop at the same move; the evaluation applied
+    /// second is then used as the evaluation of an existing node, as when threads
+    /// race to create it.
+    pub fn collect_leaves<'a>(
+        &'a self,
+        max: usize,
+        tld: &mut ThreadData<Spec>,
+    ) -> Vec<PendingLeaf<'a, Spec>> {
+        let mut leaves = Vec::new();
+        for _ in 0..max {
+            let end = self.run_playout(tld, true);
+            self.gather_fallbacks(tld);
+            match end {
+                PlayoutEnd::Halted => break,
+                PlayoutEnd::Finished => (),
+                PlayoutEnd::Pending(leaf) => leaves.push(*leaf),
+            }
+        }
+        leaves
+    }
+
+    /// Finishes the playouts of `leaves` with the evaluations of their states, as
+    /// `Evaluator::evaluate_new_state` would return them for `PendingLeaf::moves`.
+    pub fn apply_evaluations<'a, I>(&'a self, leaves: I, tld: &mut ThreadData<Spec>)
+    where
+        I: IntoIterator<
+            Item = (
+                PendingLeaf<'a, Spec>,
+                Vec<MoveEvaluation<Spec>>,
+                StateEvaluation<Spec>,
+            ),
+        >,
+    {
+        for (leaf, move_eval, state_eval) in leaves {
+            self.apply_evaluation(leaf, move_eval, state_eval, tld);
+        }
+    }
+
+    /// Finishes the playout of one leaf. See `apply_evaluations`.
+    pub fn apply_evaluation<'a>(
+        &'a self,
+        mut leaf: PendingLeaf<'a, Spec>,
+        move_eval: Vec<MoveEvaluation<Spec>>,
+        state_eval: StateEvaluation<Spec>,
+        tld: &mut ThreadData<Spec>,
+    ) {
+        assert!(
+            std::ptr::eq(leaf.tree, self),
+            "applied a leaf collected from another tree"
+        );
+        // From here on the playout is finished or abandoned below, not on drop.
+        leaf.applied = true;
+        let timer = PhaseTimer::new(false);
+        let mut node_path = std::mem::take(&mut leaf.node_path);
+        tld.clear_path();
+        for (i, choice) in leaf.path.iter().enumerate() {
+            let node = if i == 0 {
+                &self.root_node
+            } else {
+                node_path[i - 1]
+            };
+            tld.push_path(&choice.mov, node.move_index(choice));
+        }
+        let parent = node_path.last().copied().unwrap_or(&self.root_node);
+        let choice = *leaf.path.last().unwrap();
+        let moves = leaf.moves.take().unwrap();
+        let created = build_node(
+            &self.manager,
+            &self.eval,
+            &self.tree_policy,
+            &leaf.state,
+            (moves, move_eval, state_eval),
+            Some(self.make_handle(parent, tld)),
+            &self.terminality_mismatches,
+        );
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+        let (node, did_we_create) =
+            self.attach_child(&leaf.state, leaf.hash, choice, parent, tld, created);
+        match self.check_cycle(&node_path, node) {
+            CycleCheck::None => {
+                node_path.push(node);
+                self.enter_node(node);
+            }
+            CycleCheck::UseCurrent => (),
+            CycleCheck::Use(e) => {
+                self.finish_playout(&leaf.path, &node_path, &leaf.players, tld, &e, timer);
+                return;
+            }
+        }
+        if node.skip_playouts {
+            self.abandon_playout(&leaf.path, &node_path);
+            return;
+        }
+        let new_evaln = if did_we_create {
+            None
+        } else {
+            Some(self.eval.evaluate_existing_state(
+                &leaf.state,
+                &node.evaln,
+                self.make_handle(node, tld),
+            ))
+        };
+        let evaln = new_evaln.as_ref().unwrap_or(&node.evaln);
+        emit_event!(SearchEvent::Playout {
+            depth: leaf.path.len()
+        });
+        self.finish_playout(&leaf.path, &node_path, &leaf.players, tld, evaln, timer);
+    }
+
+    fn run_playout<'a>(&'a self, tld: &mut ThreadData<Spec>, defer: bool) -> PlayoutEnd<'a, Spec> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!(target: "mcts", "playout").entered();
+        let sentinel = IncreaseSentinel::new(&self.num_nodes);
+        if sentinel.num_nodes >= self.node_limit() {
+            self.halted_playouts.fetch_add(1, Ordering::Relaxed);
+            return PlayoutEnd::Halted;
+        }
+        let mut state = self.root_state.clone();
+        let mut path: SmallVec<[&MoveInfo<Spec>; LARGE_DEPTH]> = SmallVec::new();
+        let mut node_path: SmallVec<[&SearchNode<Spec>; LARGE_DEPTH]> = SmallVec::new();
+        let mut players = PathPlayers::new();
+        let mut did_we_create = false;
+        let mut node = &self.root_node;
+        let mut hash = self.root_hash;
+        let mut timer = PhaseTimer::new(self.sample_phases());
+        timer.switch(Phase::Select);
+        tld.clear_path();
+        loop {
+            if node.moves.len() == node.num_excluded.load(Ordering::Relaxed) {
+                break;
+            }
+            if path.len() >= self.manager.max_playout_length() {
+                break;
+            }
+            let floor_choice = if path.is_empty() {
+                self.floor_choice()
+            } else {
+                None
+            };
+            let noise_choice = if path.is_empty() && floor_choice.is_none() {
+                self.noise_choice(tld)
+            } else {
+                None
+            };
+            let choice = if let Some(choice) = floor_choice.or(noise_choice) {
+                choice
+            } else if let Some(choice) = self.unsaturated_choice(node, tld) {
+                choice
+            } else if node.num_excluded.load(Ordering::Relaxed) == 0 {
+                let handle = self.make_handle(node, tld);
+                self.tree_policy.choose_child(node.moves.iter(), handle)
+            } else {
+                let moves = node.moves.iter().filter(|x| !x.is_excluded());
+                // Moves may be excluded since `num_excluded` was read.
+                if moves.clone().next().is_none() {
+                    break;
+                }
+                let handle = self.make_handle(node, tld);
+                self.tree_policy.choose_child(moves, handle)
+            };
+            choice.stats.down(self.virtual_loss_mode());
+            self.enter_move(choice);
+            let player = players.index_of(state.current_player());
+            let new_hash = hash.map(|h| self.table.hash_after(&state, h, &choice.mov));
+            if state.make_move(&choice.mov).is_err() {
+                self.move_errors.fetch_add(1, Ordering::Relaxed);
+                choice.stats.undo_down(self.virtual_loss_mode());
+                self.leave_move(choice);
+                match self.manager.move_error_behaviour() {
+                    MoveErrorBehaviour::Panic => panic!(
+                        "make_move failed for the move selected at depth {} (see MCTS::move_error_behaviour)",
+                        path.len()
+                    ),
+                    MoveErrorBehaviour::SkipAndExclude => {
+                        node.exclude(choice);
+                        continue;
+                    }
+                    MoveErrorBehaviour::AbortPlayout => {
+                        self.abandon_playout(&path, &node_path);
+                        return PlayoutEnd::Finished;
+                    }
+                }
+            }
+            if floor_choice.is_some() {
+                self.floor_visits.fetch_add(1, Ordering::Relaxed);
+            }
+            if noise_choice.is_some() {
+                self.noisy_root_choices.fetch_add(1, Ordering::Relaxed);
+            }
+            hash = new_hash;
+            players.push(player);
+            path.push(choice);
+            tld.push_path(&choice.mov, node.move_index(choice));
+            assert!(path.len() <= self.manager.max_playout_length(),
+                "playout length exceeded maximum of {} (maybe the transposition table is creating an infinite loop?)",
+                self.manager.max_playout_length());
+
+            let (new_node, new_did_we_create) = if !defer {
+                self.descend(&state, hash, choice, node, tld, &mut timer)
+            } else if let Some(child) = self.find_child(&state, hash, choice, node, tld) {
+                (child, false)
+            } else {
+                let moves = Some(state.available_moves());
+                return PlayoutEnd::Pending(Box::new(PendingLeaf {
+                    tree: self,
+                    state,
+                    moves,
+                    hash,
+                    path: path.into_vec(),
+                    node_path: node_path.into_vec(),
+                    players,
+                    applied: false,
+                    _sentinel: sentinel,
+                }));
+            };
+            timer.switch(Phase::Select);
+            node = new_node;
+            did_we_create = new_did_we_create;
+            match self.check_cycle(&node_path, node) {
+                CycleCheck::None => (),
+                CycleCheck::UseCurrent => break,
+                CycleCheck::Use(e) => {
+                    self.finish_playout(&path, &node_path, &players, tld, &e, timer);
+                    return PlayoutEnd::Finished;
+                }
+            }
+            node_path.push(node);
+            if self.enter_node(node) as u64 <= self.manager.visits_before_expansion() {
+                break;
+            }
+        }
+        if node.skip_playouts {
+            self.abandon_playout(&path, &node_path);
+            return PlayoutEnd::Finished;
+        }
+        let new_evaln = if did_we_create {
+            None
+        } else {
+            timer.switch(Phase::Evaluate);
+            Some(self.eval.evaluate_existing_state(
+                &state,
+                &node.evaln,
+                self.make_handle(node, tld),
+            ))
+        };
+
+        let evaln = new_evaln.as_ref().unwrap_or(&node.evaln);
+        emit_event!(SearchEvent::Playout { depth: path.len() });
+
+        self.finish_playout(&path, &node_path, &players, tld, evaln, timer);
+        PlayoutEnd::Finished
+    }
+
+    /// Whether `node`, just reached from the end of `node_path`, closes a cycle, and
+    /// what `MCTS::cycle_behaviour` says to do about it.
+    fn check_cycle(
+        &self,
+        node_path: &[&SearchNode<Spec>],
+        node: &SearchNode<Spec>,
+    ) -> CycleCheck<StateEvaluation<Spec>> {
+        let behaviour = self.manager.cycle_behaviour();
+        if let CycleBehaviour::Ignore = behaviour {
+            return CycleCheck::None;
+        }
+        if !is_cycle(node_path, node) {
+            return CycleCheck::None;
+        }
+        emit_event!(SearchEvent::CycleDetected {
+            depth: node_path.len() + 1
+        });
+        match behaviour {
+            CycleBehaviour::Ignore => unreachable!(),
+            CycleBehaviour::PanicWhenCycleDetected => panic!("cycle detected! you should do one of the following:\n- make states acyclic\n- remove transposition table\n- change cycle_behaviour()"),
+            CycleBehaviour::UseCurrentEvalWhenCycleDetected => CycleCheck::UseCurrent,
+            CycleBehaviour::UseThisEvalWhenCycleDetected(e) => CycleCheck::Use(e),
+        }
+    }
+
+    /// The tree policy's choice among `node`'s moves that aren't saturated under
+    /// `MCTS::max_in_flight`, or `None` when none of them is saturated, or all of
+    /// them are.
+    fn unsaturated_choice<'a>(
+        &'a self,
+        node: &'a SearchNode<Spec>,
+        tld: &mut ThreadData<Spec>,
+    ) -> Option<&'a MoveInfo<Spec>> {
+        let limit = self.manager.max_in_flight();
+        if limit == usize::MAX {
+            return None;
+        }
+        let selectable = node.moves.iter().filter(|x| !x.is_excluded());
+        if selectable.clone().all(|x| x.in_flight() < limit) {
+            return None;
+        }
+        let mut open = selectable.filter(move |x| x.in_flight() < limit).peekable();
+        open.peek()?;
+        self.saturated_selections.fetch_add(1, Ordering::Relaxed);
+        let handle = self.make_handle(node, tld);
+        Some(self.tree_policy.choose_child(open, handle))
+    }
+
+    fn enter_move(&self, choice: &MoveInfo<Spec>) {
+        if self.manager.max_in_flight() != usize::MAX {
+            choice.stats.flags.enter();
+        }
+    }
+
+    fn leave_move(&self, choice: &MoveInfo<Spec>) {
+        if self.manager.max_in_flight() != usize::MAX {
+            choice.stats.flags.leave();
+        }
+    }
+
+    /// Undoes the virtual loss of a playout that won't be backpropagated.
+    fn abandon_playout(&self, path: &[&MoveInfo<Spec>], node_path: &[&SearchNode<Spec>]) {
+        for choice in path {
+            choice.stats.undo_down(self.virtual_loss_mode());
+            self.leave_move(choice);
+        }
+        for node in node_path {
+            node.stats.undo_down(self.virtual_loss_mode());
+        }
+    }
+
+    fn descend<'a, 'b>(
+        &'a self,
+        state: &Spec::State,
+        hash: Option<u64>,
+        choice: &MoveInfo<Spec>,
+        current_node: &'b SearchNode<Spec>,
+        tld: &'b mut ThreadData<Spec>,
+        timer: &mut PhaseTimer,
+    ) -> (&'a SearchNode<Spec>, bool) {
+        if let Some(node) = self.find_child(state, hash, choice, current_node, tld) {
+            return (node, false);
+        }
+        timer.switch(Phase::Expand);
+        let created = create_node(
+            &self.manager,
+            &self.eval,
+            &self.tree_policy,
+            state,
+            Some(self.make_handle(current_node, tld)),
+            timer,
+            &self.terminality_mismatches,
+        );
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+        self.attach_child(state, hash, choice, current_node, tld, created)
+    }
+
+    /// The node `choice` leads to, from the tree or the transposition table, or
+    /// `None` if it has to be created.
+    fn find_child<'a>(
+        &'a self,
+        state: &Spec::State,
+        hash: Option<u64>,
+        choice: &MoveInfo<Spec>,
+        current_node: &SearchNode<Spec>,
+        tld: &mut ThreadData<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        let child = choice.child.load() as *const SearchNode<Spec>;
+        if !child.is_null() {
+            return unsafe { Some(&*child) };
+        }
+        let handle = self.make_handle(current_node, tld);
+        let found = match hash {
+            Some(hash) => self.table.lookup_hashed(state, hash, handle),
+            None => self.table.lookup(state, handle),
+        };
+        if let Some(node) = found {
+            let child = choice
+                .child
+                .publish(node as *const _ as *mut _)
+                .err()
+                .unwrap_or_else(null_mut) as *const SearchNode<Spec>;
+            if child.is_null() {
+                self.transposition_table_hits
+                    .fetch_add(1, Ordering::Relaxed);
+                emit_event!(SearchEvent::TranspositionHit);
+                return Some(node);
+            } else {
+                return unsafe { Some(&*child) };
+            }
+        }
+        None
+    }
+
+    /// Makes `created` the node `choice` leads to, unless another thread or the
+    /// transposition table got there first. Returns the node `choice` leads to and
+    /// whether it's `created`.
+    fn attach_child<'a>(
+        &'a self,
+        state: &Spec::State,
+        hash: Option<u64>,
+        choice: &MoveInfo<Spec>,
+        current_node: &SearchNode<Spec>,
+        tld: &mut ThreadData<Spec>,
+        created: SearchNode<Spec>,
+    ) -> (&'a SearchNode<Spec>, bool) {
+        let created = self.allocate_node(created);
+        let other_child = choice.child.publish(created).err().unwrap_or_else(null_mut);
+        if !other_child.is_null() {
+            self.expansion_contention_events
+                .fetch_add(1, Ordering::Relaxed);
+            self.orphaned
+                .lock()
+                .unwrap()
+                .push(unsafe { Box::from_raw(created) });
+            return unsafe { (&*other_child, false) };
+        }
+        let handle = self.make_handle(current_node, tld);
+        let existing = match hash {
+            Some(hash) => self
+                .table
+                .insert_hashed(state, hash, unsafe { &*created }, handle),
+            None => self.table.insert(state, unsafe { &*created }, handle),
+        };
+        if let Some(existing) = existing {
+            self.delayed_transposition_table_hits
+                .fetch_add(1, Ordering::Relaxed);
+            emit_event!(SearchEvent::TranspositionHit);
+            let existing_ptr = existing as *const _ as *mut _;
+            choice.child.replace(existing_ptr);
+            self.orphaned
+                .lock()
+                .unwrap()
+                .push(unsafe { Box::from_raw(created) });
+            return (existing, false);
+        }
+        // Read only with the tree at rest; see `MoveInfo::owned`.
+        choice.stats.flags.set(MoveFlags::OWNED, true);
+        self.num_nodes.fetch_add(1, Ordering::Relaxed);
+        self.nodes_created.fetch_add(1, Ordering::Relaxed);
+        emit_event!(SearchEvent::Expansion);
+        unsafe { (&*created, true) }
+    }
+
+    fn finish_playout(
+        &self,
+        path: &[&MoveInfo<Spec>],
+        node_path: &[&SearchNode<Spec>],
+        players: &PathPlayers<Spec>,
+        tld: &mut ThreadData<Spec>,
+        evaln: &StateEvaluation<Spec>,
+        mut timer: PhaseTimer,
+    ) {
+        timer.switch(Phase::Backpropagate);
+        self.depth_histogram[path.len().min(DEPTH_BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+        let playouts = self.playouts.fetch_add(1, Ordering::Relaxed) + 1;
+        let stability_interval = self.stability_interval.load(Ordering::Relaxed);
+        if stability_interval != 0 && playouts.is_multiple_of(stability_interval) {
+            self.sample_stability(playouts);
+        }
+        node_path
+            .last()
+            .copied()
+            .unwrap_or(&self.root_node)
+            .evaluations
+            .fetch_add(1, Ordering::Relaxed);
+        if self.recording.load(Ordering::Relaxed) {
+            self.record_playout(path, node_path, evaln);
+        }
+        let symmetry = self.manager.evaluation_symmetry();
+        let mut interpreted: Option<(usize, f64)> = None;
+        let mut root_reward = None;
+        // After a cycle, `path` ends with the move back into it, which has no node
+        // of its own in `node_path`.
+        for (i, (move_info, (turn, player))) in path.iter().zip(players.iter()).enumerate().rev() {
+            let evaln_value = match (symmetry, interpreted) {
+                (EvaluationSymmetry::Constant, Some((_, value))) => value,
+                (EvaluationSymmetry::ZeroSumTwoPlayer, Some((first, value))) => {
+                    if first == turn {
+                        value
+                    } else {
+                        -value
+                    }
+                }
+                _ => {
+                    let value = self.eval.interpret_evaluation_for_player(evaln, player);
+                    let value = self.check_reward(value);
+                    interpreted = Some((turn, value));
+                    value
+                }
+            };
+            if let Some(node) = node_path.get(i) {
+                node.stats.up(self.virtual_loss_mode(), evaln_value);
+            }
+            move_info.stats.up(self.virtual_loss_mode(), evaln_value);
+            self.leave_move(move_info);
+            root_reward = Some(evaln_value);
+            if let Some(history) = &self.history {
+                history.record(&move_info.mov, evaln_value);
+            }
+            if let Some(stats) = &self.shared_stats {
+                stats.record(&move_info.mov, evaln_value);
+            }
+            unsafe {
+                self.manager
+                    .on_backpropagation(evaln, self.make_handle(&*move_info.child.load(), tld));
+            }
+        }
+        self.manager
+            .on_backpropagation(evaln, self.make_handle(&self.root_node, tld));
+        self.manager.after_backpropagation(path, evaln);
+        if let Some(reward) = root_reward {
+            self.tree_policy.on_root_reward(reward);
+        }
+        if let Some(live) = self.live.get() {
+            let index = path.first().map(|x| self.root_move_index(x));
+            live.record(index, root_reward.unwrap_or(0.0));
+        }
+        if let Some(nanos) = timer.stop() {
+            for (total, x) in self.phase_nanos.iter().zip(nanos.iter()) {
+                total.fetch_add(*x, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn check_reward(&self, reward: f64) -> f64 {
+        if reward.is_finite() {
+            return reward;
+        }
+        match self.manager.non_finite_rewards() {
+            NonFiniteRewards::PanicInDebug if !cfg!(debug_assertions) => reward,
+            NonFiniteRewards::PanicInDebug | NonFiniteRewards::Panic => {
+                panic!("interpret_evaluation_for_player returned {}", reward)
+            }
+            NonFiniteRewards::Substitute(value) => {
+                self.non_finite_rewards.fetch_add(1, Ordering::Relaxed);
+                value
+            }
+        }
+    }
+
+    /// `TreePolicy::virtual_loss` if the policy has one, else
+    /// `MCTS::virtual_loss_mode`.
+    pub fn virtual_loss_mode(&self) -> VirtualLossMode {
+        self.tree_policy
+            .virtual_loss()
+            .unwrap_or_else(|| self.manager.virtual_loss_mode())
+    }
+
+    /// The number of rewards replaced under `NonFiniteRewards::Substitute`.
+    pub fn non_finite_rewards(&self) -> usize {
+        self.non_finite_rewards.load(Ordering::Relaxed)
+    }
+
+    /// The number of nodes created whose `is_terminal()` and `available_moves()`
+    /// disagree. See `MCTS::on_terminality_mismatch`.
+    pub fn terminality_mismatches(&self) -> usize {
+        self.terminality_mismatches.load(Ordering::Relaxed)
+    }
+
+    /// The number of moves whose `make_move` failed during tree descent. See
+    /// `MCTS::move_error_behaviour`.
+    pub fn move_errors(&self) -> usize {
+        self.move_errors.load(Ordering::Relaxed)
+    }
+
+    /// The number of times selection passed over a move saturated under
+    /// `MCTS::max_in_flight`.
+    pub fn saturated_selections(&self) -> usize {
+        self.saturated_selections.load(Ordering::Relaxed)
+    }
+
+    /// The choices the threads' `SelectionRng`s made by a fallback, gathered after
+    /// each playout with `SelectionRng::take_fallbacks`.
+    pub fn selection_fallbacks(&self) -> usize {
+        self.selection_fallbacks.load(Ordering::Relaxed)
+    }
+
+    fn gather_fallbacks(&self, tld: &mut ThreadData<Spec>) {
+        let fallbacks = tld.policy_data.take_fallbacks();
+        if fallbacks > 0 {
+            self.selection_fallbacks
+                .fetch_add(fallbacks as usize, Ordering::Relaxed);
+        }
+    }
+
+    /// See `MCTSManager::live_stats`. Once taken, every playout also updates the
+    /// view's own copy of the root statistics.
+    pub fn live_stats(&self) -> LiveStats<Move<Spec>> {
+        let live = self.live.get_or_init(|| {
+            let moves = self.root_node.moves.iter();
+            let moves = moves.map(|x| (x.mov.clone(), x.visits(), x.sum_rewards()));
+            Arc::new(LiveRoot::new(moves, self.root_visits()))
+        });
+        live.handle()
+    }
+
+    /// The index in the root's moves of `mov`, one of them.
+    fn root_move_index(&self, mov: &MoveInfo<Spec>) -> usize {
+        let start = self.root_node.moves.as_ptr() as usize;
+        (mov as *const MoveInfo<Spec> as usize - start) / std::mem::size_of::<MoveInfo<Spec>>()
+    }
+
+    /// The number of playouts refused because the tree had reached its node limit.
+    pub fn halted_playouts(&self) -> usize {
+        self.halted_playouts.load(Ordering::Relaxed)
+    }
+
+    /// The number of nodes created and then discarded because another thread
+    /// expanded the same move first.
+    pub fn expansion_contention_events(&self) -> usize {
+        self.expansion_contention_events.load(Ordering::Relaxed)
+    }
+
+    /// The number of times the search found the node for a move in the
+    /// transposition table instead of creating it.
+    pub fn transposition_table_hits(&self) -> usize {
+        self.transposition_table_hits.load(Ordering::Relaxed)
+    }
+
+    /// Evaluates each of `states` and puts a node for it in the transposition table,
+    /// so that searches reaching it later find it there instead of evaluating it.
+    /// The nodes are made as the search makes them, with their move evaluations
+    /// and without children, but no playout goes through them: they have no
+    /// visits. The tree keeps them until it's reset or gets a new root.
+    ///
+    /// States the table already holds aren't evaluated again. Trees without a
+    /// table store nothing.
+    pub fn preload_states(
+        &mut self,
+        states: &[Spec::State],
+        tld: &mut ThreadData<Spec>,
+    ) -> PreloadReport {
+        let mut report = PreloadReport::default();
+        if std::mem::size_of::<Spec::TranspositionTable>() == 0 {
+            report.not_stored = states.len();
+            return report;
+        }
+        for state in states {
+            let hash = self.table.root_hash(state);
+            let handle = self.make_handle(&self.root_node, tld);
+            let found = match hash {
+                Some(hash) => self.table.lookup_hashed(state, hash, handle),
+                None => self.table.lookup(state, handle),
+            };
+            if found.is_some() {
+                report.present += 1;
+                continue;
+            }
+            let created = Box::new(create_node(
+                &self.manager,
+                &self.eval,
+                &self.tree_policy,
+                state,
+                None,
+                &mut PhaseTimer::default(),
+                &self.terminality_mismatches,
+            ));
+            *self.evaluations.get_mut() += 1;
+            let handle = self.make_handle(&self.root_node, tld);
+            let existing = match hash {
+                Some(hash) => self.table.insert_hashed(state, hash, &created, handle),
+                None => self.table.insert(state, &created, handle),
+            };
+            if existing.is_some() {
+                report.present += 1;
+                continue;
+            }
+            let handle = self.make_handle(&self.root_node, tld);
+            let stored = match hash {
+                Some(hash) => self.table.lookup_hashed(state, hash, handle),
+                None => self.table.lookup(state, handle),
+            };
+            if stored.is_some_and(|x| std::ptr::eq(x, &*created)) {
+                report.inserted += 1;
+            } else {
+                report.not_stored += 1;
+            }
+            // Kept either way: a table may store a node without finding it later.
+            *self.num_nodes.get_mut() += 1;
+            *self.nodes_created.get_mut() += 1;
+            self.preloaded.push(created);
+        }
+        report
+    }
+
+    pub(crate) fn preloaded_nodes(&self) -> impl Iterator<Item = NodeHandle<'_, Spec>> {
+        self.preloaded.iter().map(|node| NodeHandle { node })
+    }
+
+    /// Adds a playout's visit to `node`, refreshing its cached visits when they're
+    /// due, and returns its visits as `AtomicNodeStats::down` does.
+    fn enter_node(&self, node: &SearchNode<Spec>) -> usize {
+        let visits = node.stats.down(self.virtual_loss_mode());
+        let interval = self.manager.cached_visits_interval();
+        // Other threads' visits, and virtual ones, can step over any particular count.
+        if interval != 0
+            && visits.saturating_sub(node.cached_visits.load(Ordering::Relaxed)) >= interval
+        {
+            node.cached_visits.store(visits, Ordering::Relaxed);
+        }
+        visits
+    }
+
+    /// Whether this thread's next playout should be timed.
+    fn sample_phases(&self) -> bool {
+        thread_local! {
+            static PLAYOUTS: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+        }
+        let interval = self.phase_interval.load(Ordering::Relaxed);
+        if interval == 0 {
+            return false;
+        }
+        PLAYOUTS.with(|x| {
+            let n = x.get().wrapping_add(1);
+            x.set(n);
+            n % interval == 0
+        })
+    }
+
+    /// Times the phases of one playout in every `interval` on each thread, or none
+    /// when `interval` is zero. See `SearchSummary::phase_breakdown`.
+    pub fn profile_phases(&self, interval: usize) {
+        self.phase_interval.store(interval, Ordering::Relaxed);
+    }
+
+    /// The time spent in each phase by the playouts sampled so far.
+    pub fn phase_times(&self) -> PhaseTimes {
+        PhaseTimes::from_atomics(&self.phase_nanos)
+    }
+
+    #[cold]
+    fn record_playout(
+        &self,
+        path: &[&MoveInfo<Spec>],
+        node_path: &[&SearchNode<Spec>],
+        evaln: &StateEvaluation<Spec>,
+    ) {
+        let parents = std::iter::once(&self.root_node).chain(node_path.iter().cloned());
+        let move_indices = path
+            .iter()
+            .zip(parents)
+            .map(|(choice, parent)| {
+                parent
+                    .moves
+                    .iter()
+                    .position(|x| std::ptr::eq(x, *choice))
+                    .unwrap()
+            })
+            .collect();
+        let record = PlayoutRecord {
+            move_indices,
+            evaluation: evaln.clone(),
+            thread: std::thread::current().id(),
+        };
+        let capacity = self.recording_capacity.load(Ordering::Relaxed);
+        let mut records = self.records.lock().unwrap();
+        while records.len() >= capacity.max(1) {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Starts or stops recording playouts. Only the last `capacity` playouts are kept.
+    pub fn record_playouts(&self, enabled: bool, capacity: usize) {
+        self.recording_capacity.store(capacity, Ordering::Relaxed);
+        self.recording.store(enabled, Ordering::Relaxed);
+    }
+
+    /// The recorded playouts, oldest first.
+    pub fn recorded_playouts(&self) -> Vec<PlayoutRecord<Spec>> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    #[cold]
+    fn sample_stability(&self, playouts: u64) {
+        let moves = &self.root_node.moves;
+        let best_move_index = if moves.is_empty() {
+            None
+        } else {
+            let best = self.manager.select_child_after_search(moves);
+            moves.iter().position(|x| std::ptr::eq(x, best))
+        };
+        let sample = StabilitySample {
+            playouts,
+            best_move_index,
+            root_value: self.root_value().map_or(f64::NAN, |x| x.mean),
+        };
+        let capacity = self.stability_capacity.load(Ordering::Relaxed);
+        let mut samples = self.stability.lock().unwrap();
+        while samples.len() >= capacity.max(1) {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    /// Samples the root's best move and value every `interval` playouts, keeping the
+    /// last `capacity` samples, or stops sampling when `interval` is zero. `reset`
+    /// drops the samples but keeps the setting.
+    pub fn track_stability(&self, interval: u64, capacity: usize) {
+        self.stability_capacity.store(capacity, Ordering::Relaxed);
+        self.stability_interval.store(interval, Ordering::Relaxed);
+    }
+
+    /// The samples taken by `track_stability`, oldest first.
+    pub fn stability_samples(&self) -> Vec<StabilitySample> {
+        self.stability.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Plays the moves of a recorded playout from the root state and returns the
+    /// resulting state, or `None` if the path no longer exists in the tree.
+    pub fn replay(&self, record: &PlayoutRecord<Spec>) -> Option<Spec::State> {
+        let mut state = self.root_state.clone();
+        let mut node = self.root_node();
+        for (i, &index) in record.move_indices.iter().enumerate() {
+            let choice = node.node.moves.get(index)?;
+            state.make_move(&choice.mov).ok()?;
+            if i + 1 < record.move_indices.len() {
+                node = choice.child()?;
+            }
+        }
+        Some(state)
+    }
+
+    fn make_handle<'a>(
+        &'a self,
+        node: &'a SearchNode<Spec>,
+        tld: &'a mut ThreadData<Spec>,
+    ) -> SearchHandle<'a, Spec> {
+        SearchHandle {
+            node,
+            tld,
+            manager: &self.manager,
+            history: self.history.as_deref(),
+            table: &self.table,
+        }
+    }
+
+    pub fn root_state(&self) -> &Spec::State {
+        &self.root_state
+    }
+
+    /// Runs `Evaluator::evaluate_new_state` for the root state again and gives the
+    /// root's moves, excluded ones included, the new move evaluations, keeping their
+    /// visits and rewards. For evaluators whose priors depend on context that has
+    /// changed since the root was evaluated. Moves are matched by equality; a move
+    /// the new evaluation doesn't cover keeps its old one. Returns the number of
+    /// moves updated. Like the root's first evaluation, it runs without a
+    /// `SearchHandle`.
+    ///
+    /// May run during a search, which sees each move's old or new evaluation.
+    pub fn refresh_root_evaluation(&self) -> usize
+    where
+        MoveEvaluation<Spec>: UpdatableMoveEvaluation,
+        Move<Spec>: PartialEq,
+    {
+        let state = &self.root_state;
+        let moves = state.available_moves();
+        let (evalns, _) = self.eval.evaluate_new_state(state, &moves, None);
+        let moves: Vec<_> = moves.into_iter().collect();
+        self.tree_policy.validate_evaluations(&evalns);
+        let root = &self.root_node;
+        let mut updated = 0;
+        for info in root.moves.iter().chain(&root.excluded_moves) {
+            if let Some(i) = moves.iter().position(|x| *x == info.mov) {
+                info.update_move_evaluation(evalns[i].load());
+                updated += 1;
+            }
+        }
+        updated
+    }
+    /// A read-only cursor at the root, for walking the tree.
+    ///
+    /// The handles borrow the tree, so nodes can't be freed while they're alive. They
+    /// are meant for inspecting the tree between searches: they can be used while
+    /// playouts are running, but then each statistic is read separately and the
+    /// numbers may not add up.
+    pub fn root_node(&self) -> NodeHandle<'_, Spec> {
+        NodeHandle {
+            node: &self.root_node,
+        }
+    }
+
+    /// The moves chosen by `MCTS::select_child_after_search`, starting at the root.
+    /// Stops at the first node that has no visited moves, so this is empty before any
+    /// playouts or if the root has no moves.
+    pub fn principal_variation(&self, num_moves: usize) -> Vec<MoveInfoHandle<'_, Spec>> {
+        self.principal_variation_reliable(num_moves, 1)
+    }
+
+    /// Like `principal_variation`, but stops at the first chosen move with fewer than
+    /// `min_visits` visits, whose statistics are too noisy to trust.
+    pub fn principal_variation_reliable(
+        &self,
+        num_moves: usize,
+        min_visits: u64,
+    ) -> Vec<MoveInfoHandle<'_, Spec>> {
+        self.line_from(self.root_node(), num_moves, min_visits)
+    }
+
+    /// `principal_variation_reliable`, starting at `node` instead of the root.
+    pub(crate) fn line_from<'a>(
+        &'a self,
+        node: NodeHandle<'a, Spec>,
+        num_moves: usize,
+        min_visits: u64,
+    ) -> Vec<MoveInfoHandle<'a, Spec>> {
+        let min_visits = min_visits.max(1);
+        let mut result = Vec::new();
+        let mut crnt = node.node;
+        while crnt.moves.len() > crnt.num_excluded.load(Ordering::Relaxed)
+            && result.len() < num_moves
+        {
+            let choice = self.manager.select_child_after_search(&crnt.moves);
+            if choice.visits() < min_visits {
+                break;
+            }
+            result.push(choice);
+            let child = choice.child.load() as *const SearchNode<Spec>;
+            if child.is_null() {
+                break;
             } else {
                 unsafe {
                     crnt = &*child;
@@ -446,6 +2037,124 @@ impl<Spec: MCTS> SearchTree<Spec> {
         result
     }
 
+    /// The moves of `principal_variation`, each with its mean reward and the mean
+    /// reward of its parent node, both for the player making the move. A node's value
+    /// for the player to move there is the mean over all of its moves, so no rewards
+    /// are negated, whatever the game's `EvaluationSymmetry`.
+    pub fn annotated_principal_variation(&self, num_moves: usize) -> Vec<AnnotatedPly<'_, Spec>> {
+        self.annotated_principal_variation_with(num_moves, NodeValueKind::Accumulated)
+    }
+
+    /// `annotated_principal_variation`, with each move's `value` of the given kind.
+    pub fn annotated_principal_variation_with(
+        &self,
+        num_moves: usize,
+        kind: NodeValueKind,
+    ) -> Vec<AnnotatedPly<'_, Spec>> {
+        let mut result = Vec::new();
+        let mut parent = &self.root_node;
+        let mut state = self.root_state.clone();
+        for choice in self.principal_variation(num_moves) {
+            let (visits, sum): (u64, f64) = parent
+                .moves
+                .iter()
+                .fold((0, 0.0), |(v, s), x| (v + x.visits(), s + x.sum_rewards()));
+            let parent_value = sum / visits as f64;
+            let value = self.move_value(&state, choice, kind);
+            result.push(AnnotatedPly {
+                move_info: choice,
+                visits: choice.visits(),
+                value,
+                parent_value,
+                delta: value - parent_value,
+            });
+            let child = choice.child.load() as *const SearchNode<Spec>;
+            if child.is_null() || state.make_move(&choice.mov).is_err() {
+                break;
+            }
+            parent = unsafe { &*child };
+        }
+        result
+    }
+
+    /// The value of `mov`, made from `state`, for the player making it: its mean
+    /// reward, or the visit-weighted mean of the moves of the node it leads to (see
+    /// `NodeValueKind`). NaN for unvisited moves.
+    pub fn move_value(
+        &self,
+        state: &Spec::State,
+        mov: &MoveInfo<Spec>,
+        kind: NodeValueKind,
+    ) -> f64 {
+        let accumulated = mov.avg_reward();
+        let child = match (kind, mov.child()) {
+            (NodeValueKind::ChildrenWeighted, Some(child)) => child,
+            _ => return accumulated,
+        };
+        let (visits, sum) = child
+            .moves()
+            .filter(|x| !x.is_excluded())
+            .fold((0, 0.0), |(v, s), x| (v + x.visits(), s + x.sum_rewards()));
+        if visits == 0 {
+            return accumulated;
+        }
+        let mean = sum / visits as f64;
+        let mut after = state.clone();
+        if after.make_move(&mov.mov).is_err() {
+            return accumulated;
+        }
+        if after.current_player() == state.current_player() {
+            return mean;
+        }
+        // The child's moves are rewarded for the player moving there.
+        match self.manager.evaluation_symmetry() {
+            EvaluationSymmetry::Constant => mean,
+            EvaluationSymmetry::ZeroSumTwoPlayer => -mean,
+            EvaluationSymmetry::General => accumulated,
+        }
+    }
+
+    /// The root's moves, in node order, with the score the tree policy currently
+    /// gives each of them and, with the `reward_variance` feature, a confidence interval
+    /// on their mean reward.
+    pub fn root_move_stats_detailed(&self) -> Vec<DetailedMoveStats<'_, Spec>> {
+        let moves = &self.root_node.moves;
+        let parent_visits = moves.iter().map(|x| x.visits()).sum();
+        moves
+            .iter()
+            .map(|x| DetailedMoveStats {
+                move_info: x,
+                excluded: x.is_excluded(),
+                visits: x.visits(),
+                mean: x.avg_reward(),
+                policy_score: self.tree_policy.score_child(x, parent_visits),
+                #[cfg(feature = "reward_variance")]
+                confidence_interval: x.confidence_interval(),
+            })
+            .collect()
+    }
+
+    /// The root's moves, in node order, with their visits and mean rewards. Cheaper
+    /// than `root_move_stats_detailed`, and like it borrows the moves from the tree
+    /// instead of cloning them.
+    pub fn root_move_stats_ref(&self) -> Vec<RootMoveStats<'_, Move<Spec>>> {
+        self.root_move_stats_with(NodeValueKind::Accumulated)
+    }
+
+    /// `root_move_stats_ref`, with each move's `mean` the value of the given kind.
+    pub fn root_move_stats_with(&self, kind: NodeValueKind) -> Vec<RootMoveStats<'_, Move<Spec>>> {
+        self.root_node
+            .moves
+            .iter()
+            .map(|x| RootMoveStats {
+                mov: &x.mov,
+                excluded: x.is_excluded(),
+                visits: x.visits(),
+                mean: self.move_value(&self.root_state, x, kind),
+            })
+            .collect()
+    }
+
     pub fn diagnose(&self) -> String {
         let mut s = String::new();
         s.push_str(&format!(
@@ -467,6 +2176,30 @@ impl<Spec: MCTS> SearchTree<Spec> {
             "{} expansion contention events\n",
             thousands_separate(self.expansion_contention_events.load(Ordering::Relaxed))
         ));
+        s.push_str(&format!(
+            "{} non-finite rewards replaced\n",
+            thousands_separate(self.non_finite_rewards.load(Ordering::Relaxed))
+        ));
+        s.push_str(&format!(
+            "{} terminality mismatches\n",
+            thousands_separate(self.terminality_mismatches.load(Ordering::Relaxed))
+        ));
+        s.push_str(&format!(
+            "{} move errors\n",
+            thousands_separate(self.move_errors.load(Ordering::Relaxed))
+        ));
+        s.push_str(&format!(
+            "{} saturated selections\n",
+            thousands_separate(self.saturated_selections.load(Ordering::Relaxed))
+        ));
+        s.push_str(&format!(
+            "{} playouts refused at the node limit\n",
+            thousands_separate(self.halted_playouts.load(Ordering::Relaxed))
+        ));
+        s.push_str(&format!(
+            "{} root floor visits\n",
+            thousands_separate(self.floor_visits.load(Ordering::Relaxed))
+        ));
         s.push_str(&format!(
             "{} orphaned nodes\n",
             self.orphaned.lock().unwrap().len()
@@ -477,10 +2210,93 @@ impl<Spec: MCTS> SearchTree<Spec> {
 
 pub type MoveInfoHandle<'a, Spec> = &'a MoveInfo<Spec>;
 
+/// See `SearchTree::root_move_stats_ref`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RootMoveStats<'a, M> {
+    pub mov: &'a M,
+    /// See `MoveInfo::is_excluded`.
+    pub excluded: bool,
+    pub visits: u64,
+    /// NaN for unvisited moves.
+    pub mean: f64,
+}
+
+/// See `SearchTree::preload_states`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PreloadReport {
+    /// States given a node in the transposition table.
+    pub inserted: usize,
+    /// States the table already held, which weren't evaluated again.
+    pub present: usize,
+    /// States evaluated but that the table didn't store, for example because it's
+    /// full, or because the tree has no table.
+    pub not_stored: usize,
+}
+
+/// See `SearchTree::root_move_stats_detailed`.
+pub struct DetailedMoveStats<'a, Spec: 'a + MCTS> {
+    pub move_info: MoveInfoHandle<'a, Spec>,
+    /// See `MoveInfo::is_excluded`.
+    pub excluded: bool,
+    pub visits: u64,
+    /// NaN for unvisited moves.
+    pub mean: f64,
+    /// The score given by `TreePolicy::score_child`.
+    pub policy_score: f64,
+    /// See `MoveInfo::confidence_interval`.
+    #[cfg(feature = "reward_variance")]
+    pub confidence_interval: Option<(f64, f64)>,
+}
+
+/// A move of `SearchTree::annotated_principal_variation`. Values are mean rewards
+/// for the player making the move.
+pub struct AnnotatedPly<'a, Spec: 'a + MCTS> {
+    pub move_info: MoveInfoHandle<'a, Spec>,
+    pub visits: u64,
+    /// The move's mean reward, or its value of the kind given to
+    /// `annotated_principal_variation_with`.
+    pub value: f64,
+    /// The mean reward over all of the parent node's moves: the value of the
+    /// position before the move was chosen.
+    pub parent_value: f64,
+    /// `value - parent_value`. Negative when the move is worse than the position's
+    /// average, as when a player's best move still lets the evaluation swing
+    /// against them.
+    pub delta: f64,
+}
+
+/// How the stats and principal variation APIs value a move, e.g.
+/// `SearchTree::root_move_stats_with`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NodeValueKind {
+    /// The mean of every reward backed up through the move. It includes the
+    /// evaluation of the node the move leads to, and rewards from when few of
+    /// that node's moves had been tried.
+    #[default]
+    Accumulated,
+    /// The visit-weighted mean of the moves of the node the move leads to, as they
+    /// stand now; excluded moves are skipped. Falls back to `Accumulated` for a move
+    /// without a node or whose node has no visited moves, and when the player moving
+    /// there is another one and `MCTS::evaluation_symmetry` is `General`, which
+    /// gives no way to convert the rewards between players.
+    ChildrenWeighted,
+}
+
+/// The order of the rows in `SearchTree::format_moves`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveSort {
+    /// Most visited first.
+    Visits,
+    /// Highest mean reward first. Unvisited moves come last.
+    Mean,
+}
+
 impl<Spec: MCTS> SearchTree<Spec>
 where
     Move<Spec>: Debug,
 {
+    /// Prints the root's moves to stdout, most visited first, each followed by the
+    /// moves of its child. See `format_moves` for a table.
     pub fn debug_moves(&self) {
         let mut moves: Vec<&MoveInfo<Spec>> = self.root_node.moves.iter().collect();
         moves.sort_by_key(|x| -(x.visits() as i64));
@@ -497,6 +2313,144 @@ where
     }
 }
 
+impl<Spec: MCTS> SearchTree<Spec>
+where
+    Move<Spec>: Debug,
+    MoveEvaluation<Spec>: Debug,
+{
+    /// A table of the root's moves with their visits, mean reward and move evaluation,
+    /// one row per move. Ties keep the order of the moves in the node.
+    pub fn format_moves(&self, sort: MoveSort, limit: Option<usize>) -> String {
+        let mut moves: Vec<&MoveInfo<Spec>> = self.root_node.moves.iter().collect();
+        match sort {
+            MoveSort::Visits => moves.sort_by_key(|x| std::cmp::Reverse(x.visits())),
+            MoveSort::Mean => moves.sort_by(|a, b| {
+                let key = |x: &MoveInfo<Spec>| x.mean_reward_or(f64::NEG_INFINITY);
+                key(b)
+                    .partial_cmp(&key(a))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }),
+        }
+        moves.truncate(limit.unwrap_or(moves.len()));
+        let names: Vec<String> = moves.iter().map(|x| format!("{:?}", x.mov)).collect();
+        let width = names.iter().map(|x| x.len()).max().unwrap_or(0).max(4);
+        let mut s = format!(
+            "{:<width$}  {:>8}  {:>10}  prior\n",
+            "move",
+            "visits",
+            "mean",
+            width = width
+        );
+        for (mov, name) in moves.iter().zip(names) {
+            let mean = if mov.visits() == 0 {
+                "-".to_string()
+            } else {
+                format!("{:.4}", mov.avg_reward())
+            };
+            s.push_str(&format!(
+                "{:<width$}  {:>8}  {:>10}  {:?}\n",
+                name,
+                mov.visits(),
+                mean,
+                mov.move_evaluation,
+                width = width
+            ));
+        }
+        if self.root_node.pruned_moves > 0 {
+            s.push_str(&format!(
+                "({} moves pruned by max_children)\n",
+                self.root_node.pruned_moves
+            ));
+        }
+        s
+    }
+}
+
+impl<Spec: MCTS> SearchTree<Spec>
+where
+    Move<Spec>: Debug,
+{
+    /// An indented rendering of the tree down to `depth` plies, showing each move's
+    /// visits and mean reward. Children are listed most visited first (ties keep
+    /// the order of the moves in the node), at most 5 per node; see `TreeDisplay::top`.
+    /// Unvisited moves are left out.
+    ///
+    /// The tree isn't locked, so this is meant for use between searches.
+    pub fn display_to_depth(&self, depth: usize) -> TreeDisplay<'_, Spec> {
+        TreeDisplay {
+            tree: self,
+            depth,
+            top: 5,
+        }
+    }
+}
+
+/// See `SearchTree::display_to_depth`.
+pub struct TreeDisplay<'a, Spec: 'a + MCTS> {
+    tree: &'a SearchTree<Spec>,
+    depth: usize,
+    top: usize,
+}
+
+impl<'a, Spec: MCTS> TreeDisplay<'a, Spec> {
+    /// Show at most `k` children per node.
+    pub fn top(mut self, k: usize) -> Self {
+        self.top = k;
+        self
+    }
+
+    fn fmt_children(&self, f: &mut Formatter, node: &SearchNode<Spec>, depth: usize) -> fmt::Result
+    where
+        Move<Spec>: Debug,
+    {
+        let mut moves: Vec<&MoveInfo<Spec>> =
+            node.moves.iter().filter(|x| x.visits() > 0).collect();
+        moves.sort_by_key(|x| std::cmp::Reverse(x.visits()));
+        let indent = depth * 2;
+        for mov in moves.iter().take(self.top) {
+            writeln!(
+                f,
+                "{:indent$}{:?} ({} visits, mean {:.4})",
+                "",
+                mov.mov,
+                mov.visits(),
+                mov.avg_reward(),
+                indent = indent
+            )?;
+            if depth + 1 < self.depth {
+                if let Some(child) = mov.child() {
+                    self.fmt_children(f, child.node, depth + 1)?;
+                }
+            }
+        }
+        if moves.len() > self.top {
+            writeln!(
+                f,
+                "{:indent$}… {} more",
+                "",
+                moves.len() - self.top,
+                indent = indent
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, Spec: MCTS> Display for TreeDisplay<'a, Spec>
+where
+    Move<Spec>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let root = &self.tree.root_node;
+        let visits: u64 = root.moves.iter().map(|x| x.visits()).sum();
+        writeln!(f, "root ({} visits)", visits)?;
+        if self.depth > 0 {
+            self.fmt_children(f, root, 0)?;
+        }
+        Ok(())
+    }
+}
+
 impl<Spec: MCTS> SearchTree<Spec>
 where
     Move<Spec>: Display,
@@ -516,17 +2470,104 @@ pub struct NodeHandle<'a, Spec: 'a + MCTS> {
 }
 
 impl<'a, Spec: MCTS> NodeHandle<'a, Spec> {
+    /// The node's own statistics: its visits count every playout that went through
+    /// it, from any parent.
+    pub(crate) fn node_stats(&self) -> NodeStats {
+        NodeStats {
+            visits: self.node.stats.visits.load(Ordering::Relaxed) as u64,
+            sum_rewards: self.node.stats.sum_evaluations.load(Ordering::Relaxed),
+        }
+    }
+    /// The number of playouts that ended at this node.
+    pub(crate) fn evaluations(&self) -> u64 {
+        self.node.evaluations.load(Ordering::Relaxed) as u64
+    }
+    /// The number of moves `MCTS::max_children` discarded when the node was created.
+    pub fn pruned_moves(&self) -> usize {
+        self.node.pruned_moves
+    }
+    /// The moves `MCTS::exclude_move` excluded when the node was created, with their
+    /// evaluations. They aren't among `moves`, and can't be included again.
+    pub fn excluded_moves(&self) -> Moves<'a, Spec> {
+        Moves {
+            iter: self.node.excluded_moves.iter(),
+        }
+    }
+    /// Excludes the move at `index` in `moves` from selection, returning false if
+    /// it already was. It keeps its statistics and child, and `include_child` makes
+    /// it selectable again. May be called during a search, though a playout that has
+    /// already chosen the move still goes through it. A node whose moves are all
+    /// excluded is evaluated as a leaf.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn exclude_child(&self, index: usize) -> bool {
+        self.node.exclude(&self.node.moves[index])
+    }
+    /// Makes the move at `index` in `moves` selectable again after `exclude_child`
+    /// or `MoveErrorBehaviour::SkipAndExclude` excluded it, returning false if it
+    /// wasn't excluded.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is out of bounds.
+    pub fn include_child(&self, index: usize) -> bool {
+        self.node.include(&self.node.moves[index])
+    }
+    /// The number of `moves` excluded from selection.
+    pub fn num_excluded_children(&self) -> usize {
+        self.node.num_excluded.load(Ordering::Relaxed)
+    }
+    /// The data made by `Evaluator::make_node_data` when the node was created.
     pub fn data(&self) -> &'a Spec::NodeData {
         &self.node.data
     }
-    pub fn moves(&self) -> Moves<Spec> {
+    /// The node's moves, in the order `available_moves` returned them, stably sorted
+    /// by `GameState::move_order_hint`. The order depends only on the state, never
+    /// on which thread created the node or when, so seeded searches list moves the
+    /// same way every time.
+    pub fn moves(&self) -> Moves<'a, Spec> {
         Moves {
             iter: self.node.moves.iter(),
         }
     }
+    /// The evaluator's evaluation of this node's state.
+    pub fn state_evaluation(&self) -> &'a StateEvaluation<Spec> {
+        &self.node.evaln
+    }
+    /// Each move with a snapshot of its statistics and its child node, if expanded.
+    pub fn children(
+        &self,
+    ) -> impl Iterator<Item = (&'a Move<Spec>, NodeStats, Option<NodeHandle<'a, Spec>>)> {
+        self.moves().map(|x| (x.get_move(), x.stats(), x.child()))
+    }
+    /// The total visits of this node's moves.
+    pub fn visits(&self) -> u64 {
+        self.moves().map(|x| x.visits()).sum()
+    }
+    /// The mean reward over all visits of this node's moves, for the player choosing
+    /// between them. NaN when unvisited.
+    pub fn mean_value(&self) -> f64 {
+        let sum: f64 = self.moves().map(|x| x.sum_rewards()).sum();
+        sum / self.visits() as f64
+    }
+    /// The child reached by `mov`, or `None` if it isn't a move here or hasn't been
+    /// expanded.
+    pub fn descend(&self, mov: &Move<Spec>) -> Option<NodeHandle<'a, Spec>>
+    where
+        Move<Spec>: PartialEq,
+    {
+        self.moves()
+            .find(|x| x.get_move() == mov)
+            .and_then(|x| x.child())
+    }
     pub fn into_raw(&self) -> *const () {
         self.node as *const _ as *const ()
     }
+    /// # Safety
+    ///
+    /// `ptr` must have been produced by `into_raw` on a node of a tree that is still alive.
     pub unsafe fn from_raw(ptr: *const ()) -> Self {
         NodeHandle {
             node: &*(ptr as *const SearchNode<Spec>),
@@ -550,43 +2591,249 @@ pub struct SearchHandle<'a, Spec: 'a + MCTS> {
     node: &'a SearchNode<Spec>,
     tld: &'a mut ThreadData<Spec>,
     manager: &'a Spec,
+    history: Option<&'a HistoryTable<Move<Spec>>>,
+    table: &'a Spec::TranspositionTable,
 }
 
 impl<'a, Spec: MCTS> SearchHandle<'a, Spec> {
+    /// A handle to the same node that borrows this one.
+    pub(crate) fn reborrow(&mut self) -> SearchHandle<'_, Spec> {
+        SearchHandle {
+            node: self.node,
+            tld: self.tld,
+            manager: self.manager,
+            history: self.history,
+            table: self.table,
+        }
+    }
+    /// The node being evaluated, or whose children are being chosen between.
     pub fn node(&self) -> NodeHandle<'a, Spec> {
         NodeHandle { node: self.node }
     }
+    /// The data of the thread running the playout. Tree policies draw their random
+    /// numbers from its `policy_data`.
     pub fn thread_data(&mut self) -> &mut ThreadData<Spec> {
         self.tld
     }
     pub fn mcts(&self) -> &'a Spec {
         self.manager
     }
+    /// The statistics of the node for `state` in the transposition table, for
+    /// evaluators that want to know whether a state has been seen before. The rewards
+    /// are for the player who moved into `state`.
+    ///
+    /// This is only a lookup, so it's safe while other threads insert. Like the
+    /// search's own lookups it can miss states the table dropped, and an approximate
+    /// table can return another state's node when their hashes collide. Always
+    /// `None` without a table.
+    pub fn probe_transposition(&mut self, state: &Spec::State) -> Option<NodeStats> {
+        let table = self.table;
+        table
+            .lookup(state, self.reborrow())
+            .map(|node| NodeHandle { node }.node_stats())
+    }
+    /// The moves selected from the root so far in this playout. When a new state is
+    /// evaluated, they lead from the root state to it; during backpropagation they
+    /// are the whole playout's.
+    pub fn path_moves(&self) -> &[Move<Spec>] {
+        self.tld.path()
+    }
+    /// Whether the timed search running this playout has run out of time. Always
+    /// false for searches that aren't timed.
+    ///
+    /// Slow evaluators can check this and return a cheaper estimate, so that
+    /// `MCTSManager::playout_for` and `playout_parallel_for` don't overshoot their
+    /// deadline by a whole evaluation.
+    pub fn deadline_exceeded(&self) -> bool {
+        self.tld
+            .deadline()
+            .is_some_and(|deadline| clock::Instant::now() >= deadline)
+    }
+    /// The data of this handle's node, from `Evaluator::make_node_data`.
+    pub fn node_data(&self) -> &'a Spec::NodeData {
+        &self.node.data
+    }
+    /// The visits of this handle's node as of its last refresh, for tree policies
+    /// to use instead of the sum of its children's, under
+    /// `MCTS::cached_visits_interval`. `None` when the spec doesn't cache them, and
+    /// for nodes not refreshed yet, like the root, whose visits aren't counted.
+    pub fn cached_visits(&self) -> Option<u64> {
+        if self.manager.cached_visits_interval() == 0 {
+            return None;
+        }
+        match self.node.cached_visits.load(Ordering::Relaxed) {
+            0 => None,
+            visits => Some(visits as u64),
+        }
+    }
+    /// The search's history table, if it has one.
+    pub fn history(&self) -> Option<&'a HistoryTable<Move<Spec>>> {
+        self.history
+    }
+    /// Updates the evaluation of the move at `index` in this handle's node.
+    /// See `MoveInfo::update_move_evaluation`.
+    pub fn update_move_evaluation(
+        &self,
+        index: usize,
+        value: <MoveEvaluation<Spec> as UpdatableMoveEvaluation>::Value,
+    ) where
+        MoveEvaluation<Spec>: UpdatableMoveEvaluation,
+    {
+        self.node.moves[index].update_move_evaluation(value);
+    }
 }
 
-impl NodeStats {
-    fn new() -> Self {
-        NodeStats {
-            sum_evaluations: AtomicF64::new(0.0),
-            visits: AtomicUsize::new(0),
+impl<Flags: Default> AtomicNodeStats<Flags> {
+    pub(crate) fn new() -> Self {
+        AtomicNodeStats {
+            sum_evaluations: AtomicStat::new(0.0),
+            #[cfg(feature = "reward_variance")]
+            sum_squared_evaluations: AtomicStat::new(0.0),
+            visits: CheckedAtomicUsize::new(0),
+            flags: Flags::default(),
         }
     }
-    fn down<Spec: MCTS>(&self, manager: &Spec) {
+    /// Returns the number of visits including this one, but not its virtual visits.
+    pub(crate) fn down(&self, virtual_loss: VirtualLossMode) -> usize {
         self.sum_evaluations
-            .fetch_sub(manager.virtual_loss(), Ordering::Relaxed);
-        self.visits.fetch_add(1, Ordering::Relaxed);
+            .fetch_sub(virtual_loss.reward(), Ordering::Relaxed);
+        self.visits
+            .fetch_add(1 + virtual_loss.visits(), Ordering::Relaxed)
+            + 1
+    }
+    pub(crate) fn undo_down(&self, virtual_loss: VirtualLossMode) {
+        self.sum_evaluations
+            .fetch_add(virtual_loss.reward(), Ordering::Relaxed);
+        self.visits
+            .fetch_sub(1 + virtual_loss.visits(), Ordering::Relaxed);
     }
-    fn up<Spec: MCTS>(&self, manager: &Spec, evaln: f64) {
-        let delta = evaln + manager.virtual_loss();
+    pub(crate) fn up(&self, virtual_loss: VirtualLossMode, evaln: f64) {
+        let delta = evaln + virtual_loss.reward();
         self.sum_evaluations.fetch_add(delta, Ordering::Relaxed);
+        if virtual_loss.visits() != 0 {
+            self.visits
+                .fetch_sub(virtual_loss.visits(), Ordering::Relaxed);
+        }
+        #[cfg(feature = "reward_variance")]
+        self.sum_squared_evaluations
+            .fetch_add(evaln * evaln, Ordering::Relaxed);
     }
-    fn replace(&self, other: &NodeStats) {
-        self.visits
-            .store(other.visits.load(Ordering::Relaxed), Ordering::Relaxed);
-        self.sum_evaluations.store(
-            other.sum_evaluations.load(Ordering::Relaxed),
-            Ordering::Relaxed,
-        );
+}
+
+/// A playout stopped by `SearchTree::collect_leaves` at a move whose node doesn't
+/// exist yet, waiting for the evaluation of the state the move leads to. Pass it to
+/// `SearchTree::apply_evaluations` to finish the playout; dropping it instead takes
+/// the playout's virtual loss back.
+pub struct PendingLeaf<'a, Spec: MCTS> {
+    tree: &'a SearchTree<Spec>,
+    state: Spec::State,
+    moves: Option<MoveList<Spec>>,
+    hash: Option<u64>,
+    path: Vec<&'a MoveInfo<Spec>>,
+    node_path: Vec<&'a SearchNode<Spec>>,
+    players: PathPlayers<Spec>,
+    applied: bool,
+    _sentinel: IncreaseSentinel<'a>,
+}
+
+impl<'a, Spec: MCTS> PendingLeaf<'a, Spec> {
+    /// The state to evaluate.
+    pub fn state(&self) -> &Spec::State {
+        &self.state
+    }
+    /// The available moves of `state`, in the order their evaluations are expected.
+    pub fn moves(&self) -> &MoveList<Spec> {
+        self.moves.as_ref().unwrap()
+    }
+    /// The moves from the root to `state`.
+    pub fn path(&self) -> impl Iterator<Item = &'a Move<Spec>> + '_ {
+        self.path.iter().map(|x| &x.mov)
+    }
+    /// The number of moves from the root to `state`.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+}
+
+impl<'a, Spec: MCTS> Drop for PendingLeaf<'a, Spec> {
+    fn drop(&mut self) {
+        if !self.applied {
+            self.tree.abandon_playout(&self.path, &self.node_path);
+        }
+    }
+}
+
+/// The players to move at each ply of a playout, each distinct player stored once.
+struct PathPlayers<Spec: MCTS> {
+    distinct: SmallVec<[PlayerOf<Spec>; 2]>,
+    /// Indices into `distinct`.
+    turns: SmallVec<[usize; LARGE_DEPTH]>,
+}
+
+impl<Spec: MCTS> PathPlayers<Spec> {
+    fn new() -> Self {
+        Self {
+            distinct: SmallVec::new(),
+            turns: SmallVec::new(),
+        }
+    }
+
+    /// The index of `player`, cloning it if it hasn't moved yet in this playout.
+    fn index_of(&mut self, player: &PlayerOf<Spec>) -> usize {
+        match self.distinct.iter().position(|x| x == player) {
+            Some(index) => index,
+            None => {
+                self.distinct.push(player.clone());
+                self.distinct.len() - 1
+            }
+        }
+    }
+
+    /// Adds a ply by the player at `index`.
+    fn push(&mut self, index: usize) {
+        self.turns.push(index);
+    }
+
+    /// The index and player of each ply.
+    fn iter(
+        &self,
+    ) -> impl ExactSizeIterator<Item = (usize, &PlayerOf<Spec>)> + DoubleEndedIterator {
+        let distinct = &self.distinct;
+        self.turns.iter().map(move |&x| (x, &distinct[x]))
+    }
+}
+
+/// How a playout run by `SearchTree::run_playout` ended.
+enum PlayoutEnd<'a, Spec: MCTS> {
+    /// The node limit stopped it before it started.
+    Halted,
+    /// It was backpropagated, or abandoned.
+    Finished,
+    /// It's waiting for an evaluation.
+    Pending(Box<PendingLeaf<'a, Spec>>),
+}
+
+/// What to do about the node a playout just reached. See `SearchTree::check_cycle`.
+enum CycleCheck<E> {
+    None,
+    /// Stop, and evaluate the node as it is.
+    UseCurrent,
+    /// Stop, and use this evaluation.
+    Use(E),
+}
+
+/// Root moves excluded by `SearchTree::exclude_root_moves_except`, which are
+/// included again when it's dropped.
+pub(crate) struct RootExclusions<'a, Spec: MCTS> {
+    root: &'a SearchNode<Spec>,
+    indices: Vec<usize>,
+}
+
+impl<'a, Spec: MCTS> Drop for RootExclusions<'a, Spec> {
+    fn drop(&mut self) {
+        for &i in &self.indices {
+            self.root.include(&self.root.moves[i]);
+        }
     }
 }
 
@@ -607,3 +2854,55 @@ impl<'a> Drop for IncreaseSentinel<'a> {
         self.x.fetch_sub(1, Ordering::Relaxed);
     }
 }
+
+/// Allocations made ahead by `SearchTree::reserve`. Threads take them without
+/// locking: each claims a slot by counting `left` down, so no two get the same one.
+struct ReservedNodes<T> {
+    slots: Vec<*mut MaybeUninit<T>>,
+    /// The slots before this index haven't been taken yet.
+    left: AtomicUsize,
+}
+
+unsafe impl<T: Send> Send for ReservedNodes<T> {}
+unsafe impl<T: Send> Sync for ReservedNodes<T> {}
+
+impl<T> Default for ReservedNodes<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            left: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<T> ReservedNodes<T> {
+    fn left(&self) -> usize {
+        self.left.load(Ordering::Relaxed)
+    }
+
+    fn add(&mut self, count: usize) {
+        // The slots past `left` were taken, and are owned by their nodes now.
+        self.slots.truncate(*self.left.get_mut());
+        self.slots.reserve(count);
+        for _ in 0..count {
+            self.slots.push(Box::into_raw(Box::new_uninit()));
+        }
+        *self.left.get_mut() = self.slots.len();
+    }
+
+    fn take(&self) -> Option<Box<MaybeUninit<T>>> {
+        let left = self
+            .left
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| x.checked_sub(1))
+            .ok()?;
+        Some(unsafe { Box::from_raw(self.slots[left - 1]) })
+    }
+}
+
+impl<T> Drop for ReservedNodes<T> {
+    fn drop(&mut self) {
+        for &slot in &self.slots[..*self.left.get_mut()] {
+            drop(unsafe { Box::from_raw(slot) });
+        }
+    }
+}
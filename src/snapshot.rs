@@ -0,0 +1,157 @@
+use super::*;
+use std::collections::{HashMap, VecDeque};
+
+/// An immutable copy of a search tree: the moves, their statistics and evaluations,
+/// and the state evaluations, without the atomics, node data or transposition table
+/// of the live tree. It can be sent to another thread while the manager goes on
+/// searching or is reset.
+///
+/// Nodes shared through the transposition table are copied once and stay shared.
+pub struct TreeSnapshot<Spec: MCTS> {
+    nodes: Vec<SnapshotNodeData<Spec>>,
+    moves: Vec<SnapshotMove<Spec>>,
+}
+
+struct SnapshotNodeData<Spec: MCTS> {
+    evaln: StateEvaluation<Spec>,
+    moves: std::ops::Range<usize>,
+}
+
+/// A move in a `TreeSnapshot`.
+pub struct SnapshotMove<Spec: MCTS> {
+    mov: Move<Spec>,
+    move_evaluation: MoveEvaluation<Spec>,
+    stats: NodeStats,
+    child: Option<usize>,
+}
+
+impl<Spec: MCTS> SnapshotMove<Spec> {
+    pub fn get_move(&self) -> &Move<Spec> {
+        &self.mov
+    }
+    pub fn move_evaluation(&self) -> &MoveEvaluation<Spec> {
+        &self.move_evaluation
+    }
+    pub fn stats(&self) -> NodeStats {
+        self.stats
+    }
+    pub fn visits(&self) -> u64 {
+        self.stats.visits
+    }
+    pub fn sum_rewards(&self) -> f64 {
+        self.stats.sum_rewards
+    }
+}
+
+impl<Spec: MCTS> SearchTree<Spec>
+where
+    Move<Spec>: Clone,
+    MoveEvaluation<Spec>: Clone,
+{
+    /// Copies the tree in one pass.
+    ///
+    /// The statistics are read with the same relaxed loads as `MoveInfo::visits`, so
+    /// a snapshot taken while a search is running may be slightly skewed: a move's
+    /// visits and rewards can come from different playouts, and a parent can disagree
+    /// with its children. Take it between searches for an exact copy.
+    pub fn snapshot(&self) -> TreeSnapshot<Spec> {
+        let mut snapshot = TreeSnapshot {
+            nodes: Vec::new(),
+            moves: Vec::new(),
+        };
+        let mut ids: HashMap<*const (), usize> = HashMap::new();
+        let root = self.root_node();
+        ids.insert(root.into_raw(), 0);
+        // Breadth first, so nodes are copied in the order their ids were given out.
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            let start = snapshot.moves.len();
+            for mov in node.moves() {
+                let child = mov.child().map(|child| {
+                    let id = ids.len();
+                    *ids.entry(child.into_raw()).or_insert_with(|| {
+                        queue.push_back(child);
+                        id
+                    })
+                });
+                snapshot.moves.push(SnapshotMove {
+                    mov: mov.get_move().clone(),
+                    move_evaluation: mov.move_evaluation().clone(),
+                    stats: mov.stats(),
+                    child,
+                });
+            }
+            snapshot.nodes.push(SnapshotNodeData {
+                evaln: node.state_evaluation().clone(),
+                moves: start..snapshot.moves.len(),
+            });
+        }
+        snapshot
+    }
+}
+
+impl<Spec: MCTS> TreeSnapshot<Spec> {
+    pub fn root_node(&self) -> SnapshotNode<'_, Spec> {
+        SnapshotNode {
+            snapshot: self,
+            index: 0,
+        }
+    }
+    /// The number of distinct nodes copied.
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+/// A cursor into a `TreeSnapshot`, with the same reading methods as `NodeHandle`.
+pub struct SnapshotNode<'a, Spec: MCTS> {
+    snapshot: &'a TreeSnapshot<Spec>,
+    index: usize,
+}
+
+impl<'a, Spec: MCTS> Clone for SnapshotNode<'a, Spec> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, Spec: MCTS> Copy for SnapshotNode<'a, Spec> {}
+
+impl<'a, Spec: MCTS> SnapshotNode<'a, Spec> {
+    pub fn moves(&self) -> std::slice::Iter<'a, SnapshotMove<Spec>> {
+        let range = self.snapshot.nodes[self.index].moves.clone();
+        self.snapshot.moves[range].iter()
+    }
+    pub fn state_evaluation(&self) -> &'a StateEvaluation<Spec> {
+        &self.snapshot.nodes[self.index].evaln
+    }
+    /// Each move with its statistics and its child node, if expanded.
+    pub fn children(
+        &self,
+    ) -> impl Iterator<Item = (&'a Move<Spec>, NodeStats, Option<SnapshotNode<'a, Spec>>)> {
+        let snapshot = self.snapshot;
+        self.moves().map(move |x| {
+            let child = x.child.map(|index| SnapshotNode { snapshot, index });
+            (&x.mov, x.stats, child)
+        })
+    }
+    /// The total visits of this node's moves.
+    pub fn visits(&self) -> u64 {
+        self.moves().map(|x| x.visits()).sum()
+    }
+    /// The mean reward over all visits of this node's moves, for the player choosing
+    /// between them. NaN when unvisited.
+    pub fn mean_value(&self) -> f64 {
+        let sum: f64 = self.moves().map(|x| x.sum_rewards()).sum();
+        sum / self.visits() as f64
+    }
+    /// The child reached by `mov`, or `None` if it isn't a move here or hadn't been
+    /// expanded.
+    pub fn descend(&self, mov: &Move<Spec>) -> Option<SnapshotNode<'a, Spec>>
+    where
+        Move<Spec>: PartialEq,
+    {
+        self.children().find(|x| x.0 == mov).and_then(|x| x.2)
+    }
+}
@@ -0,0 +1,41 @@
+//! Running a parallel search's workers on threads the caller already owns.
+
+/// Runs the workers of `MCTSManager::playout_n_parallel_on` and
+/// `playout_parallel_for_on`. The manager makes each worker's thread data when it
+/// starts, from the search's seed and the worker's index, and drops it when it
+/// returns, so nothing a pool thread ran carries over to the next search.
+pub trait Spawner {
+    /// Calls `work(i)` once for each `i` in `0..workers`, and returns once every
+    /// call has. The calls may share threads, and the manager only relies on them
+    /// running at the same time for speed: a worker started after the budget ran
+    /// out returns at once.
+    fn run(&self, workers: usize, work: &(dyn Fn(usize) + Sync));
+}
+
+/// Spawns a thread for each worker and joins them all, as
+/// `MCTSManager::playout_n_parallel` does.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpawnThreads;
+
+impl Spawner for SpawnThreads {
+    fn run(&self, workers: usize, work: &(dyn Fn(usize) + Sync)) {
+        crossbeam::scope(|scope| {
+            for i in 0..workers {
+                scope.spawn(move || work(i));
+            }
+        });
+    }
+}
+
+/// Runs the workers as jobs in the pool. With more workers than the pool has
+/// threads, the extra ones wait for a thread.
+#[cfg(feature = "rayon")]
+impl Spawner for rayon::ThreadPool {
+    fn run(&self, workers: usize, work: &(dyn Fn(usize) + Sync)) {
+        self.scope(|scope| {
+            for i in 0..workers {
+                scope.spawn(move |_| work(i));
+            }
+        });
+    }
+}
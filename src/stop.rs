@@ -0,0 +1,60 @@
+//! Conditions that end a search before its budget runs out.
+
+use super::*;
+
+/// A condition for `MCTSManager::playout_n_until` to stop a search early, for
+/// conditions on the tree that a `playout_until` closure can't see. Checked every
+/// `interval` playouts, since checking walks the tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StopWhen {
+    condition: Condition,
+    interval: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Condition {
+    PvDepth { depth: usize, min_visits: u64 },
+}
+
+impl StopWhen {
+    /// Checks every 64 playouts unless set with `check_every`.
+    pub const DEFAULT_INTERVAL: u64 = 64;
+
+    /// When the principal variation is at least `depth` moves deep with every move
+    /// in it visited at least `min_visits` times, as `principal_variation_reliable`
+    /// finds it. A line that reaches the end of the game sooner, with that many
+    /// visits, counts as deep enough: there is nothing left to search below it.
+    pub fn pv_depth(depth: usize, min_visits: u64) -> Self {
+        Self {
+            condition: Condition::PvDepth { depth, min_visits },
+            interval: Self::DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Checks the condition every `playouts` playouts.
+    pub fn check_every(mut self, playouts: u64) -> Self {
+        self.interval = playouts.max(1);
+        self
+    }
+
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Whether the condition holds for `tree` now.
+    pub fn is_met<Spec: MCTS>(&self, tree: &SearchTree<Spec>) -> bool {
+        match self.condition {
+            Condition::PvDepth { depth, min_visits } => {
+                let pv = tree.principal_variation_reliable(depth, min_visits);
+                if pv.len() >= depth {
+                    return true;
+                }
+                let game_over = |x: &&MoveInfo<Spec>| {
+                    x.child()
+                        .is_some_and(|child| child.moves().next().is_none())
+                };
+                pv.last().is_some_and(game_over)
+            }
+        }
+    }
+}
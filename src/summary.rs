@@ -0,0 +1,580 @@
+use super::*;
+use clock::Instant;
+use std::fmt;
+
+/// Number of histogram buckets: one per depth from 0 to 63, then one for deeper playouts.
+pub const DEPTH_BUCKETS: usize = 65;
+
+/// How many playouts ended at each depth.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepthHistogram {
+    counts: [u64; DEPTH_BUCKETS],
+}
+
+impl Default for DepthHistogram {
+    fn default() -> Self {
+        Self {
+            counts: [0; DEPTH_BUCKETS],
+        }
+    }
+}
+
+impl DepthHistogram {
+    pub(crate) fn from_atomics(counts: &[AtomicUsize; DEPTH_BUCKETS]) -> Self {
+        let mut result = Self::default();
+        for (x, y) in result.counts.iter_mut().zip(counts.iter()) {
+            *x = y.load(Ordering::Relaxed) as u64;
+        }
+        result
+    }
+
+    fn since(&self, earlier: &Self) -> Self {
+        let mut result = self.clone();
+        for (x, y) in result.counts.iter_mut().zip(earlier.counts.iter()) {
+            *x -= y;
+        }
+        result
+    }
+
+    /// The number of playouts per depth. The last bucket counts playouts of
+    /// `DEPTH_BUCKETS - 1` plies or more.
+    pub fn counts(&self) -> &[u64; DEPTH_BUCKETS] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The smallest depth such that at least a fraction `p` of playouts ended at that
+    /// depth or shallower: `percentile(0.5)` is the median depth. Returns `None` when
+    /// there are no playouts. Playouts in the overflow bucket count as
+    /// `DEPTH_BUCKETS - 1`.
+    pub fn percentile(&self, p: f64) -> Option<usize> {
+        assert!(
+            (0.0..=1.0).contains(&p),
+            "percentile {} is not in [0, 1]",
+            p
+        );
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let target = ((p * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (depth, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return Some(depth);
+            }
+        }
+        Some(DEPTH_BUCKETS - 1)
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let sum: u64 = self
+            .counts
+            .iter()
+            .enumerate()
+            .map(|(depth, &count)| depth as u64 * count)
+            .sum();
+        Some(sum as f64 / total as f64)
+    }
+}
+
+/// How many nodes the tree holds, and how many of those came from `reserve`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub nodes: usize,
+    /// Nodes allocated by `SearchTree::reserve` so far.
+    pub nodes_reserved: usize,
+    /// Reserved nodes that have been handed out. Nodes dropped after losing an
+    /// expansion race are counted too.
+    pub reserved_nodes_used: usize,
+    /// The size of a `MoveInfo`, not counting anything its move or move evaluation
+    /// allocate. Smaller with the `f32_stats` feature.
+    pub bytes_per_move: usize,
+    /// The states the transposition table keeps copies of.
+    pub state_storage: StateStorage,
+}
+
+/// The value of the root position for the player to move there, from
+/// `MCTSManager::root_value`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ValueEstimate {
+    /// The mean reward over the visits to the root's moves. Each move's rewards are
+    /// for the player who makes it, which is the player to move at the root.
+    pub mean: f64,
+    /// The standard error of `mean`, from the sample variance of the rewards.
+    /// Infinite with a single visit. Needs the `reward_variance` feature.
+    #[cfg(feature = "reward_variance")]
+    pub std_error: f64,
+    pub visits: u64,
+}
+
+/// Statistics about one call to one of the `MCTSManager` search methods.
+#[derive(Clone, Debug)]
+pub struct SearchSummary {
+    pub playouts: u64,
+    pub nodes_created: u64,
+    pub elapsed: Duration,
+    pub depth_histogram: DepthHistogram,
+    /// Time spent in each phase by the sampled playouts. Zero unless phase profiling
+    /// is on; see `MCTSManager::profile_phases`.
+    pub phase_times: PhaseTimes,
+    /// Rewards replaced under `NonFiniteRewards::Substitute`.
+    pub non_finite_rewards: u64,
+    /// Nodes created whose `is_terminal()` and `available_moves()` disagree. States
+    /// with moves are only checked in builds with debug assertions; see
+    /// `MCTS::on_terminality_mismatch`.
+    pub terminality_mismatches: u64,
+    /// Moves whose `make_move` failed during tree descent. See
+    /// `MCTS::move_error_behaviour`.
+    pub move_errors: u64,
+    /// Playouts that took a root move because of `MCTSManager::min_root_visits`.
+    pub floor_visits: u64,
+    /// Playouts that took a random root move because of `MCTSManager::root_noise`.
+    pub noisy_root_choices: u64,
+    /// Times selection passed over a move saturated under `MCTS::max_in_flight`.
+    pub saturated_selections: u64,
+    /// Choices the threads' `SelectionRng`s couldn't make as asked and made by a
+    /// fallback instead, such as `WeightedRng` given NaN or infinite weights. See
+    /// `SelectionRng::take_fallbacks`.
+    pub selection_fallbacks: u64,
+    /// Nodes expanded by two threads at once, of which one was discarded. A high
+    /// count means the threads are duplicating each other's work.
+    pub expansion_contention_events: u64,
+    /// Moves whose node was found in the transposition table instead of created.
+    /// See `SearchTree::transposition_table_hits`.
+    pub transposition_table_hits: u64,
+    /// Whether the tree reached its node limit (see `MCTS::node_limit` and
+    /// `MCTSManager::max_nodes`) during the search, which then stopped growing. The
+    /// statistics are still usable, but weaker than the budget would have made them.
+    pub expansion_halted: bool,
+    /// Playouts refused during the search because the tree had reached its node
+    /// limit. `MCTSManager::playout_parallel_for` stops each thread at its first
+    /// refusal; the other searches keep trying until their budget runs out.
+    pub playouts_after_halt: u64,
+    /// `SearchTree::root_visits` at the end of the search, including the visits of
+    /// earlier searches.
+    pub root_visits: u64,
+    /// `SearchTree::root_sum_rewards` at the end of the search, for the player to
+    /// move at the root.
+    pub root_sum_rewards: f64,
+    /// The playouts run by each thread of `MCTSManager::playout_parallel_for`, or
+    /// worker of `playout_parallel_for_on`. Empty for the other searches.
+    pub thread_playouts: Vec<u64>,
+    pub stop_reason: StopReason,
+}
+
+/// Why a search stopped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StopReason {
+    /// It used up its playouts or time, or the caller stopped it.
+    #[default]
+    Budget,
+    /// The root had only one move to choose from, so there was nothing to search.
+    /// The search ran at most one playout, so that `best_move` reports the move.
+    /// See `MCTS::stop_on_forced_move`.
+    ForcedMove,
+    /// The `StopWhen` condition of `MCTSManager::playout_n_until` was met.
+    Condition,
+}
+
+impl SearchSummary {
+    pub fn playouts_per_second(&self) -> f64 {
+        self.playouts as f64 / self.elapsed.as_secs_f64()
+    }
+
+    pub fn nodes_per_second(&self) -> f64 {
+        self.nodes_created as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// `root_sum_rewards / root_visits`; NaN without visits.
+    pub fn root_mean(&self) -> f64 {
+        self.root_sum_rewards / self.root_visits as f64
+    }
+
+    /// The fraction of sampled time spent in each phase, or `None` if no playouts
+    /// were sampled.
+    pub fn phase_breakdown(&self) -> Option<PhaseBreakdown> {
+        self.phase_times.breakdown()
+    }
+}
+
+impl fmt::Display for SearchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} playouts in {:.3}s ({:.0} playouts/sec)",
+            thousands_separate(self.playouts as usize),
+            self.elapsed.as_secs_f64(),
+            self.playouts_per_second()
+        )?;
+        writeln!(
+            f,
+            "{} nodes created ({:.0} nodes/sec)",
+            thousands_separate(self.nodes_created as usize),
+            self.nodes_per_second()
+        )?;
+        if let (Some(mean), Some(median), Some(max)) = (
+            self.depth_histogram.mean(),
+            self.depth_histogram.percentile(0.5),
+            self.depth_histogram.percentile(1.0),
+        ) {
+            writeln!(
+                f,
+                "playout depth: mean {:.1}, median {}, max {}",
+                mean, median, max
+            )?;
+        }
+        if self.root_visits > 0 {
+            writeln!(
+                f,
+                "root: {} visits, mean reward {:.3} for the player to move",
+                thousands_separate(self.root_visits as usize),
+                self.root_mean()
+            )?;
+        }
+        if self.non_finite_rewards > 0 {
+            writeln!(
+                f,
+                "{} non-finite rewards replaced",
+                thousands_separate(self.non_finite_rewards as usize)
+            )?;
+        }
+        if self.terminality_mismatches > 0 {
+            writeln!(
+                f,
+                "{} states where is_terminal() and available_moves() disagree",
+                thousands_separate(self.terminality_mismatches as usize)
+            )?;
+        }
+        if self.move_errors > 0 {
+            writeln!(
+                f,
+                "{} moves failed during tree descent",
+                thousands_separate(self.move_errors as usize)
+            )?;
+        }
+        if self.floor_visits > 0 {
+            writeln!(
+                f,
+                "{} playouts spent on the root exploration floor",
+                thousands_separate(self.floor_visits as usize)
+            )?;
+        }
+        if self.noisy_root_choices > 0 {
+            writeln!(
+                f,
+                "{} playouts took a random root move",
+                thousands_separate(self.noisy_root_choices as usize)
+            )?;
+        }
+        if self.saturated_selections > 0 {
+            writeln!(
+                f,
+                "{} selections passed over saturated moves",
+                thousands_separate(self.saturated_selections as usize)
+            )?;
+        }
+        if self.selection_fallbacks > 0 {
+            writeln!(
+                f,
+                "{} random choices fell back to uniform",
+                thousands_separate(self.selection_fallbacks as usize)
+            )?;
+        }
+        if self.expansion_contention_events > 0 {
+            writeln!(
+                f,
+                "{} expansions duplicated by another thread",
+                thousands_separate(self.expansion_contention_events as usize)
+            )?;
+        }
+        match self.stop_reason {
+            StopReason::Budget => (),
+            StopReason::ForcedMove => writeln!(f, "stopped at once: the root has a forced move")?,
+            StopReason::Condition => writeln!(f, "stopped early: the stop condition was met")?,
+        }
+        if self.expansion_halted {
+            writeln!(
+                f,
+                "node limit reached: {} playouts refused",
+                thousands_separate(self.playouts_after_halt as usize)
+            )?;
+        }
+        if let Some(phases) = self.phase_breakdown() {
+            writeln!(
+                f,
+                "phases: select {:.1}%, expand {:.1}%, evaluate {:.1}%, backpropagate {:.1}%",
+                phases.select * 100.0,
+                phases.expand * 100.0,
+                phases.evaluate * 100.0,
+                phases.backpropagate * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// What one call to `MCTSManager::playout_chunk` did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkResult {
+    /// Playouts run by this chunk.
+    pub playouts: u64,
+    /// Playouts run by every chunk since the chunked search started.
+    pub total_playouts: u64,
+    /// Whether `total_playouts` has reached the target set with
+    /// `MCTSManager::chunk_target`. Never set without a target.
+    pub target_reached: bool,
+    /// Whether the tree has reached its node limit, so that further chunks won't run
+    /// any playouts.
+    pub node_limit_reached: bool,
+    /// Whether the root has a forced move, so that chunks run at most one playout.
+    /// See `StopReason::ForcedMove`.
+    pub forced_move: bool,
+}
+
+impl ChunkResult {
+    /// Whether there is no point in running another chunk.
+    pub fn is_done(&self) -> bool {
+        self.target_reached || self.node_limit_reached || self.forced_move
+    }
+}
+
+/// The state of a tree at the start of a search, to compute the search's summary from.
+pub(crate) struct Checkpoint {
+    start: Instant,
+    nodes: usize,
+    non_finite_rewards: usize,
+    terminality_mismatches: usize,
+    move_errors: usize,
+    floor_visits: usize,
+    noisy_root_choices: usize,
+    saturated_selections: usize,
+    selection_fallbacks: usize,
+    expansion_contention_events: usize,
+    transposition_table_hits: usize,
+    halted_playouts: usize,
+    histogram: DepthHistogram,
+    phase_times: PhaseTimes,
+}
+
+impl Checkpoint {
+    pub(crate) fn new<Spec: MCTS>(tree: &SearchTree<Spec>) -> Self {
+        Self {
+            start: Instant::now(),
+            nodes: tree.num_nodes(),
+            non_finite_rewards: tree.non_finite_rewards(),
+            terminality_mismatches: tree.terminality_mismatches(),
+            move_errors: tree.move_errors(),
+            floor_visits: tree.floor_visits(),
+            noisy_root_choices: tree.noisy_root_choices(),
+            saturated_selections: tree.saturated_selections(),
+            selection_fallbacks: tree.selection_fallbacks(),
+            expansion_contention_events: tree.expansion_contention_events(),
+            transposition_table_hits: tree.transposition_table_hits(),
+            halted_playouts: tree.halted_playouts(),
+            histogram: tree.depth_histogram(),
+            phase_times: tree.phase_times(),
+        }
+    }
+
+    pub(crate) fn finish<Spec: MCTS>(&self, tree: &SearchTree<Spec>) -> SearchSummary {
+        let depth_histogram = tree.depth_histogram().since(&self.histogram);
+        let playouts_after_halt = (tree.halted_playouts() - self.halted_playouts) as u64;
+        SearchSummary {
+            playouts: depth_histogram.total(),
+            nodes_created: tree.num_nodes().saturating_sub(self.nodes) as u64,
+            elapsed: self.start.elapsed(),
+            depth_histogram,
+            phase_times: tree.phase_times().since(&self.phase_times),
+            non_finite_rewards: (tree.non_finite_rewards() - self.non_finite_rewards) as u64,
+            terminality_mismatches: (tree.terminality_mismatches() - self.terminality_mismatches)
+                as u64,
+            move_errors: (tree.move_errors() - self.move_errors) as u64,
+            floor_visits: (tree.floor_visits() - self.floor_visits) as u64,
+            noisy_root_choices: (tree.noisy_root_choices() - self.noisy_root_choices) as u64,
+            saturated_selections: (tree.saturated_selections() - self.saturated_selections) as u64,
+            selection_fallbacks: (tree.selection_fallbacks() - self.selection_fallbacks) as u64,
+            expansion_contention_events: (tree.expansion_contention_events()
+                - self.expansion_contention_events) as u64,
+            transposition_table_hits: (tree.transposition_table_hits()
+                - self.transposition_table_hits) as u64,
+            expansion_halted: playouts_after_halt > 0,
+            playouts_after_halt,
+            root_visits: tree.root_visits(),
+            root_sum_rewards: tree.root_sum_rewards(),
+            thread_playouts: Vec::new(),
+            stop_reason: StopReason::Budget,
+        }
+    }
+}
+
+pub(crate) const PHASES: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Phase {
+    Select,
+    Expand,
+    Evaluate,
+    Backpropagate,
+}
+
+/// Accumulates the time one playout spends in each phase. Does nothing unless the
+/// playout was picked for sampling, so it costs a branch per phase change otherwise.
+#[derive(Default)]
+pub(crate) struct PhaseTimer {
+    current: Option<(Phase, Instant)>,
+    nanos: [u64; PHASES],
+}
+
+impl PhaseTimer {
+    pub(crate) fn new(active: bool) -> Self {
+        Self {
+            current: if active {
+                Some((Phase::Select, Instant::now()))
+            } else {
+                None
+            },
+            nanos: [0; PHASES],
+        }
+    }
+
+    /// Charges the time since the last switch to the current phase and starts
+    /// timing `phase`. Returns the phase that was current.
+    pub(crate) fn switch(&mut self, phase: Phase) -> Phase {
+        match self.current {
+            Some((previous, since)) => {
+                let now = Instant::now();
+                self.nanos[previous as usize] += (now - since).as_nanos() as u64;
+                self.current = Some((phase, now));
+                previous
+            }
+            None => phase,
+        }
+    }
+
+    /// The time spent in each phase, if this playout was sampled.
+    pub(crate) fn stop(mut self) -> Option<[u64; PHASES]> {
+        let (phase, _) = self.current?;
+        self.switch(phase);
+        Some(self.nanos)
+    }
+}
+
+/// Time spent in each phase of a playout.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PhaseTimes {
+    /// Walking down the tree: the tree policy, `make_move` and the transposition table.
+    pub select: Duration,
+    /// Creating new nodes, apart from evaluating them.
+    pub expand: Duration,
+    /// Calls to the evaluator.
+    pub evaluate: Duration,
+    pub backpropagate: Duration,
+}
+
+impl PhaseTimes {
+    pub(crate) fn from_atomics(nanos: &[AtomicU64; PHASES]) -> Self {
+        let get =
+            |phase: Phase| Duration::from_nanos(nanos[phase as usize].load(Ordering::Relaxed));
+        Self {
+            select: get(Phase::Select),
+            expand: get(Phase::Expand),
+            evaluate: get(Phase::Evaluate),
+            backpropagate: get(Phase::Backpropagate),
+        }
+    }
+
+    fn since(&self, earlier: &Self) -> Self {
+        Self {
+            select: self.select - earlier.select,
+            expand: self.expand - earlier.expand,
+            evaluate: self.evaluate - earlier.evaluate,
+            backpropagate: self.backpropagate - earlier.backpropagate,
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.select + self.expand + self.evaluate + self.backpropagate
+    }
+
+    pub fn breakdown(&self) -> Option<PhaseBreakdown> {
+        let total = self.total().as_secs_f64();
+        if total == 0.0 {
+            return None;
+        }
+        Some(PhaseBreakdown {
+            select: self.select.as_secs_f64() / total,
+            expand: self.expand.as_secs_f64() / total,
+            evaluate: self.evaluate.as_secs_f64() / total,
+            backpropagate: self.backpropagate.as_secs_f64() / total,
+        })
+    }
+}
+
+/// The fraction of time spent in each phase. The fractions sum to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhaseBreakdown {
+    pub select: f64,
+    pub expand: f64,
+    pub evaluate: f64,
+    pub backpropagate: f64,
+}
+
+/// The root's best move and value at one point in a search, sampled by
+/// `SearchTree::track_stability`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StabilitySample {
+    /// `SearchTree::total_playouts` when the sample was taken.
+    pub playouts: u64,
+    /// The index among the root's moves of the move `select_child_after_search`
+    /// chose, if any.
+    pub best_move_index: Option<usize>,
+    /// `ValueEstimate::mean` of the root, or NaN before any visits.
+    pub root_value: f64,
+}
+
+/// How much the search's mind changed over the samples kept, from
+/// `MCTSManager::stability_report`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StabilityReport {
+    /// Oldest first.
+    pub samples: Vec<StabilitySample>,
+    /// The number of times the best move differs from the one in the previous sample.
+    pub flips: usize,
+    /// The sample variance of the root values, NaN with fewer than two samples.
+    pub value_variance: f64,
+}
+
+impl StabilityReport {
+    pub fn new(samples: Vec<StabilitySample>) -> Self {
+        let flips = samples
+            .windows(2)
+            .filter(|x| x[0].best_move_index != x[1].best_move_index)
+            .count();
+        let values: Vec<f64> = samples
+            .iter()
+            .map(|x| x.root_value)
+            .filter(|x| !x.is_nan())
+            .collect();
+        let value_variance = if values.len() < 2 {
+            f64::NAN
+        } else {
+            let n = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / n;
+            values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0)
+        };
+        Self {
+            samples,
+            flips,
+            value_variance,
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Checking the move a search chose for blunders too shallow for it to see.
+
+use super::*;
+
+/// How `MCTSManager::best_move_checked` checks the search's move. A move is
+/// refuted when one of the opponent's replies ends the game with a win for
+/// someone other than the player moving at the root, which takes only
+/// `GameState` and no search. Optionally, it's also refuted when the tree says a
+/// reply is very good for the player making it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TacticalCheck {
+    max_rejections: usize,
+    /// The mean reward, and the visits needed to trust it, of a refuting reply.
+    bad_reply: Option<(f64, u64)>,
+}
+
+impl TacticalCheck {
+    /// Rejects up to 3 moves unless set with `max_rejections`.
+    pub const DEFAULT_MAX_REJECTIONS: usize = 3;
+
+    /// Rejects moves that lose at once.
+    pub fn new() -> Self {
+        Self {
+            max_rejections: Self::DEFAULT_MAX_REJECTIONS,
+            bad_reply: None,
+        }
+    }
+
+    /// The most moves rejected before giving up and playing the search's move.
+    pub fn max_rejections(mut self, moves: usize) -> Self {
+        self.max_rejections = moves;
+        self
+    }
+
+    /// Also rejects a move when one of the replies to it has a mean reward of at
+    /// least `mean`, for the player making the reply, over at least `min_visits`
+    /// visits.
+    pub fn reject_replies_above(mut self, mean: f64, min_visits: u64) -> Self {
+        self.bad_reply = Some((mean, min_visits.max(1)));
+        self
+    }
+
+    /// Checks the root moves of `tree`, the search's move first and then the others
+    /// with visits, most visited first, until one isn't refuted.
+    pub fn check<Spec: MCTS>(&self, tree: &SearchTree<Spec>) -> TacticalReport<Move<Spec>> {
+        let mut report = TacticalReport {
+            chosen: None,
+            rejected: Vec::new(),
+            all_refuted: false,
+        };
+        let best = match tree.principal_variation(1).first() {
+            Some(&best) => best,
+            None => return report,
+        };
+        let mut others: Vec<_> = tree
+            .root_node()
+            .moves()
+            .filter(|x| !std::ptr::eq(*x, best) && !x.is_excluded() && x.visits() > 0)
+            .collect();
+        others.sort_by_key(|x| std::cmp::Reverse(x.visits()));
+        for candidate in std::iter::once(best).chain(others) {
+            if report.rejected.len() == self.max_rejections {
+                break;
+            }
+            match self.refutation(tree, candidate) {
+                Some(refutation) => report
+                    .rejected
+                    .push((candidate.get_move().clone(), refutation)),
+                None => {
+                    report.chosen = Some(candidate.get_move().clone());
+                    return report;
+                }
+            }
+        }
+        report.chosen = Some(best.get_move().clone());
+        report.all_refuted = !report.rejected.is_empty();
+        report
+    }
+
+    fn refutation<Spec: MCTS>(
+        &self,
+        tree: &SearchTree<Spec>,
+        candidate: &MoveInfo<Spec>,
+    ) -> Option<Refutation<Move<Spec>>> {
+        let root = tree.root_state();
+        let mut state = root.clone();
+        state.make_move(candidate.get_move()).ok()?;
+        for reply in state.available_moves() {
+            let mut after = state.clone();
+            if after.make_move(&reply).is_err() {
+                continue;
+            }
+            let lost = after
+                .get_winner()
+                .is_some_and(|winner| winner != *root.current_player());
+            if lost && after.is_terminal() {
+                return Some(Refutation::Loss(reply));
+            }
+        }
+        let (mean, min_visits) = self.bad_reply?;
+        let reply = candidate
+            .child()?
+            .moves()
+            .filter(|x| x.visits() >= min_visits && x.avg_reward() >= mean)
+            .max_by(|a, b| a.avg_reward().total_cmp(&b.avg_reward()))?;
+        Some(Refutation::Mean(
+            reply.get_move().clone(),
+            reply.avg_reward(),
+        ))
+    }
+}
+
+impl Default for TacticalCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The reply that made `TacticalCheck` reject a move.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Refutation<M> {
+    /// The reply wins the game for the opponent.
+    Loss(M),
+    /// The reply has this mean reward, for the player making it.
+    Mean(M, f64),
+}
+
+impl<M> Refutation<M> {
+    pub fn reply(&self) -> &M {
+        match self {
+            Refutation::Loss(mov) | Refutation::Mean(mov, _) => mov,
+        }
+    }
+}
+
+/// See `MCTSManager::best_move_checked`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TacticalReport<M> {
+    /// The first move that wasn't refuted or, when `all_refuted`, the search's move.
+    /// `None` when the root has no visited moves.
+    pub chosen: Option<M>,
+    /// The moves rejected, in the order they were checked, with their refutations.
+    pub rejected: Vec<(M, Refutation<M>)>,
+    /// Whether every move checked was refuted, up to `TacticalCheck::max_rejections`.
+    pub all_refuted: bool,
+}
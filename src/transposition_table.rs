@@ -1,12 +1,17 @@
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, HashMap},
     hash::{Hash, Hasher},
+    sync::Mutex,
 };
 
 use super::*;
 use atomics::*;
 use search_tree::*;
 
+/// # Safety
+///
+/// The search tree trusts the table to hand back only nodes it was given, and to
+/// follow the contract on `insert` below.
 pub unsafe trait TranspositionTable<Spec: MCTS>: Sync + Sized {
     /// **If this function inserts a value, it must return `None`.** Failure to follow
     /// this rule will lead to memory safety violation.
@@ -43,6 +48,62 @@ pub unsafe trait TranspositionTable<Spec: MCTS>: Sync + Sized {
         key: &Spec::State,
         handle: SearchHandle<Spec>,
     ) -> Option<&'a SearchNode<Spec>>;
+
+    /// Tables that can use a hash maintained incrementally by the search return the
+    /// root's hash here. The search then calls `hash_after` on every step and uses
+    /// `insert_hashed`/`lookup_hashed` instead of `insert`/`lookup`.
+    fn root_hash(&self, _state: &Spec::State) -> Option<u64> {
+        None
+    }
+
+    /// The hash of the state reached by playing `mov` in `state`, whose hash is `current`.
+    /// Only called when `root_hash` returns `Some`.
+    fn hash_after(&self, _state: &Spec::State, current: u64, _mov: &Move<Spec>) -> u64 {
+        current
+    }
+
+    /// Makes room for `entries` entries in total, if the table can grow. Called with
+    /// no search running. Defaults to doing nothing.
+    fn reserve(&mut self, _entries: usize) {}
+
+    /// Forgets every node, before `SearchTree::reset` frees them. Called with no
+    /// search running. Tables that hold on to nodes must implement it; the default
+    /// does nothing.
+    fn clear(&mut self) {}
+
+    /// Every node in the table, for `SearchTree::validate` to check that they're
+    /// still in the tree. Called with no search running. Tables that can't list their
+    /// nodes return an empty `Vec`, the default.
+    fn nodes(&self) -> Vec<*const SearchNode<Spec>> {
+        Vec::new()
+    }
+
+    /// The states the table keeps copies of, for `SearchTree::memory_stats`. Tables
+    /// that only keep hashes return the default, all zeros.
+    fn state_storage(&self) -> StateStorage {
+        StateStorage::default()
+    }
+
+    /// Like `insert`, with the key's incrementally maintained hash.
+    fn insert_hashed<'a>(
+        &'a self,
+        key: &Spec::State,
+        _hash: u64,
+        value: &'a SearchNode<Spec>,
+        handle: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.insert(key, value, handle)
+    }
+
+    /// Like `lookup`, with the key's incrementally maintained hash.
+    fn lookup_hashed<'a>(
+        &'a self,
+        key: &Spec::State,
+        _hash: u64,
+        handle: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.lookup(key, handle)
+    }
 }
 
 unsafe impl<Spec: MCTS<TranspositionTable = Self>> TranspositionTable<Spec> for () {
@@ -64,20 +125,20 @@ unsafe impl<Spec: MCTS<TranspositionTable = Self>> TranspositionTable<Spec> for
     }
 }
 
-pub struct ApproxQuadraticProbingHashTable<K: Hash, V> {
+pub struct ApproxQuadraticProbingHashTable<K, V> {
     arr: Box<[Entry16<K, V>]>,
     capacity: usize,
     mask: usize,
     size: AtomicUsize,
 }
 
-struct Entry16<K: Hash, V> {
-    k: AtomicU64,
-    v: AtomicPtr<V>,
+struct Entry16<K, V> {
+    k: CheckedAtomicU64,
+    v: PublishedPtr<V>,
     _marker: std::marker::PhantomData<K>,
 }
 
-impl<K: Hash, V> Default for Entry16<K, V> {
+impl<K, V> Default for Entry16<K, V> {
     fn default() -> Self {
         Self {
             k: Default::default(),
@@ -86,23 +147,38 @@ impl<K: Hash, V> Default for Entry16<K, V> {
         }
     }
 }
-impl<K: Hash, V> Clone for Entry16<K, V> {
+impl<K, V> Clone for Entry16<K, V> {
     fn clone(&self) -> Self {
         Self {
-            k: AtomicU64::new(self.k.load(Ordering::Relaxed)),
-            v: AtomicPtr::new(self.v.load(Ordering::Relaxed)),
+            k: CheckedAtomicU64::new(self.k.load(Ordering::Relaxed)),
+            v: PublishedPtr::new(self.v.load()),
             _marker: Default::default(),
         }
     }
 }
 
-impl<K: Hash, V> ApproxQuadraticProbingHashTable<K, V> {
-    pub fn new(capacity: usize) -> Self {
+impl<K, V> ApproxQuadraticProbingHashTable<K, V> {
+    /// A table with room for `size` entries, rounded up to a power of 2.
+    /// `new(0)` makes a table with a single entry.
+    pub fn new(size: usize) -> Self {
+        let capacity = size
+            .checked_next_power_of_two()
+            .expect("transposition table size is too large");
+        Self::with_capacity(capacity)
+    }
+    /// A table with room for exactly `size` entries, which must be a power of 2.
+    pub fn with_exact_size(size: usize) -> Result<Self, TableSizeError> {
+        if size.is_power_of_two() {
+            Ok(Self::with_capacity(size))
+        } else {
+            Err(TableSizeError(size))
+        }
+    }
+    fn with_capacity(capacity: usize) -> Self {
+        // Loom's atomics are bigger.
+        #[cfg(not(loom))]
         assert!(std::mem::size_of::<Entry16<K, V>>() <= 16);
-        assert!(
-            capacity.count_ones() == 1,
-            "the capacity must be a power of 2"
-        );
+        debug_assert!(capacity.is_power_of_two());
         let arr = vec![Entry16::default(); capacity].into_boxed_slice();
         let mask = capacity - 1;
         Self {
@@ -119,29 +195,61 @@ impl<K: Hash, V> ApproxQuadraticProbingHashTable<K, V> {
         }
         Self::new(capacity)
     }
+    /// The number of entries.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// Grows the table, if needed, to the size `enough_to_hold(num)` would give it,
+    /// moving the existing entries over. Entries that don't fit within the probe
+    /// limit of the new table are dropped, as a full table would have.
+    pub fn reserve(&mut self, num: usize) {
+        let grown = Self::enough_to_hold(num);
+        if grown.capacity <= self.capacity {
+            return;
+        }
+        for entry in self.arr.iter() {
+            let hash = entry.k.load(Ordering::Relaxed);
+            let value = entry.v.load();
+            if hash != 0 && !value.is_null() {
+                grown.insert_hash(hash, unsafe { &*value });
+            }
+        }
+        *self = grown;
+    }
+    /// Empties the table, keeping its capacity.
+    pub fn clear(&mut self) {
+        *self = Self::with_capacity(self.capacity);
+    }
+}
+
+/// Returned by `ApproxQuadraticProbingHashTable::with_exact_size` for a size that
+/// isn't a power of 2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TableSizeError(pub usize);
+
+impl std::fmt::Display for TableSizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "transposition table size {} is not a power of 2", self.0)
+    }
 }
 
-unsafe impl<K: Hash, V> Sync for ApproxQuadraticProbingHashTable<K, V> {}
-unsafe impl<K: Hash, V> Send for ApproxQuadraticProbingHashTable<K, V> {}
+impl std::error::Error for TableSizeError {}
 
+unsafe impl<K, V> Sync for ApproxQuadraticProbingHashTable<K, V> {}
+unsafe impl<K, V> Send for ApproxQuadraticProbingHashTable<K, V> {}
+
+/// An `ApproxQuadraticProbingHashTable` keyed by game state. Requires `Spec::State: Hash`.
 pub type ApproxTable<Spec> =
     ApproxQuadraticProbingHashTable<<Spec as MCTS>::State, SearchNode<Spec>>;
 
-fn get_or_write<'a, V>(ptr: &AtomicPtr<V>, v: &'a V) -> Option<&'a V> {
-    let result = ptr
-        .compare_exchange(
-            std::ptr::null_mut(),
-            v as *const _ as *mut _,
-            Ordering::Relaxed,
-            Ordering::Relaxed,
-        )
-        .unwrap_or_else(|x| x);
-
-    convert(result)
+fn get_or_write<'a, V>(ptr: &PublishedPtr<V>, v: &'a V) -> Option<&'a V> {
+    ptr.publish(v as *const _ as *mut _)
+        .err()
+        .and_then(|x| convert(x))
 }
 
 fn convert<'a, V>(ptr: *const V) -> Option<&'a V> {
-    if ptr == std::ptr::null() {
+    if ptr.is_null() {
         None
     } else {
         unsafe { Some(&*ptr) }
@@ -150,33 +258,22 @@ fn convert<'a, V>(ptr: *const V) -> Option<&'a V> {
 
 const PROBE_LIMIT: usize = 16;
 
-unsafe impl<Spec> TranspositionTable<Spec> for ApproxTable<Spec>
-where
-    Spec::State: Hash,
-    Spec: MCTS,
-{
-    fn insert<'a>(
-        &'a self,
-        key: &Spec::State,
-        value: &'a SearchNode<Spec>,
-        handle: SearchHandle<Spec>,
-    ) -> Option<&'a SearchNode<Spec>> {
+impl<K, V> ApproxQuadraticProbingHashTable<K, V> {
+    pub(crate) fn insert_hash<'a>(&'a self, my_hash: u64, value: &'a V) -> Option<&'a V> {
         if self.size.load(Ordering::Relaxed) * 3 > self.capacity * 2 {
-            return self.lookup(key, handle);
+            return self.lookup_hash(my_hash);
         }
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        let my_hash = hasher.finish();
         if my_hash == 0 {
             return None;
         }
         let mut posn = my_hash as usize & self.mask;
         for inc in 1..(PROBE_LIMIT + 1) {
+            debug_assert!(posn < self.arr.len());
             let entry = unsafe { self.arr.get_unchecked(posn) };
-            let key_here = entry.k.load(Ordering::Relaxed) as u64;
+            let key_here = entry.k.load(Ordering::Relaxed);
             if key_here == my_hash {
-                let value_here = entry.v.load(Ordering::Relaxed);
-                if value_here != std::ptr::null_mut() {
+                let value_here = entry.v.load();
+                if !value_here.is_null() {
                     return unsafe { Some(&*value_here) };
                 }
                 return get_or_write(&entry.v, value);
@@ -184,11 +281,11 @@ where
             if key_here == 0 {
                 let key_here = entry
                     .k
-                    .compare_exchange(0, my_hash as FakeU64, Ordering::Relaxed, Ordering::Relaxed)
+                    .compare_exchange(0, my_hash, Ordering::Relaxed, Ordering::Relaxed)
                     .unwrap_or_else(|x| x);
 
                 self.size.fetch_add(1, Ordering::Relaxed);
-                if key_here == 0 || key_here == my_hash as FakeU64 {
+                if key_here == 0 || key_here == my_hash {
                     return get_or_write(&entry.v, value);
                 }
             }
@@ -197,20 +294,22 @@ where
         }
         None
     }
-    fn lookup<'a>(
-        &'a self,
-        key: &Spec::State,
-        _: SearchHandle<Spec>,
-    ) -> Option<&'a SearchNode<Spec>> {
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        let my_hash = hasher.finish();
+    fn values(&self) -> Vec<*const V> {
+        self.arr
+            .iter()
+            .map(|x| x.v.load() as *const V)
+            .filter(|x| !x.is_null())
+            .collect()
+    }
+
+    pub(crate) fn lookup_hash(&self, my_hash: u64) -> Option<&V> {
         let mut posn = my_hash as usize & self.mask;
         for inc in 1..(PROBE_LIMIT + 1) {
+            debug_assert!(posn < self.arr.len());
             let entry = unsafe { self.arr.get_unchecked(posn) };
-            let key_here = entry.k.load(Ordering::Relaxed) as u64;
+            let key_here = entry.k.load(Ordering::Relaxed);
             if key_here == my_hash {
-                return convert(entry.v.load(Ordering::Relaxed));
+                return convert(entry.v.load());
             }
             if key_here == 0 {
                 return None;
@@ -221,3 +320,281 @@ where
         None
     }
 }
+
+fn full_hash<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+unsafe impl<Spec> TranspositionTable<Spec> for ApproxTable<Spec>
+where
+    Spec::State: Hash,
+    Spec: MCTS,
+{
+    fn reserve(&mut self, entries: usize) {
+        ApproxQuadraticProbingHashTable::reserve(self, entries);
+    }
+    fn clear(&mut self) {
+        ApproxQuadraticProbingHashTable::clear(self);
+    }
+    fn nodes(&self) -> Vec<*const SearchNode<Spec>> {
+        self.values()
+    }
+    fn insert<'a>(
+        &'a self,
+        key: &Spec::State,
+        value: &'a SearchNode<Spec>,
+        _: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.insert_hash(full_hash(key), value)
+    }
+    fn lookup<'a>(
+        &'a self,
+        key: &Spec::State,
+        _: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.lookup_hash(full_hash(key))
+    }
+}
+
+/// A transposition table for states that maintain their own hash incrementally.
+///
+/// The search carries the hash down the tree using `IncrementallyHashable::hash_after`,
+/// so states are never hashed from scratch except at the root (and when verifying).
+pub struct IncrementalTable<Spec: MCTS> {
+    table: ApproxQuadraticProbingHashTable<Spec::State, SearchNode<Spec>>,
+    verify: bool,
+}
+
+impl<Spec: MCTS> IncrementalTable<Spec> {
+    /// See `ApproxQuadraticProbingHashTable::new`.
+    pub fn new(size: usize) -> Self {
+        Self {
+            table: ApproxQuadraticProbingHashTable::new(size),
+            verify: false,
+        }
+    }
+    pub fn enough_to_hold(num: usize) -> Self {
+        Self {
+            table: ApproxQuadraticProbingHashTable::enough_to_hold(num),
+            verify: false,
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.table.capacity()
+    }
+    /// See `ApproxQuadraticProbingHashTable::reserve`.
+    pub fn reserve(&mut self, num: usize) {
+        self.table.reserve(num);
+    }
+    /// Check every incremental hash against `full_hash`, panicking on a mismatch.
+    /// This is slow and intended for debugging `hash_after` implementations.
+    pub fn with_verification(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+    fn check(&self, key: &Spec::State, hash: u64)
+    where
+        Spec::State: IncrementallyHashable,
+    {
+        if self.verify {
+            assert_eq!(
+                key.full_hash(),
+                hash,
+                "incremental hash doesn't match the full hash of the state"
+            );
+        }
+    }
+}
+
+unsafe impl<Spec> TranspositionTable<Spec> for IncrementalTable<Spec>
+where
+    Spec::State: IncrementallyHashable,
+    Spec: MCTS,
+{
+    fn reserve(&mut self, entries: usize) {
+        self.table.reserve(entries);
+    }
+    fn clear(&mut self) {
+        self.table.clear();
+    }
+    fn nodes(&self) -> Vec<*const SearchNode<Spec>> {
+        self.table.values()
+    }
+    fn insert<'a>(
+        &'a self,
+        key: &Spec::State,
+        value: &'a SearchNode<Spec>,
+        _: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.table.insert_hash(key.full_hash(), value)
+    }
+    fn lookup<'a>(
+        &'a self,
+        key: &Spec::State,
+        _: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.table.lookup_hash(key.full_hash())
+    }
+    fn root_hash(&self, state: &Spec::State) -> Option<u64> {
+        Some(state.full_hash())
+    }
+    fn hash_after(&self, state: &Spec::State, current: u64, mov: &Move<Spec>) -> u64 {
+        state.hash_after(current, mov)
+    }
+    fn insert_hashed<'a>(
+        &'a self,
+        key: &Spec::State,
+        hash: u64,
+        value: &'a SearchNode<Spec>,
+        _: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.check(key, hash);
+        self.table.insert_hash(hash, value)
+    }
+    fn lookup_hashed<'a>(
+        &'a self,
+        key: &Spec::State,
+        hash: u64,
+        _: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        self.check(key, hash);
+        self.table.lookup_hash(hash)
+    }
+}
+
+/// The copies of states a transposition table keeps, as reported in
+/// `MemoryStats::state_storage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StateStorage {
+    /// The states stored.
+    pub states: usize,
+    /// Their sizes, by `MCTS::state_size_hint`.
+    pub bytes: usize,
+    /// The entries stored with only their hash because the states had reached
+    /// the table's byte limit.
+    pub downgraded_entries: usize,
+}
+
+/// A transposition table that keeps a copy of each state it holds, so that two
+/// states whose hashes collide never share a node: a lookup only finds a node
+/// when its state is equal to the key. Requires `Spec::State: Hash + Eq + Send`.
+///
+/// The copies can take a lot of memory on big games. Their sizes are counted with
+/// `MCTS::state_size_hint`, and once they reach `with_max_state_bytes`, new
+/// entries are stored with only their hash and are verified by it alone, like
+/// `ApproxTable`'s. See `StateStorage`.
+///
+/// The states are kept behind a lock, so this table is slower than `ApproxTable`
+/// under many threads.
+pub struct ExactTable<Spec: MCTS> {
+    table: ApproxQuadraticProbingHashTable<Spec::State, SearchNode<Spec>>,
+    states: Mutex<HashMap<u64, Spec::State>>,
+    max_state_bytes: usize,
+    state_bytes: AtomicUsize,
+    downgraded: AtomicUsize,
+}
+
+impl<Spec: MCTS> ExactTable<Spec> {
+    /// See `ApproxQuadraticProbingHashTable::new`.
+    pub fn new(size: usize) -> Self {
+        Self::from_table(ApproxQuadraticProbingHashTable::new(size))
+    }
+    pub fn enough_to_hold(num: usize) -> Self {
+        Self::from_table(ApproxQuadraticProbingHashTable::enough_to_hold(num))
+    }
+    fn from_table(table: ApproxQuadraticProbingHashTable<Spec::State, SearchNode<Spec>>) -> Self {
+        Self {
+            table,
+            states: Mutex::new(HashMap::new()),
+            max_state_bytes: usize::MAX,
+            state_bytes: AtomicUsize::new(0),
+            downgraded: AtomicUsize::new(0),
+        }
+    }
+    /// Stops storing states once they take `bytes`, by `MCTS::state_size_hint`.
+    /// Unlimited by default.
+    pub fn with_max_state_bytes(mut self, bytes: usize) -> Self {
+        self.max_state_bytes = bytes;
+        self
+    }
+    pub fn max_state_bytes(&self) -> usize {
+        self.max_state_bytes
+    }
+    pub fn capacity(&self) -> usize {
+        self.table.capacity()
+    }
+    /// The entries stored with only their hash.
+    pub fn downgraded_entries(&self) -> usize {
+        self.downgraded.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl<Spec> TranspositionTable<Spec> for ExactTable<Spec>
+where
+    Spec::State: Hash + Eq + Send,
+    Spec: MCTS,
+{
+    fn reserve(&mut self, entries: usize) {
+        self.table.reserve(entries);
+    }
+    fn clear(&mut self) {
+        self.table.clear();
+        self.states.get_mut().unwrap().clear();
+        *self.state_bytes.get_mut() = 0;
+        *self.downgraded.get_mut() = 0;
+    }
+    fn nodes(&self) -> Vec<*const SearchNode<Spec>> {
+        self.table.values()
+    }
+    fn state_storage(&self) -> StateStorage {
+        StateStorage {
+            states: self.states.lock().unwrap().len(),
+            bytes: self.state_bytes.load(Ordering::Relaxed),
+            downgraded_entries: self.downgraded_entries(),
+        }
+    }
+    fn insert<'a>(
+        &'a self,
+        key: &Spec::State,
+        value: &'a SearchNode<Spec>,
+        handle: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        let hash = full_hash(key);
+        let mut states = self.states.lock().unwrap();
+        if states.get(&hash).is_some_and(|x| x != key) {
+            // Another state has this hash; keep the node out of the table.
+            return None;
+        }
+        let existing = self.table.insert_hash(hash, value);
+        let inserted = existing.is_none()
+            && self
+                .table
+                .lookup_hash(hash)
+                .is_some_and(|x| std::ptr::eq(x, value));
+        if inserted {
+            let size = handle.mcts().state_size_hint(key);
+            let bytes = self.state_bytes.load(Ordering::Relaxed);
+            if bytes.saturating_add(size) <= self.max_state_bytes {
+                states.insert(hash, key.clone());
+                self.state_bytes.store(bytes + size, Ordering::Relaxed);
+            } else {
+                self.downgraded.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        existing
+    }
+    fn lookup<'a>(
+        &'a self,
+        key: &Spec::State,
+        _: SearchHandle<Spec>,
+    ) -> Option<&'a SearchNode<Spec>> {
+        let hash = full_hash(key);
+        let states = self.states.lock().unwrap();
+        if states.get(&hash).is_some_and(|x| x != key) {
+            return None;
+        }
+        self.table.lookup_hash(hash)
+    }
+}
@@ -2,14 +2,28 @@ extern crate rand;
 use self::rand::{Rng, SeedableRng};
 
 use super::*;
+use atomic_float::AtomicF64;
+use atomics::*;
+#[cfg(feature = "events")]
+use events::SearchEvent;
 use rand::prelude::StdRng;
 use search_tree::*;
 use std::{self, marker::PhantomData};
 
+/// Chooses which move a playout follows from each node. Policies can be written
+/// outside the crate with `MoveInfo`'s accessors, `SearchHandle::thread_data` and
+/// `SelectionRng`; see `examples/custom_policy.rs`.
 pub trait TreePolicy<Spec: MCTS<TreePolicy = Self>>: Sync + Sized {
+    /// What the evaluator gives each move of a new node, such as a prior.
     type MoveEvaluation: Sync + Send;
+    /// Per-thread state, usually just a random number generator: `PolicyRng` or
+    /// `WeightedRng`. The search reaches it through `SearchHandle::thread_data`, and
+    /// uses it to choose between moves at random when pruning with
+    /// `MCTS::random_extra_children`.
     type ThreadLocalData: Default + SelectionRng;
 
+    /// Returns one of `moves`, which is never empty. `moves` can be iterated several
+    /// times, for example to sum the visits first.
     fn choose_child<'a, MoveIter>(
         &self,
         moves: MoveIter,
@@ -18,11 +32,88 @@ pub trait TreePolicy<Spec: MCTS<TreePolicy = Self>>: Sync + Sized {
     where
         MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone;
     fn validate_evaluations(&self, _evalns: &[Self::MoveEvaluation]) {}
+
+    /// The score `choose_child` would give `child` if its parent's children had
+    /// `parent_visits` visits between them. Used for reporting, not during the search.
+    /// Policies that don't say return NaN.
+    fn score_child(&self, _child: &MoveInfo<Spec>, _parent_visits: u64) -> f64 {
+        f64::NAN
+    }
+
+    /// Thread data whose randomness comes from `seed`, for reproducible searches.
+    /// Policies that can't be seeded return `None`, and get `Default` thread data.
+    fn seeded_thread_data(&self, _seed: u64) -> Option<Self::ThreadLocalData> {
+        None
+    }
+
+    /// The virtual loss to use instead of `MCTS::virtual_loss_mode`, for policies
+    /// that need a particular kind: PUCT, whose scores are means, works best with
+    /// virtual visits, while raw reward losses have to be on the scale of the
+    /// rewards. `None`, the default, leaves it to the spec.
+    fn virtual_loss(&self) -> Option<VirtualLossMode> {
+        None
+    }
+
+    /// How promising a move's evaluation says it is, for `MCTS::max_children` to
+    /// keep the best moves. Policies without priors return 0, which keeps the first
+    /// moves in the node's order.
+    fn move_prior(&self, _evaln: &Self::MoveEvaluation) -> f64 {
+        0.0
+    }
+
+    /// Called once per playout, after backpropagation, with the reward of the root
+    /// move it took, for the player to move at the root. Playouts that end at the
+    /// root don't call it. Does nothing by default.
+    fn on_root_reward(&self, _reward: f64) {}
+}
+
+/// How a policy chooses between children with exactly the same score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TieBreak {
+    /// Pick uniformly at random using the thread's `SelectionRng`.
+    #[default]
+    Random,
+    /// Pick the child that comes first in the node's move order.
+    FirstMove,
+}
+
+impl TieBreak {
+    pub fn select_by_key<R, T, Iter, KeyFn>(
+        self,
+        rng: &mut R,
+        elts: Iter,
+        key_fn: KeyFn,
+    ) -> Option<T>
+    where
+        R: SelectionRng,
+        Iter: Iterator<Item = T>,
+        KeyFn: Fn(&T) -> f64,
+        T: Clone,
+    {
+        match self {
+            TieBreak::Random => rng.select_by_key(elts, key_fn),
+            TieBreak::FirstMove => {
+                let mut choice = None;
+                let mut best_so_far = f64::NEG_INFINITY;
+                for elt in elts {
+                    let score = nan_to_worst(key_fn(&elt));
+                    if choice.is_none() || score > best_so_far {
+                        choice = Some(elt);
+                        best_so_far = score;
+                    }
+                }
+                choice
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct UCTPolicy<MV> {
     exploration_constant: f64,
+    tie_break: TieBreak,
+    reward_spread: Option<RewardSpread>,
+    virtual_loss: Option<VirtualLossMode>,
     _phantom: PhantomData<MV>,
 }
 
@@ -35,46 +126,313 @@ impl<MV> UCTPolicy<MV> {
         );
         Self {
             exploration_constant,
+            tie_break: TieBreak::Random,
+            reward_spread: None,
+            virtual_loss: None,
             _phantom: Default::default(),
         }
     }
 
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Scales the exploration term by the standard deviation of the root rewards
+    /// seen so far (see `RewardSpread`), so that the exploration constant is
+    /// dimensionless: the same constant, around 1, suits games whose rewards are
+    /// -1 to 1 and games whose rewards are in the hundreds. Until there are two
+    /// rewards the scale is 1.
+    ///
+    /// The spread is kept by the policy, so it carries over to later searches with
+    /// the same policy, such as after `MCTSManager::reset`.
+    pub fn with_adaptive_exploration(mut self, enabled: bool) -> Self {
+        self.reward_spread = if enabled {
+            Some(RewardSpread::new())
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Uses `mode` instead of the spec's `MCTS::virtual_loss_mode`. See
+    /// `TreePolicy::virtual_loss`.
+    pub fn with_virtual_loss(mut self, mode: VirtualLossMode) -> Self {
+        self.virtual_loss = Some(mode);
+        self
+    }
+
     pub fn exploration_constant(&self) -> f64 {
         self.exploration_constant
     }
+
+    /// The root rewards seen so far, with `with_adaptive_exploration`.
+    pub fn reward_spread(&self) -> Option<&RewardSpread> {
+        self.reward_spread.as_ref()
+    }
+
+    /// What the exploration term is multiplied by: the exploration constant,
+    /// scaled by the reward spread with `with_adaptive_exploration`.
+    pub fn effective_exploration(&self) -> f64 {
+        let scale = self
+            .reward_spread
+            .as_ref()
+            .and_then(|x| x.std_dev())
+            .unwrap_or(1.0);
+        self.exploration_constant * scale
+    }
+
+    fn score(&self, sum_rewards: f64, child_visits: u64, parent_visits: u64) -> f64 {
+        self.score_with(
+            self.exploration_constant,
+            sum_rewards,
+            child_visits,
+            parent_visits,
+        )
+    }
+
+    fn score_with(
+        &self,
+        exploration: f64,
+        sum_rewards: f64,
+        child_visits: u64,
+        parent_visits: u64,
+    ) -> f64 {
+        // http://mcts.ai/pubs/mcts-survey-master.pdf
+        if child_visits == 0 {
+            f64::INFINITY
+        } else {
+            let parent_visits = parent_visits as f64;
+            let child_visits = child_visits as f64;
+            let explore_term = (parent_visits.ln() / child_visits).sqrt();
+            let mean_action_value = sum_rewards / child_visits;
+            exploration * explore_term + mean_action_value
+        }
+    }
+}
+
+/// The running mean and variance of a stream of rewards, by Welford's algorithm,
+/// for `UCTPolicy::with_adaptive_exploration`.
+///
+/// The three statistics are separate atomics, so concurrent updates can interleave
+/// and the estimate is then slightly off; it is never torn, and is meant for
+/// scaling, not reporting. They're f64 even with the `f32_stats` feature: the mean
+/// is updated by ever smaller steps, which an f32 would round away.
+#[derive(Debug)]
+pub struct RewardSpread {
+    count: AtomicU64,
+    mean: AtomicF64,
+    sum_squared_deviations: AtomicF64,
+}
+
+impl RewardSpread {
+    pub fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            mean: AtomicF64::new(0.0),
+            sum_squared_deviations: AtomicF64::new(0.0),
+        }
+    }
+
+    pub fn record(&self, reward: f64) {
+        let n = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        let delta = reward - self.mean.load(Ordering::Relaxed);
+        let mean = self.mean.fetch_add(delta / n as f64, Ordering::Relaxed) + delta / n as f64;
+        self.sum_squared_deviations
+            .fetch_add(delta * (reward - mean), Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// The mean, or `None` before the first reward.
+    pub fn mean(&self) -> Option<f64> {
+        if self.count() == 0 {
+            None
+        } else {
+            Some(self.mean.load(Ordering::Relaxed))
+        }
+    }
+
+    /// The sample standard deviation, or `None` with fewer than two rewards.
+    pub fn std_dev(&self) -> Option<f64> {
+        let n = self.count();
+        if n < 2 {
+            return None;
+        }
+        let m2 = self.sum_squared_deviations.load(Ordering::Relaxed);
+        Some((m2.max(0.0) / (n - 1) as f64).sqrt())
+    }
+}
+
+impl Default for RewardSpread {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A snapshot: the clone doesn't see the original's later rewards.
+impl Clone for RewardSpread {
+    fn clone(&self) -> Self {
+        Self {
+            count: AtomicU64::new(self.count()),
+            mean: AtomicF64::new(self.mean.load(Ordering::Relaxed)),
+            sum_squared_deviations: AtomicF64::new(
+                self.sum_squared_deviations.load(Ordering::Relaxed),
+            ),
+        }
+    }
 }
 
 const RECIPROCAL_TABLE_LEN: usize = 128;
 
+/// PUCT selection using each move's prior, as in AlphaGo. The move evaluations are
+/// the priors: plain `f64`s by default, or `AtomicPrior`s to be able to change them
+/// during the search.
 #[derive(Clone, Debug)]
-pub struct AlphaGoPolicy {
+pub struct AlphaGoPolicy<P = f64> {
     exploration_constant: f64,
-    reciprocals: Vec<f64>,
+    tie_break: TieBreak,
+    prior_annealing: Option<PriorAnnealing>,
+    reciprocals: [f64; RECIPROCAL_TABLE_LEN],
+    _phantom: PhantomData<P>,
 }
 
 impl AlphaGoPolicy {
     pub fn new(exploration_constant: f64) -> Self {
+        Self::with_exploration_constant(exploration_constant)
+    }
+}
+
+impl<P> AlphaGoPolicy<P> {
+    /// Like `new`, for any type of prior.
+    pub fn with_exploration_constant(exploration_constant: f64) -> Self {
         assert!(
             exploration_constant > 0.0,
             "exploration constant is {} (must be positive)",
             exploration_constant
         );
-        let reciprocals = (0..RECIPROCAL_TABLE_LEN)
-            .map(|x| if x == 0 { 2.0 } else { 1.0 / x as f64 })
-            .collect();
+        let reciprocals = std::array::from_fn(|x| if x == 0 { 2.0 } else { 1.0 / x as f64 });
         Self {
             exploration_constant,
+            tie_break: TieBreak::Random,
+            prior_annealing: None,
             reciprocals,
+            _phantom: PhantomData,
         }
     }
 
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Mixes a uniform prior into the stored ones, more of it the more visits the
+    /// choosing node has, so that moves a policy network misjudges get explored
+    /// eventually. The mix is worked out at every selection; the stored priors
+    /// don't change. `score_child` can't see a move's siblings, so it reports
+    /// scores with the stored priors.
+    pub fn with_prior_annealing(mut self, schedule: PriorAnnealing) -> Self {
+        self.prior_annealing = Some(schedule);
+        self
+    }
+
+    pub fn prior_annealing(&self) -> Option<PriorAnnealing> {
+        self.prior_annealing
+    }
+
     pub fn exploration_constant(&self) -> f64 {
         self.exploration_constant
     }
 
+    /// The uniform share of the prior for a node with `moves` moves and
+    /// `parent_visits` visits between them.
+    fn mixing(&self, parent_visits: u64, moves: usize) -> Mixing {
+        match self.prior_annealing {
+            Some(schedule) if moves > 0 => Mixing {
+                weight: schedule.weight(parent_visits),
+                uniform: 1.0 / moves as f64,
+            },
+            _ => Mixing::NONE,
+        }
+    }
+
+    fn explore_coef(&self, parent_visits: u64) -> f64 {
+        let total_visits = parent_visits + 1;
+        self.exploration_constant * (total_visits as f64).sqrt()
+    }
+
+    /// An unvisited move with a `Prior::init_q` scores that value plus its
+    /// exploration term as it would be after one visit. Without one, it scores
+    /// twice its exploration term (see `reciprocal`).
+    fn score<Spec: MCTS<TreePolicy = Self>>(
+        &self,
+        mov: &MoveInfo<Spec>,
+        explore_coef: f64,
+        mixing: Mixing,
+    ) -> f64
+    where
+        P: Prior,
+    {
+        self.score_stats(
+            mov.sum_rewards(),
+            mov.visits(),
+            mov.move_evaluation(),
+            explore_coef,
+            mixing,
+        )
+    }
+
+    /// `score`, for a move with these statistics and prior.
+    fn score_stats(
+        &self,
+        sum_rewards: f64,
+        child_visits: u64,
+        evaln: &P,
+        explore_coef: f64,
+        mixing: Mixing,
+    ) -> f64
+    where
+        P: Prior,
+    {
+        let policy_evaln = mixing.apply(evaln.prior());
+
+        if child_visits == 0 {
+            if let Some(init_q) = evaln.init_q() {
+                return init_q + explore_coef * policy_evaln;
+            }
+        }
+        (sum_rewards + explore_coef * policy_evaln) * self.reciprocal(child_visits as usize)
+    }
+
+    /// Checks that the priors are non-negative and sum to about 1.
+    fn validate_priors(&self, evalns: &[P])
+    where
+        P: Prior,
+    {
+        for x in evalns {
+            assert!(
+                x.prior() >= -1e-6,
+                "Move evaluation is {} (must be non-negative)",
+                x.prior()
+            );
+        }
+        if !evalns.is_empty() {
+            let evaln_sum: f64 = evalns.iter().map(|x| x.prior()).sum();
+            assert!(
+                (evaln_sum - 1.0).abs() < 0.1,
+                "Sum of evaluations is {} (should sum to 1)",
+                evaln_sum
+            );
+        }
+    }
+
+    /// `1 / x`, looked up in a table for small `x`. The reciprocal of 0 is taken to
+    /// be 2, so unvisited moves score higher than they would after one visit.
     fn reciprocal(&self, x: usize) -> f64 {
         if x < RECIPROCAL_TABLE_LEN {
-            unsafe { *self.reciprocals.get_unchecked(x) }
+            self.reciprocals[x]
         } else {
             1.0 / x as f64
         }
@@ -93,32 +451,123 @@ impl<Spec: MCTS<TreePolicy = Self>, MV: Send + Sync> TreePolicy<Spec> for UCTPol
     where
         MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
     {
+        let exploration = self.effective_exploration();
+        if let Some(parent_visits) = handle.cached_visits() {
+            return self
+                .tie_break
+                .select_by_key(&mut handle.thread_data().policy_data, moves, |mov| {
+                    self.score_with(exploration, mov.sum_rewards(), mov.visits(), parent_visits)
+                })
+                .unwrap();
+        }
+        // Scored with the same visits they're summed from.
         let snap_shot = moves.clone().map(|x| x.visits()).collect::<Vec<_>>();
         let parent_visits = snap_shot.iter().sum::<u64>();
-        handle
-            .thread_data()
-            .policy_data
-            .select_by_key(moves.zip(snap_shot), |(mov, child_visits)| {
-                let sum_rewards = mov.sum_rewards();
-                // http://mcts.ai/pubs/mcts-survey-master.pdf
-                if *child_visits == 0 {
-                    std::f64::INFINITY
-                } else {
-                    let parent_visits = parent_visits as f64;
-                    let child_visits = *child_visits as f64;
-                    let explore_term = (parent_visits.ln() / child_visits).sqrt();
-                    let mean_action_value = sum_rewards as f64 / child_visits;
-                    self.exploration_constant * explore_term + mean_action_value
-                }
-            })
+        self.tie_break
+            .select_by_key(
+                &mut handle.thread_data().policy_data,
+                moves.zip(snap_shot),
+                |(mov, child_visits)| {
+                    self.score_with(exploration, mov.sum_rewards(), *child_visits, parent_visits)
+                },
+            )
             .map(|x| x.0)
             .unwrap()
     }
+
+    fn score_child(&self, child: &MoveInfo<Spec>, parent_visits: u64) -> f64 {
+        let exploration = self.effective_exploration();
+        self.score_with(
+            exploration,
+            child.sum_rewards(),
+            child.visits(),
+            parent_visits,
+        )
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<PolicyRng> {
+        Some(PolicyRng::new(seed))
+    }
+
+    fn virtual_loss(&self) -> Option<VirtualLossMode> {
+        self.virtual_loss
+    }
+
+    fn on_root_reward(&self, reward: f64) {
+        if let Some(spread) = &self.reward_spread {
+            spread.record(reward);
+        }
+    }
+}
+
+/// `UCTPolicy`, with the search's `HistoryTable` standing in for the statistics of
+/// children that have few visits of their own.
+///
+/// A child's mean reward is blended with its move's history mean, which gets the
+/// weight of `history_weight` visits: with `n` visits the history counts for
+/// `history_weight / (n + history_weight)` of the mean. An unvisited child whose move
+/// has history scores as if it had one visit at the history mean, instead of
+/// infinity, so the moves that did well elsewhere in the tree are tried first.
+/// Children whose moves have no history, and every child of a search without a
+/// history table, are scored as by `UCTPolicy`.
+#[derive(Clone, Debug)]
+pub struct HistoryPolicy<MV = ()> {
+    uct: UCTPolicy<MV>,
+    history_weight: f64,
 }
 
-impl<Spec: MCTS<TreePolicy = Self>> TreePolicy<Spec> for AlphaGoPolicy {
+impl<MV> HistoryPolicy<MV> {
+    pub fn new(exploration_constant: f64, history_weight: f64) -> Self {
+        assert!(
+            history_weight >= 0.0,
+            "history weight is {} (must not be negative)",
+            history_weight
+        );
+        Self {
+            uct: UCTPolicy::new(exploration_constant),
+            history_weight,
+        }
+    }
+
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.uct.tie_break = tie_break;
+        self
+    }
+
+    pub fn exploration_constant(&self) -> f64 {
+        self.uct.exploration_constant
+    }
+
+    pub fn history_weight(&self) -> f64 {
+        self.history_weight
+    }
+
+    fn score(
+        &self,
+        sum_rewards: f64,
+        child_visits: u64,
+        parent_visits: u64,
+        history: Option<f64>,
+    ) -> f64 {
+        let history = match history {
+            Some(x) => x,
+            None => return self.uct.score(sum_rewards, child_visits, parent_visits),
+        };
+        let visits = child_visits as f64;
+        let beta = self.history_weight / (visits + self.history_weight);
+        let mean = if child_visits == 0 {
+            history
+        } else {
+            (1.0 - beta) * sum_rewards / visits + beta * history
+        };
+        let explore_term = ((parent_visits.max(1) as f64).ln() / visits.max(1.0)).sqrt();
+        self.uct.exploration_constant * explore_term + mean
+    }
+}
+
+impl<Spec: MCTS<TreePolicy = Self>, MV: Send + Sync> TreePolicy<Spec> for HistoryPolicy<MV> {
     type ThreadLocalData = PolicyRng;
-    type MoveEvaluation = f64;
+    type MoveEvaluation = MV;
 
     fn choose_child<'a, MoveIter>(
         &self,
@@ -128,53 +577,452 @@ impl<Spec: MCTS<TreePolicy = Self>> TreePolicy<Spec> for AlphaGoPolicy {
     where
         MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
     {
-        let total_visits = moves.clone().map(|x| x.visits()).sum::<u64>() + 1;
-        let sqrt_total_visits = (total_visits as f64).sqrt();
-        let explore_coef = self.exploration_constant * sqrt_total_visits;
-
-        handle
-            .thread_data()
-            .policy_data
-            .select_by_key(moves, |mov| {
-                let sum_rewards = mov.sum_rewards() as f64;
-                let child_visits = mov.visits();
-                let policy_evaln = *mov.move_evaluation() as f64;
-
-                (sum_rewards + explore_coef * policy_evaln) * self.reciprocal(child_visits as usize)
+        let history = handle.history();
+        if let Some(parent_visits) = handle.cached_visits() {
+            return self
+                .uct
+                .tie_break
+                .select_by_key(&mut handle.thread_data().policy_data, moves, |mov| {
+                    let mean = history.and_then(|x| x.mean(mov.get_move()));
+                    self.score(mov.sum_rewards(), mov.visits(), parent_visits, mean)
+                })
+                .unwrap();
+        }
+        let snap_shot = moves.clone().map(|x| x.visits()).collect::<Vec<_>>();
+        let parent_visits = snap_shot.iter().sum::<u64>();
+        self.uct
+            .tie_break
+            .select_by_key(
+                &mut handle.thread_data().policy_data,
+                moves.zip(snap_shot),
+                |(mov, child_visits)| {
+                    let mean = history.and_then(|x| x.mean(mov.get_move()));
+                    self.score(mov.sum_rewards(), *child_visits, parent_visits, mean)
+                },
+            )
+            .map(|x| x.0)
+            .unwrap()
+    }
+
+    /// The `UCTPolicy` score: the history table isn't available here.
+    fn score_child(&self, child: &MoveInfo<Spec>, parent_visits: u64) -> f64 {
+        self.uct
+            .score(child.sum_rewards(), child.visits(), parent_visits)
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<PolicyRng> {
+        Some(PolicyRng::new(seed))
+    }
+}
+
+impl<Spec: MCTS<TreePolicy = Self>, P: Prior> TreePolicy<Spec> for AlphaGoPolicy<P> {
+    type ThreadLocalData = PolicyRng;
+    type MoveEvaluation = P;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<Spec>,
+    ) -> &'a MoveInfo<Spec>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
+    {
+        let parent_visits = handle
+            .cached_visits()
+            .unwrap_or_else(|| moves.clone().map(|x| x.visits()).sum::<u64>());
+        let explore_coef = self.explore_coef(parent_visits);
+        let mixing = match self.prior_annealing {
+            Some(_) => self.mixing(parent_visits, moves.clone().count()),
+            None => Mixing::NONE,
+        };
+        self.tie_break
+            .select_by_key(&mut handle.thread_data().policy_data, moves, |mov| {
+                self.score(mov, explore_coef, mixing)
             })
             .unwrap()
     }
 
-    fn validate_evaluations(&self, evalns: &[f64]) {
-        for &x in evalns {
-            assert!(
-                x >= -1e-6,
-                "Move evaluation is {} (must be non-negative)",
-                x
-            );
+    fn score_child(&self, child: &MoveInfo<Spec>, parent_visits: u64) -> f64 {
+        self.score(child, self.explore_coef(parent_visits), Mixing::NONE)
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<PolicyRng> {
+        Some(PolicyRng::new(seed))
+    }
+
+    fn move_prior(&self, evaln: &P) -> f64 {
+        evaln.prior()
+    }
+
+    fn validate_evaluations(&self, evalns: &[P]) {
+        self.validate_priors(evalns);
+    }
+}
+
+/// `AlphaGoPolicy` restricted to the moves with the best priors, with more of them
+/// becoming selectable as their parent gets visits: progressive unpruning, as in
+/// Chaslot et al.
+///
+/// A node starts with its `initial` best moves. The first further move becomes
+/// selectable at `base_visits` parent visits, and each one after that when the
+/// parent's visits have grown by another factor of `growth`, until every move is.
+/// The eligible moves are worked out from the current priors at every selection, so
+/// unlike progressive widening, which adds children for good, a move whose prior
+/// is lowered (see `AtomicPrior`) can be pruned again. Moves that were pruned keep
+/// their visits.
+#[derive(Clone, Debug)]
+pub struct ProgressiveUnpruning<P = f64> {
+    inner: AlphaGoPolicy<P>,
+    initial: usize,
+    growth: f64,
+    base_visits: f64,
+}
+
+impl<P> ProgressiveUnpruning<P> {
+    /// Unprunes `inner`'s moves, starting from `initial` of them, with one more at
+    /// 1 parent visit and every time the parent's visits grow by `growth` after that.
+    pub fn new(inner: AlphaGoPolicy<P>, initial: usize, growth: f64) -> Self {
+        assert!(growth > 1.0, "growth is {} (must be above 1)", growth);
+        Self {
+            inner,
+            initial: initial.max(1),
+            growth,
+            base_visits: 1.0,
         }
-        if evalns.len() >= 1 {
-            let evaln_sum: f64 = evalns.iter().sum();
-            assert!(
-                (evaln_sum - 1.0).abs() < 0.1,
-                "Sum of evaluations is {} (should sum to 1)",
-                evaln_sum
-            );
+    }
+
+    /// Sets the parent visits at which the first move beyond the initial ones
+    /// becomes selectable. Chaslot et al. use 50, with a growth of 1.3.
+    pub fn with_base_visits(mut self, base_visits: f64) -> Self {
+        self.base_visits = base_visits;
+        self
+    }
+
+    pub fn inner(&self) -> &AlphaGoPolicy<P> {
+        &self.inner
+    }
+
+    fn score<Spec: MCTS<TreePolicy = Self>>(
+        &self,
+        mov: &MoveInfo<Spec>,
+        explore_coef: f64,
+        mixing: Mixing,
+    ) -> f64
+    where
+        P: Prior,
+    {
+        let evaln = mov.move_evaluation();
+        let inner = &self.inner;
+        inner.score_stats(mov.sum_rewards(), mov.visits(), evaln, explore_coef, mixing)
+    }
+
+    /// How many of a node's `moves` moves are selectable at `parent_visits`.
+    pub fn eligible_moves(&self, parent_visits: u64, moves: usize) -> usize {
+        let mut eligible = self.initial;
+        let mut threshold = self.base_visits;
+        while eligible < moves && parent_visits as f64 >= threshold {
+            eligible += 1;
+            threshold *= self.growth;
+        }
+        eligible.min(moves)
+    }
+}
+
+impl<Spec: MCTS<TreePolicy = Self>, P: Prior> TreePolicy<Spec> for ProgressiveUnpruning<P> {
+    type ThreadLocalData = PolicyRng;
+    type MoveEvaluation = P;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<Spec>,
+    ) -> &'a MoveInfo<Spec>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
+    {
+        let parent_visits = handle
+            .cached_visits()
+            .unwrap_or_else(|| moves.clone().map(|x| x.visits()).sum::<u64>());
+        let explore_coef = self.inner.explore_coef(parent_visits);
+        let count = moves.clone().count();
+        let eligible = self.eligible_moves(parent_visits, count);
+        let mixing = self.inner.mixing(parent_visits, count);
+        let rng = &mut handle.thread_data().policy_data;
+        let score = |mov: &&MoveInfo<Spec>| self.score(mov, explore_coef, mixing);
+        if eligible >= count {
+            return self
+                .inner
+                .tie_break
+                .select_by_key(rng, moves, score)
+                .unwrap();
+        }
+        // The best priors, ties going to the earlier move.
+        let mut order: Vec<(f64, usize)> = moves
+            .clone()
+            .map(|x| x.move_evaluation().prior())
+            .zip(0..)
+            .collect();
+        order.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+        let mut keep = vec![false; count];
+        for &(_, i) in &order[..eligible] {
+            keep[i] = true;
+        }
+        let moves = moves.zip(keep).filter(|x| x.1).map(|x| x.0);
+        self.inner
+            .tie_break
+            .select_by_key(rng, moves, score)
+            .unwrap()
+    }
+
+    fn score_child(&self, child: &MoveInfo<Spec>, parent_visits: u64) -> f64 {
+        self.score(child, self.inner.explore_coef(parent_visits), Mixing::NONE)
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<PolicyRng> {
+        Some(PolicyRng::new(seed))
+    }
+
+    fn move_prior(&self, evaln: &P) -> f64 {
+        evaln.prior()
+    }
+
+    fn validate_evaluations(&self, evalns: &[P]) {
+        self.inner.validate_priors(evalns);
+    }
+}
+
+/// How much of a uniform prior `AlphaGoPolicy::with_prior_annealing` mixes into the
+/// stored priors of a node's moves, as a function of the node's visits `n`. The
+/// prior used is `(1 - w(n)) * prior + w(n) / moves`, with `w(n)` clamped to
+/// `[0, 1]`.
+#[derive(Clone, Copy, Debug)]
+pub enum PriorAnnealing {
+    /// `w(n) = max_weight * min(1, n / visits)`.
+    Linear { visits: u64, max_weight: f64 },
+    /// `w(n) = max_weight * n / (n + half_visits)`, which reaches half of
+    /// `max_weight` at `half_visits` visits.
+    Hyperbolic { half_visits: f64, max_weight: f64 },
+    /// Any schedule.
+    Custom(fn(u64) -> f64),
+}
+
+impl PriorAnnealing {
+    /// `w(n)`.
+    pub fn weight(&self, visits: u64) -> f64 {
+        let weight = match *self {
+            PriorAnnealing::Linear {
+                visits: full,
+                max_weight,
+            } => {
+                if visits >= full {
+                    max_weight
+                } else {
+                    max_weight * visits as f64 / full as f64
+                }
+            }
+            PriorAnnealing::Hyperbolic {
+                half_visits,
+                max_weight,
+            } => max_weight * visits as f64 / (visits as f64 + half_visits),
+            PriorAnnealing::Custom(f) => f(visits),
+        };
+        if weight.is_nan() {
+            0.0
+        } else {
+            weight.clamp(0.0, 1.0)
+        }
+    }
+}
+
+/// The uniform share of the priors at one selection.
+#[derive(Clone, Copy)]
+struct Mixing {
+    weight: f64,
+    uniform: f64,
+}
+
+impl Mixing {
+    const NONE: Mixing = Mixing {
+        weight: 0.0,
+        uniform: 0.0,
+    };
+
+    fn apply(self, prior: f64) -> f64 {
+        if self.weight == 0.0 {
+            prior
+        } else {
+            (1.0 - self.weight) * prior + self.weight * self.uniform
+        }
+    }
+}
+
+/// A move evaluation that `AlphaGoPolicy` can use as a prior probability.
+pub trait Prior: Sync + Send {
+    fn prior(&self) -> f64;
+
+    /// The mean reward `AlphaGoPolicy` assumes for the move until it's visited
+    /// (first-play urgency), for the player making it. `None`, the default, leaves
+    /// unvisited moves to the exploration term alone.
+    fn init_q(&self) -> Option<f64> {
+        None
+    }
+}
+
+impl Prior for f64 {
+    fn prior(&self) -> f64 {
+        *self
+    }
+}
+
+/// A prior together with a quick estimate of the move's value, such as a shallow
+/// network's, for `AlphaGoPolicy<PriorWithValue>` to score the move by before its
+/// first visit. `AlphaGoPolicy::validate_evaluations` only checks the priors.
+///
+/// Evaluators that only have priors can convert them with `From<f64>` or
+/// `from_priors`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PriorWithValue {
+    pub prior: f64,
+    pub init_q: Option<f64>,
+}
+
+impl PriorWithValue {
+    pub fn new(prior: f64, init_q: f64) -> Self {
+        Self {
+            prior,
+            init_q: Some(init_q),
+        }
+    }
+
+    /// Priors without values.
+    pub fn from_priors(priors: Vec<f64>) -> Vec<Self> {
+        priors.into_iter().map(Self::from).collect()
+    }
+}
+
+impl From<f64> for PriorWithValue {
+    fn from(prior: f64) -> Self {
+        Self {
+            prior,
+            init_q: None,
         }
     }
 }
 
+impl Prior for PriorWithValue {
+    fn prior(&self) -> f64 {
+        self.prior
+    }
+    fn init_q(&self) -> Option<f64> {
+        self.init_q
+    }
+}
+
+/// A prior that can be changed while the search is running, for example to add
+/// noise at the root. See `UpdatableMoveEvaluation`.
+pub struct AtomicPrior(AtomicStat);
+
+impl AtomicPrior {
+    pub fn new(prior: f64) -> Self {
+        AtomicPrior(AtomicStat::new(prior))
+    }
+}
+
+impl Prior for AtomicPrior {
+    fn prior(&self) -> f64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Clone for AtomicPrior {
+    fn clone(&self) -> Self {
+        AtomicPrior::new(self.prior())
+    }
+}
+
+impl std::fmt::Debug for AtomicPrior {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self.prior())
+    }
+}
+
+/// Move evaluations that can be replaced after their node is created, through
+/// `MoveInfo::update_move_evaluation`. Specs opt in by choosing such a type as
+/// their `TreePolicy::MoveEvaluation`.
+///
+/// Updates may race with the search: a reader sees either the old or the new value,
+/// never a mix of the two.
+pub trait UpdatableMoveEvaluation {
+    type Value;
+    fn load(&self) -> Self::Value;
+    fn store(&self, value: Self::Value);
+}
+
+impl UpdatableMoveEvaluation for AtomicPrior {
+    type Value = f64;
+    fn load(&self) -> f64 {
+        self.prior()
+    }
+    fn store(&self, value: f64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+}
+
+/// For move evaluations that don't fit in an atomic.
+impl<T: Clone> UpdatableMoveEvaluation for std::sync::RwLock<T> {
+    type Value = T;
+    fn load(&self) -> T {
+        self.read().unwrap().clone()
+    }
+    fn store(&self, value: T) {
+        *self.write().unwrap() = value;
+    }
+}
+
+/// Scores are compared with `>`, which is always false for NaN, so a NaN score
+/// would never be chosen and could leave nothing chosen at all. Treat it as the
+/// worst possible score instead.
+fn nan_to_worst(score: f64) -> f64 {
+    if score.is_nan() {
+        f64::NEG_INFINITY
+    } else {
+        score
+    }
+}
+
+/// Chooses an element by a key. What "by" means is up to the implementation:
+/// `PolicyRng` takes the highest key and breaks ties at random, `WeightedRng` draws
+/// in proportion to the keys. Returns `None` only when there are no elements.
 pub trait SelectionRng {
     fn select_by_key<T, Iter, KeyFn>(&mut self, elts: Iter, key_fn: KeyFn) -> Option<T>
     where
         Iter: Iterator<Item = T>,
         KeyFn: Fn(&T) -> f64,
         T: Clone;
+
+    /// The number of choices made by a fallback since the last call, when the keys
+    /// didn't allow the choice asked for. Searches call it after every playout and
+    /// add the count to `SearchSummary::selection_fallbacks`. Defaults to 0.
+    fn take_fallbacks(&mut self) -> u64 {
+        0
+    }
 }
 
+/// Chooses with probability proportional to the key. When a key is negative, every
+/// key is first shifted up so that the lowest is zero. When every weight is zero,
+/// chooses uniformly.
+///
+/// Keys that are NaN, or infinite, make no distribution. The choice then falls
+/// back to uniform among the elements with an infinite weight, or among all of
+/// them if none has one, with NaN keys counted as the lowest. Fallbacks are counted
+/// (see `fallbacks`) and draw only from this rng, so seeded choices stay
+/// reproducible.
 #[derive(Clone)]
 pub struct WeightedRng {
     rng: StdRng,
+    fallbacks: u64,
+    /// Not yet returned by `take_fallbacks`.
+    untaken: u64,
 }
 
 #[derive(Clone)]
@@ -187,12 +1035,24 @@ impl PolicyRng {
         let rng = SeedableRng::seed_from_u64(seed);
         Self { rng }
     }
+    /// Uniform in `[0, 1)`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        self.rng.gen()
+    }
 }
 
 impl WeightedRng {
     pub fn new(seed: u64) -> Self {
         let rng = SeedableRng::seed_from_u64(seed);
-        Self { rng }
+        Self {
+            rng,
+            fallbacks: 0,
+            untaken: 0,
+        }
+    }
+    /// The choices made by a fallback since the rng was created.
+    pub fn fallbacks(&self) -> u64 {
+        self.fallbacks
     }
 }
 
@@ -205,10 +1065,10 @@ impl SelectionRng for PolicyRng {
     {
         let mut choice = None;
         let mut num_optimal: u32 = 0;
-        let mut best_so_far: f64 = std::f64::NEG_INFINITY;
+        let mut best_so_far: f64 = f64::NEG_INFINITY;
         for elt in elts {
-            let score = key_fn(&elt);
-            if score > best_so_far {
+            let score = nan_to_worst(key_fn(&elt));
+            if choice.is_none() || score > best_so_far {
                 choice = Some(elt);
                 num_optimal = 1;
                 best_so_far = score;
@@ -230,40 +1090,104 @@ impl SelectionRng for WeightedRng {
         KeyFn: Fn(&T) -> f64,
         T: Clone,
     {
+        use rand::distributions::{Distribution, WeightedIndex};
         use rand::seq::SliceRandom;
 
         let options = elts.collect::<Vec<_>>();
+        let keys = options.iter().map(&key_fn).collect::<Vec<_>>();
 
-        let minimal = options
+        let minimal = keys
             .iter()
-            .map(&key_fn)
-            .min_by(|a, b| a.partial_cmp(b).unwrap())
-            .unwrap();
-        let minimal = if minimal < 0.0 { -minimal } else { 0.01 };
-
-        options
-            .choose_weighted(&mut self.rng, |v| key_fn(v) + minimal)
-            .ok()
-            .or_else(|| {
-                println!(
-                    "No weighted found, {} moves found, choosing random. {:?}",
-                    options.len(),
-                    options.iter().map(&key_fn).collect::<Vec<_>>()
-                );
-                options.choose(&mut self.rng)
-            })
-            .cloned()
+            .map(|&x| nan_to_worst(x))
+            .fold(f64::INFINITY, f64::min);
+        let shift = if minimal < 0.0 { -minimal } else { 0.0 };
+        let mut weights = keys.iter().map(|&x| x + shift).collect::<Vec<_>>();
+        if weights.iter().any(|x| !x.is_finite()) {
+            // A NaN or infinitely negative key shifts every other weight to
+            // infinity, which leaves NaN only where the lowest keys were.
+            self.fallbacks += 1;
+            self.untaken += 1;
+            emit_event!(SearchEvent::WeightedFallback {
+                options: options.len()
+            });
+            let infinite = weights.contains(&f64::INFINITY);
+            for x in &mut weights {
+                *x = if !infinite || *x == f64::INFINITY {
+                    1.0
+                } else {
+                    0.0
+                };
+            }
+        }
+        let total = weights.iter().sum::<f64>();
+        if total == 0.0 {
+            return options.choose(&mut self.rng).cloned();
+        }
+        if total == f64::INFINITY {
+            // Finite weights whose sum overflows.
+            let max = weights.iter().copied().fold(0.0, f64::max);
+            for x in &mut weights {
+                *x /= max;
+            }
+        }
+        let index = WeightedIndex::new(&weights)
+            .expect("finite, non-negative weights with a positive sum")
+            .sample(&mut self.rng);
+        options.into_iter().nth(index)
+    }
+
+    fn take_fallbacks(&mut self) -> u64 {
+        std::mem::take(&mut self.untaken)
     }
 }
 
+/// Randomly seeded. Searches seed thread data from their master seed instead (see
+/// `MCTSManager::seed`), so this is only a fallback for thread data made elsewhere.
 impl Default for WeightedRng {
     fn default() -> Self {
-        Self::new(rand::random())
+        Self::new(random_seed())
     }
 }
 
+/// Randomly seeded. Searches seed thread data from their master seed instead (see
+/// `MCTSManager::seed`), so this is only a fallback for thread data made elsewhere.
 impl Default for PolicyRng {
     fn default() -> Self {
-        Self::new(rand::random())
+        Self::new(random_seed())
+    }
+}
+
+#[cfg(feature = "thread_rng")]
+pub(crate) fn random_seed() -> u64 {
+    rand::random()
+}
+
+/// Without `thread_rng`, unseeded searches differ from each other but are the same
+/// every time the program runs.
+#[cfg(not(feature = "thread_rng"))]
+pub(crate) fn random_seed() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let mut z = NEXT
+        .fetch_add(1, Ordering::Relaxed)
+        .wrapping_add(1)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reciprocal_across_the_table_boundary() {
+        let policy = AlphaGoPolicy::new(1.0);
+        assert_eq!(policy.reciprocal(0), 2.0);
+        assert_eq!(policy.reciprocal(1), 1.0);
+        for x in 2..1000 {
+            assert_eq!(policy.reciprocal(x), 1.0 / x as f64, "1 / {}", x);
+        }
+        assert_eq!(policy.reciprocal(usize::MAX), 1.0 / usize::MAX as f64);
     }
 }
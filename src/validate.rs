@@ -0,0 +1,238 @@
+use super::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// A broken invariant found by `SearchTree::validate`, with the moves from the root
+/// to the node it was found at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvariantViolation<M> {
+    /// The first path to the node found, breadth first. Empty for the root and for
+    /// `Violation::DanglingTableEntry`.
+    pub path: Vec<M>,
+    pub violation: Violation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// The node's visits aren't its children's visits plus the playouts that ended
+    /// at it. Only checked for nodes whose children have no other parent.
+    VisitCount {
+        visits: u64,
+        children_visits: u64,
+        evaluations: u64,
+    },
+    /// The sum of rewards of the node, or of one of its moves, isn't finite.
+    NonFiniteReward { sum_rewards: f64 },
+    /// The node is owned by `owners` moves instead of exactly one (none for the
+    /// root). Nodes found in the transposition table are reached through moves that
+    /// don't own them, and don't count.
+    Ownership { owners: usize },
+    /// The node can't be reached from the root through owning moves alone, so those
+    /// moves form a cycle.
+    OwnershipCycle,
+    /// The transposition table holds a node that isn't in the tree.
+    DanglingTableEntry,
+}
+
+impl<M: fmt::Debug> fmt::Display for InvariantViolation<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at {:?}: ", self.path)?;
+        match &self.violation {
+            Violation::VisitCount {
+                visits,
+                children_visits,
+                evaluations,
+            } => write!(
+                f,
+                "{} visits, but {} through children and {} ending here",
+                visits, children_visits, evaluations
+            ),
+            Violation::NonFiniteReward { sum_rewards } => {
+                write!(f, "sum of rewards is {}", sum_rewards)
+            }
+            Violation::Ownership { owners } => write!(f, "owned by {} moves", owners),
+            Violation::OwnershipCycle => write!(f, "unreachable from the root through owners"),
+            Violation::DanglingTableEntry => {
+                write!(f, "the transposition table holds a node not in the tree")
+            }
+        }
+    }
+}
+
+struct NodeInfo<'a, Spec: MCTS> {
+    node: NodeHandle<'a, Spec>,
+    path: Vec<Move<Spec>>,
+    parents: usize,
+    owners: usize,
+}
+
+impl<Spec: MCTS> SearchTree<Spec> {
+    /// Checks the tree's invariants and returns every violation found:
+    ///
+    /// - a node's visits are its children's visits plus the playouts that ended at it,
+    ///   for nodes whose children have no other parent;
+    /// - the sums of rewards are finite;
+    /// - every node but the root and those made by `preload_states` is owned by
+    ///   exactly one move, and the owning moves form trees below those nodes, so
+    ///   any cycle goes through the transposition table;
+    /// - the transposition table only holds nodes in the tree, if it can list them
+    ///   (see `TranspositionTable::nodes`).
+    ///
+    /// This is a debugging aid, as slow as walking the whole tree. It must only run
+    /// with no search running, or it will find the statistics of playouts in flight.
+    pub fn validate(&self) -> Result<(), Vec<InvariantViolation<Move<Spec>>>> {
+        let mut violations = Vec::new();
+        let root = self.root_node();
+        let mut nodes: Vec<NodeInfo<Spec>> = Vec::new();
+        let mut ids: HashMap<*const (), usize> = HashMap::new();
+        ids.insert(root.into_raw(), 0);
+        nodes.push(NodeInfo {
+            node: root,
+            path: Vec::new(),
+            parents: 0,
+            owners: 0,
+        });
+        let mut queue = VecDeque::new();
+        queue.push_back(0);
+        // Preloaded nodes are reached from the root if the search found them in the
+        // table, and walked on their own after it otherwise.
+        let mut preloaded = self.preloaded_nodes();
+        loop {
+            while let Some(id) = queue.pop_front() {
+                for mov in nodes[id].node.moves() {
+                    let child = match mov.child() {
+                        Some(child) => child,
+                        None => continue,
+                    };
+                    let child_id = match ids.get(&child.into_raw()) {
+                        Some(&child_id) => child_id,
+                        None => {
+                            let mut path = nodes[id].path.clone();
+                            path.push(mov.get_move().clone());
+                            ids.insert(child.into_raw(), nodes.len());
+                            queue.push_back(nodes.len());
+                            nodes.push(NodeInfo {
+                                node: child,
+                                path,
+                                parents: 0,
+                                owners: 0,
+                            });
+                            nodes.len() - 1
+                        }
+                    };
+                    nodes[child_id].parents += 1;
+                    if mov.owns_child() {
+                        nodes[child_id].owners += 1;
+                    }
+                }
+            }
+            let next = preloaded.find(|x| !ids.contains_key(&x.into_raw()));
+            let Some(node) = next else { break };
+            ids.insert(node.into_raw(), nodes.len());
+            queue.push_back(nodes.len());
+            nodes.push(NodeInfo {
+                node,
+                path: Vec::new(),
+                parents: 0,
+                owners: 0,
+            });
+        }
+        let unowned: HashSet<_> = std::iter::once(self.root_node())
+            .chain(self.preloaded_nodes())
+            .map(|x| x.into_raw())
+            .collect();
+
+        for (id, info) in nodes.iter().enumerate() {
+            let mut report = |violation| {
+                violations.push(InvariantViolation {
+                    path: info.path.clone(),
+                    violation,
+                })
+            };
+            let node = &info.node;
+            // The root's own statistics aren't kept; every playout goes through it.
+            let stats = if id == 0 {
+                NodeStats {
+                    visits: self.depth_histogram().total() + self.carried_root_visits(),
+                    sum_rewards: 0.0,
+                }
+            } else {
+                node.node_stats()
+            };
+            if !stats.sum_rewards.is_finite() {
+                report(Violation::NonFiniteReward {
+                    sum_rewards: stats.sum_rewards,
+                });
+            }
+            for mov in node.moves() {
+                if !mov.sum_rewards().is_finite() {
+                    report(Violation::NonFiniteReward {
+                        sum_rewards: mov.sum_rewards(),
+                    });
+                }
+            }
+            let children: Vec<&NodeInfo<Spec>> = node
+                .moves()
+                .filter_map(|x| x.child())
+                .map(|x| &nodes[ids[&x.into_raw()]])
+                .collect();
+            if children.iter().all(|x| x.parents == 1) {
+                let children_visits = children.iter().map(|x| x.node.node_stats().visits).sum();
+                if stats.visits != children_visits + node.evaluations() {
+                    report(Violation::VisitCount {
+                        visits: stats.visits,
+                        children_visits,
+                        evaluations: node.evaluations(),
+                    });
+                }
+            }
+            let expected_owners = if unowned.contains(&node.into_raw()) {
+                0
+            } else {
+                1
+            };
+            if info.owners != expected_owners {
+                report(Violation::Ownership {
+                    owners: info.owners,
+                });
+            }
+        }
+
+        let mut owned = unowned.clone();
+        let mut stack: Vec<_> = std::iter::once(self.root_node())
+            .chain(self.preloaded_nodes())
+            .collect();
+        while let Some(node) = stack.pop() {
+            for mov in node.moves().filter(|x| x.owns_child()) {
+                if let Some(child) = mov.child() {
+                    if owned.insert(child.into_raw()) {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        for info in &nodes {
+            if info.owners == 1 && !owned.contains(&info.node.into_raw()) {
+                violations.push(InvariantViolation {
+                    path: info.path.clone(),
+                    violation: Violation::OwnershipCycle,
+                });
+            }
+        }
+
+        for node in self.transposition_table().nodes() {
+            if !ids.contains_key(&(node as *const ())) {
+                violations.push(InvariantViolation {
+                    path: Vec::new(),
+                    violation: Violation::DanglingTableEntry,
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use super::*;
+use std::marker::PhantomData;
+use tree_policy::{SelectionRng, UCTPolicy};
+
+/// A ready-made `MCTS` implementation for two-player zero-sum games, so a game only
+/// needs its `GameState` implementation:
+///
+/// ```text
+/// type MyMCTS = TwoPlayerZeroSumSpec<MyGame>;
+/// let mut mcts = MyMCTS::manager(MyGame::default());
+/// ```
+///
+/// States are evaluated by `WinDrawLossEvaluator`, children are chosen with
+/// `UCTPolicy`, and there is no transposition table.
+pub struct TwoPlayerZeroSumSpec<S>(PhantomData<fn() -> S>);
+
+impl<S> Default for TwoPlayerZeroSumSpec<S> {
+    fn default() -> Self {
+        TwoPlayerZeroSumSpec(PhantomData)
+    }
+}
+
+impl<S: GameState + Default + Sync> TwoPlayerZeroSumSpec<S> {
+    /// The exploration constant `manager` uses.
+    pub const EXPLORATION_CONSTANT: f64 = 1.4;
+
+    /// A manager searching from `state`.
+    pub fn manager(state: S) -> MCTSManager<Self> {
+        MCTSManager::builder(Self::default())
+            .state(state)
+            .evaluator(WinDrawLossEvaluator)
+            .policy(UCTPolicy::new(Self::EXPLORATION_CONSTANT))
+            .transposition_table(())
+            .build()
+    }
+}
+
+impl<S: GameState + Default + Sync> MCTS for TwoPlayerZeroSumSpec<S> {
+    type State = S;
+    type Eval = WinDrawLossEvaluator;
+    type TreePolicy = UCTPolicy<()>;
+    type NodeData = ();
+    type TranspositionTable = ();
+    type ExtraThreadData = ();
+
+    fn evaluation_symmetry(&self) -> EvaluationSymmetry {
+        EvaluationSymmetry::ZeroSumTwoPlayer
+    }
+}
+
+/// Evaluates a state by playing uniformly random moves to the end of the game. The
+/// winner gets a reward of 1 and the loser -1; a draw is worth 0 to both.
+///
+/// The random moves come from the search thread's `SelectionRng`, so seeded searches
+/// stay reproducible.
+pub struct WinDrawLossEvaluator;
+
+impl WinDrawLossEvaluator {
+    /// The winner of a random playout from `state`, or `None` for a draw.
+    pub fn rollout<S: GameState, R: SelectionRng>(state: &S, rng: &mut R) -> Option<S::Player> {
+        let mut state = state.clone();
+        loop {
+            let moves = state.available_moves().into_iter();
+            match rng.select_by_key(moves, |_| 0.0) {
+                Some(mov) => {
+                    if state.make_move(&mov).is_err() {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+        state.get_winner()
+    }
+}
+
+impl<S: GameState + Default + Sync> Evaluator<TwoPlayerZeroSumSpec<S>> for WinDrawLossEvaluator {
+    type StateEvaluation = Option<S::Player>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &S,
+        _: &MoveList<TwoPlayerZeroSumSpec<S>>,
+        handle: Option<SearchHandle<TwoPlayerZeroSumSpec<S>>>,
+    ) -> (Vec<()>, Option<S::Player>) {
+        // `MoveList` can only be iterated by value.
+        let num_moves = state.available_moves().into_iter().count();
+        let winner = match handle {
+            Some(mut handle) => Self::rollout(state, &mut handle.thread_data().policy_data),
+            None => Self::rollout(state, &mut tree_policy::PolicyRng::default()),
+        };
+        (vec![(); num_moves], winner)
+    }
+
+    fn evaluate_existing_state(
+        &self,
+        _: &S,
+        evaln: &Option<S::Player>,
+        _: SearchHandle<TwoPlayerZeroSumSpec<S>>,
+    ) -> Option<S::Player> {
+        evaln.clone()
+    }
+
+    fn interpret_evaluation_for_player(
+        &self,
+        evaln: &Option<S::Player>,
+        player: &S::Player,
+    ) -> f64 {
+        match evaln {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// The same dimensionless constant for every game.
+const CONSTANT: f64 = 1.0;
+
+fn adaptive<MV>() -> UCTPolicy<MV> {
+    UCTPolicy::new(CONSTANT).with_adaptive_exploration(true)
+}
+
+#[test]
+fn records_one_reward_per_playout() {
+    let mut mcts = MCTSManager::new(
+        CountingGame(0),
+        CountingMCTS,
+        CountingEvaluator,
+        adaptive(),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n(300);
+    let spread = mcts.tree_policy().reward_spread().unwrap();
+    assert_eq!(spread.count(), 300);
+    let root = mcts.tree().root_node();
+    assert!((spread.mean().unwrap() - root.mean_value()).abs() < 1e-9);
+    assert!(spread.std_dev().unwrap() > 0.0);
+    assert!(
+        (mcts.tree_policy().effective_exploration() - CONSTANT * spread.std_dev().unwrap()).abs()
+            < 1e-12
+    );
+}
+
+#[test]
+fn matches_a_direct_computation() {
+    let spread = RewardSpread::new();
+    assert_eq!(spread.mean(), None);
+    spread.record(4.0);
+    assert_eq!(spread.std_dev(), None);
+    for x in [2.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+        spread.record(x);
+    }
+    assert_eq!(spread.mean(), Some(5.0));
+    // The squared deviations sum to 32 over 8 rewards.
+    assert!((spread.std_dev().unwrap() - (32.0f64 / 7.0).sqrt()).abs() < 1e-12);
+}
+
+#[test]
+fn off_by_default() {
+    let mut mcts = counting_manager(5.0);
+    mcts.playout_n(100);
+    assert!(mcts.tree_policy().reward_spread().is_none());
+    assert_eq!(mcts.tree_policy().effective_exploration(), 5.0);
+}
+
+#[test]
+fn plays_the_counting_game() {
+    let mut mcts = MCTSManager::new(
+        CountingGame(0),
+        CountingMCTS,
+        CountingEvaluator,
+        adaptive(),
+        (),
+    );
+    mcts.seed(Some(1));
+    mcts.playout_n(20_000);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.principal_variation(10), vec![CountingMove::Add; 10]);
+}
+
+#[test]
+fn plays_tictactoe() {
+    type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+    // X to move completes the column 0, 3, 6, then blocks O's diagonal 0, 4, 8.
+    for (cells, best) in [
+        ([1, 2, 0, 0, 2, 0, 1, 0, 0], 3),
+        ([2, 1, 0, 0, 2, 0, 1, 0, 0], 8),
+    ] {
+        let mut mcts = MCTSManager::builder(Spec::default())
+            .state(TicTacToe { cells })
+            .evaluator(WinDrawLossEvaluator)
+            .policy(adaptive())
+            .transposition_table(())
+            .build();
+        mcts.seed(Some(2));
+        mcts.playout_n(5_000);
+        mcts.tree().validate().unwrap();
+        assert_eq!(mcts.best_move(), Some(best), "{:?}", cells);
+        let std_dev = mcts
+            .tree_policy()
+            .reward_spread()
+            .unwrap()
+            .std_dev()
+            .unwrap();
+        assert!(std_dev > 0.0 && std_dev <= 1.0 + 1e-9, "{}", std_dev);
+    }
+}
+
+#[test]
+fn shared_across_threads() {
+    let mut mcts = MCTSManager::new(
+        CountingGame(0),
+        CountingMCTS,
+        CountingEvaluator,
+        adaptive(),
+        (),
+    );
+    mcts.playout_n_parallel(4_000, 4);
+    let spread = mcts.tree_policy().reward_spread().unwrap();
+    assert_eq!(spread.count(), 4_000);
+    assert!(spread.std_dev().unwrap().is_finite());
+}
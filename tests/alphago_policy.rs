@@ -0,0 +1,139 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Pick one of four moves, worth `VALUES[i]`; then the game is over.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Pick(Option<usize>);
+
+const VALUES: [f64; 4] = [0.1, 0.9, 0.3, 0.5];
+
+impl GameState for Pick {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        match self.0 {
+            Some(_) => vec![],
+            None => (0..4).collect(),
+        }
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.0 = Some(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+/// Uniform priors, with the true values as quick estimates when `quick_values`.
+struct PickEvaluator {
+    quick_values: bool,
+}
+
+impl Evaluator<PickMCTS> for PickEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Pick,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<PickMCTS>>,
+    ) -> (Vec<PriorWithValue>, f64) {
+        let priors = vec![0.25; moves.len()];
+        let evalns = if self.quick_values {
+            moves
+                .iter()
+                .map(|&i| PriorWithValue::new(0.25, VALUES[i]))
+                .collect()
+        } else {
+            PriorWithValue::from_priors(priors)
+        };
+        (evalns, state.0.map_or(0.0, |i| VALUES[i]))
+    }
+    fn evaluate_existing_state(&self, _: &Pick, evaln: &f64, _: SearchHandle<PickMCTS>) -> f64 {
+        *evaln
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct PickMCTS;
+
+impl MCTS for PickMCTS {
+    type State = Pick;
+    type Eval = PickEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy<PriorWithValue>;
+    type TranspositionTable = ();
+}
+
+fn pick_manager(quick_values: bool) -> MCTSManager<PickMCTS> {
+    let mut mcts = MCTSManager::new(
+        Pick::default(),
+        PickMCTS,
+        PickEvaluator { quick_values },
+        AlphaGoPolicy::with_exploration_constant(0.1),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts
+}
+
+fn root_visits(mcts: &MCTSManager<PickMCTS>) -> Vec<u64> {
+    mcts.tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect()
+}
+
+#[test]
+fn init_q_ranks_unvisited_moves() {
+    let mut mcts = pick_manager(true);
+    mcts.playout_n(20);
+    mcts.tree().validate().unwrap();
+    // The best quick value is right, so the other moves are never worth a visit.
+    assert_eq!(root_visits(&mcts), vec![0, 20, 0, 0]);
+    let unvisited = mcts.tree().root_node().moves().next().unwrap();
+    let explore_coef = 0.1 * 21f64.sqrt();
+    assert_eq!(
+        mcts.tree_policy().score_child(unvisited, 20),
+        VALUES[0] + explore_coef * 0.25
+    );
+}
+
+#[test]
+fn without_init_q_the_first_move_tried_keeps_the_search() {
+    // An unvisited move scores twice its exploration term, which early on is less
+    // than the mean reward of any visited move here.
+    let mut mcts = pick_manager(false);
+    mcts.playout_n(4);
+    mcts.tree().validate().unwrap();
+    let visits = root_visits(&mcts);
+    assert_eq!(visits.iter().filter(|&&x| x > 0).count(), 1, "{:?}", visits);
+}
+
+#[test]
+fn priors_convert_without_values() {
+    assert_eq!(
+        PriorWithValue::from(0.5),
+        PriorWithValue {
+            prior: 0.5,
+            init_q: None
+        }
+    );
+    assert_eq!(0.5.init_q(), None);
+    assert_eq!(PriorWithValue::new(0.5, -1.0).init_q(), Some(-1.0));
+    let priors: Vec<PriorWithValue> = PriorWithValue::from_priors(vec![0.25, 0.75]);
+    assert_eq!(priors.iter().map(|x| x.prior()).sum::<f64>(), 1.0);
+}
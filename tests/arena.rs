@@ -0,0 +1,278 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::arena::*;
+use mcts::prelude::*;
+
+type NimMCTS = TwoPlayerZeroSumSpec<Nim>;
+
+fn random(state: &Nim, seed: u64) -> Option<u8> {
+    let moves = state.available_moves();
+    moves.get(seed as usize % moves.len().max(1)).cloned()
+}
+
+fn perfect(state: &Nim, _: u64) -> Option<u8> {
+    match state.stones % 3 {
+        0 => Some(1),
+        x => Some(x),
+    }
+}
+
+#[test]
+fn perfect_play_wins_every_game_it_starts() {
+    let result = play_match(&mut perfect, &mut random, 10, false);
+    assert_eq!(
+        result.a_first,
+        Tally {
+            wins: 10,
+            draws: 0,
+            losses: 0
+        }
+    );
+    assert_eq!(result.b_first.games(), 0);
+    assert_eq!(result.pairs, [0; 5]);
+}
+
+#[test]
+fn alternating_colors() {
+    let result = play_match(&mut perfect, &mut perfect, 6, true);
+    assert_eq!(
+        result.a_first,
+        Tally {
+            wins: 3,
+            draws: 0,
+            losses: 0
+        }
+    );
+    assert_eq!(
+        result.b_first,
+        Tally {
+            wins: 0,
+            draws: 0,
+            losses: 3
+        }
+    );
+    // Each pair is one win and one loss.
+    assert_eq!(result.pairs, [0, 0, 3, 0, 0]);
+    assert_eq!(result.total().score(), 0.5);
+}
+
+#[test]
+fn no_move_ends_the_game() {
+    let mut pass = |_: &Nim, _: u64| None;
+    let result = play_match(&mut pass, &mut random, 2, true);
+    assert_eq!(
+        result.total(),
+        Tally {
+            wins: 0,
+            draws: 2,
+            losses: 0
+        }
+    );
+    assert_eq!(result.pairs, [0, 0, 1, 0, 0]);
+}
+
+#[test]
+fn seeded_matches_repeat() {
+    let start = Nim {
+        stones: 20,
+        to_move: 0,
+    };
+    let mut mcts = MCTSAgent::new(NimMCTS::manager, Budget::Playouts(20));
+    let a = play_match_from(&start, &mut mcts, &mut random, 8, true, 5);
+    let b = play_match_from(&start, &mut mcts, &mut random, 8, true, 5);
+    // Everything but the timing repeats.
+    assert_eq!(
+        (a.a_first, a.b_first, a.pairs),
+        (b.a_first, b.b_first, b.pairs)
+    );
+    let moves = |x: &MatchResult<u8>| -> Vec<_> {
+        x.moves()
+            .map(|x| (x.mov, x.playouts, x.root_value, x.by_a))
+            .collect()
+    };
+    assert_eq!(moves(&a), moves(&b));
+}
+
+#[test]
+fn every_move_is_reported() {
+    let mut mcts = MCTSAgent::new(NimMCTS::manager, Budget::Playouts(20));
+    let options = MatchOptions {
+        alternate_colors: true,
+        budget_a: Some(Budget::Playouts(50)),
+        ..Default::default()
+    };
+    let result = play_match_with(&Nim::default(), &mut mcts, &mut random, 4, &options);
+    assert_eq!(result.games.len(), 4);
+    for game in &result.games {
+        // Every game of Nim from 10 stones takes between 5 and 10 moves.
+        assert!((5..=10).contains(&game.moves.len()));
+        let stones: u8 = game.moves.iter().map(|x| x.mov.unwrap()).sum();
+        assert_eq!(stones, 10);
+        let first = &game.moves[0];
+        assert_eq!(first.by_a, game.a_first);
+        let mut stones = 10;
+        for report in &game.moves {
+            if report.by_a {
+                assert_eq!(report.budget, Some(Budget::Playouts(50)));
+                // Taking the last stone is forced, so it isn't searched.
+                let expected = if stones == 1 { 1 } else { 50 };
+                assert_eq!(report.playouts, expected);
+                assert!(report.root_value.is_some());
+            } else {
+                assert_eq!(report.budget, None);
+                assert_eq!(report.playouts, 0);
+            }
+            stones -= report.mov.unwrap();
+        }
+    }
+    let summary = result.summary_of(true);
+    assert_eq!(summary.moves, result.moves().filter(|x| x.by_a).count());
+    let playouts: u64 = result.moves().filter(|x| x.by_a).map(|x| x.playouts).sum();
+    assert_eq!(summary.playouts, playouts);
+    assert!(summary.median <= summary.p90 && summary.p90 <= summary.max);
+    assert_eq!(result.summary().moves, result.moves().count());
+}
+
+// Timed searches need a clock under the `wasm` feature.
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn time_budgets_are_respected() {
+    let mut mcts = MCTSAgent::new(NimMCTS::manager, Budget::Playouts(20));
+    let budget = std::time::Duration::from_millis(10);
+    let options = MatchOptions {
+        budget_a: Some(Budget::Time(budget)),
+        ..Default::default()
+    };
+    let result = play_match_with(&Nim::default(), &mut mcts, &mut random, 2, &options);
+    let tolerance = std::time::Duration::from_millis(250);
+    // Forced moves aren't searched, so only the others take the whole budget.
+    let searched = |x: &&MoveReport<u8>| x.by_a && x.playouts > 1;
+    for report in result.moves().filter(searched) {
+        assert!(report.time >= budget);
+        assert!(report.playouts > 0);
+        assert!(!report.exceeded_budget(tolerance), "{:?}", report);
+    }
+    let summary = result.summary_of(true);
+    assert!(summary.median >= budget);
+    assert!(summary.max_overrun <= tolerance);
+}
+
+#[test]
+fn search_beats_random_moves() {
+    let mut mcts = MCTSAgent::new(NimMCTS::manager, Budget::Playouts(2000));
+    let result = play_match(&mut mcts, &mut random, 10, true);
+    assert!(result.total().score() >= 0.9, "{:?}", result);
+}
+
+fn self_play(
+    temperature: f64,
+    playouts: usize,
+    options: SelfPlayOptions,
+) -> Vec<TrainingSample<Nim>> {
+    generate_self_play_game(NimMCTS::manager, |_| temperature, |_| playouts, &options)
+}
+
+#[test]
+fn greedy_self_play_is_won_by_the_first_player() {
+    let samples = self_play(0.0, 2000, SelfPlayOptions::default());
+    assert_eq!(samples[0].state, Nim::default());
+    for (i, sample) in samples.iter().enumerate() {
+        let expected = if sample.state.to_move == 0 { 1.0 } else { -1.0 };
+        assert_eq!(sample.outcome, expected, "sample {}", i);
+        let sum: f64 = sample.policy.iter().map(|x| x.1).sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+        assert_eq!(sample.policy.len(), sample.state.available_moves().len());
+    }
+    assert_eq!(samples.last().unwrap().state.to_move, 0);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn training_samples_round_trip_through_json() {
+    let samples = self_play(1.0, 200, SelfPlayOptions::default());
+    let json = serde_json::to_string(&samples).unwrap();
+    let read: Vec<TrainingSample<Nim>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.len(), samples.len());
+    for (a, b) in read.iter().zip(&samples) {
+        assert_eq!(a.state, b.state);
+        assert_eq!(a.policy, b.policy);
+        assert_eq!(a.outcome, b.outcome);
+    }
+    assert!(json.starts_with(r#"[{"state":{"stones":10,"to_move":0},"policy":[[1,"#));
+}
+
+#[test]
+fn self_play_repeats_with_the_same_seed() {
+    let options = SelfPlayOptions {
+        seed: 9,
+        ..Default::default()
+    };
+    let states = |samples: Vec<TrainingSample<Nim>>| -> Vec<Nim> {
+        samples.into_iter().map(|x| x.state).collect()
+    };
+    assert_eq!(
+        states(self_play(1.0, 50, options.clone())),
+        states(self_play(1.0, 50, options))
+    );
+}
+
+#[test]
+fn the_losing_side_resigns() {
+    let options = SelfPlayOptions {
+        resign_threshold: Some(0.0),
+        ..Default::default()
+    };
+    let samples = self_play(0.0, 2000, options);
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].outcome, 1.0);
+}
+
+#[test]
+fn long_games_are_drawn() {
+    let options = SelfPlayOptions {
+        max_moves: Some(2),
+        ..Default::default()
+    };
+    let samples = self_play(1.0, 50, options);
+    assert_eq!(samples.len(), 2);
+    assert!(samples.iter().all(|x| x.outcome == 0.0));
+}
+
+#[test]
+fn sign_test_p_values() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-12;
+    assert_eq!(sign_test(0, 5), 1.0);
+    assert!(close(sign_test(5, 0), 1.0 / 32.0));
+    assert!(close(sign_test(1, 0), 0.5));
+    // Half the mass, plus the middle term of 20 choose 10.
+    assert!(close(
+        sign_test(10, 10),
+        0.5 + 184_756.0 / (1u64 << 21) as f64
+    ));
+    // Large counts neither overflow nor underflow to nonsense.
+    let p = sign_test(5200, 4800);
+    assert!(p > 0.0 && p < 1e-4, "{}", p);
+    assert!((sign_test(5000, 5000) - 0.5).abs() < 0.01);
+}
+
+#[test]
+fn strength_test_tells_agents_apart() {
+    let openings: Vec<Nim> = (8..14).map(|stones| Nim { stones, to_move: 0 }).collect();
+    let report = strength_test(&openings, &mut perfect, &mut random, 5, 3);
+    assert_eq!(report.tally.games(), 60);
+    assert_eq!(report.pairs.iter().sum::<u64>(), 30);
+    let (won, lost) = report.decided_pairs();
+    assert_eq!(lost, 0);
+    assert_eq!(report.p_value, sign_test(won, 0));
+    assert!(report.a_is_stronger(0.01), "{:?}", report);
+
+    let reversed = strength_test(&openings, &mut random, &mut perfect, 5, 3);
+    assert!(reversed.p_value > 0.99);
+    let even = strength_test(&openings, &mut perfect, &mut perfect, 5, 3);
+    assert_eq!(even.decided_pairs(), (0, 0));
+    assert_eq!(even.p_value, 1.0);
+    assert!(!even.a_is_stronger(0.5));
+}
@@ -0,0 +1,102 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// FillMCTS with a hook counting playouts and the plies they covered.
+#[derive(Default)]
+struct CountingMCTS {
+    calls: AtomicUsize,
+    plies: AtomicUsize,
+    consistent: AtomicUsize,
+}
+
+impl MCTS for CountingMCTS {
+    type State = FillGame;
+    type Eval = CountingEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+    fn after_backpropagation(&self, path: &[&MoveInfo<Self>], evaln: &usize) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.plies.fetch_add(path.len(), Ordering::Relaxed);
+        let score: usize = path.iter().map(|x| *x.get_move()).sum();
+        if score == *evaln && path.iter().all(|x| x.visits() > 0) {
+            self.consistent.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+struct CountingEvaluator;
+
+impl Evaluator<CountingMCTS> for CountingEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<CountingMCTS>>,
+    ) -> (Vec<()>, usize) {
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &usize,
+        _: SearchHandle<CountingMCTS>,
+    ) -> usize {
+        *evaln
+    }
+}
+
+fn manager() -> MCTSManager<CountingMCTS> {
+    MCTSManager::new(
+        FillGame::new(6, 3),
+        CountingMCTS::default(),
+        CountingEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    )
+}
+
+#[test]
+fn hook_is_called_once_per_playout() {
+    let mut mcts = manager();
+    let summary = mcts.playout_n(300);
+    mcts.tree().validate().unwrap();
+    let spec = mcts.tree().spec();
+    assert_eq!(spec.calls.load(Ordering::Relaxed), 300);
+    assert_eq!(spec.consistent.load(Ordering::Relaxed), 300);
+    let plies: usize = summary
+        .depth_histogram
+        .counts()
+        .iter()
+        .enumerate()
+        .map(|(depth, &count)| depth * count as usize)
+        .sum();
+    assert_eq!(spec.plies.load(Ordering::Relaxed), plies);
+}
+
+#[test]
+fn hook_is_called_from_every_thread() {
+    let mut mcts = manager();
+    mcts.playout_n_parallel(1000, 4);
+    mcts.tree().validate().unwrap();
+    let spec = mcts.tree().spec();
+    assert_eq!(spec.calls.load(Ordering::Relaxed), 1000);
+    assert_eq!(spec.consistent.load(Ordering::Relaxed), 1000);
+}
@@ -0,0 +1,119 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+fn manager(game: FillGame) -> MCTSManager<FillMCTS> {
+    MCTSManager::new(
+        game,
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    )
+}
+
+#[test]
+fn no_playouts() {
+    let mut mcts = manager(FillGame::new(4, 2));
+    assert!(mcts.principal_variation(1).is_empty());
+    assert!(mcts.principal_variation_states(3).is_empty());
+    assert_eq!(mcts.best_move(), None);
+    assert_eq!(mcts.try_best_move(), Err(BestMoveError::NoPlayouts));
+    mcts.playout_n(0);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.try_best_move(), Err(BestMoveError::NoPlayouts));
+}
+
+#[test]
+fn terminal_root() {
+    let mut mcts = manager(FillGame::new(4, 0));
+    mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+    assert!(mcts.principal_variation(1).is_empty());
+    assert!(mcts.principal_variation_states(1).is_empty());
+    assert_eq!(mcts.try_best_move(), Err(BestMoveError::TerminalRoot));
+}
+
+// Claims not to be over, but has nothing to play.
+#[derive(Clone, Debug, Default)]
+struct Stuck;
+
+impl GameState for Stuck {
+    type Move = ();
+    type Player = ();
+    type MoveList = Vec<()>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<()> {
+        vec![]
+    }
+    fn make_move(&mut self, _: &()) -> Result<(), ()> {
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+    fn is_terminal(&self) -> bool {
+        false
+    }
+}
+
+struct StuckEvaluator;
+
+impl Evaluator<StuckMCTS> for StuckEvaluator {
+    type StateEvaluation = ();
+
+    fn evaluate_new_state(
+        &self,
+        _: &Stuck,
+        _: &Vec<()>,
+        _: Option<SearchHandle<StuckMCTS>>,
+    ) -> (Vec<()>, ()) {
+        (vec![], ())
+    }
+    fn interpret_evaluation_for_player(&self, _: &(), _: &()) -> f64 {
+        0.0
+    }
+    fn evaluate_existing_state(&self, _: &Stuck, _: &(), _: SearchHandle<StuckMCTS>) {}
+}
+
+#[derive(Default)]
+struct StuckMCTS;
+
+impl MCTS for StuckMCTS {
+    type State = Stuck;
+    type Eval = StuckEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn no_legal_moves() {
+    let mut mcts = MCTSManager::new(Stuck, StuckMCTS, StuckEvaluator, UCTPolicy::new(1.0), ());
+    mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+    assert!(mcts.principal_variation(1).is_empty());
+    assert_eq!(mcts.try_best_move(), Err(BestMoveError::NoLegalMoves));
+}
+
+#[test]
+fn best_move_after_search() {
+    let mut mcts = manager(FillGame::new(4, 2));
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    // 2 then 3 and 3 then 2 reach the same node, so either can come first.
+    let mut pv = mcts.principal_variation(5);
+    assert_eq!(mcts.try_best_move(), Ok(pv[0]));
+    pv.sort();
+    assert_eq!(pv, vec![2, 3]);
+    assert_eq!(mcts.principal_variation_states(5).len(), 3);
+}
@@ -0,0 +1,69 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+fn builder() -> MCTSManagerBuilder<FillMCTS> {
+    MCTSManager::builder(FillMCTS)
+}
+
+fn root_visits(mcts: &MCTSManager<FillMCTS>) -> Vec<u64> {
+    mcts.tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect()
+}
+
+#[test]
+fn pieces_can_be_given_in_any_order() {
+    let mut mcts = builder()
+        .transposition_table(ApproxTable::new(1024))
+        .policy(UCTPolicy::new(5.0))
+        .evaluator(FillEvaluator)
+        .state(FillGame::new(5, 3))
+        .build();
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    assert_eq!(root_visits(&mcts).iter().sum::<u64>(), 100);
+}
+
+#[test]
+fn seeded_searches_repeat() {
+    let search = |seed| {
+        let mut mcts = builder()
+            .state(FillGame::new(6, 3))
+            .evaluator(FillEvaluator)
+            .policy(UCTPolicy::new(5.0))
+            .transposition_table(ApproxTable::new(1024))
+            .seed(seed)
+            .build();
+        mcts.playout_n(300);
+        mcts.tree().validate().unwrap();
+        root_visits(&mcts)
+    };
+    assert_eq!(search(42), search(42));
+    assert_eq!(search(7), search(7));
+}
+
+#[test]
+fn max_nodes_overrides_the_spec() {
+    let mut mcts = builder()
+        .state(FillGame::new(8, 4))
+        .evaluator(FillEvaluator)
+        .policy(UCTPolicy::new(5.0))
+        .transposition_table(ApproxTable::new(1024))
+        .max_nodes(20)
+        .build();
+    mcts.print_on_playout_error(false);
+    mcts.playout_n_parallel(1000, 2);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.tree().node_limit(), 20);
+    assert!(mcts.tree().num_nodes() <= 20);
+    let mcts = mcts.reset();
+    assert_eq!(mcts.tree().node_limit(), 20);
+}
@@ -0,0 +1,197 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct CachedMCTS {
+    interval: usize,
+}
+
+impl MCTS for CachedMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn cached_visits_interval(&self) -> usize {
+        self.interval
+    }
+}
+
+fn manager(interval: usize, exploration_constant: f64) -> MCTSManager<CachedMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(8, 4),
+        CachedMCTS { interval },
+        FillEvaluator,
+        UCTPolicy::new(exploration_constant),
+        (),
+    );
+    mcts.seed(Some(1));
+    mcts
+}
+
+#[test]
+fn statistics_are_exact() {
+    // `Recording` doesn't look at parent visits, so caching them changes nothing.
+    let mut exact = recording_manager(0);
+    exact.playout_n(1000);
+    let mut cached = recording_manager(4);
+    cached.playout_n(1000);
+    cached.tree().validate().unwrap();
+    assert_eq!(cached.root_move_stats_ref(), exact.root_move_stats_ref());
+    assert_eq!(cached.tree().num_nodes(), exact.tree().num_nodes());
+
+    let mut cached = manager(4, 5.0);
+    cached.playout_n(2000);
+    cached.tree().validate().unwrap();
+    let visits: u64 = cached.root_move_stats_ref().iter().map(|x| x.visits).sum();
+    assert_eq!(visits, 2000);
+    assert_eq!(cached.best_move(), Some(7));
+}
+
+#[test]
+fn parallel_statistics_are_exact() {
+    let mut mcts = manager(8, 5.0);
+    let summary = mcts.playout_n_parallel(5000, 8);
+    assert_eq!(summary.playouts, 5000);
+    mcts.tree().validate().unwrap();
+    let visits: u64 = mcts.root_move_stats_ref().iter().map(|x| x.visits).sum();
+    assert_eq!(visits, 5000);
+}
+
+// Visits the least visited move, or with `greedy` the most visited, recording what
+// `cached_visits` gave and the sum of the visits it stands in for.
+#[derive(Default)]
+struct Recording {
+    seen: Mutex<Vec<(Option<u64>, u64)>>,
+    greedy: bool,
+}
+
+#[derive(Default)]
+struct RecordingMCTS {
+    interval: usize,
+    virtual_visits: u64,
+}
+
+impl MCTS for RecordingMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = Recording;
+    type TranspositionTable = ();
+
+    fn cached_visits_interval(&self) -> usize {
+        self.interval
+    }
+    fn virtual_loss_mode(&self) -> VirtualLossMode {
+        VirtualLossMode::VisitsAndReward(self.virtual_visits, 0.0)
+    }
+}
+
+impl TreePolicy<RecordingMCTS> for Recording {
+    type MoveEvaluation = ();
+    type ThreadLocalData = PolicyRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        handle: SearchHandle<RecordingMCTS>,
+    ) -> &'a MoveInfo<RecordingMCTS>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<RecordingMCTS>> + Clone,
+    {
+        let sum = moves.clone().map(|x| x.visits()).sum();
+        self.seen
+            .lock()
+            .unwrap()
+            .push((handle.cached_visits(), sum));
+        if self.greedy {
+            moves.max_by_key(|x| x.visits()).unwrap()
+        } else {
+            moves.min_by_key(|x| x.visits()).unwrap()
+        }
+    }
+}
+
+fn recording_manager(interval: usize) -> MCTSManager<RecordingMCTS> {
+    recording_manager_with(
+        RecordingMCTS {
+            interval,
+            virtual_visits: 0,
+        },
+        Recording::default(),
+    )
+}
+
+fn recording_manager_with(spec: RecordingMCTS, policy: Recording) -> MCTSManager<RecordingMCTS> {
+    MCTSManager::new(FillGame::new(8, 4), spec, FillEvaluator, policy, ())
+}
+
+#[test]
+fn cached_visits_lag_by_less_than_the_interval() {
+    let mut mcts = recording_manager(4);
+    mcts.playout_n(500);
+    let seen = mcts.tree().tree_policy().seen.lock().unwrap();
+    // Every playout starts at the root, which has none.
+    assert!(seen.iter().filter(|x| x.0.is_none()).count() >= 500);
+    let cached: Vec<_> = seen
+        .iter()
+        .filter_map(|&(cached, sum)| cached.map(|x| (x, sum)))
+        .collect();
+    assert!(cached.len() > 500);
+    for (visits, sum) in cached {
+        // The node's own visits are its children's, its first playout's and this
+        // one's.
+        assert_eq!(visits % 4, 0);
+        assert!(
+            visits <= sum + 2 && visits + 4 > sum + 2,
+            "{} {}",
+            visits,
+            sum
+        );
+    }
+}
+
+#[test]
+fn cached_visits_keep_up_with_virtual_visits() {
+    // The greedy policy sends a batch's playouts down one line, and those waiting for
+    // an evaluation keep their virtual visits, so the line's counts grow by 4 at a
+    // time and can step over any particular count.
+    let mcts = recording_manager_with(
+        RecordingMCTS {
+            interval: 4,
+            virtual_visits: 3,
+        },
+        Recording {
+            greedy: true,
+            ..Default::default()
+        },
+    );
+    let tree = mcts.tree();
+    let mut tld = ThreadData::new(PolicyRng::new(0), ());
+    for _ in 0..100 {
+        let leaves = tree.collect_leaves(8, &mut tld);
+        let evaluated = leaves.into_iter().map(|leaf| {
+            let moves = vec![(); leaf.moves().len()];
+            let score = leaf.state().score();
+            (leaf, moves, score)
+        });
+        tree.apply_evaluations(evaluated, &mut tld);
+    }
+    tree.validate().unwrap();
+    let seen = tree.tree_policy().seen.lock().unwrap();
+    let lag = seen
+        .iter()
+        .filter_map(|&(cached, sum)| cached.map(|x| sum.saturating_sub(x)))
+        .max()
+        .unwrap();
+    assert!(lag < 4, "cached visits {} behind", lag);
+}
@@ -0,0 +1,148 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Three moves, each one of 12 numbers, generated in an order that depends on the
+/// state. Higher numbers score more.
+#[derive(Clone, Default)]
+struct Scramble(Vec<u8>);
+
+impl GameState for Scramble {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.0.len() == 3 {
+            return vec![];
+        }
+        let offset = self.0.iter().sum::<u8>() + self.0.len() as u8 * 5;
+        (0..12).map(|i| (i * 7 + offset) % 12).collect()
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+    fn move_order_hint(&self, mov: &u8) -> i32 {
+        (*mov == 11) as i32
+    }
+}
+
+struct ScrambleEvaluator;
+
+impl Evaluator<ScrambleMCTS> for ScrambleEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Scramble,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<ScrambleMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let score = state.0.iter().map(|&x| x as f64).sum();
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &Scramble,
+        evaln: &f64,
+        _: SearchHandle<ScrambleMCTS>,
+    ) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct ScrambleMCTS {
+    max_children: Option<usize>,
+}
+
+impl MCTS for ScrambleMCTS {
+    type State = Scramble;
+    type Eval = ScrambleEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn virtual_loss(&self) -> f64 {
+        5.0
+    }
+    fn max_children(&self) -> usize {
+        self.max_children.unwrap_or(usize::MAX)
+    }
+    fn random_extra_children(&self) -> usize {
+        3
+    }
+}
+
+fn search(spec: ScrambleMCTS, seed: u64) -> MCTSManager<ScrambleMCTS> {
+    let mut mcts = MCTSManager::new(
+        Scramble::default(),
+        spec,
+        ScrambleEvaluator,
+        UCTPolicy::new(10.0),
+        (),
+    );
+    mcts.seed(Some(seed));
+    mcts.playout_n_parallel(3000, 8);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+fn moves(node: &NodeHandle<ScrambleMCTS>) -> Vec<u8> {
+    node.moves().map(|x| *x.get_move()).collect()
+}
+
+/// Checks that every node expanded in both trees has the same children in the same
+/// order, and returns how many there were.
+fn compare(a: NodeHandle<ScrambleMCTS>, b: NodeHandle<ScrambleMCTS>) -> usize {
+    assert_eq!(moves(&a), moves(&b));
+    let mut compared = 1;
+    for (x, y) in a.moves().zip(b.moves()) {
+        if let (Some(x), Some(y)) = (x.child(), y.child()) {
+            compared += compare(x, y);
+        }
+    }
+    compared
+}
+
+#[test]
+fn root_moves_keep_the_generated_order() {
+    let a = search(ScrambleMCTS::default(), 1);
+    let b = search(ScrambleMCTS::default(), 1);
+    let root = a.tree().root_node();
+    // 11 moves first for its hint, the rest as generated.
+    assert_eq!(moves(&root), vec![11, 0, 7, 2, 9, 4, 6, 1, 8, 3, 10, 5]);
+    assert_eq!(moves(&root), moves(&b.tree().root_node()));
+}
+
+#[test]
+fn same_seed_same_children_with_eight_threads() {
+    let a = search(ScrambleMCTS::default(), 1);
+    let b = search(ScrambleMCTS::default(), 1);
+    let compared = compare(a.tree().root_node(), b.tree().root_node());
+    assert!(compared > 10, "{} nodes compared", compared);
+}
+
+#[test]
+fn same_seed_same_random_children_with_eight_threads() {
+    let spec = || ScrambleMCTS {
+        max_children: Some(2),
+    };
+    let a = search(spec(), 1);
+    let b = search(spec(), 1);
+    assert_eq!(a.tree().root_node().moves().count(), 5);
+    let compared = compare(a.tree().root_node(), b.tree().root_node());
+    assert!(compared > 10, "{} nodes compared", compared);
+}
@@ -0,0 +1,88 @@
+// Chunked searches need a clock under the `wasm` feature.
+#![cfg(not(feature = "wasm"))]
+
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+use std::time::Duration;
+
+fn root_stats(mcts: &MCTSManager<CountingMCTS>) -> Vec<(u64, f64)> {
+    let root = mcts.tree().root_node();
+    root.moves()
+        .map(|x| (x.visits(), x.sum_rewards()))
+        .collect()
+}
+
+#[test]
+fn chunks_match_a_one_shot_search() {
+    let mut one_shot = counting_manager(5.0);
+    one_shot.seed(Some(3));
+    let expected = one_shot.playout_n(100_000);
+
+    let mut chunked = counting_manager(5.0);
+    chunked.seed(Some(3));
+    chunked.chunk_target(Some(100_000));
+    let mut chunks = 0;
+    loop {
+        let result = chunked.playout_chunk(100, Duration::from_secs(3600));
+        chunks += 1;
+        assert_eq!(result.total_playouts, chunks * 100);
+        if result.is_done() {
+            assert!(result.target_reached);
+            assert!(!result.node_limit_reached);
+            break;
+        }
+    }
+    assert_eq!(chunks, 1000);
+    assert_eq!(root_stats(&chunked), root_stats(&one_shot));
+    assert_eq!(chunked.tree().num_nodes(), one_shot.tree().num_nodes());
+    let summary = chunked.finish_chunks().unwrap();
+    assert_eq!(summary.playouts, expected.playouts);
+    assert_eq!(summary.nodes_created, expected.nodes_created);
+    assert_eq!(summary.depth_histogram, expected.depth_histogram);
+    assert!(chunked.chunked_summary().is_none());
+}
+
+#[test]
+fn chunks_stop_at_the_target() {
+    let mut mcts = counting_manager(5.0);
+    mcts.chunk_target(Some(250));
+    let result = mcts.playout_chunk(200, Duration::from_secs(3600));
+    assert_eq!(result.playouts, 200);
+    assert!(!result.is_done());
+    let result = mcts.playout_chunk(200, Duration::from_secs(3600));
+    assert_eq!(result.playouts, 50);
+    assert!(result.target_reached);
+    let result = mcts.playout_chunk(200, Duration::from_secs(3600));
+    assert_eq!(result.playouts, 0);
+    assert_eq!(mcts.chunked_summary().unwrap().playouts, 250);
+}
+
+#[test]
+fn chunks_respect_their_duration() {
+    let mut mcts = counting_manager(5.0);
+    let result = mcts.playout_chunk(usize::MAX, Duration::ZERO);
+    assert_eq!(result.playouts, 0);
+    let result = mcts.playout_chunk(usize::MAX, Duration::from_millis(20));
+    assert!(result.playouts > 0);
+    // Without a target, a chunked search is never done by itself.
+    assert!(!result.is_done());
+    let summary = mcts.chunked_summary().unwrap();
+    assert!(summary.elapsed >= Duration::from_millis(20));
+    assert!(summary.elapsed < Duration::from_secs(5));
+}
+
+#[test]
+fn chunks_stop_at_the_node_limit() {
+    let mut mcts = counting_manager(5.0);
+    mcts.max_nodes(20);
+    let result = mcts.playout_chunk(1000, Duration::from_secs(3600));
+    assert!(result.node_limit_reached);
+    assert!(result.is_done());
+    assert!(result.playouts < 1000);
+    assert!(mcts.finish_chunks().unwrap().expansion_halted);
+    assert!(mcts.last_search_degraded());
+}
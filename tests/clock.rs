@@ -0,0 +1,38 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Every reading moves the time on by a millisecond.
+static MILLIS: AtomicU64 = AtomicU64::new(0);
+
+fn ticking_clock() -> Duration {
+    Duration::from_millis(MILLIS.fetch_add(1, Ordering::Relaxed))
+}
+
+#[test]
+fn searches_are_timed_with_the_installed_clock() {
+    clock::set_clock(ticking_clock);
+    assert!(clock::has_clock());
+    // Player 1 wins with cell 2; anything else lets player 2 win with cell 5.
+    let state = TicTacToe {
+        cells: [1, 1, 0, 2, 2, 0, 0, 0, 0],
+    };
+    let mut mcts = TwoPlayerZeroSumSpec::manager(state);
+    mcts.seed(Some(3));
+    let summary = mcts.playout_for(Duration::from_millis(500));
+    mcts.tree().validate().unwrap();
+    clock::clear_clock();
+    // One reading per playout, and a few around the search.
+    assert!(
+        summary.playouts > 490 && summary.playouts < 500,
+        "{} playouts",
+        summary.playouts
+    );
+    assert!(summary.elapsed >= Duration::from_millis(500));
+    assert_eq!(mcts.best_move(), Some(2));
+}
@@ -0,0 +1,100 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+/// What `FillEvaluator` would say about a leaf, worked out outside the search.
+fn evaluate(leaf: &PendingLeaf<FillMCTS>) -> (Vec<()>, usize) {
+    (vec![(); leaf.moves().len()], leaf.state().score())
+}
+
+fn root_visits(tree: &SearchTree<FillMCTS>) -> Vec<u64> {
+    tree.root_node().moves().map(|x| x.visits()).collect()
+}
+
+#[test]
+fn batches_evaluated_outside_find_the_best_move() {
+    let tree = fill_tree(6, 3);
+    let mut tld = fill_thread_data(1);
+    let mut leaves = 0;
+    for _ in 0..200 {
+        let batch = tree.collect_leaves(8, &mut tld);
+        assert!(batch.len() <= 8);
+        leaves += batch.len();
+        for leaf in &batch {
+            assert_eq!(leaf.path().count(), leaf.depth());
+            assert_eq!(leaf.moves(), &leaf.state().available_moves());
+        }
+        let evaluated: Vec<_> = batch
+            .into_iter()
+            .map(|leaf| {
+                let (moves, state) = evaluate(&leaf);
+                (leaf, moves, state)
+            })
+            .collect();
+        tree.apply_evaluations(evaluated, &mut tld);
+    }
+    tree.validate().unwrap();
+    // Every playout was backpropagated, whether it waited for an evaluation or not.
+    assert_eq!(root_visits(&tree).iter().sum::<u64>(), 1600);
+    assert!(leaves > 0 && leaves < 1600);
+    // Any order of the three highest cells is best.
+    let visits = root_visits(&tree);
+    assert!(visits[3..].iter().sum::<u64>() > 4 * visits[..3].iter().sum::<u64>());
+}
+
+#[test]
+fn dropped_leaves_take_their_virtual_loss_back() {
+    let tree = fill_tree(6, 3);
+    let mut tld = fill_thread_data(2);
+    for _ in 0..10 {
+        tree.playout(&mut tld);
+    }
+    let visits = root_visits(&tree);
+    let nodes = tree.num_nodes();
+    let leaves = tree.collect_leaves(4, &mut tld);
+    assert!(!leaves.is_empty());
+    // Pending leaves hold their places under the node limit.
+    assert!(tree.num_nodes() > nodes);
+    drop(leaves);
+    assert_eq!(tree.num_nodes(), nodes);
+    tree.validate().unwrap();
+    // Only the playouts that ended inside the tree count.
+    let finished = root_visits(&tree).iter().sum::<u64>() - visits.iter().sum::<u64>();
+    assert!(finished < 4);
+}
+
+#[test]
+fn applied_leaves_create_their_nodes() {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(3);
+    // Every root move is unexplored, so every playout stops at one.
+    let leaves = tree.collect_leaves(4, &mut tld);
+    assert_eq!(leaves.len(), 4);
+    assert_eq!(tree.num_nodes(), 5);
+    for leaf in leaves {
+        assert_eq!(leaf.depth(), 1);
+        let (moves, state) = evaluate(&leaf);
+        tree.apply_evaluation(leaf, moves, state, &mut tld);
+    }
+    tree.validate().unwrap();
+    assert_eq!(tree.num_nodes(), 5);
+    for mov in tree.root_node().moves() {
+        assert_eq!(mov.visits(), 1);
+        assert_eq!(mov.sum_rewards(), *mov.get_move() as f64);
+        assert!(mov.child().is_some());
+    }
+}
+
+#[test]
+#[should_panic(expected = "another tree")]
+fn leaves_belong_to_their_tree() {
+    let tree = fill_tree(4, 2);
+    let other = fill_tree(4, 2);
+    let mut tld = fill_thread_data(4);
+    let leaf = tree.collect_leaves(1, &mut tld).pop().unwrap();
+    let (moves, state) = evaluate(&leaf);
+    other.apply_evaluation(leaf, moves, state, &mut tld);
+}
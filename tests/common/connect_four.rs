@@ -0,0 +1,238 @@
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+pub const COLUMNS: usize = 7;
+pub const ROWS: usize = 6;
+
+// Each column takes `ROWS + 1` bits of a bitboard, bottom row first; the extra bit
+// stays empty so that lines can't wrap from one column into the next.
+const HEIGHT: usize = ROWS + 1;
+const SQUARES: usize = COLUMNS * HEIGHT;
+
+const fn zobrist_keys() -> [[u64; SQUARES]; 2] {
+    let mut keys = [[0; SQUARES]; 2];
+    let mut z: u64 = 0;
+    let mut i = 0;
+    while i < 2 * SQUARES {
+        z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut x = z;
+        x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i / SQUARES][i % SQUARES] = x ^ (x >> 31);
+        i += 1;
+    }
+    keys
+}
+
+const ZOBRIST: [[u64; SQUARES]; 2] = zobrist_keys();
+
+fn bottom(column: usize) -> u64 {
+    1 << (column * HEIGHT)
+}
+
+fn top(column: usize) -> u64 {
+    1 << (column * HEIGHT + ROWS - 1)
+}
+
+fn has_four(stones: u64) -> bool {
+    // Vertical, horizontal and both diagonals.
+    [1, HEIGHT, HEIGHT - 1, HEIGHT + 1].iter().any(|&shift| {
+        let pairs = stones & (stones >> shift);
+        pairs & (pairs >> (2 * shift)) != 0
+    })
+}
+
+/// Connect Four on a 7×6 board, as a bitboard per player. Players are 0, who
+/// moves first, and 1; a move is the column (0 to 6) to drop a stone into.
+///
+/// The Zobrist hash of the stones is updated on every move, and `Hash` writes
+/// just that, so transposition tables hash a state in constant time.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectFour {
+    stones: [u64; 2],
+    to_move: u8,
+    hash: u64,
+}
+
+impl ConnectFour {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// The state after dropping stones into `columns` in turn.
+    ///
+    /// # Panics
+    ///
+    /// If one of the moves is illegal.
+    pub fn from_columns(columns: &[usize]) -> Self {
+        let mut state = Self::new();
+        for column in columns {
+            state
+                .make_move(column)
+                .unwrap_or_else(|_| panic!("can't play column {}", column));
+        }
+        state
+    }
+    fn occupied(&self) -> u64 {
+        self.stones[0] | self.stones[1]
+    }
+    /// The number of stones on the board.
+    pub fn plies(&self) -> usize {
+        self.occupied().count_ones() as usize
+    }
+    pub fn can_play(&self, column: usize) -> bool {
+        column < COLUMNS && self.occupied() & top(column) == 0 && self.get_winner().is_none()
+    }
+    /// The bit of the square a stone dropped into `column` lands on.
+    fn landing(&self, column: usize) -> u64 {
+        let occupied = self.occupied();
+        (occupied + bottom(column)) & !occupied & (0x3F << (column * HEIGHT))
+    }
+    /// Whether dropping a stone into `column` wins for the player to move.
+    pub fn is_winning_move(&self, column: usize) -> bool {
+        self.can_play(column) && has_four(self.stones[self.to_move as usize] | self.landing(column))
+    }
+    /// The Zobrist hash of the stones, kept up to date by `make_move`.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+    /// The player (0 or 1) of the stone at `column` and `row`, counting rows from
+    /// the bottom.
+    pub fn stone(&self, column: usize, row: usize) -> Option<u8> {
+        let bit = 1 << (column * HEIGHT + row);
+        (0..2).find(|&p| self.stones[p as usize] & bit != 0)
+    }
+}
+
+impl Hash for ConnectFour {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl GameState for ConnectFour {
+    type Move = usize;
+    type Player = u8;
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &u8 {
+        &self.to_move
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        (0..COLUMNS).filter(|&c| self.can_play(c)).collect()
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        if !self.can_play(*mov) {
+            return Err(());
+        }
+        let square = self.landing(*mov);
+        self.hash ^= ZOBRIST[self.to_move as usize][square.trailing_zeros() as usize];
+        self.stones[self.to_move as usize] |= square;
+        self.to_move = 1 - self.to_move;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<u8> {
+        (0..2).find(|&p| has_four(self.stones[p as usize]))
+    }
+    fn is_terminal(&self) -> bool {
+        self.get_winner().is_some() || self.plies() == COLUMNS * ROWS
+    }
+}
+
+impl IncrementallyHashable for ConnectFour {
+    fn full_hash(&self) -> u64 {
+        (0..SQUARES)
+            .flat_map(|square| (0..2).map(move |p| (p, square)))
+            .filter(|&(p, square)| self.stones[p] & (1 << square) != 0)
+            .fold(0, |h, (p, square)| h ^ ZOBRIST[p][square])
+    }
+    fn hash_after(&self, current: u64, mov: &usize) -> u64 {
+        let square = self.landing(*mov).trailing_zeros() as usize;
+        current ^ ZOBRIST[self.to_move as usize][square]
+    }
+}
+
+impl fmt::Display for ConnectFour {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in (0..ROWS).rev() {
+            for column in 0..COLUMNS {
+                let c = match self.stone(column, row) {
+                    Some(0) => 'X',
+                    Some(_) => 'O',
+                    None => '.',
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "0123456")
+    }
+}
+
+/// Random rollouts to the end of the game, as `WinDrawLossEvaluator` does.
+pub struct ConnectFourEvaluator;
+
+impl Evaluator<ConnectFourMCTS> for ConnectFourEvaluator {
+    type StateEvaluation = Option<u8>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &ConnectFour,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<ConnectFourMCTS>>,
+    ) -> (Vec<()>, Option<u8>) {
+        let winner = match handle {
+            Some(mut handle) => {
+                WinDrawLossEvaluator::rollout(state, &mut handle.thread_data().policy_data)
+            }
+            None => WinDrawLossEvaluator::rollout(state, &mut PolicyRng::default()),
+        };
+        (vec![(); moves.len()], winner)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &Option<u8>, player: &u8) -> f64 {
+        match evaln {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &ConnectFour,
+        evaln: &Option<u8>,
+        _: SearchHandle<ConnectFourMCTS>,
+    ) -> Option<u8> {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+pub struct ConnectFourMCTS;
+
+impl MCTS for ConnectFourMCTS {
+    type State = ConnectFour;
+    type Eval = ConnectFourEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ExactTable<Self>;
+
+    fn evaluation_symmetry(&self) -> EvaluationSymmetry {
+        EvaluationSymmetry::ZeroSumTwoPlayer
+    }
+}
+
+/// A Connect Four search from `state`, with an `ExactTable` big enough for
+/// `nodes` nodes.
+pub fn connect_four_manager(state: ConnectFour, nodes: usize) -> MCTSManager<ConnectFourMCTS> {
+    MCTSManager::new(
+        state,
+        ConnectFourMCTS,
+        ConnectFourEvaluator,
+        UCTPolicy::new(1.4),
+        ExactTable::enough_to_hold(nodes),
+    )
+}
@@ -0,0 +1,99 @@
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Count from 0 towards 100 by adding or subtracting 1. The evaluation is the count,
+/// so the best line only adds. Every node has one parent and a new node is created
+/// on every playout, which makes this a simple benchmark.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CountingGame(pub i64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CountingMove {
+    Add,
+    Sub,
+}
+
+impl GameState for CountingGame {
+    type Move = CountingMove;
+    type Player = ();
+    type MoveList = Vec<CountingMove>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<CountingMove> {
+        if self.0 == 100 {
+            vec![]
+        } else {
+            vec![CountingMove::Add, CountingMove::Sub]
+        }
+    }
+    fn make_move(&mut self, mov: &CountingMove) -> Result<(), ()> {
+        match *mov {
+            CountingMove::Add => self.0 += 1,
+            CountingMove::Sub => self.0 -= 1,
+        }
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        if self.0 == 100 {
+            Some(())
+        } else {
+            None
+        }
+    }
+}
+
+pub struct CountingEvaluator;
+
+/// Works for any spec playing `CountingGame` without move evaluations, like
+/// `FillEvaluator`.
+impl<Spec> Evaluator<Spec> for CountingEvaluator
+where
+    Spec: MCTS<State = CountingGame, Eval = CountingEvaluator>,
+    Spec::TreePolicy: TreePolicy<Spec, MoveEvaluation = ()>,
+{
+    type StateEvaluation = i64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &CountingGame,
+        moves: &Vec<CountingMove>,
+        _: Option<SearchHandle<Spec>>,
+    ) -> (Vec<()>, i64) {
+        (vec![(); moves.len()], state.0)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &i64, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &CountingGame, evaln: &i64, _: SearchHandle<Spec>) -> i64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+pub struct CountingMCTS;
+
+impl MCTS for CountingMCTS {
+    type State = CountingGame;
+    type Eval = CountingEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn virtual_loss(&self) -> f64 {
+        500.0
+    }
+}
+
+/// A counting game search from 0, with the given exploration constant.
+pub fn counting_manager(exploration_constant: f64) -> MCTSManager<CountingMCTS> {
+    MCTSManager::new(
+        CountingGame(0),
+        CountingMCTS,
+        CountingEvaluator,
+        UCTPolicy::new(exploration_constant),
+        (),
+    )
+}
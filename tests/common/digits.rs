@@ -0,0 +1,71 @@
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Pick `length` digits. Priors follow the digits, so a small search favours the
+/// high ones.
+#[derive(Clone, Default)]
+pub struct Digits {
+    pub picked: Vec<u8>,
+    pub length: usize,
+}
+
+impl Digits {
+    pub fn new(length: usize) -> Self {
+        Digits {
+            picked: vec![],
+            length,
+        }
+    }
+}
+
+impl GameState for Digits {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.picked.len() == self.length {
+            vec![]
+        } else {
+            (0..10).collect()
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.picked.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+/// Scores a state with the given function of the digits picked so far.
+pub struct DigitsEvaluator(pub fn(&[u8]) -> f64);
+
+/// Works for any spec playing `Digits` with priors, like `FillEvaluator`.
+impl<Spec> Evaluator<Spec> for DigitsEvaluator
+where
+    Spec: MCTS<State = Digits, Eval = DigitsEvaluator>,
+    Spec::TreePolicy: TreePolicy<Spec, MoveEvaluation = f64>,
+{
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Digits,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<Spec>>,
+    ) -> (Vec<f64>, f64) {
+        let priors = moves.iter().map(|&x| x as f64 / 45.0).collect();
+        (priors, (self.0)(&state.picked))
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &Digits, evaln: &f64, _: SearchHandle<Spec>) -> f64 {
+        *evaln
+    }
+}
@@ -0,0 +1,74 @@
+use mcts::nmcs::Puzzle;
+use mcts::*;
+
+/// Twenty steps. Move 0 is safe and scores 1. Move 1 is a gamble: the next move
+/// must be the right one of ten, for 5, or the game ends there. Random rollouts
+/// after a gamble almost always lose, so the mean reward of gambling is poor
+/// while its best reward is much better than playing safe.
+#[derive(Clone, Debug, Default)]
+pub struct Gamble {
+    pub steps: usize,
+    pub gambling: bool,
+    pub over: bool,
+    pub score: u32,
+}
+
+pub const STEPS: usize = 20;
+
+impl Gamble {
+    pub fn right_move(&self) -> u8 {
+        (self.steps * 7 % 10) as u8
+    }
+}
+
+impl GameState for Gamble {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.over || self.steps == STEPS {
+            vec![]
+        } else if self.gambling {
+            (0..10).collect()
+        } else {
+            vec![0, 1]
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        if self.gambling {
+            if *mov == self.right_move() {
+                self.score += 5;
+            } else {
+                self.over = true;
+            }
+            self.gambling = false;
+        } else if *mov == 0 {
+            self.score += 1;
+        } else {
+            self.gambling = true;
+        }
+        self.steps += 1;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+impl Puzzle for Gamble {
+    fn score(&self) -> f64 {
+        self.score as f64
+    }
+}
+
+pub fn replay(moves: &[u8]) -> Gamble {
+    let mut state = Gamble::default();
+    for mov in moves {
+        state.make_move(mov).unwrap();
+    }
+    state
+}
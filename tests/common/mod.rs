@@ -0,0 +1,231 @@
+#![allow(dead_code)]
+
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+mod connect_four;
+mod counting;
+mod digits;
+mod gamble;
+mod nim;
+mod ring;
+mod tictactoe;
+
+#[allow(unused_imports)]
+pub use self::connect_four::*;
+#[allow(unused_imports)]
+pub use self::counting::*;
+#[allow(unused_imports)]
+pub use self::digits::*;
+#[allow(unused_imports)]
+pub use self::gamble::*;
+#[allow(unused_imports)]
+pub use self::nim::*;
+#[allow(unused_imports)]
+pub use self::ring::*;
+#[allow(unused_imports)]
+pub use self::tictactoe::*;
+
+/// Fill `target` of the `cells` cells, in any order. The score is the sum of the
+/// filled indices, so the best line fills the highest cells. Every order of the
+/// same cells reaches the same state, which exercises the transposition table.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FillGame {
+    pub cells: Vec<bool>,
+    pub target: usize,
+}
+
+impl FillGame {
+    pub fn new(cells: usize, target: usize) -> Self {
+        Self {
+            cells: vec![false; cells],
+            target,
+        }
+    }
+    pub fn filled(&self) -> usize {
+        self.cells.iter().filter(|&&x| x).count()
+    }
+    pub fn score(&self) -> usize {
+        (0..self.cells.len()).filter(|&i| self.cells[i]).sum()
+    }
+}
+
+impl GameState for FillGame {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        if self.filled() == self.target {
+            return vec![];
+        }
+        (0..self.cells.len()).filter(|&i| !self.cells[i]).collect()
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.cells[*mov] = true;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+pub struct FillEvaluator;
+
+/// Works for any spec playing `FillGame` without move evaluations, so tests can
+/// vary the spec's hooks.
+impl<Spec> Evaluator<Spec> for FillEvaluator
+where
+    Spec: MCTS<State = FillGame, Eval = FillEvaluator>,
+    Spec::TreePolicy: TreePolicy<Spec, MoveEvaluation = ()>,
+{
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<Spec>>,
+    ) -> (Vec<()>, usize) {
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &FillGame, evaln: &usize, _: SearchHandle<Spec>) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+pub struct FillMCTS;
+
+impl MCTS for FillMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+pub fn fill_tree(cells: usize, target: usize) -> SearchTree<FillMCTS> {
+    SearchTree::new(
+        FillGame::new(cells, target),
+        FillMCTS,
+        UCTPolicy::new(5.0),
+        FillEvaluator,
+        ApproxTable::new(1024),
+    )
+}
+
+pub fn fill_thread_data(seed: u64) -> ThreadData<FillMCTS> {
+    ThreadData::new(PolicyRng::new(seed), ())
+}
+
+/// Just enough JSON to check what the crate writes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn parse(s: &str) -> Json {
+        let mut chars = s.trim().chars().peekable();
+        let value = parse_json(&mut chars);
+        assert!(chars.next().is_none(), "trailing characters");
+        value
+    }
+    pub fn get(&self, key: &str) -> &Json {
+        match self {
+            Json::Object(fields) => &fields.iter().find(|x| x.0 == key).unwrap().1,
+            _ => panic!("not an object"),
+        }
+    }
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Json::Number(x) => *x,
+            _ => panic!("not a number"),
+        }
+    }
+    pub fn as_array(&self) -> &[Json] {
+        match self {
+            Json::Array(x) => x,
+            _ => panic!("not an array"),
+        }
+    }
+}
+
+fn parse_json(chars: &mut std::iter::Peekable<std::str::Chars>) -> Json {
+    match chars.next().unwrap() {
+        'n' => {
+            assert_eq!(chars.by_ref().take(3).collect::<String>(), "ull");
+            Json::Null
+        }
+        '"' => {
+            let mut s = String::new();
+            loop {
+                match chars.next().unwrap() {
+                    '"' => return Json::String(s),
+                    '\\' => s.push(chars.next().unwrap()),
+                    c => s.push(c),
+                }
+            }
+        }
+        '[' => {
+            let mut items = Vec::new();
+            if chars.peek() == Some(&']') {
+                chars.next();
+                return Json::Array(items);
+            }
+            loop {
+                items.push(parse_json(chars));
+                match chars.next().unwrap() {
+                    ',' => (),
+                    ']' => return Json::Array(items),
+                    c => panic!("unexpected {}", c),
+                }
+            }
+        }
+        '{' => {
+            let mut fields = Vec::new();
+            loop {
+                let key = match parse_json(chars) {
+                    Json::String(key) => key,
+                    x => panic!("bad key {:?}", x),
+                };
+                assert_eq!(chars.next(), Some(':'));
+                fields.push((key, parse_json(chars)));
+                match chars.next().unwrap() {
+                    ',' => (),
+                    '}' => return Json::Object(fields),
+                    c => panic!("unexpected {}", c),
+                }
+            }
+        }
+        c => {
+            let mut s = c.to_string();
+            while let Some(&c) = chars.peek() {
+                if c.is_ascii_digit() || "+-.eE".contains(c) {
+                    s.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            Json::Number(s.parse().unwrap())
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use mcts::*;
+
+/// Take one or two stones; whoever takes the last stone wins. Leaving a multiple of
+/// three stones wins, so the player to move from the default 10 stones should win.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Nim {
+    pub stones: u8,
+    pub to_move: u8,
+}
+
+impl Nim {
+    pub fn new(stones: u8) -> Self {
+        Nim { stones, to_move: 0 }
+    }
+}
+
+impl Default for Nim {
+    fn default() -> Self {
+        Nim::new(10)
+    }
+}
+
+impl GameState for Nim {
+    type Move = u8;
+    type Player = u8;
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &u8 {
+        &self.to_move
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        (1..=self.stones.min(2)).collect()
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.stones -= mov;
+        self.to_move = 1 - self.to_move;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<u8> {
+        if self.stones == 0 {
+            Some(1 - self.to_move)
+        } else {
+            None
+        }
+    }
+}
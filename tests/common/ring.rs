@@ -0,0 +1,54 @@
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Steps of one or two around a ring of four positions, which never ends, so with a
+/// transposition table every playout that isn't stopped by an expansion runs into a
+/// cycle.
+#[derive(Clone, Debug, Default, PartialEq, Hash)]
+pub struct Ring(pub u8);
+
+impl GameState for Ring {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        vec![1, 2]
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0 = (self.0 + mov) % 4;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+/// Scores a position by its place on the ring.
+pub struct RingEvaluator;
+
+impl<Spec> Evaluator<Spec> for RingEvaluator
+where
+    Spec: MCTS<State = Ring, Eval = RingEvaluator>,
+    Spec::TreePolicy: TreePolicy<Spec, MoveEvaluation = ()>,
+{
+    type StateEvaluation = u8;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Ring,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<Spec>>,
+    ) -> (Vec<()>, u8) {
+        (vec![(); moves.len()], state.0)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &u8, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &Ring, evaln: &u8, _: SearchHandle<Spec>) -> u8 {
+        *evaln
+    }
+}
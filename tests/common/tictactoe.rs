@@ -0,0 +1,49 @@
+use mcts::*;
+
+// Cells hold 0 when empty, or the player (1 or 2) who took them.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TicTacToe {
+    pub cells: [u8; 9],
+}
+
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+impl GameState for TicTacToe {
+    type Move = usize;
+    type Player = u8;
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &u8 {
+        if self.cells.iter().filter(|&&x| x != 0).count() % 2 == 0 {
+            &1
+        } else {
+            &2
+        }
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        if self.get_winner().is_some() {
+            return vec![];
+        }
+        (0..9).filter(|&i| self.cells[i] == 0).collect()
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.cells[*mov] = *self.current_player();
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<u8> {
+        LINES
+            .iter()
+            .map(|line| line.map(|i| self.cells[i]))
+            .find(|line| line[0] != 0 && line[0] == line[1] && line[1] == line[2])
+            .map(|line| line[0])
+    }
+}
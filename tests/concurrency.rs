@@ -0,0 +1,94 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::Barrier;
+use std::thread;
+
+// The races these tests look for are narrow, so each one is run on many fresh
+// trees, with the threads released together.
+const ROUNDS: usize = 200;
+
+fn tree(state: FillGame) -> SearchTree<FillMCTS> {
+    SearchTree::new(
+        state,
+        FillMCTS,
+        UCTPolicy::new(5.0),
+        FillEvaluator,
+        ApproxTable::new(1024),
+    )
+}
+
+/// Runs `playouts` playouts on each of `threads` threads, all starting at once.
+fn race(tree: &SearchTree<FillMCTS>, threads: usize, playouts: usize) {
+    let barrier = Barrier::new(threads);
+    thread::scope(|s| {
+        for i in 0..threads {
+            let barrier = &barrier;
+            s.spawn(move || {
+                let mut tld = fill_thread_data(i as u64);
+                barrier.wait();
+                for _ in 0..playouts {
+                    assert!(tree.playout(&mut tld));
+                }
+            });
+        }
+    });
+    tree.validate().unwrap();
+}
+
+#[test]
+fn two_threads_expanding_the_same_leaf() {
+    for _ in 0..ROUNDS {
+        // A single move, so both threads expand the same child.
+        let tree = tree(FillGame::new(1, 1));
+        race(&tree, 2, 1);
+        assert_eq!(tree.num_nodes(), 2);
+        let root = tree.root_node();
+        let mov = root.moves().next().unwrap();
+        assert_eq!(mov.visits(), 2);
+        let child = mov.child().unwrap();
+        assert_eq!(*child.state_evaluation(), 0);
+        assert_eq!(child.moves().count(), 0);
+    }
+}
+
+#[test]
+fn concurrent_backpropagation_on_one_child() {
+    for _ in 0..ROUNDS / 10 {
+        // Cell 0 is already filled, so the only move fills cell 1, for a score of 1.
+        let tree = tree(FillGame {
+            cells: vec![true, false],
+            target: 2,
+        });
+        race(&tree, 4, 250);
+        // A move's statistics are a copy of its child's, taken at the end of each
+        // backpropagation, and the last copy made may not be the newest. The child's
+        // own counts are exact, which the copy made by one more playout shows.
+        assert!(tree.playout(&mut fill_thread_data(0)));
+        let root = tree.root_node();
+        let mov = root.moves().next().unwrap();
+        assert_eq!(mov.visits(), 1001);
+        assert_eq!(mov.sum_rewards(), 1001.0);
+        assert_eq!(root.visits(), 1001);
+    }
+}
+
+#[test]
+fn transposition_table_insert_races() {
+    for _ in 0..ROUNDS {
+        // Filling 0 then 1 and 1 then 0 reach the same state.
+        let tree = tree(FillGame::new(2, 2));
+        race(&tree, 2, 4);
+        assert_eq!(tree.num_nodes(), 4);
+        let root = tree.root_node();
+        let a = root.descend(&0).and_then(|x| x.descend(&1)).unwrap();
+        let b = root.descend(&1).and_then(|x| x.descend(&0)).unwrap();
+        assert_eq!(a.into_raw(), b.into_raw());
+        assert_eq!(*a.state_evaluation(), 1);
+    }
+}
@@ -0,0 +1,75 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+#[test]
+fn lines_win() {
+    // Vertical, horizontal and both diagonals, each completed by player 0.
+    for columns in [
+        &[0, 1, 0, 1, 0, 1, 0][..],
+        &[0, 0, 1, 1, 2, 2, 3],
+        &[0, 1, 1, 2, 2, 3, 2, 3, 3, 6, 3],
+        &[6, 5, 5, 4, 4, 3, 4, 3, 3, 0, 3],
+    ] {
+        let state = ConnectFour::from_columns(columns);
+        assert_eq!(state.get_winner(), Some(0), "{:?}", columns);
+        assert!(state.is_terminal());
+        assert!(state.available_moves().is_empty());
+    }
+    // Three in a column and one on top of the next column's stones don't connect.
+    let state = ConnectFour::from_columns(&[0, 1, 0, 1, 0, 2, 1, 1, 1]);
+    assert_eq!(state.get_winner(), None);
+}
+
+#[test]
+fn full_column_is_illegal() {
+    let mut state = ConnectFour::from_columns(&[3, 3, 3, 3, 3, 3]);
+    assert!(!state.can_play(3));
+    assert!(!state.available_moves().contains(&3));
+    assert!(state.make_move(&3).is_err());
+    assert_eq!(state.plies(), 6);
+}
+
+#[test]
+fn incremental_hash_matches_full_hash() {
+    let columns = [3, 3, 2, 4, 4, 2, 5, 1, 0, 6, 6, 6];
+    let mut state = ConnectFour::new();
+    assert_eq!(state.hash(), state.full_hash());
+    for column in &columns {
+        let expected = state.hash_after(state.hash(), column);
+        state.make_move(column).unwrap();
+        assert_eq!(state.hash(), expected);
+        assert_eq!(state.hash(), state.full_hash());
+    }
+    // Another order of the same stones reaches the same state.
+    let transposed = ConnectFour::from_columns(&[3, 4, 2, 3, 4, 2, 5, 1, 0, 6, 6, 6]);
+    assert_eq!(transposed, state);
+    assert_eq!(transposed.hash(), state.hash());
+}
+
+#[test]
+fn takes_a_win() {
+    // Player 0 has three in the bottom row and wins at column 4; column 0 is taken.
+    let state = ConnectFour::from_columns(&[1, 0, 2, 0, 3, 6]);
+    assert!(state.is_winning_move(4));
+    let mut mcts = connect_four_manager(state, 20_000);
+    mcts.seed(Some(1));
+    mcts.playout_n(2000);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.best_move(), Some(4));
+}
+
+#[test]
+fn blocks_a_threat() {
+    let state = ConnectFour::from_columns(&[1, 0, 2, 6, 3]);
+    assert_eq!(*state.current_player(), 1);
+    let mut mcts = connect_four_manager(state, 50_000);
+    mcts.seed(Some(1));
+    mcts.playout_n(10_000);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.best_move(), Some(4));
+    assert!(mcts.memory_stats().state_storage.states > 0);
+}
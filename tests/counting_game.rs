@@ -0,0 +1,15 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+
+#[test]
+fn counting_game_adds() {
+    let mut mcts = counting_manager(5.0);
+    mcts.seed(Some(1));
+    mcts.playout_n(10_000);
+    mcts.tree().validate().unwrap();
+    let pv = mcts.principal_variation(10);
+    assert_eq!(pv, vec![CountingMove::Add; 10]);
+}
@@ -0,0 +1,165 @@
+// Timed searches need a clock under the `wasm` feature.
+#![cfg(not(feature = "wasm"))]
+
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// Takes `sleep` to evaluate a state, in steps of a millisecond. With `poll`, it
+/// gives up as soon as the search's deadline has passed.
+struct SleepyEvaluator {
+    sleep: Duration,
+    poll: bool,
+    saw_deadline: AtomicBool,
+}
+
+impl SleepyEvaluator {
+    fn new(sleep: Duration, poll: bool) -> Self {
+        Self {
+            sleep,
+            poll,
+            saw_deadline: AtomicBool::new(false),
+        }
+    }
+    fn nap(&self, handle: &SearchHandle<SleepyMCTS>) {
+        let mut slept = Duration::ZERO;
+        while slept < self.sleep {
+            if handle.deadline_exceeded() {
+                self.saw_deadline.store(true, Ordering::SeqCst);
+                if self.poll {
+                    return;
+                }
+            }
+            thread::sleep(Duration::from_millis(1));
+            slept += Duration::from_millis(1);
+        }
+    }
+}
+
+impl Evaluator<SleepyMCTS> for SleepyEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<SleepyMCTS>>,
+    ) -> (Vec<()>, usize) {
+        if let Some(handle) = handle {
+            self.nap(&handle);
+        }
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &usize,
+        handle: SearchHandle<SleepyMCTS>,
+    ) -> usize {
+        self.nap(&handle);
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct SleepyMCTS;
+
+impl MCTS for SleepyMCTS {
+    type State = FillGame;
+    type Eval = SleepyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+fn manager(eval: SleepyEvaluator) -> MCTSManager<SleepyMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(8, 4),
+        SleepyMCTS,
+        eval,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    mcts.seed(Some(0));
+    mcts
+}
+
+const DURATION: Duration = Duration::from_millis(200);
+
+#[test]
+fn counts_each_threads_playouts() {
+    let mut mcts = manager(SleepyEvaluator::new(Duration::from_millis(2), false));
+    let summary = mcts.playout_parallel_for(DURATION, 4);
+    mcts.tree().validate().unwrap();
+    assert_eq!(summary.thread_playouts.len(), 4);
+    assert!(summary.thread_playouts.iter().all(|&x| x > 0));
+    assert_eq!(
+        summary.thread_playouts.iter().sum::<u64>(),
+        summary.playouts
+    );
+    assert!(summary.elapsed >= DURATION);
+}
+
+#[test]
+fn overshoots_by_at_most_one_playout() {
+    let playout = Duration::from_millis(50);
+    let mut mcts = manager(SleepyEvaluator::new(playout, false));
+    let summary = mcts.playout_parallel_for(DURATION, 4);
+    // Sleeping isn't exact, so allow for another playout's worth.
+    assert!(
+        summary.elapsed < DURATION + 2 * playout,
+        "took {:?}",
+        summary.elapsed
+    );
+}
+
+#[test]
+fn evaluators_can_stop_at_the_deadline() {
+    // Without polling, a playout started just before the deadline ends a second later.
+    let mut mcts = manager(SleepyEvaluator::new(Duration::from_secs(1), true));
+    let summary = mcts.playout_parallel_for(DURATION, 4);
+    assert!(mcts.evaluator().saw_deadline.load(Ordering::SeqCst));
+    assert!(
+        summary.elapsed < DURATION + Duration::from_millis(100),
+        "took {:?}",
+        summary.elapsed
+    );
+
+    let mut mcts = manager(SleepyEvaluator::new(Duration::from_secs(1), true));
+    let summary = mcts.playout_for(DURATION);
+    assert!(mcts.evaluator().saw_deadline.load(Ordering::SeqCst));
+    assert!(
+        summary.elapsed < DURATION + Duration::from_millis(100),
+        "took {:?}",
+        summary.elapsed
+    );
+}
+
+#[test]
+fn never_exceeded_without_a_deadline() {
+    let mut mcts = manager(SleepyEvaluator::new(Duration::from_millis(1), false));
+    mcts.playout_for(Duration::from_millis(20));
+    // The timed search's last playout may have seen its deadline, but the searches
+    // after it don't.
+    mcts.evaluator().saw_deadline.store(false, Ordering::SeqCst);
+    let summary = mcts.playout_n(20);
+    assert!(summary.thread_playouts.is_empty());
+    mcts.playout_n_parallel(20, 2);
+    assert!(!mcts.evaluator().saw_deadline.load(Ordering::SeqCst));
+}
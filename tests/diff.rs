@@ -0,0 +1,69 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+fn searched_tree(exploration_constant: f64) -> SearchTree<FillMCTS> {
+    let tree = SearchTree::new(
+        FillGame::new(5, 3),
+        FillMCTS,
+        UCTPolicy::new(exploration_constant),
+        FillEvaluator,
+        ApproxTable::new(1024),
+    );
+    let mut tld = fill_thread_data(7);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    tree
+}
+
+#[test]
+fn identical_searches_have_no_differences() {
+    let diff = searched_tree(5.0).diff(&searched_tree(5.0), &DiffOptions::default());
+    assert_eq!(diff.pv_divergence, None);
+    assert!(!diff.moves.is_empty());
+    for x in &diff.moves {
+        assert_eq!(x.visit_delta(), 0);
+        assert_eq!(x.mean_delta(), 0.0);
+    }
+}
+
+#[test]
+fn diff_of_different_exploration_constants() {
+    let a = searched_tree(5.0);
+    let b = searched_tree(0.5);
+    let options = DiffOptions {
+        max_depth: 1,
+        ..Default::default()
+    };
+    let diff = a.diff(&b, &options);
+    assert_eq!(diff.pv_divergence, Some(0));
+    assert_eq!(
+        diff.to_string(),
+        "\
+principal variations differ at ply 0
+0: visits 6 -> 1 (-5), mean 4.5000 -> 0.0000 (-4.5000)
+1: visits 4 -> 1 (-3), mean 3.2500 -> 1.0000 (-2.2500)
+2: visits 51 -> 1 (-50), mean 7.6078 -> 2.0000 (-5.6078)
+3: visits 95 -> 1 (-94), mean 8.1053 -> 3.0000 (-5.1053)
+4: visits 44 -> 196 (+152), mean 7.4773 -> 8.8827 (+1.4054)
+"
+    );
+
+    // The second search hardly explored anything but 4, so many of the first
+    // search's deeper lines have no visits in the second.
+    let diff = a.diff(&b, &DiffOptions::default());
+    let missing = diff.moves.iter().filter(|x| x.visits.1 == 0).count();
+    assert!(missing > 0);
+    assert!(diff
+        .moves
+        .iter()
+        .filter(|x| x.visits.1 == 0)
+        .all(|x| x.path.len() > 1 && x.mean.1.is_nan()));
+}
@@ -0,0 +1,61 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+fn searched_tree() -> SearchTree<FillMCTS> {
+    let tree = fill_tree(5, 3);
+    let mut tld = fill_thread_data(7);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    tree
+}
+
+#[test]
+fn display_two_plies_of_top_three() {
+    assert_eq!(
+        searched_tree().display_to_depth(2).top(3).to_string(),
+        "\
+root (200 visits)
+3 (95 visits, mean 8.1053)
+  4 (40 visits, mean 8.5250)
+  2 (39 visits, mean 8.5128)
+  1 (10 visits, mean 6.8000)
+  … 1 more
+2 (51 visits, mean 7.6078)
+  4 (26 visits, mean 8.4615)
+  3 (17 visits, mean 8.0588)
+  1 (4 visits, mean 4.7500)
+  … 1 more
+4 (44 visits, mean 7.4773)
+  3 (18 visits, mean 8.2778)
+  2 (12 visits, mean 7.7500)
+  1 (8 visits, mean 6.7500)
+  … 1 more
+… 2 more
+"
+    );
+}
+
+#[test]
+fn display_root_only() {
+    assert_eq!(
+        searched_tree().display_to_depth(0).to_string(),
+        "root (200 visits)\n"
+    );
+    assert_eq!(
+        searched_tree().display_to_depth(1).to_string(),
+        "\
+root (200 visits)
+3 (95 visits, mean 8.1053)
+2 (51 visits, mean 7.6078)
+4 (44 visits, mean 7.4773)
+0 (6 visits, mean 4.5000)
+1 (4 visits, mean 3.2500)
+"
+    );
+}
@@ -0,0 +1,116 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// The value of a state for player 0, by a made-up heuristic for unfinished games.
+#[derive(Default)]
+struct NimEvaluator {
+    interpretations: AtomicUsize,
+}
+
+impl Evaluator<NimMCTS> for NimEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Nim,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<NimMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let value = match state.get_winner() {
+            Some(0) => 1.0,
+            Some(_) => -1.0,
+            None => {
+                let good = if state.stones.is_multiple_of(3) {
+                    -0.5
+                } else {
+                    0.5
+                };
+                if state.to_move == 0 {
+                    good
+                } else {
+                    -good
+                }
+            }
+        };
+        (vec![(); moves.len()], value)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, player: &u8) -> f64 {
+        self.interpretations.fetch_add(1, Ordering::Relaxed);
+        if *player == 0 {
+            *evaln
+        } else {
+            -*evaln
+        }
+    }
+    fn evaluate_existing_state(&self, _: &Nim, evaln: &f64, _: SearchHandle<NimMCTS>) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct NimMCTS(EvaluationSymmetry);
+
+impl MCTS for NimMCTS {
+    type State = Nim;
+    type Eval = NimEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn evaluation_symmetry(&self) -> EvaluationSymmetry {
+        self.0
+    }
+}
+
+fn search(symmetry: EvaluationSymmetry) -> SearchTree<NimMCTS> {
+    let tree = SearchTree::new(
+        Nim::default(),
+        NimMCTS(symmetry),
+        UCTPolicy::new(1.0),
+        NimEvaluator::default(),
+        ApproxTable::new(1024),
+    );
+    let mut tld = ThreadData::new(PolicyRng::new(3), ());
+    for _ in 0..500 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    tree
+}
+
+#[test]
+fn zero_sum_shortcut_gives_identical_statistics() {
+    let general = search(EvaluationSymmetry::General);
+    let zero_sum = search(EvaluationSymmetry::ZeroSumTwoPlayer);
+    let options = DiffOptions {
+        max_depth: 10,
+        ..Default::default()
+    };
+    let diff = general.diff(&zero_sum, &options);
+    assert_eq!(diff.pv_divergence, None);
+    assert!(diff.moves.len() > 10);
+    for x in &diff.moves {
+        assert_eq!(x.visits.0, x.visits.1);
+        assert_eq!(x.mean.0, x.mean.1);
+    }
+
+    let calls =
+        |tree: &SearchTree<NimMCTS>| tree.evaluator().interpretations.load(Ordering::Relaxed);
+    let plies: usize = general
+        .depth_histogram()
+        .counts()
+        .iter()
+        .enumerate()
+        .map(|(depth, &count)| depth * count as usize)
+        .sum();
+    assert_eq!(calls(&general), plies);
+    assert_eq!(calls(&zero_sum), 500);
+}
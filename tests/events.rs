@@ -0,0 +1,50 @@
+#![cfg(feature = "events")]
+
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::events::*;
+use mcts::*;
+use std::sync::Mutex;
+
+static EVENTS: Mutex<Vec<SearchEvent>> = Mutex::new(Vec::new());
+
+fn record(event: &SearchEvent) {
+    EVENTS.lock().unwrap().push(event.clone());
+}
+
+#[test]
+fn search_emits_events() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(6, 3),
+        FillMCTS,
+        FillEvaluator,
+        mcts::tree_policy::UCTPolicy::new(5.0),
+        mcts::transposition_table::ApproxTable::new(1024),
+    );
+    set_event_sink(record);
+    mcts.playout_n(2048);
+    mcts.tree().validate().unwrap();
+    clear_event_sink();
+    mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+
+    let events = EVENTS.lock().unwrap();
+    let count = |f: fn(&SearchEvent) -> bool| events.iter().filter(|x| f(x)).count();
+    assert_eq!(count(|x| matches!(x, SearchEvent::Playout { .. })), 2048);
+    assert_eq!(
+        count(|x| matches!(x, SearchEvent::Expansion)),
+        mcts.tree().num_nodes() - 1
+    );
+    assert!(count(|x| matches!(x, SearchEvent::TranspositionHit)) > 0);
+    let progress: Vec<u64> = events
+        .iter()
+        .filter_map(|x| match x {
+            SearchEvent::Progress { playouts, .. } => Some(*playouts),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(progress, vec![1024, 2048]);
+}
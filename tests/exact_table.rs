@@ -0,0 +1,207 @@
+extern crate mcts;
+
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::hash::{Hash, Hasher};
+
+/// Fill 3 of 6 cells, in any order, scoring the sum of the filled indices. The
+/// padding makes every state 4 KiB. With `collide`, states with the same number of
+/// filled cells hash the same.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Big {
+    cells: [bool; 6],
+    collide: bool,
+    padding: [u64; 512],
+}
+
+impl Default for Big {
+    fn default() -> Self {
+        Self {
+            cells: [false; 6],
+            collide: false,
+            padding: [0; 512],
+        }
+    }
+}
+
+impl Big {
+    fn filled(&self) -> usize {
+        self.cells.iter().filter(|&&x| x).count()
+    }
+    fn score(&self) -> usize {
+        (0..6).filter(|&i| self.cells[i]).sum()
+    }
+}
+
+impl Hash for Big {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        if self.collide {
+            self.filled().hash(state);
+        } else {
+            self.cells.hash(state);
+        }
+    }
+}
+
+impl GameState for Big {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        if self.filled() == 3 {
+            return vec![];
+        }
+        (0..6).filter(|&i| !self.cells[i]).collect()
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.cells[*mov] = true;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct BigEvaluator;
+
+impl Evaluator<BigMCTS> for BigEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Big,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<BigMCTS>>,
+    ) -> (Vec<()>, usize) {
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &Big, evaln: &usize, _: SearchHandle<BigMCTS>) -> usize {
+        *evaln
+    }
+}
+
+/// With `Some(hint)`, every state is said to take `hint` bytes.
+#[derive(Default)]
+struct BigMCTS(Option<usize>);
+
+impl MCTS for BigMCTS {
+    type State = Big;
+    type Eval = BigEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ExactTable<Self>;
+
+    fn state_size_hint(&self, state: &Big) -> usize {
+        self.0.unwrap_or(std::mem::size_of_val(state))
+    }
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+const STATE_SIZE: usize = std::mem::size_of::<Big>();
+
+fn searched(spec: BigMCTS, state: Big, table: ExactTable<BigMCTS>) -> MCTSManager<BigMCTS> {
+    let mut mcts = MCTSManager::new(state, spec, BigEvaluator, UCTPolicy::new(5.0), table);
+    mcts.seed(Some(0));
+    mcts.playout_n(2_000);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+#[test]
+fn counts_every_stored_state() {
+    let mcts = searched(BigMCTS(None), Big::default(), ExactTable::new(1024));
+    let entries = mcts.tree().transposition_table().nodes().len();
+    // Every node but the root.
+    assert_eq!(entries, mcts.tree().num_nodes() - 1);
+    assert_eq!(
+        mcts.memory_stats().state_storage,
+        StateStorage {
+            states: entries,
+            bytes: entries * STATE_SIZE,
+            downgraded_entries: 0,
+        }
+    );
+}
+
+#[test]
+fn uses_the_size_hint() {
+    let mcts = searched(BigMCTS(Some(100)), Big::default(), ExactTable::new(1024));
+    let entries = mcts.tree().transposition_table().nodes().len();
+    assert_eq!(mcts.memory_stats().state_storage.bytes, entries * 100);
+}
+
+#[test]
+fn downgrades_entries_past_the_limit() {
+    let table = ExactTable::new(1024).with_max_state_bytes(10 * STATE_SIZE + STATE_SIZE / 2);
+    let mcts = searched(BigMCTS(None), Big::default(), table);
+    let storage = mcts.memory_stats().state_storage;
+    let entries = mcts.tree().transposition_table().nodes().len();
+    assert!(entries > 20);
+    assert_eq!(storage.states, 10);
+    assert_eq!(storage.bytes, 10 * STATE_SIZE);
+    assert_eq!(storage.downgraded_entries, entries - 10);
+    let table = mcts.tree().transposition_table();
+    assert_eq!(table.downgraded_entries(), entries - 10);
+    // The downgraded entries still serve as transpositions.
+    assert_eq!(mcts.tree().num_nodes(), entries + 1);
+}
+
+/// Checks that every node's evaluation is the score of the state it's reached by.
+fn check_evaluations(node: NodeHandle<BigMCTS>, state: &Big) {
+    assert_eq!(*node.state_evaluation(), state.score(), "{:?}", state.cells);
+    for mov in node.moves() {
+        if let Some(child) = mov.child() {
+            let mut state = state.clone();
+            state.make_move(mov.get_move()).unwrap();
+            check_evaluations(child, &state);
+        }
+    }
+}
+
+#[test]
+fn colliding_states_get_their_own_nodes() {
+    let state = Big {
+        collide: true,
+        ..Big::default()
+    };
+    let mcts = searched(BigMCTS(None), state.clone(), ExactTable::new(1024));
+    check_evaluations(mcts.tree().root_node(), &state);
+    // The cells can be filled in any order.
+    let mut pv = mcts.principal_variation(3);
+    pv.sort();
+    assert_eq!(pv, vec![3, 4, 5]);
+}
+
+#[test]
+fn colliding_states_share_nodes_once_downgraded() {
+    let state = Big {
+        collide: true,
+        ..Big::default()
+    };
+    let mcts = searched(
+        BigMCTS(None),
+        state,
+        ExactTable::new(1024).with_max_state_bytes(0),
+    );
+    // One node per number of filled cells.
+    assert_eq!(mcts.tree().num_nodes(), 4);
+    assert_eq!(mcts.memory_stats().state_storage.downgraded_entries, 3);
+}
+
+#[test]
+fn clear_forgets_the_states() {
+    let mcts = searched(BigMCTS(None), Big::default(), ExactTable::new(1024));
+    let mcts = mcts.reset();
+    assert_eq!(mcts.memory_stats().state_storage, StateStorage::default());
+}
@@ -0,0 +1,173 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// Pick a digit three times; the score is their sum. Odd digits are generated but
+// illegal, and worth 100 each, so a search that played them would prefer them.
+fn score(digits: &[u8]) -> f64 {
+    digits
+        .iter()
+        .map(|&x| if x % 2 == 1 { 100.0 } else { x as f64 })
+        .sum()
+}
+
+#[derive(Default)]
+struct DigitsMCTS {
+    /// Also exclude every move after the first.
+    exclude_all_later: bool,
+}
+
+impl MCTS for DigitsMCTS {
+    type State = Digits;
+    type Eval = DigitsEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+
+    fn exclude_move(&self, state: &Digits, mov: &u8) -> bool {
+        mov % 2 == 1 || (self.exclude_all_later && !state.picked.is_empty())
+    }
+}
+
+fn search(spec: DigitsMCTS) -> MCTSManager<DigitsMCTS> {
+    let mut mcts = MCTSManager::new(
+        Digits::new(3),
+        spec,
+        DigitsEvaluator(score),
+        AlphaGoPolicy::new(1.0),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n(2000);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+#[test]
+fn excluded_moves_are_never_visited() {
+    let mcts = search(DigitsMCTS::default());
+    let mut stack = vec![mcts.tree().root_node()];
+    let mut nodes = 0;
+    while let Some(node) = stack.pop() {
+        nodes += 1;
+        if node.moves().next().is_none() {
+            continue;
+        }
+        assert!(node.moves().all(|x| x.get_move() % 2 == 0));
+        assert_eq!(node.excluded_moves().count(), 5);
+        for mov in node.excluded_moves() {
+            assert_eq!(mov.get_move() % 2, 1);
+            assert_eq!(mov.visits(), 0);
+            assert!(mov.child().is_none());
+            // Excluded moves keep their evaluations.
+            assert_eq!(*mov.move_evaluation(), *mov.get_move() as f64 / 45.0);
+        }
+        stack.extend(node.moves().filter_map(|x| x.child()));
+    }
+    assert!(nodes > 1);
+    assert_eq!(mcts.principal_variation(3), vec![8, 8, 8]);
+}
+
+#[test]
+fn excluding_every_move_uses_the_no_moves_behaviour() {
+    // The default behaviour treats the state as terminal.
+    let mcts = search(DigitsMCTS {
+        exclude_all_later: true,
+    });
+    let root = mcts.tree().root_node();
+    assert!(root.moves().filter_map(|x| x.child()).count() > 1);
+    for child in root.moves().filter_map(|x| x.child()) {
+        assert_eq!(child.moves().count(), 0);
+        assert_eq!(child.excluded_moves().count(), 10);
+    }
+    assert_eq!(mcts.principal_variation(3), vec![8]);
+}
+
+fn root_visits(mcts: &MCTSManager<DigitsMCTS>) -> Vec<(u8, u64)> {
+    let root = mcts.tree().root_node();
+    root.moves().map(|x| (*x.get_move(), x.visits())).collect()
+}
+
+#[test]
+fn excluded_children_get_no_new_visits() {
+    let mut mcts = search(DigitsMCTS::default());
+    assert!(mcts.exclude_root_move(&8));
+    assert!(!mcts.exclude_root_move(&8));
+    // Odd moves were set aside when the node was created.
+    assert!(!mcts.exclude_root_move(&7));
+    let before = root_visits(&mcts);
+    let stats = mcts.root_move_stats_ref();
+    assert!(stats.iter().all(|x| x.excluded == (*x.mov == 8)));
+    assert_eq!(mcts.tree().root_node().num_excluded_children(), 1);
+
+    mcts.playout_n(2000);
+    mcts.tree().validate().unwrap();
+    for ((mov, old), (_, new)) in before.iter().zip(root_visits(&mcts)) {
+        if *mov == 8 {
+            assert_eq!(new, *old);
+        } else {
+            assert!(new >= *old);
+        }
+    }
+    // The excluded move keeps its statistics but leaves the principal variation.
+    let eight = mcts
+        .root_move_stats_detailed()
+        .into_iter()
+        .find(|x| *x.move_info.get_move() == 8);
+    let eight = eight.unwrap();
+    assert!(eight.excluded && eight.visits > 0 && eight.mean > 0.0);
+    assert_eq!(mcts.principal_variation(3), vec![6, 8, 8]);
+    assert_eq!(mcts.best_move(), Some(6));
+}
+
+#[test]
+fn included_children_are_selected_again() {
+    let mut mcts = search(DigitsMCTS::default());
+    mcts.exclude_root_move(&8);
+    mcts.playout_n(500);
+    assert!(mcts.include_root_move(&8));
+    assert!(!mcts.include_root_move(&8));
+    let before = root_visits(&mcts);
+    mcts.playout_n(2000);
+    mcts.tree().validate().unwrap();
+    let after = root_visits(&mcts);
+    let eight = |visits: &[(u8, u64)]| visits.iter().find(|x| x.0 == 8).unwrap().1;
+    assert!(eight(&after) > eight(&before));
+    assert!(mcts.root_move_stats_ref().iter().all(|x| !x.excluded));
+    assert_eq!(mcts.principal_variation(1), vec![8]);
+}
+
+#[test]
+fn children_below_the_root_can_be_excluded() {
+    let mut mcts = search(DigitsMCTS::default());
+    let root = mcts.tree().root_node();
+    let eight = root.moves().position(|x| *x.get_move() == 8).unwrap();
+    let child = root.moves().nth(eight).unwrap().child().unwrap();
+    for i in 0..child.moves().count() {
+        assert!(child.exclude_child(i));
+    }
+    let visits = child.moves().map(|x| x.visits()).sum::<u64>();
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    let child = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .nth(eight)
+        .unwrap()
+        .child()
+        .unwrap();
+    assert_eq!(child.moves().map(|x| x.visits()).sum::<u64>(), visits);
+    // With nothing left to choose, the principal variation stops there.
+    assert_eq!(mcts.principal_variation(3), vec![8]);
+    let most_visited = (0..child.moves().count())
+        .max_by_key(|&i| child.moves().nth(i).unwrap().visits())
+        .unwrap();
+    assert!(child.include_child(most_visited));
+    assert_eq!(mcts.principal_variation(3), vec![8, 8, 8]);
+}
@@ -0,0 +1,115 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+#[test]
+fn explains_a_seeded_search() {
+    let mut mcts = TwoPlayerZeroSumSpec::<TicTacToe>::manager(TicTacToe::default());
+    mcts.seed(Some(1));
+    assert_eq!(
+        mcts.explain_best_move().to_string(),
+        "no move searched yet\n"
+    );
+    mcts.playout_n(2000);
+    let explanation = mcts.explain_best_move();
+    assert_eq!(
+        explanation.to_string(),
+        "2000 visits; values for the player to move
+best  4: 1170 visits (58.5%), mean +0.510, score 0.623, then [6, 7, 1]
+rival 2: 174 visits (8.7%), mean +0.328, score 0.620, then [4, 0, 1]
+rival 6: 155 visits (7.8%), mean +0.310, score 0.620, then [4, 7, 8]
+rival 5: 141 visits (7.0%), mean +0.291, score 0.616, then [8, 1, 4]
+"
+    );
+
+    let best = explanation.best().unwrap();
+    assert_eq!(Some(best.mov), mcts.best_move());
+    assert_eq!(best.continuation, mcts.principal_variation(4)[1..]);
+    let rivals = explanation.rivals();
+    assert_eq!(rivals.len(), 3);
+    assert!(rivals.windows(2).all(|x| x[0].visits >= x[1].visits));
+    let stats = mcts.root_move_stats_ref();
+    assert!(stats
+        .iter()
+        .filter(|x| *x.mov != best.mov && rivals.iter().all(|r| r.mov != *x.mov))
+        .all(|x| x.visits <= rivals[2].visits));
+}
+
+// Tic-tac-toe with PUCT, priors favouring the centre and the game's result as the
+// only evaluation.
+#[derive(Default)]
+struct PriorMCTS;
+
+struct Results;
+
+impl Evaluator<PriorMCTS> for Results {
+    type StateEvaluation = Option<u8>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &TicTacToe,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<PriorMCTS>>,
+    ) -> (Vec<f64>, Option<u8>) {
+        let weight = |&mov: &usize| if mov == 4 { 3.0 } else { 1.0 };
+        let total: f64 = moves.iter().map(weight).sum();
+        let priors = moves.iter().map(|x| weight(x) / total).collect();
+        (priors, state.get_winner())
+    }
+    fn interpret_evaluation_for_player(&self, winner: &Option<u8>, player: &u8) -> f64 {
+        match winner {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &TicTacToe,
+        winner: &Option<u8>,
+        _: SearchHandle<PriorMCTS>,
+    ) -> Option<u8> {
+        *winner
+    }
+}
+
+impl MCTS for PriorMCTS {
+    type State = TicTacToe;
+    type Eval = Results;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn shows_priors_and_exclusions() {
+    let mut mcts = MCTSManager::new(
+        TicTacToe::default(),
+        PriorMCTS,
+        Results,
+        AlphaGoPolicy::new(1.0),
+        (),
+    );
+    mcts.seed(Some(1));
+    mcts.playout_n(1000);
+    mcts.exclude_root_move(&4);
+    mcts.playout_n(500);
+    // The most visited move is excluded, so the best move is the next one.
+    assert_eq!(
+        mcts.explain_best_move().to_string(),
+        "1500 visits; values for the player to move
+best  8: 154 visits (10.3%), mean +0.000, prior 0.091, score 0.023, then [4, 7, 6]
+rival 4: 272 visits (18.1%), mean +0.000, prior 0.273, score 0.039, excluded, then [8, 6, 7]
+rival 1: 154 visits (10.3%), mean +0.000, prior 0.091, score 0.023, then [4, 7, 8]
+rival 2: 154 visits (10.3%), mean +0.000, prior 0.091, score 0.023, then [4, 7, 8]
+"
+    );
+    let explanation = mcts.tree().explain_best_move(8, 1);
+    assert_eq!(explanation.moves.len(), 9);
+    assert!(explanation.moves.iter().all(|x| x.continuation.len() == 1));
+}
@@ -0,0 +1,177 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::export::*;
+
+fn dot(options: &DotOptions) -> String {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(1);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    let mut out = Vec::new();
+    tree.write_dot(&mut out, options).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn dot_writes_transpositions_once() {
+    let options = DotOptions {
+        max_depth: 10,
+        ..Default::default()
+    };
+    let out = dot(&options);
+    assert!(out.starts_with("digraph mcts {\n"));
+    assert!(out.ends_with("}\n"));
+    let edges = out.lines().filter(|x| x.contains("->")).count();
+    let nodes = out
+        .lines()
+        .filter(|x| x.contains("[label=") && !x.contains("->"))
+        .count();
+    // 4 first moves, then 12 second moves reaching only 6 distinct states.
+    assert_eq!(edges, 16);
+    assert_eq!(nodes, 1 + 4 + 6);
+}
+
+#[test]
+fn dot_respects_depth_and_is_deterministic() {
+    let options = DotOptions {
+        max_depth: 1,
+        min_visits: 1,
+        edge_labels: false,
+    };
+    let out = dot(&options);
+    assert_eq!(out, dot(&options));
+    assert_eq!(out.lines().filter(|x| x.contains("->")).count(), 4);
+    assert!(out.contains("  n0 -> n1;\n"));
+}
+
+#[test]
+fn iter_nodes_counts_every_node_of_a_seeded_search() {
+    let mut mcts = counting_manager(5.0);
+    mcts.seed(Some(2));
+    mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    let records: Vec<_> = mcts.tree().iter_nodes(1).collect();
+    // No node is shared, so each is produced once.
+    assert_eq!(records.len(), mcts.tree().num_nodes());
+    assert!(records[0].path.is_empty());
+    assert_eq!(records[0].visits, 500);
+    assert!(records[0].prior.is_nan());
+    let mut again = counting_manager(5.0);
+    again.seed(Some(2));
+    again.playout_n(500);
+    let paths: Vec<_> = again.tree().iter_nodes(1).map(|x| x.path).collect();
+    assert_eq!(
+        paths,
+        records.iter().map(|x| x.path.clone()).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iter_nodes_is_a_depth_first_walk() {
+    let mut mcts = counting_manager(5.0);
+    mcts.seed(Some(2));
+    mcts.playout_n(500);
+    let records: Vec<_> = mcts.tree().iter_nodes(10).collect();
+    assert!(records.len() > 1);
+    for pair in records.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        assert_eq!(next.depth, next.path.len());
+        assert!(next.visits >= 10);
+        // Each node follows its parent or a node sharing a prefix with it.
+        assert!(next.depth <= prev.depth + 1);
+        assert_eq!(next.path[..next.depth - 1], prev.path[..next.depth - 1]);
+    }
+    let root = mcts.tree().root_node();
+    let first = root.moves().find(|x| x.visits() >= 10).unwrap();
+    assert_eq!(records[1].path, vec![first.get_move()]);
+    assert_eq!(records[1].mean, first.avg_reward());
+}
+
+#[test]
+fn iter_nodes_walks_transpositions_once_per_path() {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(1);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    // The root, 4 first moves and 12 second moves, reaching 6 distinct states.
+    assert_eq!(tree.iter_nodes(1).count(), 1 + 4 + 12);
+    assert!(tree.iter_nodes(1).skip(1).all(|x| x.prior == 0.0));
+}
+
+fn counting_move_bytes(mov: &CountingMove) -> Vec<u8> {
+    vec![match mov {
+        CountingMove::Add => b'+',
+        CountingMove::Sub => b'-',
+    }]
+}
+
+#[test]
+fn dump_round_trips_a_large_tree_compactly() {
+    let mut mcts = counting_manager(5.0);
+    mcts.seed(Some(2));
+    mcts.playout_n(20_000);
+    let tree = mcts.tree();
+    let mut out = Vec::new();
+    tree.write_dump(&mut out, 1, counting_move_bytes).unwrap();
+    let dump = TreeDump::read(&mut &out[..]).unwrap();
+    assert_eq!(dump.nodes.len(), tree.num_nodes());
+    assert!(dump.nodes.len() > 1000);
+    assert!(out.len() < 20 * dump.nodes.len());
+    assert_eq!(dump.moves.len(), 2);
+    assert_eq!(dump.nodes[0].parent, None);
+    assert_eq!(dump.nodes[0].visits, 20_000);
+    let children: Vec<_> = dump.children(0).collect();
+    let root_moves: Vec<_> = tree.root_node().moves().collect();
+    assert_eq!(children.len(), root_moves.len());
+    for (&child, mov) in children.iter().zip(root_moves) {
+        assert_eq!(
+            dump.move_bytes(child).unwrap(),
+            &counting_move_bytes(mov.get_move())[..]
+        );
+        assert_eq!(dump.nodes[child].visits, mov.visits());
+        assert_eq!(dump.nodes[child].mean, mov.avg_reward() as f32);
+    }
+    for (index, node) in dump.nodes.iter().enumerate().skip(1) {
+        assert!(node.parent.unwrap() < index);
+        assert!(node.visits <= dump.nodes[node.parent.unwrap()].visits);
+    }
+}
+
+#[test]
+fn dump_expands_transpositions_once() {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(1);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    let mut out = Vec::new();
+    tree.write_dump(&mut out, 1, |&x| vec![x as u8]).unwrap();
+    let dump = TreeDump::read(&mut &out[..]).unwrap();
+    // The root, 4 first moves and 12 second moves; the second moves share the 4
+    // move table entries.
+    assert_eq!(dump.nodes.len(), 1 + 4 + 12);
+    assert_eq!(dump.moves.len(), 4);
+    assert_eq!(dump.nodes[0].visits, 200);
+    let first: u64 = dump.children(0).map(|x| dump.nodes[x].visits).sum();
+    assert_eq!(first, 200);
+}
+
+#[test]
+fn dump_rejects_bad_input() {
+    let tree = fill_tree(4, 2);
+    let mut out = Vec::new();
+    tree.write_dump(&mut out, 1, |&x| vec![x as u8]).unwrap();
+    assert_eq!(TreeDump::read(&mut &out[..]).unwrap().nodes.len(), 1);
+    let error = TreeDump::read(&mut &out[..out.len() - 1]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    out[0] = b'X';
+    let error = TreeDump::read(&mut &out[..]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
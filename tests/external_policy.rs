@@ -0,0 +1,100 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// A tree policy written outside the crate, with nothing but its public API: greedy
+/// on the mean reward, with unvisited moves valued at `optimism` so that each is
+/// tried once. Ties are broken at random.
+struct OptimisticPolicy {
+    optimism: f64,
+}
+
+impl<Spec: MCTS<TreePolicy = Self>> TreePolicy<Spec> for OptimisticPolicy {
+    type MoveEvaluation = ();
+    type ThreadLocalData = PolicyRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<Spec>,
+    ) -> &'a MoveInfo<Spec>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
+    {
+        handle
+            .thread_data()
+            .policy_data
+            .select_by_key(moves, |mov| mov.mean_reward_or(self.optimism))
+            .unwrap()
+    }
+
+    fn score_child(&self, child: &MoveInfo<Spec>, _parent_visits: u64) -> f64 {
+        child.mean_reward_or(self.optimism)
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<PolicyRng> {
+        Some(PolicyRng::new(seed))
+    }
+}
+
+#[derive(Default)]
+struct OptimisticMCTS;
+
+impl MCTS for OptimisticMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = OptimisticPolicy;
+    type TranspositionTable = ();
+}
+
+fn manager() -> MCTSManager<OptimisticMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(6, 2),
+        OptimisticMCTS,
+        FillEvaluator,
+        OptimisticPolicy { optimism: 100.0 },
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts
+}
+
+#[test]
+fn finds_the_best_fill() {
+    let mut mcts = manager();
+    mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    let mut pv = mcts.principal_variation(2);
+    pv.sort();
+    assert_eq!(pv, vec![4, 5]);
+}
+
+#[test]
+fn scores_use_the_mean_reward() {
+    let mut mcts = manager();
+    mcts.playout_n(100);
+    for stats in mcts.root_move_stats_detailed() {
+        let mov = stats.move_info;
+        assert_eq!(
+            mov.mean_reward(),
+            Some(mov.sum_rewards() / mov.visits() as f64)
+        );
+        assert_eq!(stats.policy_score, mov.mean_reward().unwrap());
+    }
+}
+
+#[test]
+fn unvisited_moves_have_no_mean() {
+    let mcts = manager();
+    for mov in mcts.tree().root_node().moves() {
+        assert_eq!(mov.mean_reward(), None);
+        assert_eq!(mov.mean_reward_or(-1.0), -1.0);
+        assert!(mov.avg_reward().is_nan());
+    }
+}
@@ -0,0 +1,122 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+#[cfg(not(feature = "wasm"))]
+use std::time::Duration;
+
+fn manager(state: FillGame) -> MCTSManager<FillMCTS> {
+    MCTSManager::new(
+        state,
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    )
+}
+
+/// One cell left to fill.
+fn one_move_left() -> FillGame {
+    let mut state = FillGame::new(4, 3);
+    for cell in [0, 1, 3] {
+        state.make_move(&cell).unwrap();
+    }
+    state.target = 4;
+    state
+}
+
+#[test]
+fn a_single_legal_move_is_not_searched() {
+    let mut mcts = manager(one_move_left());
+    let summary = mcts.playout_n(100_000);
+    assert_eq!(summary.stop_reason, StopReason::ForcedMove);
+    assert_eq!(summary.playouts, 1);
+    assert_eq!(mcts.best_move(), Some(2));
+    assert!(summary.to_string().contains("forced move"));
+    // The move has its visit now, so further searches run no playouts at all.
+    let summary = mcts.playout_n_parallel(1000, 2);
+    assert_eq!(summary.stop_reason, StopReason::ForcedMove);
+    assert_eq!(summary.playouts, 0);
+    // `playout_for` needs a clock under the `wasm` feature.
+    #[cfg(not(feature = "wasm"))]
+    {
+        let summary = mcts.playout_for(Duration::from_secs(10));
+        assert_eq!(summary.playouts, 0);
+    }
+    assert_eq!(mcts.best_move(), Some(2));
+}
+
+#[test]
+fn choices_are_searched() {
+    let mut mcts = manager(FillGame::new(4, 2));
+    let summary = mcts.playout_n(100);
+    assert_eq!(summary.stop_reason, StopReason::Budget);
+    assert_eq!(summary.playouts, 100);
+    assert!(mcts.tree().forced_root_move().is_none());
+}
+
+// Chunked searches need a clock under the `wasm` feature.
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn chunks_stop_at_a_forced_move() {
+    let mut mcts = manager(one_move_left());
+    mcts.chunk_target(Some(10_000));
+    let chunk = mcts.playout_chunk(100, Duration::from_secs(10));
+    assert!(chunk.forced_move && chunk.is_done());
+    assert_eq!(chunk.playouts, 1);
+    assert_eq!(mcts.playout_chunk(100, Duration::from_secs(10)).playouts, 0);
+}
+
+/// `FillMCTS` with every root move but one excluded.
+#[derive(Default)]
+struct OneRootMove {
+    keep_searching: bool,
+}
+
+impl MCTS for OneRootMove {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn exclude_move(&self, state: &FillGame, mov: &usize) -> bool {
+        state.filled() == 0 && *mov != 1
+    }
+    fn stop_on_forced_move(&self) -> bool {
+        !self.keep_searching
+    }
+}
+
+fn restricted(keep_searching: bool) -> MCTSManager<OneRootMove> {
+    MCTSManager::new(
+        FillGame::new(4, 2),
+        OneRootMove { keep_searching },
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    )
+}
+
+#[test]
+fn a_root_restricted_to_one_move_is_forced() {
+    let mut mcts = restricted(false);
+    let forced = mcts.tree().forced_root_move().unwrap();
+    assert_eq!(*forced.get_move(), 1);
+    let summary = mcts.playout_n(1000);
+    assert_eq!(summary.stop_reason, StopReason::ForcedMove);
+    assert_eq!(summary.playouts, 1);
+    assert_eq!(mcts.best_move(), Some(1));
+
+    // Unless the spec wants the tree below the move.
+    let mut mcts = restricted(true);
+    let summary = mcts.playout_n(1000);
+    assert_eq!(summary.stop_reason, StopReason::Budget);
+    assert_eq!(summary.playouts, 1000);
+    assert_eq!(mcts.principal_variation(2), vec![1, 3]);
+}
@@ -0,0 +1,43 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+fn searched_tree() -> SearchTree<FillMCTS> {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(7);
+    for _ in 0..100 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    tree
+}
+
+#[test]
+fn format_moves_by_visits() {
+    assert_eq!(
+        searched_tree().format_moves(MoveSort::Visits, None),
+        "\
+move    visits        mean  prior
+3           44      4.3864  ()
+2           35      4.2286  ()
+1           13      3.0000  ()
+0            8      1.8750  ()
+"
+    );
+}
+
+#[test]
+fn format_moves_by_mean_with_limit() {
+    assert_eq!(
+        searched_tree().format_moves(MoveSort::Mean, Some(3)),
+        "\
+move    visits        mean  prior
+3           44      4.3864  ()
+2           35      4.2286  ()
+1           13      3.0000  ()
+"
+    );
+}
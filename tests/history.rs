@@ -0,0 +1,191 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::Arc;
+
+struct HistoryEvaluator;
+
+impl Evaluator<HistoryMCTS> for HistoryEvaluator {
+    type StateEvaluation = Option<u8>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &TicTacToe,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<HistoryMCTS>>,
+    ) -> (Vec<()>, Option<u8>) {
+        let winner = match handle {
+            Some(mut handle) => {
+                WinDrawLossEvaluator::rollout(state, &mut handle.thread_data().policy_data)
+            }
+            None => WinDrawLossEvaluator::rollout(state, &mut PolicyRng::new(0)),
+        };
+        (vec![(); moves.len()], winner)
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &TicTacToe,
+        evaln: &Option<u8>,
+        _: SearchHandle<HistoryMCTS>,
+    ) -> Option<u8> {
+        *evaln
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &Option<u8>, player: &u8) -> f64 {
+        match evaln {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistoryMCTS;
+
+impl MCTS for HistoryMCTS {
+    type State = TicTacToe;
+    type Eval = HistoryEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = HistoryPolicy;
+    type TranspositionTable = ();
+
+    fn evaluation_symmetry(&self) -> EvaluationSymmetry {
+        EvaluationSymmetry::ZeroSumTwoPlayer
+    }
+}
+
+fn negamax(state: &TicTacToe) -> i32 {
+    if let Some(winner) = state.get_winner() {
+        return if winner == *state.current_player() {
+            1
+        } else {
+            -1
+        };
+    }
+    let moves = state.available_moves();
+    moves
+        .iter()
+        .map(|mov| -negamax(&after(state, mov)))
+        .max()
+        .unwrap_or(0)
+}
+
+fn after(state: &TicTacToe, mov: &usize) -> TicTacToe {
+    let mut state = state.clone();
+    state.make_move(mov).unwrap();
+    state
+}
+
+fn optimal_moves(state: &TicTacToe) -> Vec<usize> {
+    let value = negamax(state);
+    let moves = state.available_moves().into_iter();
+    moves
+        .filter(|mov| -negamax(&after(state, mov)) == value)
+        .collect()
+}
+
+fn manager(state: TicTacToe, seed: u64) -> MCTSManager<HistoryMCTS> {
+    let mut mcts = MCTSManager::new(
+        state,
+        HistoryMCTS,
+        HistoryEvaluator,
+        HistoryPolicy::new(1.0, 0.5),
+        (),
+    );
+    mcts.seed(Some(seed));
+    mcts
+}
+
+/// Of the first 500 playouts of a search, how many ended with a best move that
+/// gives away some of the position's value, counted every 10 playouts.
+fn playouts_misled(
+    state: &TicTacToe,
+    history: Option<Arc<HistoryTable<usize>>>,
+    seed: u64,
+) -> usize {
+    let optimal = optimal_moves(state);
+    let mut mcts = manager(state.clone(), seed);
+    mcts.set_history_table(history);
+    let mut misled = 0;
+    for _ in 0..50 {
+        mcts.playout_n(10);
+        mcts.tree().validate().unwrap();
+        if !optimal.contains(&mcts.best_move().unwrap()) {
+            misled += 10;
+        }
+    }
+    misled
+}
+
+fn positions(state: TicTacToe, depth: usize, out: &mut Vec<TicTacToe>) {
+    if depth == 0 {
+        out.push(state);
+        return;
+    }
+    for mov in state.available_moves() {
+        positions(after(&state, &mov), depth - 1, out);
+    }
+}
+
+#[test]
+fn history_carries_over_to_the_next_move() {
+    let mut sample = Vec::new();
+    positions(TicTacToe::default(), 2, &mut sample);
+    positions(TicTacToe::default(), 3, &mut sample);
+    let (mut with, mut without) = (0, 0);
+    for (seed, state) in sample.into_iter().enumerate().step_by(3) {
+        let seed = seed as u64;
+        let history = Arc::new(HistoryTable::new(64));
+        let mut mcts = manager(state.clone(), seed);
+        mcts.set_history_table(Some(history.clone()));
+        mcts.playout_n(1000);
+        mcts.tree().validate().unwrap();
+        let next = after(&state, &mcts.best_move().unwrap());
+        // Only positions where the choice matters.
+        if next.available_moves().len() < 2 * optimal_moves(&next).len() {
+            continue;
+        }
+        with += playouts_misled(&next, Some(history), seed);
+        without += playouts_misled(&next, None, seed);
+    }
+    assert!(
+        with * 3 < without * 2,
+        "{} playouts misled with history, {} without",
+        with,
+        without
+    );
+}
+
+#[test]
+fn table_is_updated_during_backpropagation() {
+    let history = Arc::new(HistoryTable::new(64));
+    let mut mcts = MCTSManager::builder(HistoryMCTS)
+        .state(TicTacToe::default())
+        .evaluator(HistoryEvaluator)
+        .policy(HistoryPolicy::new(1.0, 0.5))
+        .transposition_table(())
+        .history_table(history.clone())
+        .seed(0)
+        .build();
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    assert!(Arc::ptr_eq(mcts.history_table().unwrap(), &history));
+    // Every playout records each move on its path, at least the first.
+    let recorded: u64 = (0..9).map(|mov| history.visits(&mov)).sum();
+    assert!(recorded >= 100);
+    let root_visits: u64 = (0..9)
+        .map(|mov| mcts.tree().root_node().moves().nth(mov).unwrap().visits())
+        .sum();
+    assert_eq!(root_visits, 100);
+    for mov in 0..9 {
+        let mean = history.mean(&mov).unwrap();
+        assert!((-1.0..=1.0).contains(&mean));
+    }
+    history.clear();
+    assert_eq!(history.mean(&4), None);
+}
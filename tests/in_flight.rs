@@ -0,0 +1,196 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Tictactoe with random rollouts, like `TwoPlayerZeroSumSpec`, but with a limit
+/// on the playouts in flight through each move.
+#[derive(Default)]
+struct Limited(usize);
+
+struct RolloutEvaluator;
+
+impl Evaluator<Limited> for RolloutEvaluator {
+    type StateEvaluation = Option<u8>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &TicTacToe,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<Limited>>,
+    ) -> (Vec<()>, Option<u8>) {
+        let winner = match handle {
+            Some(mut handle) => {
+                WinDrawLossEvaluator::rollout(state, &mut handle.thread_data().policy_data)
+            }
+            None => WinDrawLossEvaluator::rollout(state, &mut PolicyRng::new(0)),
+        };
+        (vec![(); moves.len()], winner)
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &TicTacToe,
+        evaln: &Option<u8>,
+        _: SearchHandle<Limited>,
+    ) -> Option<u8> {
+        *evaln
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &Option<u8>, player: &u8) -> f64 {
+        match evaln {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+}
+
+impl MCTS for Limited {
+    type State = TicTacToe;
+    type Eval = RolloutEvaluator;
+    type TreePolicy = UCTPolicy<()>;
+    type NodeData = ();
+    type TranspositionTable = ();
+    type ExtraThreadData = ();
+
+    fn max_in_flight(&self) -> usize {
+        self.0
+    }
+    fn evaluation_symmetry(&self) -> EvaluationSymmetry {
+        EvaluationSymmetry::ZeroSumTwoPlayer
+    }
+}
+
+fn manager(limit: usize, cells: [u8; 9]) -> MCTSManager<Limited> {
+    MCTSManager::new(
+        TicTacToe { cells },
+        Limited(limit),
+        RolloutEvaluator,
+        UCTPolicy::new(1.4),
+        (),
+    )
+}
+
+fn all_in_flight(node: NodeHandle<Limited>, out: &mut Vec<usize>) {
+    for mov in node.moves() {
+        out.push(mov.in_flight());
+        if let Some(child) = mov.child() {
+            all_in_flight(child, out);
+        }
+    }
+}
+
+#[test]
+fn nothing_in_flight_between_searches() {
+    let mut mcts = manager(1, [0; 9]);
+    mcts.playout_n_parallel(5_000, 8);
+    mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    let mut counts = Vec::new();
+    all_in_flight(mcts.tree().root_node(), &mut counts);
+    assert!(counts.len() > 100);
+    assert!(counts.iter().all(|&x| x == 0));
+}
+
+#[test]
+fn one_thread_never_saturates_a_limit_of_one() {
+    let mut mcts = manager(1, [0; 9]);
+    let summary = mcts.playout_n(2_000);
+    assert_eq!(summary.saturated_selections, 0);
+    assert_eq!(summary.expansion_contention_events, 0);
+}
+
+#[test]
+fn still_finds_the_best_move() {
+    // X to move completes the column 0, 3, 6.
+    let mut mcts = manager(1, [1, 2, 0, 0, 2, 0, 1, 0, 0]);
+    mcts.playout_n_parallel(10_000, 8);
+    mcts.tree().validate().unwrap();
+    // A thread descheduled in the middle of a playout through the winning move keeps
+    // the others out of it, so on a loaded machine its visits can fall behind. Its
+    // mean reward can't; ties go to the more visited move.
+    let root = mcts.tree().root_node();
+    let key = |x: &&MoveInfo<Limited>| (x.avg_reward(), x.visits());
+    let best = root
+        .moves()
+        .filter(|x| x.visits() > 0)
+        .max_by(|a, b| key(a).partial_cmp(&key(b)).unwrap())
+        .unwrap();
+    assert_eq!(*best.get_move(), 3);
+    assert_eq!(best.avg_reward(), 1.0);
+}
+
+#[test]
+fn spreads_threads_out() {
+    let mut unlimited = 0;
+    let mut limited = 0;
+    for _ in 0..5 {
+        unlimited += manager(usize::MAX, [0; 9])
+            .playout_n_parallel(20_000, 8)
+            .expansion_contention_events;
+        let summary = manager(1, [0; 9]).playout_n_parallel(20_000, 8);
+        assert!(summary.saturated_selections > 0);
+        limited += summary.expansion_contention_events;
+    }
+    assert!(limited <= unlimited, "{} > {}", limited, unlimited);
+}
+
+/// With `true`, a cycle is scored with the spec's evaluation rather than the
+/// current one.
+struct RingMCTS(bool);
+
+impl MCTS for RingMCTS {
+    type State = Ring;
+    type Eval = RingEvaluator;
+    type TreePolicy = UCTPolicy<()>;
+    type NodeData = ();
+    type TranspositionTable = ApproxTable<Self>;
+    type ExtraThreadData = ();
+
+    fn max_in_flight(&self) -> usize {
+        1
+    }
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        if self.0 {
+            CycleBehaviour::UseThisEvalWhenCycleDetected(0)
+        } else {
+            CycleBehaviour::UseCurrentEvalWhenCycleDetected
+        }
+    }
+}
+
+#[test]
+fn cycles_leave_nothing_in_flight() {
+    for use_this in [false, true] {
+        let mut mcts = MCTSManager::new(
+            Ring(0),
+            RingMCTS(use_this),
+            RingEvaluator,
+            UCTPolicy::new(1.0),
+            ApproxTable::new(64),
+        );
+        mcts.playout_n(500);
+        mcts.tree().validate().unwrap();
+        let root = mcts.tree().root_node();
+        assert_eq!(root.moves().map(|x| x.visits()).sum::<u64>(), 500);
+        let mut seen = std::collections::HashSet::new();
+        let mut nodes = vec![mcts.tree().root_node()];
+        while let Some(node) = nodes.pop() {
+            if !seen.insert(node.into_raw()) {
+                continue;
+            }
+            for mov in node.moves() {
+                assert_eq!(mov.in_flight(), 0, "{:?}", mov.get_move());
+                nodes.extend(mov.child());
+            }
+        }
+        // The whole ring is reached; the root isn't in the table, so its position has
+        // a second node. No move is saturated by a leaked playout.
+        assert_eq!(seen.len(), 5);
+        let summary = mcts.playout_n(100);
+        assert_eq!(summary.saturated_selections, 0);
+    }
+}
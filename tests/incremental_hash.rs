@@ -0,0 +1,163 @@
+extern crate mcts;
+
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+const CELLS: usize = 8;
+
+fn zobrist_key(cell: usize) -> u64 {
+    let mut z = (cell as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Fill three cells of the board, in any order. Every order of the same cells
+// reaches the same state, so the tree is full of transpositions.
+#[derive(Clone, Debug, Default)]
+struct Board {
+    cells: [bool; CELLS],
+    filled: u8,
+    broken_hash: bool,
+    // Keep only the high 32 bits of the keys, which a table storing 32-bit keys
+    // would lose.
+    high_bits_only: bool,
+}
+
+impl Board {
+    fn key(&self, cell: usize) -> u64 {
+        if self.high_bits_only {
+            zobrist_key(cell) & !0xFFFF_FFFF
+        } else {
+            zobrist_key(cell)
+        }
+    }
+}
+
+impl GameState for Board {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        if self.filled == 3 {
+            return vec![];
+        }
+        (0..CELLS).filter(|&i| !self.cells[i]).collect()
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.cells[*mov] = true;
+        self.filled += 1;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+impl IncrementallyHashable for Board {
+    fn full_hash(&self) -> u64 {
+        (0..CELLS)
+            .filter(|&i| self.cells[i])
+            .fold(0, |h, i| h ^ self.key(i))
+    }
+    fn hash_after(&self, current: u64, mov: &usize) -> u64 {
+        if self.broken_hash {
+            current
+        } else {
+            current ^ self.key(*mov)
+        }
+    }
+}
+
+struct MyEvaluator;
+
+impl Evaluator<MyMCTS> for MyEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Board,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<MyMCTS>>,
+    ) -> (Vec<()>, usize) {
+        let score = (0..CELLS).filter(|&i| state.cells[i]).sum();
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &Board, evaln: &usize, _: SearchHandle<MyMCTS>) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct MyMCTS;
+
+impl MCTS for MyMCTS {
+    type State = Board;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = IncrementalTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+fn search(board: Board) -> MCTSManager<MyMCTS> {
+    let mut mcts = MCTSManager::new(
+        board,
+        MyMCTS,
+        MyEvaluator,
+        UCTPolicy::new(5.0),
+        IncrementalTable::new(1024).with_verification(true),
+    );
+    mcts.seed(Some(1));
+    mcts.playout_n(2000);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+#[test]
+fn incremental_hashes_find_transpositions() {
+    let mcts = search(Board::default());
+    assert_eq!(mcts.best_move(), Some(CELLS - 1));
+    let diagnosis = mcts.tree().diagnose();
+    assert!(
+        !diagnosis.contains("\n0 transposition table hits"),
+        "{}",
+        diagnosis
+    );
+}
+
+#[test]
+#[should_panic(expected = "incremental hash doesn't match")]
+fn verification_catches_a_broken_hash() {
+    search(Board {
+        broken_hash: true,
+        ..Default::default()
+    });
+}
+
+#[test]
+fn keys_keep_all_64_bits() {
+    let mcts = search(Board {
+        high_bits_only: true,
+        ..Default::default()
+    });
+    assert_eq!(mcts.best_move(), Some(CELLS - 1));
+    let diagnosis = mcts.tree().diagnose();
+    assert!(
+        !diagnosis.contains("\n0 transposition table hits"),
+        "{}",
+        diagnosis
+    );
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "json")]
+
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::export::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+#[test]
+fn json_root_layer_round_trips() {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(1);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    let mut out = Vec::new();
+    tree.write_json(&mut out, &JsonOptions::default()).unwrap();
+    let json = Json::parse(&String::from_utf8(out).unwrap());
+
+    let children = json.get("children").as_array();
+    assert_eq!(children.len(), 4);
+    let child_visits: f64 = children.iter().map(|x| x.get("visits").as_f64()).sum();
+    assert_eq!(json.get("visits").as_f64(), child_visits);
+    assert_eq!(child_visits, 200.0);
+    for (child, mov) in children.iter().zip(tree.root_node().moves()) {
+        assert_eq!(
+            child.get("move"),
+            &Json::String(format!("{:?}", mov.get_move()))
+        );
+        assert_eq!(child.get("visits").as_f64(), mov.visits() as f64);
+        assert_eq!(child.get("prior"), &Json::String("()".into()));
+        assert_eq!(child.get("children").as_array().len(), 3);
+    }
+}
+
+#[test]
+fn json_prunes_by_visits() {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(1);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    let options = JsonOptions {
+        max_depth: 1,
+        min_visits: 1000,
+    };
+    let mut out = Vec::new();
+    tree.write_json(&mut out, &options).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "{\"visits\":200,\"children\":[]}\n"
+    );
+}
+
+#[derive(Default)]
+struct RingMCTS;
+
+impl MCTS for RingMCTS {
+    type State = Ring;
+    type Eval = RingEvaluator;
+    type TreePolicy = UCTPolicy<()>;
+    type NodeData = ();
+    type TranspositionTable = ApproxTable<Self>;
+    type ExtraThreadData = ();
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::UseCurrentEvalWhenCycleDetected
+    }
+}
+
+#[test]
+fn json_stops_at_cycles() {
+    let mut mcts = MCTSManager::new(
+        Ring(0),
+        RingMCTS,
+        RingEvaluator,
+        UCTPolicy::new(1.0),
+        ApproxTable::new(64),
+    );
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    let options = JsonOptions {
+        max_depth: usize::MAX,
+        min_visits: 1,
+    };
+    let mut out = Vec::new();
+    mcts.tree().write_json(&mut out, &options).unwrap();
+    let json = Json::parse(&String::from_utf8(out).unwrap());
+    fn depth(json: &Json) -> usize {
+        let children = json.get("children").as_array();
+        1 + children.iter().map(depth).max().unwrap_or(0)
+    }
+    // The root's position has a node of its own, outside the table, and the table
+    // holds one node per position: at most five nodes along any path.
+    assert!(depth(&json) <= 6, "{}", depth(&json));
+    assert_eq!(json.get("visits").as_f64(), 200.0);
+}
@@ -0,0 +1,104 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn polling_during_a_parallel_search() {
+    let mut mcts = counting_manager(5.0);
+    let live = mcts.live_stats();
+    assert_eq!(live.playouts(), 0);
+    assert_eq!(live.best_move(), None);
+    let poller = live.clone();
+    let search = mcts.playout_parallel_async(4);
+    let mut last = 0;
+    let mut polls = 0;
+    while last < 20_000 {
+        let playouts = poller.playouts();
+        assert!(playouts >= last);
+        let moves = poller.root_stats();
+        let visits: u64 = moves.iter().map(|x| x.visits).sum();
+        // The moves were read after the count, so they include its playouts.
+        assert!(visits >= playouts, "{} < {}", visits, playouts);
+        assert!(moves.iter().all(|x| x.sum_rewards.is_finite()));
+        last = playouts;
+        polls += 1;
+        thread::sleep(Duration::from_millis(1));
+    }
+    search.halt();
+    assert!(polls > 1);
+    let root = mcts.tree().root_node();
+    let most_visited = root.moves().max_by_key(|x| x.visits()).unwrap();
+    assert_eq!(live.best_move(), Some(*most_visited.get_move()));
+    for (x, mov) in live.root_stats().iter().zip(root.moves()) {
+        assert_eq!(x.mov, *mov.get_move());
+        assert_eq!(x.visits, mov.visits());
+        assert!((x.sum_rewards - mov.sum_rewards()).abs() < 1e-6 * x.visits as f64);
+    }
+    assert_eq!(live.playouts(), mcts.tree().root_visits());
+}
+
+#[test]
+fn taken_late_the_view_starts_from_the_tree() {
+    let mut mcts = counting_manager(5.0);
+    mcts.playout_n(500);
+    let live = mcts.live_stats();
+    assert_eq!(live.playouts(), 500);
+    mcts.playout_n(100);
+    assert_eq!(live.playouts(), 600);
+    let visits: Vec<_> = live.root_stats().iter().map(|x| x.visits).collect();
+    let expected: Vec<_> = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect();
+    assert_eq!(visits, expected);
+    assert_eq!(live.best_move(), mcts.best_move());
+}
+
+// `FillMCTS` without a table, with the root's move 0 set aside.
+#[derive(Default)]
+struct ExcludingMCTS;
+
+impl MCTS for ExcludingMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn exclude_move(&self, state: &FillGame, mov: &usize) -> bool {
+        state.filled() == 0 && *mov == 0
+    }
+}
+
+#[test]
+fn excluded_moves() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(4, 2),
+        ExcludingMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    );
+    let live = mcts.live_stats();
+    mcts.playout_n(100);
+    let moves =
+        |live: &LiveStats<usize>| live.root_stats().iter().map(|x| x.mov).collect::<Vec<_>>();
+    assert_eq!(moves(&live), [1, 2, 3]);
+
+    // A move excluded during the search keeps its place and its statistics.
+    assert!(mcts.exclude_root_move(&3));
+    let visits = live.root_stats()[2].visits;
+    mcts.playout_n(100);
+    assert_eq!(moves(&live), [1, 2, 3]);
+    assert_eq!(live.root_stats()[2].visits, visits);
+    assert_eq!(live.playouts(), 200);
+}
@@ -0,0 +1,155 @@
+//! Model checks of the orderings the search relies on, run with
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+//!
+//! Loom runs each model under every interleaving and every value a relaxed load
+//! could see, so a release or acquire weakened to relaxed fails these tests. The
+//! crate's other tests can't run in that build.
+#![cfg(loom)]
+
+extern crate loom;
+extern crate mcts;
+
+use loom::cell::UnsafeCell;
+use loom::sync::Arc;
+use loom::thread;
+use mcts::loom_checked::*;
+use mcts::transposition_table::*;
+use mcts::*;
+
+// Stands for a node: its contents are written by the thread that creates it,
+// before it's published, and read by any thread that follows the pointer.
+struct Node {
+    moves: UnsafeCell<Vec<u32>>,
+}
+
+fn create_node(moves: &[u32]) -> *mut Node {
+    let node = Box::into_raw(Box::new(Node {
+        moves: UnsafeCell::new(Vec::new()),
+    }));
+    unsafe { (*node).moves.with_mut(|x| (*x).extend_from_slice(moves)) };
+    node
+}
+
+fn read_moves(node: *mut Node) -> Vec<u32> {
+    unsafe { (*node).moves.with(|x| (*x).clone()) }
+}
+
+#[test]
+fn expansion_publishes_the_child() {
+    loom::model(|| {
+        let child = Arc::new(PublishedPtr::<Node>::default());
+        let reader = {
+            let child = child.clone();
+            thread::spawn(move || {
+                let node = child.load();
+                if !node.is_null() {
+                    assert_eq!(read_moves(node), [1, 2, 3]);
+                }
+            })
+        };
+        let node = create_node(&[1, 2, 3]);
+        child.publish(node).unwrap();
+        reader.join().unwrap();
+        unsafe { drop(Box::from_raw(node)) };
+    });
+}
+
+#[test]
+fn losing_an_expansion_race_reads_the_winner() {
+    loom::model(|| {
+        let child = Arc::new(PublishedPtr::<Node>::default());
+        let expand = |moves: &'static [u32]| {
+            let child = child.clone();
+            thread::spawn(move || {
+                let node = create_node(moves);
+                match child.publish(node) {
+                    Ok(()) => None,
+                    Err(existing) => {
+                        // The loser discards its node and carries on with the
+                        // winner's, which it must see in full.
+                        unsafe { drop(Box::from_raw(node)) };
+                        assert!(read_moves(existing).len() == 2);
+                        Some(())
+                    }
+                }
+            })
+        };
+        let a = expand(&[1, 2]);
+        let b = expand(&[3, 4]);
+        let lost = [a.join().unwrap(), b.join().unwrap()];
+        assert_eq!(lost.iter().filter(|x| x.is_some()).count(), 1);
+        unsafe { drop(Box::from_raw(child.load())) };
+    });
+}
+
+#[test]
+fn live_stats_account_for_the_playouts_reported() {
+    loom::model(|| {
+        let recorder = LiveRecorder::new(2);
+        let stats = recorder.stats();
+        let search = thread::spawn(move || {
+            recorder.record(0, 1.0);
+            recorder.record(1, 0.0);
+        });
+        let playouts = stats.playouts();
+        let visits: u64 = stats.root_stats().iter().map(|x| x.visits).sum();
+        assert!(visits >= playouts, "{} < {}", visits, playouts);
+        search.join().unwrap();
+        assert_eq!(stats.playouts(), 2);
+    });
+}
+
+#[test]
+fn concurrent_backpropagation_into_one_move() {
+    let loss = VirtualLossMode::VisitsAndReward(1, 0.5);
+    loom::model(move || {
+        let stats = Arc::new(MoveStats::default());
+        let playout = |reward: f64| {
+            let stats = stats.clone();
+            thread::spawn(move || {
+                stats.down(loss);
+                stats.up(loss, reward);
+            })
+        };
+        let a = playout(1.0);
+        let b = playout(2.0);
+        a.join().unwrap();
+        b.join().unwrap();
+        // Neither playout's update, nor its virtual loss, is lost or left behind.
+        assert_eq!(stats.visits(), 2);
+        assert_eq!(stats.sum_rewards(), 3.0);
+    });
+}
+
+#[test]
+fn racing_inserts_share_one_node() {
+    loom::model(|| {
+        let table = Arc::new(ApproxQuadraticProbingHashTable::<(), Node>::new(4));
+        let insert = |moves: &'static [u32]| {
+            let table = table.clone();
+            thread::spawn(move || {
+                let node = create_node(moves);
+                match table_insert(&table, 7, unsafe { &*node }) {
+                    None => false,
+                    Some(existing) => {
+                        // The loser discards its node and uses the winner's, which
+                        // it must see in full.
+                        unsafe { drop(Box::from_raw(node)) };
+                        assert_eq!(read_moves(existing as *const _ as *mut _).len(), 2);
+                        true
+                    }
+                }
+            })
+        };
+        let a = insert(&[1, 2]);
+        let b = insert(&[3, 4]);
+        let lost = [a.join().unwrap(), b.join().unwrap()];
+        assert_eq!(lost.iter().filter(|&&x| x).count(), 1);
+        let winner = table_lookup(&table, 7).unwrap() as *const Node as *mut Node;
+        assert_eq!(read_moves(winner).len(), 2);
+        unsafe { drop(Box::from_raw(winner)) };
+    });
+}
@@ -0,0 +1,153 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Scores like `FillEvaluator`, and counts the states it evaluated.
+#[derive(Default)]
+struct MeteredEvaluator {
+    evaluated: AtomicUsize,
+}
+
+impl Evaluator<MeteredMCTS> for MeteredEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<MeteredMCTS>>,
+    ) -> (Vec<()>, usize) {
+        self.evaluated.fetch_add(1, Ordering::Relaxed);
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &usize,
+        _: SearchHandle<MeteredMCTS>,
+    ) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct MeteredMCTS;
+
+impl MCTS for MeteredMCTS {
+    type State = FillGame;
+    type Eval = MeteredEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+fn manager() -> MCTSManager<MeteredMCTS> {
+    MCTSManager::builder(MeteredMCTS)
+        .state(FillGame::new(5, 3))
+        .evaluator(MeteredEvaluator::default())
+        .policy(UCTPolicy::new(5.0))
+        .transposition_table(ApproxTable::new(1024))
+        .seed(11)
+        .build()
+}
+
+fn root_stats(mcts: &MCTSManager<MeteredMCTS>) -> Vec<NodeStats> {
+    mcts.tree().root_node().moves().map(|x| x.stats()).collect()
+}
+
+#[test]
+fn accessors_see_the_owned_components() {
+    let mut mcts = manager();
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    // The root and at most 25 more distinct states.
+    let evaluated = mcts.evaluator().evaluated.load(Ordering::Relaxed);
+    assert!(
+        evaluated > 1 && evaluated <= 26,
+        "{} evaluations",
+        evaluated
+    );
+    assert_eq!(evaluated, mcts.tree().num_nodes());
+    assert_eq!(*mcts.root_state(), FillGame::new(5, 3));
+    assert_eq!(mcts.tree_policy().exploration_constant(), 5.0);
+    let _: &MeteredMCTS = mcts.spec();
+}
+
+#[test]
+fn parts_round_trip() {
+    let mut mcts = manager();
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    let stats = root_stats(&mcts);
+    let pv = mcts.principal_variation(3);
+    mcts.resign_patience(5).set_resign_streak(2);
+
+    let parts = mcts.into_parts();
+    assert_eq!(parts.seed, Some(11));
+    assert!(parts.print_on_playout_error);
+    assert_eq!(parts.resign_patience, 5);
+    assert_eq!(parts.resign_streak, 2);
+    let evaluated = parts
+        .search_tree
+        .evaluator()
+        .evaluated
+        .load(Ordering::Relaxed);
+
+    let mut mcts = MCTSManager::from_parts(parts);
+    assert_eq!(mcts.resign_streak(), 2);
+    assert_eq!(root_stats(&mcts), stats);
+    assert_eq!(mcts.principal_variation(3), pv);
+    assert_eq!(
+        mcts.evaluator().evaluated.load(Ordering::Relaxed),
+        evaluated
+    );
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    let visits: u64 = root_stats(&mcts).iter().map(|x| x.visits).sum();
+    assert_eq!(visits, 300);
+}
+
+#[test]
+fn parts_come_out_of_the_tree() {
+    let mut mcts = manager();
+    mcts.playout_n(200);
+    let evaluated = mcts.evaluator().evaluated.load(Ordering::Relaxed);
+
+    let parts = mcts.into_parts().search_tree.into_parts();
+    assert_eq!(parts.root_state, FillGame::new(5, 3));
+    assert_eq!(parts.tree_policy.exploration_constant(), 5.0);
+    assert_eq!(parts.evaluator.evaluated.load(Ordering::Relaxed), evaluated);
+    assert_eq!(parts.transposition_table.capacity(), 1024);
+
+    // A new search with the same components.
+    let mut mcts = MCTSManager::new(
+        parts.root_state,
+        parts.spec,
+        parts.evaluator,
+        parts.tree_policy,
+        parts.transposition_table,
+    );
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    // The table's entries went with the old nodes, so the states are evaluated again.
+    assert_eq!(
+        mcts.evaluator().evaluated.load(Ordering::Relaxed),
+        evaluated + mcts.tree().num_nodes()
+    );
+    let visits: u64 = root_stats(&mcts).iter().map(|x| x.visits).sum();
+    assert_eq!(visits, 100);
+}
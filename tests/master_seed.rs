@@ -0,0 +1,73 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+fn manager(seed: u64) -> MCTSManager<FillMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(12, 4),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1 << 12),
+    );
+    mcts.seed(Some(seed));
+    mcts
+}
+
+fn root_visits(mcts: &MCTSManager<FillMCTS>) -> Vec<u64> {
+    mcts.tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect()
+}
+
+#[test]
+fn same_master_seed_same_move_with_four_threads() {
+    // X to move wins on 2; every other move lets O complete its row on 5.
+    let search = || {
+        let mut mcts = TwoPlayerZeroSumSpec::manager(TicTacToe {
+            cells: [1, 1, 0, 2, 2, 0, 1, 0, 0],
+        });
+        mcts.seed(Some(7));
+        mcts.playout_n_parallel(3000, 4);
+        mcts.tree().validate().unwrap();
+        mcts
+    };
+    let (a, b) = (search(), search());
+    assert_eq!(a.best_move(), b.best_move());
+    assert_eq!(a.tree().root_visits(), b.tree().root_visits());
+}
+
+#[test]
+fn same_master_seed_same_search_on_one_thread() {
+    let mut a = manager(7);
+    let mut b = manager(7);
+    a.playout_n(2000);
+    b.playout_n(2000);
+    a.tree().validate().unwrap();
+    assert_eq!(root_visits(&a), root_visits(&b));
+    assert_eq!(a.principal_variation(4), b.principal_variation(4));
+
+    // A search on a single worker thread is seeded like the manager's own thread.
+    let mut c = manager(7);
+    c.playout_n_parallel(2000, 1);
+    assert_eq!(root_visits(&a), root_visits(&c));
+}
+
+#[test]
+fn master_seeds_change_the_search() {
+    let mut first = manager(0);
+    first.playout_n(500);
+    let differs = (1..5).any(|seed| {
+        let mut other = manager(seed);
+        other.playout_n(500);
+        root_visits(&other) != root_visits(&first)
+    });
+    assert!(differs);
+}
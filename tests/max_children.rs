@@ -0,0 +1,181 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+use std::time::{Duration, Instant};
+
+const WIDTH: u16 = 1000;
+const PLANTED: u16 = 737;
+
+/// Three moves of `WIDTH` choices each. Only starting with `PLANTED` scores.
+#[derive(Clone, Default)]
+struct Wide(Vec<u16>);
+
+impl GameState for Wide {
+    type Move = u16;
+    type Player = ();
+    type MoveList = Vec<u16>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u16> {
+        if self.0.len() == 3 {
+            vec![]
+        } else {
+            (0..WIDTH).collect()
+        }
+    }
+    fn make_move(&mut self, mov: &u16) -> Result<(), ()> {
+        self.0.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+/// A good prior: the planted move gets a tenth of the probability.
+struct WideEvaluator;
+
+impl Evaluator<WideMCTS> for WideEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Wide,
+        moves: &Vec<u16>,
+        _: Option<SearchHandle<WideMCTS>>,
+    ) -> (Vec<f64>, f64) {
+        let other = 0.9 / (WIDTH - 1) as f64;
+        let priors = moves
+            .iter()
+            .map(|&x| if x == PLANTED { 0.1 } else { other })
+            .collect();
+        let value = if state.0.first() == Some(&PLANTED) {
+            1.0
+        } else {
+            0.0
+        };
+        (priors, value)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &Wide, evaln: &f64, _: SearchHandle<WideMCTS>) -> f64 {
+        *evaln
+    }
+}
+
+struct WideMCTS {
+    max_children: usize,
+    random_extra_children: usize,
+}
+
+impl MCTS for WideMCTS {
+    type State = Wide;
+    type Eval = WideEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+
+    fn max_children(&self) -> usize {
+        self.max_children
+    }
+    fn random_extra_children(&self) -> usize {
+        self.random_extra_children
+    }
+}
+
+fn manager(max_children: usize, random_extra_children: usize) -> MCTSManager<WideMCTS> {
+    let mut mcts = MCTSManager::new(
+        Wide::default(),
+        WideMCTS {
+            max_children,
+            random_extra_children,
+        },
+        WideEvaluator,
+        AlphaGoPolicy::new(1.0),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts
+}
+
+/// The number of playouts `mcts` completes in `time`.
+fn playouts_in(mcts: &mut MCTSManager<WideMCTS>, time: Duration) -> u64 {
+    let start = Instant::now();
+    let mut playouts = 0;
+    while start.elapsed() < time {
+        mcts.playout_n(10);
+        playouts += 10;
+    }
+    playouts
+}
+
+#[test]
+fn prunes_to_the_best_priors() {
+    let mut mcts = manager(32, 0);
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    let root = mcts.tree().root_node();
+    assert_eq!(root.moves().count(), 32);
+    assert_eq!(root.pruned_moves(), WIDTH as usize - 32);
+    assert_eq!(mcts.root_pruned_moves(), WIDTH as usize - 32);
+    // The planted move, then the first of the ties, in their original order.
+    let kept: Vec<u16> = root.moves().map(|x| *x.get_move()).collect();
+    let mut expected: Vec<u16> = vec![PLANTED];
+    expected.extend(0..31);
+    expected.sort();
+    assert_eq!(kept, expected);
+    assert!(mcts
+        .tree()
+        .format_moves(MoveSort::Visits, None)
+        .ends_with("(968 moves pruned by max_children)\n"));
+}
+
+#[test]
+fn unlimited_by_default() {
+    let mut mcts = manager(usize::MAX, 0);
+    mcts.playout_n(10);
+    assert_eq!(mcts.tree().root_node().moves().count(), WIDTH as usize);
+    assert_eq!(mcts.root_pruned_moves(), 0);
+    assert!(!mcts
+        .tree()
+        .format_moves(MoveSort::Visits, None)
+        .contains("pruned"));
+}
+
+#[test]
+fn random_extra_children_are_reproducible() {
+    let kept = |seed| {
+        let mut mcts = manager(8, 8);
+        mcts.seed(Some(seed));
+        mcts.playout_n(50);
+        let child = mcts.tree().root_node().descend(&PLANTED).unwrap();
+        assert_eq!(child.moves().count(), 16);
+        assert_eq!(child.pruned_moves(), WIDTH as usize - 16);
+        child.moves().map(|x| *x.get_move()).collect::<Vec<u16>>()
+    };
+    assert_eq!(kept(1), kept(1));
+    assert_ne!(kept(1), kept(2));
+}
+
+#[test]
+fn more_playouts_in_the_same_time() {
+    let time = Duration::from_millis(500);
+    let mut pruned = manager(32, 0);
+    let mut full = manager(usize::MAX, 0);
+    let pruned_playouts = playouts_in(&mut pruned, time);
+    let full_playouts = playouts_in(&mut full, time);
+    assert!(
+        pruned_playouts >= 10 * full_playouts,
+        "{} playouts pruned against {}",
+        pruned_playouts,
+        full_playouts
+    );
+    pruned.tree().validate().unwrap();
+    full.tree().validate().unwrap();
+    assert_eq!(pruned.best_move(), Some(PLANTED));
+}
@@ -0,0 +1,104 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// Pick two digits; the score is their sum. Priors follow the digits, so a small
+// search never visits 0.
+fn score(digits: &[u8]) -> f64 {
+    digits.iter().sum::<u8>() as f64 / 18.0
+}
+
+#[derive(Default)]
+struct DigitsMCTS {
+    exclude_odd: bool,
+}
+
+impl MCTS for DigitsMCTS {
+    type State = Digits;
+    type Eval = DigitsEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+
+    fn exclude_move(&self, _: &Digits, mov: &u8) -> bool {
+        self.exclude_odd && mov % 2 == 1
+    }
+}
+
+fn manager(spec: DigitsMCTS, min_root_visits: u64) -> MCTSManager<DigitsMCTS> {
+    MCTSManager::builder(spec)
+        .state(Digits::new(2))
+        .evaluator(DigitsEvaluator(score))
+        .policy(AlphaGoPolicy::new(0.5))
+        .transposition_table(())
+        .seed(0)
+        .min_root_visits(min_root_visits)
+        .build()
+}
+
+fn root_visits(mcts: &MCTSManager<DigitsMCTS>) -> Vec<(u8, u64)> {
+    let moves = mcts.tree().root_node().moves();
+    moves.map(|x| (*x.get_move(), x.visits())).collect()
+}
+
+#[test]
+fn small_searches_leave_moves_unvisited_without_a_floor() {
+    let mut mcts = manager(DigitsMCTS::default(), 0);
+    let summary = mcts.playout_n(40);
+    assert_eq!(summary.floor_visits, 0);
+    assert!(root_visits(&mcts).contains(&(0, 0)));
+}
+
+#[test]
+fn every_root_move_reaches_the_floor() {
+    let mut mcts = manager(DigitsMCTS::default(), 3);
+    assert_eq!(mcts.tree().min_root_visits(), 3);
+    let summary = mcts.playout_n(40);
+    mcts.tree().validate().unwrap();
+    let visits = root_visits(&mcts);
+    assert_eq!(visits.len(), 10);
+    assert!(visits.iter().all(|x| x.1 >= 3), "{:?}", visits);
+    // The tree policy takes over once every move has its visits.
+    assert_eq!(summary.floor_visits, 30);
+    assert_eq!(mcts.tree().floor_visits(), 30);
+    assert!(summary
+        .to_string()
+        .contains("30 playouts spent on the root exploration floor"));
+    assert_eq!(mcts.playout_n(20).floor_visits, 0);
+}
+
+#[test]
+fn floor_is_skipped_when_the_budget_is_too_small() {
+    let mut mcts = manager(DigitsMCTS::default(), 3);
+    assert_eq!(mcts.playout_n(29).floor_visits, 0);
+    assert!(root_visits(&mcts).contains(&(0, 0)));
+    // The next, larger search applies it.
+    let summary = mcts.playout_n(40);
+    assert!(summary.floor_visits > 0);
+    assert!(root_visits(&mcts).iter().all(|x| x.1 >= 3));
+}
+
+#[test]
+fn excluded_moves_need_no_visits() {
+    let mut mcts = manager(DigitsMCTS { exclude_odd: true }, 4);
+    // Five moves remain, so 20 playouts are enough.
+    let summary = mcts.playout_n(20);
+    mcts.tree().validate().unwrap();
+    assert_eq!(summary.floor_visits, 20);
+    let visits = root_visits(&mcts);
+    assert_eq!(visits, vec![(0, 4), (2, 4), (4, 4), (6, 4), (8, 4)]);
+}
+
+#[test]
+fn parallel_searches_reach_the_floor() {
+    let mut mcts = manager(DigitsMCTS::default(), 5);
+    let summary = mcts.playout_n_parallel(200, 4);
+    mcts.tree().validate().unwrap();
+    assert!(summary.floor_visits >= 50);
+    assert!(root_visits(&mcts).iter().all(|x| x.1 >= 5));
+}
@@ -0,0 +1,132 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+use std::cell::Cell;
+
+thread_local! {
+    static CLONES: Cell<usize> = const { Cell::new(0) };
+}
+
+fn clones() -> usize {
+    CLONES.with(|x| x.get())
+}
+
+// A move with a payload that counts its clones on this thread.
+#[derive(Debug, PartialEq)]
+struct BigMove {
+    add: bool,
+    payload: Box<[u8; 64]>,
+}
+
+impl Clone for BigMove {
+    fn clone(&self) -> Self {
+        CLONES.with(|x| x.set(x.get() + 1));
+        Self {
+            add: self.add,
+            payload: self.payload.clone(),
+        }
+    }
+}
+
+// Count towards 50 by adding or subtracting 1; the count is the evaluation.
+#[derive(Clone, Debug, Default, PartialEq, Hash)]
+struct Counter(i64);
+
+impl GameState for Counter {
+    type Move = BigMove;
+    type Player = ();
+    type MoveList = Vec<BigMove>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<BigMove> {
+        if self.0 == 50 {
+            return vec![];
+        }
+        [true, false]
+            .iter()
+            .map(|&add| BigMove {
+                add,
+                payload: Box::new([0; 64]),
+            })
+            .collect()
+    }
+    fn make_move(&mut self, mov: &BigMove) -> Result<(), ()> {
+        self.0 += if mov.add { 1 } else { -1 };
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct MyEvaluator;
+
+impl Evaluator<MyMCTS> for MyEvaluator {
+    type StateEvaluation = i64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Counter,
+        moves: &Vec<BigMove>,
+        _: Option<SearchHandle<MyMCTS>>,
+    ) -> (Vec<()>, i64) {
+        (vec![(); moves.len()], state.0)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &i64, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &Counter, evaln: &i64, _: SearchHandle<MyMCTS>) -> i64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct MyMCTS;
+
+impl MCTS for MyMCTS {
+    type State = Counter;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+fn searched() -> MCTSManager<MyMCTS> {
+    let mut mcts = MCTSManager::new(Counter(0), MyMCTS, MyEvaluator, UCTPolicy::new(5.0), ());
+    mcts.playout_n(2000);
+    mcts
+}
+
+#[test]
+fn reading_the_tree_clones_no_moves() {
+    let mcts = searched();
+    let nodes = mcts.tree().num_nodes();
+    assert!(nodes > 50);
+    let before = clones();
+
+    let pv = mcts.principal_variation_refs(10);
+    assert_eq!(pv.len(), 10);
+    assert!(pv.iter().all(|x| x.add));
+    let stats = mcts.root_move_stats_ref();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats.iter().map(|x| x.visits).sum::<u64>(), 2000);
+    assert_eq!(mcts.root_move_stats_detailed().len(), 2);
+    assert_eq!(mcts.tree().iter_nodes(1).count(), nodes);
+    assert_eq!(mcts.principal_variation_states(10).len(), 11);
+    assert_eq!(clones(), before);
+}
+
+#[test]
+fn principal_variation_clones_once_per_ply() {
+    let mcts = searched();
+    let before = clones();
+    let pv = mcts.principal_variation(10);
+    assert_eq!(pv.len(), 10);
+    assert_eq!(clones() - before, 10);
+    assert_eq!(mcts.best_move().map(|x| x.add), Some(true));
+    assert_eq!(clones() - before, 11);
+}
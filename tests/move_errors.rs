@@ -0,0 +1,177 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+
+// Pick three of the digits 0 to 4, scoring their sum, except that picking a digit
+// in `broken` fails. A failed move leaves the state unchanged.
+#[derive(Clone, Debug, Default)]
+struct FlakyGame {
+    picked: Vec<u8>,
+    broken: Vec<u8>,
+}
+
+impl FlakyGame {
+    fn new(broken: &[u8]) -> Self {
+        FlakyGame {
+            picked: Vec::new(),
+            broken: broken.to_vec(),
+        }
+    }
+}
+
+impl GameState for FlakyGame {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.picked.len() == 3 {
+            vec![]
+        } else {
+            (0..5).collect()
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        if self.broken.contains(mov) {
+            return Err(());
+        }
+        self.picked.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct SumEvaluator;
+
+impl Evaluator<FlakyMCTS> for SumEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FlakyGame,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<FlakyMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let sum: u8 = state.picked.iter().sum();
+        (vec![(); moves.len()], sum as f64)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FlakyGame,
+        evaln: &f64,
+        _: SearchHandle<FlakyMCTS>,
+    ) -> f64 {
+        *evaln
+    }
+}
+
+struct FlakyMCTS(MoveErrorBehaviour, Option<u8>);
+
+impl MCTS for FlakyMCTS {
+    type State = FlakyGame;
+    type Eval = SumEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn move_error_behaviour(&self) -> MoveErrorBehaviour {
+        self.0
+    }
+    fn exclude_move(&self, _: &FlakyGame, mov: &u8) -> bool {
+        self.1 == Some(*mov)
+    }
+}
+
+fn manager(behaviour: MoveErrorBehaviour, broken: &[u8]) -> MCTSManager<FlakyMCTS> {
+    MCTSManager::new(
+        FlakyGame::new(broken),
+        FlakyMCTS(behaviour, None),
+        SumEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    )
+}
+
+fn root_move(mcts: &MCTSManager<FlakyMCTS>, mov: u8) -> &MoveInfo<FlakyMCTS> {
+    let mut moves = mcts.tree().root_node().moves();
+    moves.find(|x| *x.get_move() == mov).unwrap()
+}
+
+#[test]
+#[should_panic(expected = "make_move failed")]
+fn panics_by_default() {
+    let mut mcts = manager(MoveErrorBehaviour::Panic, &[4]);
+    mcts.playout_n(20);
+}
+
+#[test]
+fn skip_and_exclude_never_selects_the_move_again() {
+    let mut mcts = manager(MoveErrorBehaviour::SkipAndExclude, &[4]);
+    let summary = mcts.playout_n(300);
+    mcts.tree().validate().unwrap();
+    assert_eq!(summary.playouts, 300);
+    assert!(summary.move_errors > 0);
+    assert_eq!(summary.move_errors, mcts.tree().move_errors() as u64);
+    let broken = root_move(&mcts, 4);
+    assert!(broken.is_excluded());
+    assert_eq!(broken.visits(), 0);
+    assert!(!root_move(&mcts, 3).is_excluded());
+    assert_eq!(mcts.best_move(), Some(3));
+    // On one thread, a move fails at most once per node.
+    mcts.playout_n(300);
+    assert!(mcts.tree().move_errors() <= mcts.tree().num_nodes());
+}
+
+#[test]
+fn skip_and_exclude_evaluates_a_node_without_selectable_moves() {
+    let mut mcts = manager(MoveErrorBehaviour::SkipAndExclude, &[0, 1, 2, 3, 4]);
+    let summary = mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+    assert_eq!(summary.playouts, 10);
+    assert_eq!(summary.move_errors, 5);
+    assert!(mcts.tree().root_node().moves().all(|x| x.is_excluded()));
+}
+
+#[test]
+fn abort_playout_counts_the_error_and_backpropagates_nothing() {
+    let mut mcts = manager(MoveErrorBehaviour::AbortPlayout, &[4]);
+    let summary = mcts.playout_n(300);
+    mcts.tree().validate().unwrap();
+    assert!(summary.move_errors > 0);
+    assert_eq!(summary.playouts + summary.move_errors, 300);
+    assert!(summary
+        .to_string()
+        .contains("moves failed during tree descent"));
+    let broken = root_move(&mcts, 4);
+    assert!(!broken.is_excluded());
+    assert_eq!(broken.visits(), 0);
+    let visits: u64 = mcts.tree().root_node().moves().map(|x| x.visits()).sum();
+    assert_eq!(visits, summary.playouts);
+}
+
+#[test]
+fn moves_excluded_by_the_spec_are_marked() {
+    let mut mcts = MCTSManager::new(
+        FlakyGame::new(&[]),
+        FlakyMCTS(MoveErrorBehaviour::Panic, Some(0)),
+        SumEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    );
+    mcts.playout_n(50);
+    let root = mcts.tree().root_node();
+    let excluded: Vec<_> = root.excluded_moves().collect();
+    assert_eq!(excluded.len(), 1);
+    assert!(excluded[0].is_excluded());
+    assert!(root.moves().all(|x| !x.is_excluded()));
+}
@@ -0,0 +1,243 @@
+mod common;
+
+use common::FillGame;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+/// Plays `FillGame` with PUCT and updatable priors. Every reward is zero, so the
+/// priors alone decide where the search goes.
+#[derive(Default)]
+struct PriorMCTS {
+    /// Flip the root priors after every playout.
+    flip: bool,
+    flips: AtomicUsize,
+}
+
+struct UniformPriors;
+
+impl Evaluator<PriorMCTS> for UniformPriors {
+    type StateEvaluation = ();
+
+    fn evaluate_new_state(
+        &self,
+        _: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<PriorMCTS>>,
+    ) -> (Vec<AtomicPrior>, ()) {
+        let prior = 1.0 / moves.len() as f64;
+        (moves.iter().map(|_| AtomicPrior::new(prior)).collect(), ())
+    }
+    fn interpret_evaluation_for_player(&self, _: &(), _: &()) -> f64 {
+        0.0
+    }
+    fn evaluate_existing_state(&self, _: &FillGame, _: &(), _: SearchHandle<PriorMCTS>) {}
+}
+
+impl MCTS for PriorMCTS {
+    type State = FillGame;
+    type Eval = UniformPriors;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy<AtomicPrior>;
+    type TranspositionTable = ();
+
+    fn after_backpropagation(&self, path: &[&MoveInfo<Self>], _: &()) {
+        if self.flip {
+            let n = self.flips.fetch_add(1, Ordering::Relaxed);
+            path[0].update_move_evaluation(if n.is_multiple_of(2) { 0.25 } else { 0.75 });
+        }
+    }
+}
+
+fn manager(flip: bool) -> MCTSManager<PriorMCTS> {
+    MCTSManager::new(
+        FillGame::new(4, 2),
+        PriorMCTS {
+            flip,
+            ..Default::default()
+        },
+        UniformPriors,
+        AlphaGoPolicy::with_exploration_constant(1.0),
+        (),
+    )
+}
+
+#[test]
+fn updated_prior_steers_the_search() {
+    let mut mcts = manager(false);
+    mcts.playout_n(400);
+    mcts.tree().validate().unwrap();
+    let before: Vec<u64> = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect();
+    for mov in mcts.tree().root_node().moves() {
+        let prior = if *mov.get_move() == 2 { 1.0 } else { 0.0 };
+        mov.update_move_evaluation(prior);
+    }
+    mcts.playout_n(400);
+    mcts.tree().validate().unwrap();
+    for mov in mcts.tree().root_node().moves() {
+        let gained = mov.visits() - before[*mov.get_move()];
+        if *mov.get_move() == 2 {
+            assert!(gained > 300, "boosted move gained only {} visits", gained);
+        }
+        assert_eq!(
+            mov.move_evaluation().prior(),
+            if *mov.get_move() == 2 { 1.0 } else { 0.0 }
+        );
+    }
+}
+
+#[test]
+fn updates_during_parallel_search() {
+    let mut mcts = manager(true);
+    mcts.playout_n_parallel(4000, 4);
+    mcts.tree().validate().unwrap();
+    // The root has four moves, so the initial priors are 0.25 as well.
+    for mov in mcts.tree().root_node().moves() {
+        let prior = mov.move_evaluation().prior();
+        assert!(prior == 0.25 || prior == 0.75, "unexpected prior {}", prior);
+    }
+}
+
+#[test]
+fn locked_evaluations_are_never_torn() {
+    let slot = Arc::new(RwLock::new((0u64, 0u64)));
+    let writers: Vec<_> = (0..4u64)
+        .map(|t| {
+            let slot = slot.clone();
+            thread::spawn(move || {
+                for i in 0..10_000u64 {
+                    let x = t * 1_000_000 + i;
+                    slot.store((x, !x));
+                }
+            })
+        })
+        .collect();
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let slot = slot.clone();
+            thread::spawn(move || {
+                for _ in 0..10_000 {
+                    let (a, b) = slot.load();
+                    assert!(a == 0 && b == 0 || b == !a, "torn read ({}, {})", a, b);
+                }
+            })
+        })
+        .collect();
+    for x in writers.into_iter().chain(readers) {
+        x.join().unwrap();
+    }
+}
+
+#[test]
+fn atomic_priors_read_whole_values() {
+    let prior = Arc::new(AtomicPrior::new(0.5));
+    // Exact in f32 too, for the `f32_stats` feature.
+    let values = [0.5, 0.125, 0.375, 0.90625];
+    let threads: Vec<_> = (0..8)
+        .map(|t| {
+            let prior = prior.clone();
+            thread::spawn(move || {
+                for i in 0..10_000 {
+                    if t % 2 == 0 {
+                        prior.store(values[(i + t) % values.len()]);
+                    } else {
+                        let x = prior.load();
+                        assert!(values.contains(&x), "torn read {}", x);
+                    }
+                }
+            })
+        })
+        .collect();
+    for x in threads {
+        x.join().unwrap();
+    }
+}
+
+/// Like `PriorMCTS`, but the evaluator favours a move that can change between
+/// evaluations, standing in for an evaluator that conditions on move history.
+#[derive(Default)]
+struct ContextMCTS;
+
+struct ContextPriors {
+    favourite: AtomicUsize,
+}
+
+impl Evaluator<ContextMCTS> for ContextPriors {
+    type StateEvaluation = ();
+
+    fn evaluate_new_state(
+        &self,
+        _: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<ContextMCTS>>,
+    ) -> (Vec<AtomicPrior>, ()) {
+        let favourite = self.favourite.load(Ordering::Relaxed);
+        let weight = |x: &usize| if *x == favourite { 7.0 } else { 1.0 };
+        let total: f64 = moves.iter().map(weight).sum();
+        let priors = moves.iter().map(|x| AtomicPrior::new(weight(x) / total));
+        (priors.collect(), ())
+    }
+    fn interpret_evaluation_for_player(&self, _: &(), _: &()) -> f64 {
+        0.0
+    }
+    fn evaluate_existing_state(&self, _: &FillGame, _: &(), _: SearchHandle<ContextMCTS>) {}
+}
+
+impl MCTS for ContextMCTS {
+    type State = FillGame;
+    type Eval = ContextPriors;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy<AtomicPrior>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn refreshed_root_keeps_statistics_and_takes_new_priors() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(4, 2),
+        ContextMCTS,
+        ContextPriors {
+            favourite: AtomicUsize::new(0),
+        },
+        AlphaGoPolicy::with_exploration_constant(1.0),
+        (),
+    );
+    mcts.playout_n(400);
+    let stats = |mcts: &MCTSManager<ContextMCTS>| -> Vec<(usize, u64, f64)> {
+        let root = mcts.tree().root_node();
+        root.moves()
+            .map(|x| (*x.get_move(), x.visits(), x.sum_rewards()))
+            .collect()
+    };
+    let before = stats(&mcts);
+    mcts.evaluator().favourite.store(3, Ordering::Relaxed);
+    assert_eq!(mcts.refresh_root_evaluation(), 4);
+    assert_eq!(stats(&mcts), before);
+    for mov in mcts.tree().root_node().moves() {
+        let expected = if *mov.get_move() == 3 { 0.7 } else { 0.1 };
+        // Priors are f32 under `f32_stats`.
+        assert!((mov.move_evaluation().prior() - expected).abs() < 1e-6);
+    }
+    // Deeper nodes keep their evaluations, favouring move 0.
+    let root = mcts.tree().root_node();
+    let child = root.moves().find(|x| *x.get_move() == 1).unwrap();
+    for mov in child.child().unwrap().moves() {
+        let expected = if *mov.get_move() == 0 { 7.0 } else { 1.0 } / 9.0;
+        assert!((mov.move_evaluation().prior() - expected).abs() < 1e-6);
+    }
+    let visits_of_3 = before.iter().find(|x| x.0 == 3).unwrap().1;
+    mcts.playout_n(400);
+    mcts.tree().validate().unwrap();
+    let root = mcts.tree().root_node();
+    let now = root.moves().find(|x| *x.get_move() == 3).unwrap();
+    assert!(now.visits() - visits_of_3 > 200, "{}", now.visits());
+}
@@ -0,0 +1,116 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+
+// One move picks a number and ends the game; the score is the number picked.
+#[derive(Clone, Debug, Default)]
+struct PickGame(Option<u8>);
+
+impl GameState for PickGame {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        match self.0 {
+            Some(_) => vec![],
+            None => vec![0, 1, 2, 3],
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0 = Some(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+    fn move_order_hint(&self, mov: &u8) -> i32 {
+        // Odd numbers first, largest first.
+        (*mov as i32 % 2) * 10 + *mov as i32
+    }
+}
+
+struct MyEvaluator;
+
+impl Evaluator<MyMCTS> for MyEvaluator {
+    type StateEvaluation = u8;
+
+    fn evaluate_new_state(
+        &self,
+        state: &PickGame,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<MyMCTS>>,
+    ) -> (Vec<()>, u8) {
+        (vec![(); moves.len()], state.0.unwrap_or(0))
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &u8, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &PickGame, evaln: &u8, _: SearchHandle<MyMCTS>) -> u8 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct MyMCTS;
+
+impl MCTS for MyMCTS {
+    type State = PickGame;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn children_are_sorted_by_hint() {
+    let mcts = MCTSManager::new(
+        PickGame::default(),
+        MyMCTS,
+        MyEvaluator,
+        UCTPolicy::new(1.0),
+        (),
+    );
+    let order: Vec<u8> = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .map(|x| *x.get_move())
+        .collect();
+    assert_eq!(order, vec![3, 1, 2, 0]);
+}
+
+#[test]
+fn first_move_tie_break_visits_hinted_moves_first() {
+    let mut mcts = MCTSManager::new(
+        PickGame::default(),
+        MyMCTS,
+        MyEvaluator,
+        UCTPolicy::new(1.0).with_tie_break(TieBreak::FirstMove),
+        (),
+    );
+    let mut visited = Vec::new();
+    for _ in 0..4 {
+        let before: Vec<u64> = mcts
+            .tree()
+            .root_node()
+            .moves()
+            .map(|x| x.visits())
+            .collect();
+        mcts.playout();
+        let root = mcts.tree().root_node();
+        let (mov, _) = root
+            .moves()
+            .zip(before)
+            .find(|(x, before)| x.visits() > *before)
+            .unwrap();
+        visited.push(*mov.get_move());
+    }
+    mcts.tree().validate().unwrap();
+    assert_eq!(visited, vec![3, 1, 2, 0]);
+}
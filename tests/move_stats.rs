@@ -0,0 +1,85 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+#[test]
+fn detailed_stats_match_uct() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(5, 2),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    mcts.playout_n(300);
+    mcts.tree().validate().unwrap();
+    let stats = mcts.root_move_stats_detailed();
+    assert_eq!(stats.len(), 5);
+    let parent_visits: u64 = stats.iter().map(|x| x.visits).sum();
+    for x in &stats {
+        assert_eq!(x.visits, x.move_info.visits());
+        let expected = 5.0 * ((parent_visits as f64).ln() / x.visits as f64).sqrt() + x.mean;
+        assert!((x.policy_score - expected).abs() < 1e-9);
+        #[cfg(feature = "reward_variance")]
+        {
+            let (low, high) = x.confidence_interval.unwrap();
+            assert!(
+                low <= x.mean && x.mean <= high,
+                "{} {} {}",
+                low,
+                x.mean,
+                high
+            );
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "reward_variance")]
+fn confidence_interval_needs_two_visits() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(3, 1),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0).with_tie_break(TieBreak::FirstMove),
+        ApproxTable::new(1024),
+    );
+    let stats = mcts.root_move_stats_detailed();
+    assert!(stats.iter().all(|x| x.visits == 0));
+    assert!(stats.iter().all(|x| x.policy_score == f64::INFINITY));
+    assert!(stats.iter().all(|x| x.confidence_interval.is_none()));
+
+    mcts.playout_n(30);
+    mcts.tree().validate().unwrap();
+    // Each move ends the game, so its reward never varies.
+    for x in mcts.root_move_stats_detailed() {
+        assert_eq!(x.move_info.reward_variance(), Some(0.0));
+        assert_eq!(x.confidence_interval, Some((x.mean, x.mean)));
+    }
+}
+
+#[test]
+#[cfg(feature = "reward_variance")]
+fn variance_of_mixed_rewards() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(4, 2),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    let stats = mcts.root_move_stats_detailed();
+    let most_visited = stats.iter().max_by_key(|x| x.visits).unwrap();
+    let variance = most_visited.move_info.reward_variance().unwrap();
+    assert!(variance > 0.0);
+    let (low, high) = most_visited.confidence_interval.unwrap();
+    let expected = 1.96 * (variance / most_visited.visits as f64).sqrt();
+    assert!((high - low - 2.0 * expected).abs() < 1e-9);
+}
@@ -0,0 +1,103 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::nmcs::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// UCT scores new states with one random rollout.
+struct RolloutEvaluator;
+
+impl Evaluator<GambleMCTS> for RolloutEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Gamble,
+        moves: &Vec<u8>,
+        handle: Option<SearchHandle<GambleMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let score = match handle {
+            Some(mut handle) => {
+                nested_search(state.clone(), 0, &mut handle.thread_data().policy_data).1
+            }
+            None => nested_search(state.clone(), 0, &mut PolicyRng::new(0)).1,
+        };
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &Gamble, evaln: &f64, _: SearchHandle<GambleMCTS>) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct GambleMCTS;
+
+impl MCTS for GambleMCTS {
+    type State = Gamble;
+    type Eval = RolloutEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn returns_the_sequence_it_scored() {
+    for level in 0..3 {
+        let (moves, score) = nested_search(Gamble::default(), level, &mut PolicyRng::new(1));
+        let end = replay(&moves);
+        assert!(end.available_moves().is_empty());
+        assert_eq!(end.score(), score);
+    }
+}
+
+#[test]
+fn levels_improve() {
+    let mut scores = Vec::new();
+    for level in 0..3 {
+        let total: f64 = (0..5)
+            .map(|seed| nested_search(Gamble::default(), level, &mut PolicyRng::new(seed)).1)
+            .sum();
+        scores.push(total);
+    }
+    assert!(
+        scores[0] < scores[1] && scores[1] < scores[2],
+        "{:?}",
+        scores
+    );
+}
+
+#[test]
+fn custom_rollout_policy() {
+    // Always playing safe scores 1 a step.
+    let mut safe = |_: &Gamble, _: &[u8]| 0;
+    let (moves, score) = nested_search(Gamble::default(), 0, &mut safe);
+    assert_eq!(moves, vec![0; STEPS]);
+    assert_eq!(score, STEPS as f64);
+}
+
+#[test]
+fn level_2_beats_uct() {
+    let (_, nested) = nested_search(Gamble::default(), 2, &mut PolicyRng::new(0));
+
+    let mut mcts = MCTSManager::new(
+        Gamble::default(),
+        GambleMCTS,
+        RolloutEvaluator,
+        UCTPolicy::new(2.0),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n(100_000);
+    mcts.tree().validate().unwrap();
+    let line = replay(&mcts.principal_variation(STEPS));
+    let (_, uct) = nested_search(line, 0, &mut PolicyRng::new(0));
+
+    assert!(nested > uct, "NMCS {} against UCT {}", nested, uct);
+}
@@ -0,0 +1,134 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+
+// Pick two numbers from 0 to 3. A buggy move generator offers nothing after 0,
+// yet the game doesn't consider itself over.
+#[derive(Clone, Debug, Default)]
+struct BuggyGame(Vec<u8>);
+
+impl GameState for BuggyGame {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.is_terminal() || self.0.contains(&0) {
+            vec![]
+        } else {
+            vec![0, 1, 2, 3]
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+    fn is_terminal(&self) -> bool {
+        self.0.len() == 2
+    }
+}
+
+struct MyEvaluator;
+
+impl Evaluator<MyMCTS> for MyEvaluator {
+    type StateEvaluation = u8;
+
+    fn evaluate_new_state(
+        &self,
+        state: &BuggyGame,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<MyMCTS>>,
+    ) -> (Vec<()>, u8) {
+        // Make the broken state look attractive.
+        let score = if state.0.contains(&0) {
+            100
+        } else {
+            state.0.iter().sum()
+        };
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &u8, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(&self, _: &BuggyGame, evaln: &u8, _: SearchHandle<MyMCTS>) -> u8 {
+        *evaln
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+enum Behaviour {
+    #[default]
+    Terminal,
+    Panic,
+    Skip,
+}
+
+#[derive(Default)]
+struct MyMCTS(Behaviour);
+
+impl MCTS for MyMCTS {
+    type State = BuggyGame;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn virtual_loss(&self) -> f64 {
+        1.0
+    }
+    fn no_moves_behaviour(&self, state: &BuggyGame) -> NoMovesBehaviour<Self> {
+        match self.0 {
+            Behaviour::Terminal => NoMovesBehaviour::Terminal,
+            Behaviour::Panic => NoMovesBehaviour::panic_with_state(state),
+            Behaviour::Skip => NoMovesBehaviour::SkipPlayout,
+        }
+    }
+}
+
+fn search(behaviour: Behaviour) -> MCTSManager<MyMCTS> {
+    let mut mcts = MCTSManager::new(
+        BuggyGame::default(),
+        MyMCTS(behaviour),
+        MyEvaluator,
+        UCTPolicy::new(1.0),
+        (),
+    );
+    mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+#[test]
+fn treat_as_terminal() {
+    let mcts = search(Behaviour::Terminal);
+    assert_eq!(mcts.best_move(), Some(0));
+}
+
+#[test]
+#[should_panic(expected = "is_terminal() is false for state BuggyGame([0])")]
+fn panic_with_state() {
+    search(Behaviour::Panic);
+}
+
+#[test]
+fn skip_playout() {
+    let mcts = search(Behaviour::Skip);
+    let root = mcts.tree().root_node();
+    let zero = root.moves().find(|x| *x.get_move() == 0).unwrap();
+    // Visited once to discover the problem, then undone.
+    assert!(zero.child().is_some());
+    assert_eq!(zero.visits(), 0);
+    assert_eq!(zero.sum_rewards(), 0.0);
+    // UCT keeps trying the unvisited move, so most playouts are skipped.
+    let total: u64 = root.moves().map(|x| x.visits()).sum();
+    assert!(total < 500);
+    assert_ne!(mcts.best_move(), Some(0));
+}
@@ -0,0 +1,159 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+/// Numbers every evaluation, and makes each node's data the number of its
+/// evaluation, so a node whose data came from another evaluation stands out.
+struct NumberingEvaluator(Arc<Counts>);
+
+#[derive(Default)]
+struct Counts {
+    evaluations: AtomicUsize,
+    node_data: AtomicUsize,
+}
+
+impl Evaluator<NumberingMCTS> for NumberingEvaluator {
+    // (number, score)
+    type StateEvaluation = (usize, usize);
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<NumberingMCTS>>,
+    ) -> (Vec<()>, (usize, usize)) {
+        let number = self.0.evaluations.fetch_add(1, Ordering::SeqCst);
+        (vec![(); moves.len()], (number, state.score()))
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &(usize, usize), _: &()) -> f64 {
+        evaln.1 as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &(usize, usize),
+        _: SearchHandle<NumberingMCTS>,
+    ) -> (usize, usize) {
+        *evaln
+    }
+    fn make_node_data(&self, evaln: &(usize, usize)) -> usize {
+        self.0.node_data.fetch_add(1, Ordering::SeqCst);
+        evaln.0
+    }
+}
+
+#[derive(Default)]
+struct NumberingMCTS;
+
+impl MCTS for NumberingMCTS {
+    type State = FillGame;
+    type Eval = NumberingEvaluator;
+    type NodeData = usize;
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+fn tree(state: FillGame) -> (SearchTree<NumberingMCTS>, Arc<Counts>) {
+    let counts = Arc::new(Counts::default());
+    let tree = SearchTree::new(
+        state,
+        NumberingMCTS,
+        UCTPolicy::new(5.0),
+        NumberingEvaluator(counts.clone()),
+        ApproxTable::new(1024),
+    );
+    (tree, counts)
+}
+
+fn thread_data(seed: u64) -> ThreadData<NumberingMCTS> {
+    ThreadData::new(PolicyRng::new(seed), ())
+}
+
+/// Checks every node reachable from the root, once each, and returns their data.
+fn node_data(tree: &SearchTree<NumberingMCTS>) -> Vec<usize> {
+    let mut seen = HashSet::new();
+    let mut data = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if !seen.insert(node.into_raw()) {
+            continue;
+        }
+        assert_eq!(*node.data(), node.state_evaluation().0);
+        data.push(*node.data());
+        stack.extend(node.moves().filter_map(|x| x.child()));
+    }
+    data
+}
+
+#[test]
+fn made_from_the_nodes_own_evaluation() {
+    let (tree, eval) = tree(FillGame::new(6, 3));
+    let mut tld = thread_data(0);
+    for _ in 0..500 {
+        assert!(tree.playout(&mut tld));
+    }
+    tree.validate().unwrap();
+    let data = node_data(&tree);
+    assert_eq!(data.len(), tree.num_nodes());
+    assert_eq!(eval.node_data.load(Ordering::SeqCst), tree.num_nodes());
+    assert_eq!(eval.evaluations.load(Ordering::SeqCst), tree.num_nodes());
+}
+
+#[test]
+fn made_once_per_node_under_expansion_races() {
+    for round in 0..100 {
+        // Filling 0 then 1 and 1 then 0 reach the same state, so threads race both to
+        // expand the same child and to insert the same state in the table.
+        let (tree, eval) = tree(FillGame::new(3, 3));
+        let barrier = Barrier::new(4);
+        thread::scope(|s| {
+            for i in 0..4 {
+                let tree = &tree;
+                let barrier = &barrier;
+                s.spawn(move || {
+                    let mut tld = thread_data(round * 4 + i);
+                    barrier.wait();
+                    for _ in 0..10 {
+                        assert!(tree.playout(&mut tld));
+                    }
+                });
+            }
+        });
+        let data = node_data(&tree);
+        // Nodes lost to a race were evaluated and given data too, then dropped.
+        assert_eq!(
+            eval.node_data.load(Ordering::SeqCst),
+            eval.evaluations.load(Ordering::SeqCst)
+        );
+        tree.validate().unwrap();
+        let unique: HashSet<_> = data.iter().collect();
+        assert_eq!(unique.len(), data.len());
+        assert_eq!(data.len(), tree.num_nodes());
+    }
+}
+
+#[test]
+fn defaults_to_unit() {
+    let tree = fill_tree(4, 2);
+    let mut tld = fill_thread_data(0);
+    for _ in 0..10 {
+        assert!(tree.playout(&mut tld));
+    }
+    let root = tree.root_node();
+    assert_eq!(*root.data(), ());
+    assert_eq!(std::mem::size_of_val(root.data()), 0);
+}
@@ -0,0 +1,140 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The nodes an evaluator created, and the ones reported dropped.
+#[derive(Default)]
+struct Log {
+    created: AtomicUsize,
+    dropped: Mutex<Vec<usize>>,
+}
+
+impl Log {
+    /// Panics unless every created node was dropped exactly once.
+    fn check_all_dropped(&self) {
+        let mut dropped = self.dropped.lock().unwrap().clone();
+        dropped.sort_unstable();
+        let created = self.created.load(Ordering::SeqCst);
+        assert_eq!(dropped, (0..created).collect::<Vec<_>>());
+    }
+    fn num_dropped(&self) -> usize {
+        self.dropped.lock().unwrap().len()
+    }
+}
+
+/// A node's score, with a handle to release it under.
+#[derive(Clone)]
+struct Tracked {
+    score: usize,
+    id: usize,
+    log: Arc<Log>,
+}
+
+struct TrackingEvaluator(Arc<Log>);
+
+impl Evaluator<TrackingMCTS> for TrackingEvaluator {
+    type StateEvaluation = Tracked;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<TrackingMCTS>>,
+    ) -> (Vec<()>, Tracked) {
+        let id = self.0.created.fetch_add(1, Ordering::SeqCst);
+        let evaln = Tracked {
+            score: state.score(),
+            id,
+            log: self.0.clone(),
+        };
+        (vec![(); moves.len()], evaln)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &Tracked, _: &()) -> f64 {
+        evaln.score as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &Tracked,
+        _: SearchHandle<TrackingMCTS>,
+    ) -> Tracked {
+        evaln.clone()
+    }
+    fn on_node_dropped(evaln: &Tracked) {
+        evaln.log.dropped.lock().unwrap().push(evaln.id);
+    }
+}
+
+#[derive(Default)]
+struct TrackingMCTS;
+
+impl MCTS for TrackingMCTS {
+    type State = FillGame;
+    type Eval = TrackingEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+fn manager(log: &Arc<Log>) -> MCTSManager<TrackingMCTS> {
+    MCTSManager::new(
+        FillGame::new(10, 3),
+        TrackingMCTS,
+        TrackingEvaluator(log.clone()),
+        UCTPolicy::new(5.0),
+        ApproxTable::new(4096),
+    )
+}
+
+#[test]
+fn every_node_is_reported_once_when_the_tree_is_dropped() {
+    let log = Arc::new(Log::default());
+    let mut mcts = manager(&log);
+    mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    assert_eq!(log.num_dropped(), 0);
+    drop(mcts);
+    assert!(log.created.load(Ordering::SeqCst) > 20);
+    log.check_all_dropped();
+}
+
+#[test]
+fn reset_reports_the_old_tree() {
+    let log = Arc::new(Log::default());
+    let mut mcts = manager(&log);
+    mcts.playout_n(300);
+    let before = log.created.load(Ordering::SeqCst);
+    let mut mcts = mcts.reset();
+    // The new root was evaluated; everything before it was released.
+    assert_eq!(log.created.load(Ordering::SeqCst), before + 1);
+    assert_eq!(log.num_dropped(), before);
+    mcts.playout_n(300);
+    drop(mcts);
+    log.check_all_dropped();
+}
+
+#[test]
+fn parallel_searches_release_nothing_until_the_tree_is_dropped() {
+    let log = Arc::new(Log::default());
+    let mut mcts = manager(&log);
+    mcts.playout_n_parallel(2000, 4);
+    mcts.tree().validate().unwrap();
+    // Transpositions and expansion races may discard nodes, but nothing is
+    // released while the tree is alive.
+    assert!(log.created.load(Ordering::SeqCst) >= mcts.tree().num_nodes());
+    assert_eq!(log.num_dropped(), 0);
+    drop(mcts);
+    log.check_all_dropped();
+}
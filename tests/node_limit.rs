@@ -0,0 +1,55 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+
+#[test]
+fn a_tiny_node_cap_degrades_the_search() {
+    let mut mcts = counting_manager(5.0);
+    mcts.max_nodes(20);
+    let summary = mcts.playout_n(100);
+    assert_eq!(mcts.tree().num_nodes(), 20);
+    assert!(summary.expansion_halted);
+    // Every playout that would have created the 21st node or later is refused.
+    assert_eq!(summary.playouts + summary.playouts_after_halt, 100);
+    assert!(summary.playouts_after_halt > 50);
+    assert_eq!(summary.playouts, summary.depth_histogram.total());
+    assert!(mcts.last_search_degraded());
+    assert!(summary.to_string().contains("node limit reached"));
+    assert!(mcts
+        .tree()
+        .diagnose()
+        .contains("playouts refused at the node limit"));
+
+    // Counts are per search, and the flag stays set while the tree is full.
+    let summary = mcts.playout_n_parallel(10, 2);
+    assert_eq!(summary.playouts_after_halt, 10);
+    assert!(mcts.last_search_degraded());
+}
+
+#[test]
+fn searches_within_the_cap_are_not_degraded() {
+    let mut mcts = counting_manager(5.0);
+    let summary = mcts.playout_n(100);
+    assert!(!summary.expansion_halted);
+    assert_eq!(summary.playouts_after_halt, 0);
+    assert!(!mcts.last_search_degraded());
+    assert!(!summary.to_string().contains("node limit"));
+
+    let mut mcts = counting_manager(5.0);
+    mcts.max_nodes(1_000_000);
+    mcts.playout_n(100);
+    assert!(!mcts.last_search_degraded());
+}
+
+#[test]
+fn a_search_that_fits_clears_the_flag() {
+    let mut mcts = counting_manager(5.0);
+    mcts.max_nodes(10);
+    mcts.playout_n(50);
+    assert!(mcts.last_search_degraded());
+    mcts.max_nodes(usize::MAX);
+    mcts.playout_n(10);
+    assert!(!mcts.last_search_degraded());
+}
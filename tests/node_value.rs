@@ -0,0 +1,152 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// Two plies of a single-player game. The state after the first move 0 looks
+// great, at 100, but every move from it scores 0: the early backups through it
+// are misleading. Everything else scores 10.
+#[derive(Clone, Debug, Default, PartialEq, Hash)]
+struct Mirage(Vec<usize>);
+
+impl GameState for Mirage {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        if self.0.len() == 2 {
+            vec![]
+        } else {
+            vec![0, 1, 2]
+        }
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.0.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct MirageEvaluator;
+
+impl Evaluator<MirageMCTS> for MirageEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Mirage,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<MirageMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let score = match state.0[..] {
+            [0] => 100.0,
+            [0, _] => 0.0,
+            _ => 10.0,
+        };
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &Mirage, evaln: &f64, _: SearchHandle<MirageMCTS>) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct MirageMCTS;
+
+impl MCTS for MirageMCTS {
+    type State = Mirage;
+    type Eval = MirageEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn children_weighted_sees_through_early_backups() {
+    let mut mcts = MCTSManager::new(
+        Mirage::default(),
+        MirageMCTS,
+        MirageEvaluator,
+        UCTPolicy::new(100.0),
+        (),
+    );
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+
+    let accumulated = mcts.root_move_stats_with(NodeValueKind::Accumulated);
+    let weighted = mcts.root_move_stats_with(NodeValueKind::ChildrenWeighted);
+    assert_eq!(accumulated, mcts.root_move_stats_ref());
+    // The move's own mean still holds its first evaluation, 100 / visits.
+    let visits = accumulated[0].visits as f64;
+    assert!(visits > 4.0);
+    assert!((accumulated[0].mean - 100.0 / visits).abs() < 1e-9);
+    assert_eq!(weighted[0].mean, 0.0);
+    // The other moves' children score what they do.
+    assert_eq!(weighted[1].mean, 10.0);
+    assert_eq!(accumulated[1].mean, 10.0);
+    for (a, w) in accumulated.iter().zip(&weighted) {
+        assert_eq!((a.mov, a.visits), (w.mov, w.visits));
+    }
+}
+
+#[test]
+fn leaves_fall_back_to_accumulated() {
+    let mut mcts = MCTSManager::new(
+        Mirage::default(),
+        MirageMCTS,
+        MirageEvaluator,
+        UCTPolicy::new(100.0),
+        (),
+    );
+    // Each root move is tried once: their nodes exist, with no visited moves.
+    mcts.playout_n(3);
+    let accumulated = mcts.root_move_stats_with(NodeValueKind::Accumulated);
+    let weighted = mcts.root_move_stats_with(NodeValueKind::ChildrenWeighted);
+    assert_eq!(accumulated, weighted);
+    assert_eq!(weighted[0].mean, 100.0);
+    // The second ply's moves lead to no nodes until they're visited.
+    let state = Mirage(vec![1]);
+    let root = mcts.tree().root_node();
+    let child = root.moves().nth(1).unwrap().child().unwrap();
+    assert!(child.moves().all(|x| mcts
+        .tree()
+        .move_value(&state, x, NodeValueKind::ChildrenWeighted)
+        .is_nan()));
+}
+
+#[test]
+fn two_player_values_are_negated() {
+    let mut mcts = TwoPlayerZeroSumSpec::<TicTacToe>::manager(TicTacToe::default());
+    mcts.seed(Some(1));
+    mcts.playout_n(2000);
+    let tree = mcts.tree();
+    let weighted = mcts.root_move_stats_with(NodeValueKind::ChildrenWeighted);
+    for (mov, stats) in tree.root_node().moves().zip(&weighted) {
+        let child = mov.child().unwrap();
+        let visits: u64 = child.moves().map(|x| x.visits()).sum();
+        let sum: f64 = child.moves().map(|x| x.sum_rewards()).sum();
+        assert_eq!(stats.mean, -sum / visits as f64);
+    }
+
+    let pv = mcts.annotated_principal_variation_with(4, NodeValueKind::ChildrenWeighted);
+    let plain = mcts.annotated_principal_variation(4);
+    assert_eq!(pv.len(), plain.len());
+    assert_eq!(pv[0].value, weighted[*pv[0].move_info.get_move()].mean);
+    for (w, p) in pv.iter().zip(&plain) {
+        assert_eq!(w.parent_value, p.parent_value);
+        assert_eq!(w.delta, w.value - w.parent_value);
+    }
+}
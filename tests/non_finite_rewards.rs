@@ -0,0 +1,117 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// FillGame, except that filling cell 1 gives a NaN reward.
+struct NanEvaluator;
+
+impl Evaluator<NanMCTS> for NanEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<NanMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let score = if state.cells[1] {
+            f64::NAN
+        } else {
+            state.score() as f64
+        };
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &FillGame, evaln: &f64, _: SearchHandle<NanMCTS>) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct NanMCTS(Option<NonFiniteRewards>);
+
+impl MCTS for NanMCTS {
+    type State = FillGame;
+    type Eval = NanEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+    fn non_finite_rewards(&self) -> NonFiniteRewards {
+        self.0.unwrap_or(NonFiniteRewards::PanicInDebug)
+    }
+}
+
+fn manager(behaviour: Option<NonFiniteRewards>) -> MCTSManager<NanMCTS> {
+    MCTSManager::new(
+        FillGame::new(4, 2),
+        NanMCTS(behaviour),
+        NanEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    )
+}
+
+#[test]
+#[should_panic(expected = "interpret_evaluation_for_player returned NaN")]
+fn panics() {
+    manager(Some(NonFiniteRewards::Panic)).playout_n(100);
+}
+
+#[test]
+#[cfg_attr(debug_assertions, should_panic(expected = "returned NaN"))]
+fn panics_in_debug_builds_by_default() {
+    manager(None).playout_n(100);
+}
+
+#[test]
+fn substitutes() {
+    let mut mcts = manager(Some(NonFiniteRewards::Substitute(-10.0)));
+    let summary = mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    assert!(summary.non_finite_rewards > 0);
+    assert_eq!(
+        summary.non_finite_rewards,
+        mcts.tree().non_finite_rewards() as u64
+    );
+    for mov in mcts.tree().root_node().moves() {
+        assert!(mov.avg_reward().is_finite());
+    }
+    // Filling 2 and 3 is best, in either order.
+    let best = mcts.best_move().unwrap();
+    assert!(best == 2 || best == 3, "best move {}", best);
+}
+
+// A policy can't be confused by a NaN score.
+#[test]
+fn select_by_key_ignores_nan() {
+    let scores = [f64::NAN, 1.0, f64::NAN, 2.0, f64::NAN];
+    let mut rng = PolicyRng::new(1);
+    for _ in 0..20 {
+        assert_eq!(rng.select_by_key(0..5, |&i| scores[i]), Some(3));
+        assert_eq!(
+            TieBreak::FirstMove.select_by_key(&mut rng, 0..5, |&i| scores[i]),
+            Some(3)
+        );
+    }
+    let all_nan = [f64::NAN; 3];
+    assert!(rng.select_by_key(0..3, |&i| all_nan[i]).is_some());
+    assert_eq!(
+        TieBreak::FirstMove.select_by_key(&mut rng, 0..3, |&i| all_nan[i]),
+        Some(0)
+    );
+    assert!(WeightedRng::new(1)
+        .select_by_key(0..3, |&i| all_nan[i])
+        .is_some());
+}
@@ -0,0 +1,48 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::nmcs::Puzzle;
+use mcts::nrpa::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// The step, whether it follows a gamble, and the move tell apart every decision.
+fn move_code(state: &Gamble, mov: &u8) -> u64 {
+    (state.steps as u64 * 2 + state.gambling as u64) * 16 + *mov as u64
+}
+
+fn search(levels: u32, seed: u64) -> (Vec<u8>, f64) {
+    nrpa_search(
+        Gamble::default(),
+        levels,
+        10,
+        1.0,
+        move_code,
+        &mut PolicyRng::new(seed),
+    )
+}
+
+#[test]
+fn returns_the_sequence_it_scored() {
+    for levels in 0..3 {
+        let (moves, score) = search(levels, 1);
+        let end = replay(&moves);
+        assert!(end.available_moves().is_empty());
+        assert_eq!(end.score(), score);
+    }
+}
+
+#[test]
+fn levels_improve() {
+    let scores: Vec<f64> = (0..4)
+        .map(|levels| (0..5).map(|seed| search(levels, seed).1).sum())
+        .collect();
+    assert!(scores.windows(2).all(|x| x[0] < x[1]), "{:?}", scores);
+}
+
+#[test]
+fn same_seed_same_result() {
+    assert_eq!(search(2, 3), search(2, 3));
+}
@@ -0,0 +1,136 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::arena::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::collections::HashSet;
+
+/// Tic-tac-toe with PUCT, uniform updatable priors and the game's result as the
+/// only evaluation.
+#[derive(Default)]
+struct PriorMCTS;
+
+struct Results;
+
+impl Evaluator<PriorMCTS> for Results {
+    type StateEvaluation = Option<u8>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &TicTacToe,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<PriorMCTS>>,
+    ) -> (Vec<AtomicPrior>, Option<u8>) {
+        let prior = 1.0 / moves.len() as f64;
+        let priors = moves.iter().map(|_| AtomicPrior::new(prior)).collect();
+        (priors, state.get_winner())
+    }
+    fn interpret_evaluation_for_player(&self, winner: &Option<u8>, player: &u8) -> f64 {
+        match winner {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &TicTacToe,
+        winner: &Option<u8>,
+        _: SearchHandle<PriorMCTS>,
+    ) -> Option<u8> {
+        *winner
+    }
+}
+
+impl MCTS for PriorMCTS {
+    type State = TicTacToe;
+    type Eval = Results;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy<AtomicPrior>;
+    type TranspositionTable = ();
+}
+
+fn manager(state: TicTacToe) -> MCTSManager<PriorMCTS> {
+    MCTSManager::new(
+        state,
+        PriorMCTS,
+        Results,
+        AlphaGoPolicy::with_exploration_constant(1.0),
+        (),
+    )
+}
+
+// The positions after two plies of 20 greedy games, all with the same seed.
+fn openings(penalty: Option<&mut LineRepetitionPenalty<usize>>) -> HashSet<TicTacToe> {
+    let options = SelfPlayOptions {
+        seed: 5,
+        ..Default::default()
+    };
+    let mut openings = HashSet::new();
+    let mut penalty = penalty;
+    for _ in 0..20 {
+        let samples = match penalty.as_deref_mut() {
+            Some(penalty) => {
+                generate_varied_self_play_game(manager, |_| 0.0, |_| 200, &options, penalty)
+            }
+            None => generate_self_play_game(manager, |_| 0.0, |_| 200, &options),
+        };
+        openings.insert(samples[2].state.clone());
+    }
+    openings
+}
+
+#[test]
+fn penalized_games_open_more_ways() {
+    let plain = openings(None);
+    let mut penalty = LineRepetitionPenalty::new(2, 0.1);
+    let varied = openings(Some(&mut penalty));
+    assert_eq!(plain.len(), 1);
+    assert!(varied.len() > plain.len(), "{}", varied.len());
+    assert_eq!(penalty.lines(), 20);
+}
+
+#[test]
+fn penalties_are_shares_of_the_earlier_games() {
+    let mut penalty = LineRepetitionPenalty::new(2, 0.2);
+    assert_eq!(penalty.penalty(&[], &4), 0.0);
+    penalty.add_line(&[4, 0, 8]);
+    penalty.add_line(&[4, 2]);
+    penalty.add_line(&[0]);
+    penalty.add_line(&[4, 0, 1, 2]);
+    assert!((penalty.penalty(&[], &4) - 0.15).abs() < 1e-12);
+    assert!((penalty.penalty(&[], &0) - 0.05).abs() < 1e-12);
+    assert_eq!(penalty.penalty(&[], &8), 0.0);
+    assert!((penalty.penalty(&[4], &0) - 0.1).abs() < 1e-12);
+    // Only the first two plies count.
+    assert_eq!(penalty.penalty(&[4, 0], &8), 0.0);
+}
+
+#[test]
+fn apply_lowers_root_priors() {
+    let mut penalty = LineRepetitionPenalty::new(2, 0.5);
+    penalty.add_line(&[4, 0]);
+    penalty.add_line(&[4, 8]);
+    let mcts = manager(TicTacToe::default());
+    assert_eq!(penalty.apply(mcts.tree(), &[]), 1);
+    for mov in mcts.tree().root_node().moves() {
+        let expected = if *mov.get_move() == 4 { 0.0 } else { 1.0 / 9.0 };
+        // Priors are f32 under `f32_stats`.
+        assert!((mov.move_evaluation().prior() - expected).abs() < 1e-6);
+    }
+    let mut state = TicTacToe::default();
+    state.make_move(&4).unwrap();
+    let mcts = manager(state);
+    assert_eq!(penalty.apply(mcts.tree(), &[4]), 2);
+    let lowered = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .filter(|x| x.move_evaluation().prior() < 1.0 / 8.0)
+        .count();
+    assert_eq!(lowered, 2);
+}
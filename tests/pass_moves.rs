@@ -0,0 +1,208 @@
+extern crate mcts;
+
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// Each player has a number of tokens to play, scoring a point per token.
+// A player without tokens must pass; two passes in a row end the game.
+#[derive(Clone, Debug, Default, PartialEq, Hash)]
+struct TokenGame {
+    to_move: usize,
+    tokens: [u8; 2],
+    score: [i32; 2],
+    passes: u8,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Move {
+    Play,
+    Pass,
+}
+
+impl GameState for TokenGame {
+    type Move = Move;
+    type Player = usize;
+    type MoveList = Vec<Move>;
+
+    fn current_player(&self) -> &usize {
+        &self.to_move
+    }
+    fn available_moves(&self) -> Vec<Move> {
+        if self.is_terminal() || self.tokens[self.to_move] == 0 {
+            vec![]
+        } else {
+            vec![Move::Play]
+        }
+    }
+    fn make_move(&mut self, mov: &Move) -> Result<(), ()> {
+        match *mov {
+            Move::Play => {
+                self.tokens[self.to_move] -= 1;
+                self.score[self.to_move] += 1;
+                self.passes = 0;
+            }
+            Move::Pass => self.passes += 1,
+        }
+        self.to_move = 1 - self.to_move;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<usize> {
+        None
+    }
+    fn is_terminal(&self) -> bool {
+        self.passes >= 2
+    }
+}
+
+struct MyEvaluator;
+
+impl Evaluator<PassingMCTS> for MyEvaluator {
+    type StateEvaluation = [i32; 2];
+
+    fn evaluate_new_state(
+        &self,
+        state: &TokenGame,
+        moves: &Vec<Move>,
+        _: Option<SearchHandle<PassingMCTS>>,
+    ) -> (Vec<()>, [i32; 2]) {
+        (vec![(); moves.len()], state.score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &[i32; 2], player: &usize) -> f64 {
+        (evaln[*player] - evaln[1 - *player]) as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &TokenGame,
+        evaln: &[i32; 2],
+        _: SearchHandle<PassingMCTS>,
+    ) -> [i32; 2] {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct PassingMCTS;
+
+impl MCTS for PassingMCTS {
+    type State = TokenGame;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn no_moves_behaviour(&self, _: &TokenGame) -> NoMovesBehaviour<Self> {
+        NoMovesBehaviour::Pass(Move::Pass, ())
+    }
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::UseCurrentEvalWhenCycleDetected
+    }
+    // The tests search past a forced pass at the root.
+    fn stop_on_forced_move(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn player_without_moves_passes() {
+    let game = TokenGame {
+        to_move: 0,
+        tokens: [0, 2],
+        ..Default::default()
+    };
+    let mut mcts = MCTSManager::new(
+        game,
+        PassingMCTS,
+        MyEvaluator,
+        UCTPolicy::new(1.0),
+        ApproxTable::new(1024),
+    );
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    assert_eq!(
+        mcts.principal_variation(10),
+        vec![
+            Move::Pass,
+            Move::Play,
+            Move::Pass,
+            Move::Play,
+            Move::Pass,
+            Move::Pass
+        ]
+    );
+    let last = mcts.principal_variation_states(10).pop().unwrap();
+    assert!(last.is_terminal());
+    assert_eq!(last.score, [0, 2]);
+}
+
+#[test]
+fn passes_are_not_terminality_mismatches() {
+    let game = TokenGame {
+        to_move: 0,
+        tokens: [1, 2],
+        ..Default::default()
+    };
+    let mut mcts = MCTSManager::new(
+        game,
+        PassingMCTS,
+        MyEvaluator,
+        UCTPolicy::new(1.0),
+        ApproxTable::new(1024),
+    );
+    let summary = mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    assert!(mcts.principal_variation(10).contains(&Move::Pass));
+    assert_eq!(summary.terminality_mismatches, 0);
+    assert!(!summary.to_string().contains("is_terminal()"));
+}
+
+#[derive(Default)]
+struct TerminalMCTS;
+
+impl Evaluator<TerminalMCTS> for MyEvaluator {
+    type StateEvaluation = [i32; 2];
+
+    fn evaluate_new_state(
+        &self,
+        state: &TokenGame,
+        moves: &Vec<Move>,
+        _: Option<SearchHandle<TerminalMCTS>>,
+    ) -> (Vec<()>, [i32; 2]) {
+        (vec![(); moves.len()], state.score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &[i32; 2], player: &usize) -> f64 {
+        (evaln[*player] - evaln[1 - *player]) as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &TokenGame,
+        evaln: &[i32; 2],
+        _: SearchHandle<TerminalMCTS>,
+    ) -> [i32; 2] {
+        *evaln
+    }
+}
+
+impl MCTS for TerminalMCTS {
+    type State = TokenGame;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn no_moves_is_terminal_by_default() {
+    let game = TokenGame {
+        to_move: 0,
+        tokens: [0, 2],
+        ..Default::default()
+    };
+    let mut mcts = MCTSManager::new(game, TerminalMCTS, MyEvaluator, UCTPolicy::new(1.0), ());
+    mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.tree().root_node().moves().count(), 0);
+    assert!(mcts.principal_variation(10).is_empty());
+}
@@ -0,0 +1,126 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Seen {
+    /// The path and state of every new state evaluated with a handle.
+    new_states: Vec<(Vec<usize>, FillGame)>,
+    without_handle: usize,
+    /// Where the path buffer was, for every evaluation.
+    buffers: Vec<usize>,
+}
+
+struct PathEvaluator(Arc<Mutex<Seen>>);
+
+impl Evaluator<PathMCTS> for PathEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<PathMCTS>>,
+    ) -> (Vec<()>, usize) {
+        let mut seen = self.0.lock().unwrap();
+        match handle {
+            Some(handle) => {
+                let path = handle.path_moves();
+                seen.new_states.push((path.to_vec(), state.clone()));
+                seen.buffers.push(path.as_ptr() as usize);
+            }
+            None => seen.without_handle += 1,
+        }
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        state: &FillGame,
+        evaln: &usize,
+        handle: SearchHandle<PathMCTS>,
+    ) -> usize {
+        let path = handle.path_moves();
+        assert_eq!(replay(path), *state);
+        self.0.lock().unwrap().buffers.push(path.as_ptr() as usize);
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct PathMCTS;
+
+impl MCTS for PathMCTS {
+    type State = FillGame;
+    type Eval = PathEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+fn replay(moves: &[usize]) -> FillGame {
+    let mut state = FillGame::new(5, 3);
+    for mov in moves {
+        state.make_move(mov).unwrap();
+    }
+    state
+}
+
+fn search(playouts: usize) -> Seen {
+    let seen = Arc::new(Mutex::new(Seen::default()));
+    let tree = SearchTree::new(
+        FillGame::new(5, 3),
+        PathMCTS,
+        UCTPolicy::new(5.0),
+        PathEvaluator(seen.clone()),
+        ApproxTable::new(1024),
+    );
+    let mut tld = ThreadData::new(PolicyRng::new(0), ());
+    for _ in 0..playouts {
+        assert!(tree.playout(&mut tld));
+    }
+    tree.validate().unwrap();
+    drop(tree);
+    Arc::try_unwrap(seen).ok().unwrap().into_inner().unwrap()
+}
+
+#[test]
+fn leads_from_the_root_to_the_leaf() {
+    let seen = search(300);
+    assert!(!seen.new_states.is_empty());
+    for (path, state) in &seen.new_states {
+        // Every move fills one cell, so a leaf's depth is its number of filled cells.
+        assert_eq!(path.len(), state.filled());
+        assert_eq!(replay(path), *state);
+    }
+}
+
+#[test]
+fn handle_is_given_for_every_state_but_the_root() {
+    let seen = search(300);
+    assert_eq!(seen.without_handle, 1);
+    // Each of the 25 reachable states but the root, once each with the table.
+    assert_eq!(seen.new_states.len(), 5 + 10 + 10);
+}
+
+#[test]
+fn buffer_is_reused_across_playouts() {
+    let seen = search(300);
+    // One evaluation per playout.
+    assert_eq!(seen.buffers.len(), 300);
+    // Playouts are at most 3 moves long, which the buffer's first allocation holds.
+    assert!(seen.buffers.iter().all(|&x| x == seen.buffers[0]));
+}
@@ -0,0 +1,111 @@
+// Time stands still under the `wasm` feature unless a clock is installed.
+#![cfg(not(feature = "wasm"))]
+
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::time::{Duration, Instant};
+
+fn manager() -> MCTSManager<FillMCTS> {
+    MCTSManager::new(
+        FillGame::new(8, 4),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(4096),
+    )
+}
+
+#[test]
+fn no_breakdown_unless_profiling() {
+    let mut mcts = manager();
+    let summary = mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    assert_eq!(summary.phase_times, PhaseTimes::default());
+    assert!(summary.phase_breakdown().is_none());
+}
+
+#[test]
+fn fractions_sum_to_one() {
+    let mut mcts = manager();
+    mcts.profile_phases_with_interval(true, 4);
+    let summary = mcts.playout_n(1000);
+    mcts.tree().validate().unwrap();
+    let phases = summary.phase_breakdown().unwrap();
+    let sum = phases.select + phases.expand + phases.evaluate + phases.backpropagate;
+    assert!((sum - 1.0).abs() < 1e-9, "{:?}", phases);
+    assert!(summary.phase_times.select > Duration::from_nanos(0));
+    assert!(summary.to_string().contains("phases: select"));
+}
+
+#[test]
+fn profiling_survives_reset() {
+    let mut mcts = manager();
+    mcts.profile_phases_with_interval(true, 4);
+    mcts.playout_n(100);
+    let mut mcts = mcts.reset();
+    let summary = mcts.playout_n(500);
+    assert!(summary.phase_breakdown().is_some());
+}
+
+// Like FillEvaluator, but takes its time over new states.
+struct SlowEvaluator;
+
+impl Evaluator<SlowMCTS> for SlowEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<SlowMCTS>>,
+    ) -> (Vec<()>, usize) {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_micros(200) {}
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &usize,
+        _: SearchHandle<SlowMCTS>,
+    ) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct SlowMCTS;
+
+impl MCTS for SlowMCTS {
+    type State = FillGame;
+    type Eval = SlowEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn slow_evaluator_dominates() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(8, 4),
+        SlowMCTS,
+        SlowEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    );
+    mcts.profile_phases_with_interval(true, 1);
+    let summary = mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    let phases = summary.phase_breakdown().unwrap();
+    assert!(phases.evaluate > 0.8, "{:?}", phases);
+}
@@ -0,0 +1,94 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// Every position two plies from the start.
+fn second_plies() -> Vec<ConnectFour> {
+    let mut states = Vec::new();
+    for first in 0..COLUMNS {
+        for second in 0..COLUMNS {
+            states.push(ConnectFour::from_columns(&[first, second]));
+        }
+    }
+    states
+}
+
+#[test]
+fn reports_inserted_and_present_states() {
+    let mut mcts = connect_four_manager(ConnectFour::new(), 10_000);
+    let states = second_plies();
+    let report = mcts.preload_states(&states);
+    assert_eq!(
+        report,
+        PreloadReport {
+            inserted: 49,
+            present: 0,
+            not_stored: 0,
+        }
+    );
+    assert_eq!(mcts.tree().num_nodes(), 50);
+    mcts.tree().validate().unwrap();
+
+    let report = mcts.preload_states(&states[..10]);
+    assert_eq!(report.present, 10);
+    assert_eq!(report.inserted, 0);
+}
+
+#[test]
+fn searches_find_the_preloaded_states() {
+    let mut plain = connect_four_manager(ConnectFour::new(), 20_000);
+    plain.seed(Some(1));
+    let plain_hits = plain.playout_n(2000).transposition_table_hits;
+
+    let mut mcts = connect_four_manager(ConnectFour::new(), 20_000);
+    mcts.seed(Some(1));
+    mcts.preload_states(&second_plies());
+    let summary = mcts.playout_n(2000);
+    // Every second-ply position the search reached was a hit.
+    let reached = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .filter_map(|x| x.child())
+        .map(|x| x.moves().filter(|x| x.child().is_some()).count() as u64)
+        .sum::<u64>();
+    assert!(reached > 40, "{}", reached);
+    assert!(summary.transposition_table_hits >= reached);
+    assert!(summary.transposition_table_hits > plain_hits);
+    assert_eq!(
+        summary.transposition_table_hits as usize,
+        mcts.tree().transposition_table_hits()
+    );
+    mcts.tree().validate().unwrap();
+}
+
+#[test]
+fn trees_without_a_table_store_nothing() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(5, 2),
+        NoTableMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    );
+    let report = mcts.preload_states(&[FillGame::new(5, 2)]);
+    assert_eq!(report.not_stored, 1);
+    assert_eq!(report.inserted + report.present, 0);
+    assert_eq!(mcts.tree().num_nodes(), 1);
+}
+
+#[derive(Default)]
+struct NoTableMCTS;
+
+impl MCTS for NoTableMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
@@ -0,0 +1,103 @@
+extern crate mcts;
+
+// The counting game from the crate documentation, set up with nothing but the
+// prelude. The game's types are glob imported too, to check that the prelude
+// doesn't clash with a game's own `Move` and `Player`.
+mod game {
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Move {
+        Add,
+        Sub,
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Player;
+}
+
+use game::*;
+use mcts::prelude::*;
+
+#[derive(Clone, Debug, Default, PartialEq, Hash)]
+struct CountingGame(i64);
+
+impl GameState for CountingGame {
+    type Move = Move;
+    type Player = Player;
+    type MoveList = Vec<Move>;
+
+    fn current_player(&self) -> &Player {
+        &Player
+    }
+    fn available_moves(&self) -> Vec<Move> {
+        if self.0 == 100 {
+            vec![]
+        } else {
+            vec![Move::Add, Move::Sub]
+        }
+    }
+    fn make_move(&mut self, mov: &Move) -> Result<(), ()> {
+        match *mov {
+            Move::Add => self.0 += 1,
+            Move::Sub => self.0 -= 1,
+        }
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<Player> {
+        None
+    }
+}
+
+struct MyEvaluator;
+
+impl Evaluator<MyMCTS> for MyEvaluator {
+    type StateEvaluation = i64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &CountingGame,
+        moves: &MoveList<MyMCTS>,
+        _: Option<SearchHandle<MyMCTS>>,
+    ) -> (Vec<MoveEvaluation<MyMCTS>>, StateEvaluation<MyMCTS>) {
+        (vec![(); moves.len()], state.0)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &i64, _: &PlayerOf<MyMCTS>) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &CountingGame,
+        evaln: &i64,
+        _: SearchHandle<MyMCTS>,
+    ) -> i64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct MyMCTS;
+
+impl MCTS for MyMCTS {
+    type State = CountingGame;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::UseCurrentEvalWhenCycleDetected
+    }
+}
+
+#[test]
+fn prelude_is_enough_for_the_counting_game() {
+    let mut mcts = MCTSManager::builder(MyMCTS)
+        .state(CountingGame(0))
+        .evaluator(MyEvaluator)
+        .policy(UCTPolicy::new(0.5))
+        .transposition_table(ApproxTable::new(1024))
+        .build();
+    mcts.playout_n(10000);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.best_move(), Some(Move::Add));
+}
@@ -0,0 +1,90 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::arena::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+const PLAYOUTS: u64 = 1_000;
+
+fn preset_manager(preset: SearchPreset, state: TicTacToe) -> MCTSManager<Spec> {
+    let params = preset.params(9, PLAYOUTS);
+    MCTSManager::builder(Spec::default())
+        .state(state)
+        .evaluator(WinDrawLossEvaluator)
+        .preset(&params)
+        .transposition_table(())
+        .build()
+}
+
+fn random(state: &TicTacToe, seed: u64) -> Option<usize> {
+    let moves = state.available_moves();
+    moves.get(seed as usize % moves.len().max(1)).cloned()
+}
+
+fn score_against_random(preset: SearchPreset) -> f64 {
+    let mut agent = MCTSAgent::new(
+        |state| preset_manager(preset, state),
+        Budget::Playouts(PLAYOUTS as usize),
+    );
+    let result = play_match(&mut agent, &mut random, 100, true);
+    result.total().score()
+}
+
+#[test]
+fn balanced_beats_random() {
+    let score = score_against_random(SearchPreset::Balanced);
+    assert!(score >= 0.95, "{}", score);
+}
+
+#[test]
+fn every_preset_beats_random() {
+    for preset in [SearchPreset::Tactical, SearchPreset::Exploratory] {
+        let score = score_against_random(preset);
+        assert!(score >= 0.9, "{:?}: {}", preset, score);
+    }
+}
+
+#[test]
+fn the_builder_takes_the_policy_and_budget() {
+    let mcts = preset_manager(SearchPreset::Exploratory, TicTacToe::default());
+    let policy = mcts.tree_policy();
+    assert_eq!(policy.exploration_constant(), 2.0);
+    assert!(policy.reward_spread().is_some());
+    assert_eq!(
+        mcts.tree().virtual_loss_mode(),
+        VirtualLossMode::VisitsAndReward(3, 0.0)
+    );
+    assert_eq!(mcts.memory_stats().nodes_reserved, PLAYOUTS as usize);
+}
+
+#[test]
+fn presets_explore_more_in_order() {
+    let constants: Vec<f64> = [
+        SearchPreset::Tactical,
+        SearchPreset::Balanced,
+        SearchPreset::Exploratory,
+    ]
+    .iter()
+    .map(|x| x.exploration_constant())
+    .collect();
+    assert!(constants.windows(2).all(|x| x[0] < x[1]));
+    assert_eq!(SearchPreset::default(), SearchPreset::Balanced);
+}
+
+#[test]
+fn tables_hold_the_budget() {
+    for (branching_factor, playouts, size) in [
+        (9, 1_000, 2048),
+        (2, 10, 32),
+        (0, 0, 1),
+        (300, 100_000, 262_144),
+    ] {
+        let params = SearchPreset::Balanced.params(branching_factor, playouts);
+        assert_eq!(params.table_size, size, "{} {}", branching_factor, playouts);
+        assert!(params.table_size as u64 * 2 >= (playouts + branching_factor as u64) * 3);
+    }
+}
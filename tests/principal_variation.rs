@@ -0,0 +1,138 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+fn searched(playouts: usize) -> mcts::MCTSManager<CountingMCTS> {
+    let mut mcts = counting_manager(5.0);
+    mcts.seed(Some(3));
+    mcts.playout_n(playouts);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+#[test]
+fn stops_at_the_first_unreliable_move() {
+    let mcts = searched(300);
+    let full = mcts.principal_variation_info(100);
+    let reliable = mcts.principal_variation_reliable(100, 30);
+    assert!(!reliable.is_empty());
+    assert!(
+        reliable.len() < full.len(),
+        "{} of {}",
+        reliable.len(),
+        full.len()
+    );
+    for ((mov, visits), info) in reliable.iter().zip(&full) {
+        assert_eq!(mov, info.get_move());
+        assert_eq!(*visits, info.visits());
+        assert!(*visits >= 30);
+    }
+    assert!(full[reliable.len()].visits() < 30);
+}
+
+#[test]
+fn visits_decrease_along_the_line() {
+    let mcts = searched(300);
+    let line = mcts.principal_variation_reliable(20, 1);
+    assert_eq!(line.len(), mcts.principal_variation(20).len());
+    assert!(line.windows(2).all(|x| x[0].1 >= x[1].1));
+}
+
+#[test]
+fn respects_max_depth() {
+    let mcts = searched(300);
+    assert_eq!(mcts.principal_variation_reliable(2, 1).len(), 2);
+    assert!(mcts.principal_variation_reliable(0, 1).is_empty());
+}
+
+#[test]
+fn states_follow_the_reliable_line() {
+    let mcts = searched(300);
+    let line = mcts.principal_variation_reliable(100, 30);
+    let states = mcts.principal_variation_states_reliable(100, 30);
+    assert_eq!(states.len(), line.len() + 1);
+    assert_eq!(states[0], CountingGame(0));
+    // Nothing is reliable before any playouts, or with an impossible threshold.
+    assert!(mcts
+        .principal_variation_states_reliable(20, 1000)
+        .is_empty());
+    assert!(counting_manager(5.0)
+        .principal_variation_reliable(20, 1)
+        .is_empty());
+}
+
+fn annotated_forced_win(playouts: usize) -> Vec<(usize, f64, f64, f64)> {
+    // X to move wins only with 8, which blocks O and starts a forced win.
+    let mut mcts = TwoPlayerZeroSumSpec::manager(TicTacToe {
+        cells: [0, 0, 1, 1, 0, 0, 2, 2, 0],
+    });
+    mcts.seed(Some(0));
+    mcts.playout_n(playouts);
+    mcts.tree().validate().unwrap();
+    let line = mcts.annotated_principal_variation(9);
+    assert_eq!(line[0].parent_value, mcts.root_value().unwrap().mean);
+    let line: Vec<_> = line
+        .iter()
+        .map(|x| (*x.move_info.get_move(), x.value, x.parent_value, x.delta))
+        .collect();
+    // Ends with X completing a line.
+    let mut state = TicTacToe {
+        cells: [0, 0, 1, 1, 0, 0, 2, 2, 0],
+    };
+    for (mov, ..) in &line {
+        state.make_move(mov).unwrap();
+    }
+    assert_eq!(state.get_winner(), Some(1));
+    line
+}
+
+#[test]
+fn annotated_values_are_for_the_player_moving() {
+    let line = annotated_forced_win(20_000);
+    assert_eq!(line.len() % 2, 1);
+    for (i, &(_, value, parent_value, delta)) in line.iter().enumerate() {
+        // X moves at even plies and is winning; O is losing.
+        let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+        assert!(value * sign > 0.9, "ply {}: {}", i, value);
+        assert!(parent_value * sign > 0.9, "ply {}: {}", i, parent_value);
+        assert_eq!(delta, value - parent_value);
+        // The principal variation picks each player's best move.
+        assert!(delta >= 0.0, "ply {}: {}", i, delta);
+    }
+    // In a zero-sum game a node's value for the player to move there is close to
+    // the negated value of the move leading to it.
+    for pair in line.windows(2) {
+        assert!((pair[1].2 + pair[0].1).abs() < 0.01, "{:?}", pair);
+    }
+    assert_eq!(line[line.len() - 1].1, 1.0);
+}
+
+#[test]
+fn annotated_values_are_monotone_along_a_forced_win() {
+    let line = annotated_forced_win(20_000);
+    let winner: Vec<f64> = line.iter().step_by(2).map(|x| x.1).collect();
+    let loser: Vec<f64> = line.iter().skip(1).step_by(2).map(|x| x.1).collect();
+    assert!(winner.windows(2).all(|x| x[0] <= x[1]), "{:?}", winner);
+    assert!(loser.windows(2).all(|x| x[0] >= x[1]), "{:?}", loser);
+}
+
+#[test]
+fn annotation_follows_the_principal_variation() {
+    let mcts = searched(300);
+    let line = mcts.annotated_principal_variation(5);
+    let pv = mcts.principal_variation_info(5);
+    assert_eq!(line.len(), pv.len());
+    for (ply, info) in line.iter().zip(&pv) {
+        assert_eq!(ply.move_info.get_move(), info.get_move());
+        assert_eq!(ply.visits, info.visits());
+        assert_eq!(ply.value, info.avg_reward());
+    }
+    // A one-player game, where no value is negated.
+    assert!(line.iter().all(|x| x.parent_value > 0.0));
+    assert!(counting_manager(5.0)
+        .annotated_principal_variation(5)
+        .is_empty());
+}
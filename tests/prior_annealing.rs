@@ -0,0 +1,116 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Pick one of five moves; only the last one wins.
+#[derive(Clone, Debug, Default)]
+struct Pick(Option<usize>);
+
+impl GameState for Pick {
+    type Move = usize;
+    type Player = ();
+    type MoveList = Vec<usize>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<usize> {
+        match self.0 {
+            Some(_) => vec![],
+            None => (0..5).collect(),
+        }
+    }
+    fn make_move(&mut self, mov: &usize) -> Result<(), ()> {
+        self.0 = Some(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+/// Priors that all but rule out the winning move.
+struct Misjudged;
+
+impl Evaluator<PickMCTS> for Misjudged {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Pick,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<PickMCTS>>,
+    ) -> (Vec<f64>, f64) {
+        let priors = moves
+            .iter()
+            .map(|&x| if x == 4 { 0.001 } else { 0.24975 })
+            .collect();
+        (priors, if state.0 == Some(4) { 1.0 } else { 0.0 })
+    }
+    fn evaluate_existing_state(&self, _: &Pick, evaln: &f64, _: SearchHandle<PickMCTS>) -> f64 {
+        *evaln
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct PickMCTS;
+
+impl MCTS for PickMCTS {
+    type State = Pick;
+    type Eval = Misjudged;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+}
+
+/// The playouts it takes for the winning move to become the most visited.
+fn playouts_to_recover(policy: AlphaGoPolicy, seed: u64) -> usize {
+    let tree = SearchTree::new(Pick::default(), PickMCTS, policy, Misjudged, ());
+    let mut tld = ThreadData::new(PolicyRng::new(seed), ());
+    for n in 1..=5000 {
+        tree.playout(&mut tld);
+        let best = tree.root_node().moves().max_by_key(|x| x.visits()).unwrap();
+        if *best.get_move() == 4 {
+            return n;
+        }
+    }
+    panic!("never recovered");
+}
+
+#[test]
+fn annealing_recovers_from_a_wrong_prior_faster() {
+    let annealed = AlphaGoPolicy::new(1.0).with_prior_annealing(PriorAnnealing::Hyperbolic {
+        half_visits: 20.0,
+        max_weight: 0.5,
+    });
+    for seed in 0..4 {
+        let plain = playouts_to_recover(AlphaGoPolicy::new(1.0), seed);
+        let faster = playouts_to_recover(annealed.clone(), seed);
+        assert!(faster * 4 < plain, "{} against {} playouts", faster, plain);
+    }
+}
+
+#[test]
+fn schedules() {
+    let linear = PriorAnnealing::Linear {
+        visits: 100,
+        max_weight: 0.4,
+    };
+    assert_eq!(linear.weight(0), 0.0);
+    assert_eq!(linear.weight(50), 0.2);
+    assert_eq!(linear.weight(1000), 0.4);
+    let hyperbolic = PriorAnnealing::Hyperbolic {
+        half_visits: 10.0,
+        max_weight: 1.0,
+    };
+    assert_eq!(hyperbolic.weight(10), 0.5);
+    assert!(hyperbolic.weight(1_000_000) < 1.0);
+    // Weights outside [0, 1] are clamped.
+    assert_eq!(PriorAnnealing::Custom(|n| n as f64).weight(3), 1.0);
+    assert_eq!(PriorAnnealing::Custom(|_| -1.0).weight(3), 0.0);
+}
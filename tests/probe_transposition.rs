@@ -0,0 +1,184 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct Counters {
+    probe: bool,
+    probes: AtomicUsize,
+    hits: Mutex<Vec<NodeStats>>,
+}
+
+// Before each rollout, probes the table for every state one move on, as an
+// evaluator caching expensive features would.
+struct ProbingEvaluator;
+
+impl<Spec> Evaluator<Spec> for ProbingEvaluator
+where
+    Spec: MCTS<State = TicTacToe, Eval = Self> + AsRef<Counters>,
+    Spec::TreePolicy: TreePolicy<Spec, MoveEvaluation = ()>,
+{
+    type StateEvaluation = Option<u8>;
+
+    fn evaluate_new_state(
+        &self,
+        state: &TicTacToe,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<Spec>>,
+    ) -> (Vec<()>, Option<u8>) {
+        let winner = match handle {
+            Some(mut handle) => {
+                let counters = handle.mcts().as_ref();
+                if counters.probe {
+                    for mov in moves {
+                        let mut next = state.clone();
+                        next.make_move(mov).unwrap();
+                        counters.probes.fetch_add(1, Ordering::Relaxed);
+                        if let Some(stats) = handle.probe_transposition(&next) {
+                            counters.hits.lock().unwrap().push(stats);
+                        }
+                    }
+                }
+                WinDrawLossEvaluator::rollout(state, &mut handle.thread_data().policy_data)
+            }
+            None => None,
+        };
+        (vec![(); moves.len()], winner)
+    }
+    fn interpret_evaluation_for_player(&self, winner: &Option<u8>, player: &u8) -> f64 {
+        match winner {
+            Some(w) if w == player => 1.0,
+            Some(_) => -1.0,
+            None => 0.0,
+        }
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &TicTacToe,
+        winner: &Option<u8>,
+        _: SearchHandle<Spec>,
+    ) -> Option<u8> {
+        *winner
+    }
+}
+
+#[derive(Default)]
+struct TableMCTS(Counters);
+
+impl MCTS for TableMCTS {
+    type State = TicTacToe;
+    type Eval = ProbingEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+
+    fn cycle_behaviour(&self) -> CycleBehaviour<Self> {
+        CycleBehaviour::Ignore
+    }
+}
+
+impl AsRef<Counters> for TableMCTS {
+    fn as_ref(&self) -> &Counters {
+        &self.0
+    }
+}
+
+#[derive(Default)]
+struct NoTableMCTS(Counters);
+
+impl MCTS for NoTableMCTS {
+    type State = TicTacToe;
+    type Eval = ProbingEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+impl AsRef<Counters> for NoTableMCTS {
+    fn as_ref(&self) -> &Counters {
+        &self.0
+    }
+}
+
+fn counters(probe: bool) -> Counters {
+    Counters {
+        probe,
+        ..Default::default()
+    }
+}
+
+fn search<Spec>(spec: Spec, table: Spec::TranspositionTable, threads: usize) -> MCTSManager<Spec>
+where
+    Spec: MCTS<
+            State = TicTacToe,
+            Eval = ProbingEvaluator,
+            TreePolicy = UCTPolicy<()>,
+            ExtraThreadData = (),
+        > + AsRef<Counters>
+        + Sync,
+{
+    let mut mcts = MCTSManager::new(
+        TicTacToe::default(),
+        spec,
+        ProbingEvaluator,
+        UCTPolicy::new(1.4),
+        table,
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n_parallel(2000, threads);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+fn with_table(probe: bool, threads: usize) -> MCTSManager<TableMCTS> {
+    search(
+        TableMCTS(counters(probe)),
+        ApproxTable::new(1 << 14),
+        threads,
+    )
+}
+
+#[test]
+fn finds_transpositions() {
+    let mcts = with_table(true, 1);
+    let hits = mcts.spec().0.hits.lock().unwrap();
+    assert!(!hits.is_empty());
+    assert!(hits.len() < mcts.spec().0.probes.load(Ordering::Relaxed));
+    // Only nodes from earlier playouts are in the table, and those have been
+    // backpropagated through.
+    assert!(hits.iter().all(|x| x.visits > 0), "{:?}", hits);
+}
+
+#[test]
+fn probing_changes_nothing() {
+    let probed = with_table(true, 1);
+    let plain = with_table(false, 1);
+    let visits = |mcts: &MCTSManager<TableMCTS>| {
+        let root = mcts.tree().root_node();
+        root.moves().map(|x| x.visits()).collect::<Vec<_>>()
+    };
+    assert_eq!(visits(&probed), visits(&plain));
+    assert_eq!(probed.tree().num_nodes(), plain.tree().num_nodes());
+}
+
+#[test]
+fn safe_alongside_inserts() {
+    let mcts = with_table(true, 4);
+    assert!(!mcts.spec().0.hits.lock().unwrap().is_empty());
+}
+
+#[test]
+fn none_without_a_table() {
+    let mcts = search(NoTableMCTS(counters(true)), (), 1);
+    assert!(mcts.spec().0.probes.load(Ordering::Relaxed) > 0);
+    assert!(mcts.spec().0.hits.lock().unwrap().is_empty());
+}
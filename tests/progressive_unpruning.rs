@@ -0,0 +1,117 @@
+extern crate mcts;
+
+mod common;
+
+use common::FillGame;
+use mcts::tree_policy::*;
+use mcts::*;
+
+/// Eight moves from the root, each ending the game, with priors falling from the
+/// first move to the last. Every reward is zero, so the policy alone decides where
+/// the search goes.
+#[derive(Default)]
+struct UnpruningMCTS;
+
+struct FallingPriors;
+
+impl Evaluator<UnpruningMCTS> for FallingPriors {
+    type StateEvaluation = ();
+
+    fn evaluate_new_state(
+        &self,
+        _: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<UnpruningMCTS>>,
+    ) -> (Vec<AtomicPrior>, ()) {
+        let weight = |x: &usize| (8 - x) as f64;
+        let total: f64 = moves.iter().map(weight).sum();
+        let priors = moves.iter().map(|x| AtomicPrior::new(weight(x) / total));
+        (priors.collect(), ())
+    }
+    fn interpret_evaluation_for_player(&self, _: &(), _: &()) -> f64 {
+        0.0
+    }
+    fn evaluate_existing_state(&self, _: &FillGame, _: &(), _: SearchHandle<UnpruningMCTS>) {}
+}
+
+impl MCTS for UnpruningMCTS {
+    type State = FillGame;
+    type Eval = FallingPriors;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = ProgressiveUnpruning<AtomicPrior>;
+    type TranspositionTable = ();
+}
+
+fn policy() -> ProgressiveUnpruning<AtomicPrior> {
+    let inner = AlphaGoPolicy::with_exploration_constant(1.0);
+    ProgressiveUnpruning::new(inner, 1, 2.0).with_base_visits(10.0)
+}
+
+fn manager() -> MCTSManager<UnpruningMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(8, 1),
+        UnpruningMCTS,
+        FallingPriors,
+        policy(),
+        (),
+    );
+    mcts.seed(Some(1));
+    mcts
+}
+
+fn root_visits(mcts: &MCTSManager<UnpruningMCTS>) -> Vec<u64> {
+    let root = mcts.tree().root_node();
+    root.moves().map(|x| x.visits()).collect()
+}
+
+#[test]
+fn eligible_set_grows_on_schedule() {
+    let policy = ProgressiveUnpruning::new(AlphaGoPolicy::new(1.0), 2, 2.0);
+    let schedule: Vec<_> = [0, 1, 2, 3, 4, 7, 8, 64, 1000]
+        .iter()
+        .map(|&x| policy.eligible_moves(x, 100))
+        .collect();
+    assert_eq!(schedule, [2, 3, 4, 4, 5, 5, 6, 9, 12]);
+    assert_eq!(policy.eligible_moves(1000, 5), 5);
+    assert_eq!(policy.eligible_moves(0, 1), 1);
+
+    // As in Chaslot et al.
+    let policy = ProgressiveUnpruning::new(policy.inner().clone(), 5, 1.3).with_base_visits(50.0);
+    assert_eq!(policy.eligible_moves(49, 100), 5);
+    assert_eq!(policy.eligible_moves(50, 100), 6);
+    assert_eq!(policy.eligible_moves(64, 100), 6);
+    assert_eq!(policy.eligible_moves(65, 100), 7);
+}
+
+#[test]
+fn search_follows_the_schedule() {
+    let mut mcts = manager();
+    // The second move becomes selectable at 10 visits and the third at 20.
+    mcts.playout_n(10);
+    assert_eq!(root_visits(&mcts), [10, 0, 0, 0, 0, 0, 0, 0]);
+    mcts.playout_n(20);
+    let visits = root_visits(&mcts);
+    assert!(visits[1] > 0 && visits[2] > 0, "{:?}", visits);
+    assert!(visits[3..].iter().all(|&x| x == 0), "{:?}", visits);
+    // Every move is selectable from 640 visits.
+    mcts.playout_n(2000);
+    mcts.tree().validate().unwrap();
+    let visits = root_visits(&mcts);
+    assert!(visits.iter().all(|&x| x > 0), "{:?}", visits);
+}
+
+#[test]
+fn lowering_a_prior_prunes_the_move_again() {
+    let mut mcts = manager();
+    mcts.playout_n(30);
+    let root = mcts.tree().root_node();
+    let first = root.moves().next().unwrap();
+    assert_eq!(*first.get_move(), 0);
+    // Below every other prior, so it's now the last move to be unpruned.
+    first.update_move_evaluation(0.0);
+    let before = first.visits();
+    mcts.playout_n(50);
+    let root = mcts.tree().root_node();
+    assert_eq!(root.moves().next().unwrap().visits(), before);
+}
@@ -0,0 +1,79 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+fn manager() -> MCTSManager<FillMCTS> {
+    MCTSManager::new(
+        FillGame::new(6, 3),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    )
+}
+
+#[test]
+fn nothing_is_recorded_by_default() {
+    let mut mcts = manager();
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    assert!(mcts.recorded_playouts().is_empty());
+}
+
+#[test]
+fn replay_reaches_the_recorded_leaf() {
+    let mut mcts = manager();
+    mcts.record_playouts(true);
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    let records = mcts.recorded_playouts();
+    assert_eq!(records.len(), 200);
+    for record in &records {
+        assert!(!record.move_indices.is_empty());
+        let state = mcts.replay(record).unwrap();
+        assert_eq!(state.filled(), record.move_indices.len());
+        assert_eq!(state.score(), record.evaluation);
+    }
+}
+
+#[test]
+fn recording_is_capped() {
+    let mut mcts = manager();
+    mcts.record_playouts_with_capacity(true, 10);
+    mcts.playout_n(50);
+    mcts.tree().validate().unwrap();
+    let records = mcts.recorded_playouts();
+    assert_eq!(records.len(), 10);
+
+    let mut mcts = manager();
+    mcts.record_playouts_with_capacity(true, 10);
+    mcts.playout_n_parallel(400, 4);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.recorded_playouts().len(), 10);
+}
+
+#[test]
+fn replay_of_a_missing_path_fails() {
+    let mut mcts = manager();
+    mcts.record_playouts(true);
+    mcts.playout();
+    let mut record = mcts.recorded_playouts().pop().unwrap();
+    record.move_indices = vec![0, 0, 0, 0];
+    assert!(mcts.replay(&record).is_none());
+}
+
+#[test]
+fn recording_survives_reset() {
+    let mut mcts = manager();
+    mcts.record_playouts_with_capacity(true, 10);
+    mcts.playout_n(5);
+    let mut mcts = mcts.reset();
+    assert!(mcts.recorded_playouts().is_empty());
+    mcts.playout_n(50);
+    assert_eq!(mcts.recorded_playouts().len(), 10);
+}
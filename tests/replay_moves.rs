@@ -0,0 +1,116 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+fn searched(playouts: usize) -> MCTSManager<Spec> {
+    let mut mcts = Spec::manager(TicTacToe::default());
+    mcts.seed(Some(1));
+    mcts.playout_n(playouts);
+    mcts
+}
+
+fn played(moves: &[usize]) -> TicTacToe {
+    let mut state = TicTacToe::default();
+    for mov in moves {
+        state.make_move(mov).unwrap();
+    }
+    state
+}
+
+// A drawn game.
+const GAME: [usize; 9] = [4, 0, 2, 6, 3, 5, 1, 7, 8];
+
+#[test]
+fn replays_a_full_game() {
+    let mut mcts = searched(2000);
+    let summary = mcts.replay_moves(&GAME).unwrap();
+    assert_eq!(summary.plies, 9);
+    assert!(summary.retained_plies < 9);
+    assert_eq!(*mcts.tree().root_state(), played(&GAME));
+    assert!(mcts.tree().root_state().is_terminal());
+    assert_eq!(mcts.tree().num_nodes(), 1);
+    mcts.tree().validate().unwrap();
+}
+
+#[test]
+fn keeps_the_subtree_of_searched_moves() {
+    let mut mcts = searched(5000);
+    let pv = mcts.principal_variation(2);
+    let second = mcts.principal_variation_info(2)[1];
+    let visits = second.visits();
+    let value = second.child().unwrap().mean_value();
+    let nodes = mcts.tree().num_nodes();
+    assert!(visits > 100);
+
+    let summary = mcts.replay_moves(&pv).unwrap();
+    assert_eq!(summary.plies, 2);
+    assert_eq!(summary.retained_plies, 2);
+    assert_eq!(*mcts.tree().root_state(), played(&pv));
+    assert_eq!(mcts.tree().root_visits(), visits);
+    assert_eq!(mcts.tree().root_node().mean_value(), value);
+    assert!(mcts.tree().num_nodes() < nodes);
+    mcts.tree().validate().unwrap();
+
+    // The kept tree searches on like any other.
+    mcts.playout_n(500);
+    assert_eq!(mcts.tree().root_visits(), visits + 500);
+    mcts.tree().validate().unwrap();
+    let mut fresh = Spec::manager(played(&pv));
+    fresh.seed(Some(1));
+    fresh.playout_n(visits as usize + 500);
+    assert_eq!(mcts.best_move(), fresh.best_move());
+}
+
+#[test]
+fn rebuilds_after_an_unsearched_move() {
+    let mut mcts = searched(20);
+    let summary = mcts.replay_moves(&GAME[..6]).unwrap();
+    assert!(summary.retained_plies < 6);
+    assert_eq!(*mcts.tree().root_state(), played(&GAME[..6]));
+    assert_eq!(mcts.tree().num_nodes(), 1);
+    assert_eq!(mcts.tree().root_visits(), 0);
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+}
+
+#[test]
+fn errors_leave_the_manager_alone() {
+    let mut mcts = searched(500);
+    let nodes = mcts.tree().num_nodes();
+    // X wins with 0, 1, 2 at the fifth move.
+    let moves = [0, 3, 1, 4, 2, 5];
+    assert_eq!(
+        mcts.replay_moves(&moves),
+        Err(ReplayError::GameOver { index: 5 })
+    );
+    assert_eq!(*mcts.tree().root_state(), TicTacToe::default());
+    assert_eq!(mcts.tree().num_nodes(), nodes);
+    assert_eq!(mcts.replay_moves(&[]).unwrap().plies, 0);
+
+    let mut mcts = connect_four_manager(ConnectFour::new(), 10_000);
+    mcts.playout_n(200);
+    let error = mcts.replay_moves(&[3, 3, 3, 3, 3, 3, 3]).unwrap_err();
+    assert_eq!(error, ReplayError::IllegalMove { index: 6 });
+    assert_eq!(error.to_string(), "move 6 is illegal");
+    assert_eq!(mcts.tree().root_state().plies(), 0);
+}
+
+#[test]
+fn transposition_tables_start_afresh() {
+    let mut mcts = connect_four_manager(ConnectFour::new(), 10_000);
+    mcts.playout_n(1000);
+    let summary = mcts.replay_moves(&[3, 2]).unwrap();
+    assert_eq!(summary.retained_plies, 0);
+    assert_eq!(
+        *mcts.tree().root_state(),
+        ConnectFour::from_columns(&[3, 2])
+    );
+    assert_eq!(mcts.tree().num_nodes(), 1);
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+}
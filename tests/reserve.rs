@@ -0,0 +1,98 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+fn manager(
+    cells: usize,
+    table: usize,
+) -> MCTSManagerBuilder<FillMCTS, FillGame, FillEvaluator, UCTPolicy<()>, ApproxTable<FillMCTS>> {
+    MCTSManager::builder(FillMCTS)
+        .state(FillGame::new(cells, 3))
+        .evaluator(FillEvaluator)
+        .policy(UCTPolicy::new(5.0))
+        .transposition_table(ApproxTable::new(table))
+        .seed(5)
+}
+
+#[test]
+fn reserved_nodes_are_used_first() {
+    let mut mcts = manager(7, 16).reserve(100).build();
+    let stats = mcts.memory_stats();
+    assert_eq!(stats.nodes, 1);
+    assert_eq!(stats.nodes_reserved, 100);
+    assert_eq!(stats.reserved_nodes_used, 0);
+    assert!(mcts.tree().transposition_table().capacity() * 2 >= 101 * 3);
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    let stats = mcts.memory_stats();
+    assert!(stats.nodes > 1);
+    assert_eq!(stats.reserved_nodes_used, stats.nodes - 1);
+}
+
+#[test]
+fn reservation_is_capped_by_the_node_limit() {
+    let mut mcts = manager(7, 1024).max_nodes(10).reserve(100).build();
+    assert_eq!(mcts.memory_stats().nodes_reserved, 9);
+    mcts.reserve(100);
+    assert_eq!(mcts.memory_stats().nodes_reserved, 9);
+}
+
+#[test]
+fn reservation_does_not_change_the_search() {
+    let mut plain = manager(7, 1024).build();
+    let mut reserved = manager(7, 1024).reserve(50).build();
+    plain.playout_n(300);
+    plain.tree().validate().unwrap();
+    reserved.playout_n(300);
+    reserved.tree().validate().unwrap();
+    let stats = |mcts: &MCTSManager<FillMCTS>| -> Vec<NodeStats> {
+        mcts.tree().root_node().moves().map(|x| x.stats()).collect()
+    };
+    assert_eq!(stats(&plain), stats(&reserved));
+    assert_eq!(plain.tree().num_nodes(), reserved.tree().num_nodes());
+    // Fewer nodes than reserved, so all but the root came from the reservation.
+    assert!(reserved.tree().num_nodes() <= 50);
+    assert_eq!(
+        reserved.memory_stats().reserved_nodes_used,
+        reserved.tree().num_nodes() - 1
+    );
+}
+
+#[test]
+fn growing_the_table_keeps_its_entries() {
+    let mut mcts = manager(5, 64).build();
+    mcts.playout_n(300);
+    mcts.tree().validate().unwrap();
+    // Every set of up to three filled cells out of five.
+    assert_eq!(mcts.tree().num_nodes(), 26);
+    mcts.reserve(1000);
+    assert_eq!(mcts.tree().transposition_table().capacity(), 2048);
+    mcts.playout_n(300);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.tree().num_nodes(), 26);
+}
+
+#[test]
+fn threads_share_the_reservation() {
+    let mut mcts = counting_manager(1e6);
+    mcts.reserve(1000);
+    mcts.playout_n_parallel(600, 8);
+    mcts.tree().validate().unwrap();
+    let used = mcts.memory_stats().reserved_nodes_used;
+    assert!((600..1000).contains(&used), "{} used", used);
+    // Topping up keeps the slots that are left.
+    mcts.reserve(1000);
+    let stats = mcts.memory_stats();
+    assert_eq!(stats.nodes_reserved, 2000);
+    assert_eq!(stats.reserved_nodes_used, used);
+    mcts.playout_n_parallel(5000, 8);
+    mcts.tree().validate().unwrap();
+    let stats = mcts.memory_stats();
+    assert_eq!(stats.reserved_nodes_used, stats.nodes_reserved);
+    assert!(stats.nodes > stats.nodes_reserved);
+}
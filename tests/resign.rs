@@ -0,0 +1,86 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+// O to move can't stop all of X's threats on 1, 4 and 5.
+const LOST: [u8; 9] = [1, 0, 1, 2, 0, 0, 2, 0, 1];
+
+fn manager(cells: [u8; 9], seed: u64) -> MCTSManager<Spec> {
+    let mut mcts = Spec::manager(TicTacToe { cells });
+    mcts.seed(Some(seed));
+    mcts
+}
+
+#[test]
+fn resigns_after_enough_hopeless_queries() {
+    let mut mcts = manager(LOST, 0);
+    mcts.playout_n(5_000);
+    mcts.tree().validate().unwrap();
+    assert!(!mcts.should_resign(0.9, 1_000));
+    assert!(!mcts.should_resign(0.9, 1_000));
+    assert!(mcts.should_resign(0.9, 1_000));
+    assert_eq!(mcts.resign_streak(), 3);
+
+    mcts.resign_patience(1);
+    assert!(mcts.should_resign(0.9, 1_000));
+}
+
+#[test]
+fn too_few_playouts_start_the_count_again() {
+    let mut mcts = manager(LOST, 0);
+    mcts.resign_patience(2);
+    assert!(!mcts.should_resign(0.9, 0));
+    mcts.playout_n(5_000);
+    mcts.tree().validate().unwrap();
+    assert!(!mcts.should_resign(0.9, 1_000));
+    assert!(!mcts.should_resign(0.9, 10_000));
+    assert_eq!(mcts.resign_streak(), 0);
+    assert!(!mcts.should_resign(0.9, 1_000));
+    assert!(mcts.should_resign(0.9, 1_000));
+}
+
+#[test]
+fn streak_carries_over() {
+    let mut first = manager(LOST, 0);
+    first.playout_n(5_000);
+    first.tree().validate().unwrap();
+    first.should_resign(0.9, 1_000);
+    first.should_resign(0.9, 1_000);
+    let mut second = manager(LOST, 1);
+    second.set_resign_streak(first.resign_streak());
+    second.playout_n(5_000);
+    second.tree().validate().unwrap();
+    assert!(second.should_resign(0.9, 1_000));
+}
+
+// A false positive costs a drawn game, so on positions that are a draw with best
+// play it must not happen even once.
+#[test]
+fn never_resigns_a_draw() {
+    let drawn = [
+        [0; 9],
+        [0, 0, 0, 0, 1, 0, 0, 0, 0],
+        [1, 0, 0, 0, 2, 0, 0, 0, 0],
+        [2, 0, 0, 0, 1, 0, 0, 0, 1],
+    ];
+    // Spread over a few threads: a million playouts is slow in debug builds.
+    std::thread::scope(|s| {
+        for thread in 0..4 {
+            s.spawn(move || {
+                for seed in (thread..100).step_by(4) {
+                    let mut mcts = manager(drawn[seed as usize % drawn.len()], seed);
+                    for _ in 0..10 {
+                        mcts.playout_n(1_000);
+                        mcts.tree().validate().unwrap();
+                        assert!(!mcts.should_resign(0.95, 1_000), "seed {}", seed);
+                    }
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,80 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+fn noisy(epsilon: f64, seed: u64, playouts: usize) -> (MCTSManager<Spec>, SearchSummary) {
+    let mut mcts = Spec::manager(TicTacToe::default());
+    mcts.seed(Some(seed));
+    mcts.root_noise(epsilon);
+    let summary = mcts.playout_n(playouts);
+    (mcts, summary)
+}
+
+fn root_visits(mcts: &MCTSManager<Spec>) -> Vec<u64> {
+    mcts.root_move_stats_ref()
+        .iter()
+        .map(|x| x.visits)
+        .collect()
+}
+
+#[test]
+fn epsilon_is_realized() {
+    let n = 4000;
+    for epsilon in [0.1, 0.25, 0.5] {
+        let (mcts, summary) = noisy(epsilon, 1, n);
+        assert_eq!(mcts.tree().root_noise(), epsilon);
+        // Within four standard deviations of the binomial.
+        let tolerance = 4.0 * (epsilon * (1.0 - epsilon) / n as f64).sqrt();
+        let rate = summary.noisy_root_choices as f64 / n as f64;
+        assert!((rate - epsilon).abs() < tolerance, "{} {}", epsilon, rate);
+        assert_eq!(
+            mcts.tree().noisy_root_choices() as u64,
+            summary.noisy_root_choices
+        );
+    }
+}
+
+#[test]
+fn always_noisy_visits_the_root_moves_uniformly() {
+    let n = 9000;
+    let (mcts, summary) = noisy(1.0, 2, n);
+    assert_eq!(summary.noisy_root_choices, n as u64);
+    for visits in root_visits(&mcts) {
+        // 1000 expected, with a standard deviation of about 30.
+        assert!((880..=1120).contains(&visits), "{}", visits);
+    }
+    mcts.tree().validate().unwrap();
+}
+
+#[test]
+fn reproducible_under_the_master_seed() {
+    let (a, first) = noisy(0.3, 3, 1000);
+    let (b, second) = noisy(0.3, 3, 1000);
+    assert_eq!(first.noisy_root_choices, second.noisy_root_choices);
+    assert_eq!(root_visits(&a), root_visits(&b));
+    let (c, _) = noisy(0.3, 4, 1000);
+    assert_ne!(root_visits(&a), root_visits(&c));
+}
+
+#[test]
+fn off_by_default() {
+    let (_, summary) = noisy(0.0, 1, 500);
+    assert_eq!(summary.noisy_root_choices, 0);
+    assert!(!summary.to_string().contains("random root move"));
+    let (mcts, summary) = noisy(0.2, 1, 500);
+    assert!(summary
+        .to_string()
+        .contains("playouts took a random root move"));
+    // Out of range values are clamped, and the setting survives `reset`.
+    let mut mcts = mcts.reset();
+    assert_eq!(mcts.tree().root_noise(), 0.2);
+    mcts.root_noise(7.0);
+    assert_eq!(mcts.tree().root_noise(), 1.0);
+    mcts.root_noise(f64::NAN);
+    assert_eq!(mcts.tree().root_noise(), 0.0);
+}
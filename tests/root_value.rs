@@ -0,0 +1,122 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+fn value_after(cells: [u8; 9], playouts: usize) -> ValueEstimate {
+    let mut mcts = Spec::manager(TicTacToe { cells });
+    mcts.seed(Some(0));
+    mcts.playout_n(playouts);
+    mcts.tree().validate().unwrap();
+    mcts.root_value().unwrap()
+}
+
+#[test]
+fn none_before_any_playouts() {
+    let mcts = Spec::manager(TicTacToe::default());
+    assert_eq!(mcts.root_value(), None);
+}
+
+#[test]
+fn none_without_moves() {
+    // X has already won.
+    let mut mcts = Spec::manager(TicTacToe {
+        cells: [1, 1, 1, 2, 2, 0, 0, 0, 0],
+    });
+    mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.root_value(), None);
+}
+
+#[test]
+fn forced_win_approaches_one() {
+    // X to move completes the top row.
+    let cells = [1, 1, 0, 2, 2, 0, 0, 0, 0];
+    let early = value_after(cells, 100);
+    let late = value_after(cells, 10_000);
+    assert!(late.mean > 0.95, "{:?}", late);
+    assert!(late.mean > early.mean);
+    #[cfg(feature = "reward_variance")]
+    assert!(late.std_error < early.std_error);
+    assert_eq!(late.visits, 10_000);
+}
+
+#[test]
+fn forced_loss_approaches_minus_one() {
+    // O to move can't stop all of X's threats on 1, 4 and 5.
+    let cells = [1, 0, 1, 2, 0, 0, 2, 0, 1];
+    let late = value_after(cells, 10_000);
+    assert!(late.mean < -0.95, "{:?}", late);
+    #[cfg(feature = "reward_variance")]
+    assert!(late.std_error < 0.01);
+}
+
+#[test]
+fn matches_the_root_moves() {
+    let mut mcts = Spec::manager(TicTacToe::default());
+    mcts.seed(Some(1));
+    mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    let value = mcts.root_value().unwrap();
+    let root = mcts.tree().root_node();
+    assert_eq!(value.visits, root.visits());
+    assert!((value.mean - root.mean_value()).abs() < 1e-12);
+    // Rewards are -1, 0 or 1, so the standard error is at most 1 / sqrt(n - 1).
+    #[cfg(feature = "reward_variance")]
+    assert!(value.std_error > 0.0 && value.std_error <= 1.0 / 499f64.sqrt());
+}
+
+#[test]
+fn root_statistics_count_every_playout() {
+    let mut mcts = Spec::manager(TicTacToe::default());
+    mcts.seed(Some(2));
+    let first = mcts.playout_n(300);
+    let second = mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    let tree = mcts.tree();
+    assert_eq!(tree.root_visits(), 500);
+    assert_eq!(tree.root_visits(), tree.depth_histogram().total());
+    let sum: f64 = tree.root_node().moves().map(|x| x.sum_rewards()).sum();
+    assert_eq!(tree.root_sum_rewards(), sum);
+    // The summary has the totals at the end of each search.
+    assert_eq!(first.root_visits, 300);
+    assert_eq!(second.root_visits, 500);
+    assert_eq!(second.root_sum_rewards, sum);
+    assert!(second.to_string().contains("root: 500 visits"));
+}
+
+#[test]
+fn root_rewards_are_for_the_player_to_move() {
+    // X to move completes the top row, so X's rewards are near 1.
+    let mut mcts = Spec::manager(TicTacToe {
+        cells: [1, 1, 0, 2, 2, 0, 0, 0, 0],
+    });
+    mcts.seed(Some(0));
+    let summary = mcts.playout_n(5000);
+    assert!(summary.root_mean() > 0.9, "{}", summary.root_mean());
+    assert_eq!(summary.root_mean(), mcts.root_value().unwrap().mean);
+
+    // O to move can't stop all of X's threats, so O's rewards are near -1.
+    let mut mcts = Spec::manager(TicTacToe {
+        cells: [1, 0, 1, 2, 0, 0, 2, 0, 1],
+    });
+    mcts.seed(Some(0));
+    let summary = mcts.playout_n(5000);
+    assert!(summary.root_mean() < -0.9, "{}", summary.root_mean());
+}
+
+#[test]
+fn terminal_root_has_visits_but_no_rewards() {
+    // X has already won.
+    let mut mcts = Spec::manager(TicTacToe {
+        cells: [1, 1, 1, 2, 2, 0, 0, 0, 0],
+    });
+    let summary = mcts.playout_n(10);
+    assert_eq!(mcts.tree().root_visits(), 10);
+    assert_eq!(mcts.tree().root_sum_rewards(), 0.0);
+    assert_eq!(summary.root_visits, 10);
+}
@@ -0,0 +1,162 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+
+const PRIORS: [f64; 4] = [0.1, 0.2, 0.3, 0.4];
+
+/// One move out of four, worth its number minus one.
+#[derive(Clone, Default)]
+struct OneMove(Option<u8>);
+
+impl GameState for OneMove {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        match self.0 {
+            Some(_) => vec![],
+            None => vec![0, 1, 2, 3],
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0 = Some(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct OneMoveEvaluator;
+
+impl Evaluator<OneMoveMCTS> for OneMoveEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &OneMove,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<OneMoveMCTS>>,
+    ) -> (Vec<f64>, f64) {
+        let priors = moves.iter().map(|&x| PRIORS[x as usize]).collect();
+        let value = state.0.map_or(0.0, |x| x as f64 - 1.0);
+        (priors, value)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &OneMove,
+        evaln: &f64,
+        _: SearchHandle<OneMoveMCTS>,
+    ) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct OneMoveMCTS;
+
+impl MCTS for OneMoveMCTS {
+    type State = OneMove;
+    type Eval = OneMoveEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+}
+
+fn manager(state: OneMove) -> MCTSManager<OneMoveMCTS> {
+    MCTSManager::new(
+        state,
+        OneMoveMCTS,
+        OneMoveEvaluator,
+        AlphaGoPolicy::new(2.0),
+        (),
+    )
+}
+
+/// How often each move is drawn, over many seeds.
+fn frequencies(mcts: &MCTSManager<OneMoveMCTS>, key: RootKey) -> [f64; 4] {
+    const DRAWS: u64 = 20_000;
+    let mut counts = [0; 4];
+    for seed in 0..DRAWS {
+        counts[mcts.sample_move_weighted(key, seed).unwrap() as usize] += 1;
+    }
+    counts.map(|x| x as f64 / DRAWS as f64)
+}
+
+fn assert_close(actual: [f64; 4], expected: [f64; 4]) {
+    for (a, e) in actual.iter().zip(expected.iter()) {
+        assert!((a - e).abs() < 0.015, "{:?} against {:?}", actual, expected);
+    }
+}
+
+fn searched() -> MCTSManager<OneMoveMCTS> {
+    let mut mcts = manager(OneMove::default());
+    mcts.playout_n(400);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+#[test]
+fn in_proportion_to_visits() {
+    let mcts = searched();
+    let visits: Vec<u64> = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect();
+    let total = visits.iter().sum::<u64>() as f64;
+    let expected = [0, 1, 2, 3].map(|i| visits[i] as f64 / total);
+    assert_close(frequencies(&mcts, RootKey::Visits), expected);
+}
+
+#[test]
+fn in_proportion_to_shifted_means() {
+    // Means of -1 to 2, shifted to 0 to 3.
+    let mcts = searched();
+    assert_close(
+        frequencies(&mcts, RootKey::Mean),
+        [0.0, 1.0 / 6.0, 2.0 / 6.0, 3.0 / 6.0],
+    );
+}
+
+#[test]
+fn in_proportion_to_priors() {
+    let mcts = manager(OneMove::default());
+    assert_close(frequencies(&mcts, RootKey::Prior), PRIORS);
+}
+
+#[test]
+fn uniform_when_every_weight_is_zero() {
+    let mcts = manager(OneMove::default());
+    assert_close(frequencies(&mcts, RootKey::Visits), [0.25; 4]);
+}
+
+#[test]
+fn same_seed_same_move() {
+    let mcts = searched();
+    for seed in 0..20 {
+        assert_eq!(
+            mcts.sample_move_weighted(RootKey::Visits, seed),
+            mcts.sample_move_weighted(RootKey::Visits, seed)
+        );
+    }
+}
+
+#[test]
+fn none_without_candidates() {
+    let mcts = manager(OneMove(Some(0)));
+    assert_eq!(mcts.sample_move_weighted(RootKey::Visits, 0), None);
+    // No move has a mean before the search.
+    let mcts = manager(OneMove::default());
+    assert_eq!(mcts.sample_move_weighted(RootKey::Mean, 0), None);
+}
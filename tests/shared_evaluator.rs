@@ -0,0 +1,103 @@
+extern crate mcts;
+
+mod common;
+
+use common::FillGame;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Scores `FillGame` states, remembering the ones it has seen.
+#[derive(Default)]
+struct CachingEvaluator {
+    cache: Mutex<HashMap<FillGame, usize>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl CachingEvaluator {
+    fn hit_rate(&self) -> f64 {
+        let hits = self.hits.swap(0, Ordering::Relaxed) as f64;
+        let misses = self.misses.swap(0, Ordering::Relaxed) as f64;
+        hits / (hits + misses)
+    }
+}
+
+impl Evaluator<SharedMCTS> for CachingEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<SharedMCTS>>,
+    ) -> (Vec<()>, usize) {
+        let mut cache = self.cache.lock().unwrap();
+        let score = match cache.get(state) {
+            Some(&score) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                score
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                *cache.entry(state.clone()).or_insert(state.score())
+            }
+        };
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &usize,
+        _: SearchHandle<SharedMCTS>,
+    ) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct SharedMCTS;
+
+impl MCTS for SharedMCTS {
+    type State = FillGame;
+    type Eval = Arc<CachingEvaluator>;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+fn manager(eval: &Arc<CachingEvaluator>) -> MCTSManager<SharedMCTS> {
+    MCTSManager::new(
+        FillGame::new(8, 3),
+        SharedMCTS,
+        eval.clone(),
+        UCTPolicy::new(5.0),
+        (),
+    )
+}
+
+#[test]
+fn a_shared_cache_outlives_its_managers() {
+    let eval = Arc::new(CachingEvaluator::default());
+    let mut first = manager(&eval);
+    first.playout_n(2000);
+    assert_eq!(first.best_move(), Some(7));
+    // Without a transposition table, every order of the same cells is evaluated
+    // again, so even the first search hits the cache.
+    let first_rate = eval.hit_rate();
+    drop(first);
+
+    let mut second = manager(&eval);
+    second.playout_n(2000);
+    assert_eq!(second.best_move(), Some(7));
+    let second_rate = eval.hit_rate();
+    assert!(second_rate > 0.8, "hit rate {}", second_rate);
+    assert!(second_rate > first_rate);
+    assert_eq!(Arc::strong_count(&eval), 2);
+}
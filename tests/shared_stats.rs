@@ -0,0 +1,91 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Visits and summed rewards per move, over every search of a game.
+#[derive(Default)]
+struct MoveTable(Mutex<HashMap<CountingMove, (u64, f64)>>);
+
+impl MoveTable {
+    fn get(&self, mov: CountingMove) -> (u64, f64) {
+        let table = self.0.lock().unwrap();
+        table.get(&mov).cloned().unwrap_or_default()
+    }
+}
+
+impl SharedStats<CountingMove> for MoveTable {
+    fn record(&self, mov: &CountingMove, reward: f64) {
+        let mut table = self.0.lock().unwrap();
+        let stats = table.entry(*mov).or_default();
+        stats.0 += 1;
+        stats.1 += reward;
+    }
+}
+
+/// Per move, the sum of the statistics of every edge in the tree with that move.
+fn tree_totals(node: NodeHandle<CountingMCTS>, out: &mut HashMap<CountingMove, (u64, f64)>) {
+    for (mov, stats, child) in node.children() {
+        let total = out.entry(*mov).or_default();
+        total.0 += stats.visits;
+        total.1 += stats.sum_rewards;
+        if let Some(child) = child {
+            tree_totals(child, out);
+        }
+    }
+}
+
+fn manager(count: i64) -> MCTSManager<CountingMCTS> {
+    MCTSManager::new(
+        CountingGame(count),
+        CountingMCTS,
+        CountingEvaluator,
+        UCTPolicy::new(0.5),
+        (),
+    )
+}
+
+#[test]
+fn table_reflects_every_search_of_a_game() {
+    let table = Arc::new(MoveTable::default());
+    let mut expected: HashMap<CountingMove, (u64, f64)> = HashMap::new();
+    for count in 0..3 {
+        let mut mcts = manager(count);
+        mcts.set_shared_stats(Some(table.clone()));
+        mcts.playout_n(200);
+        tree_totals(mcts.tree().root_node(), &mut expected);
+        for mov in [CountingMove::Add, CountingMove::Sub].iter() {
+            let (got, want) = (table.get(*mov), expected[mov]);
+            assert_eq!(got.0, want.0);
+            assert!((got.1 - want.1).abs() < 1e-6);
+        }
+    }
+    assert!(table.get(CountingMove::Add).0 > 600);
+}
+
+#[test]
+fn builder_installs_the_table() {
+    let table = Arc::new(MoveTable::default());
+    let mut mcts = MCTSManager::builder(CountingMCTS)
+        .state(CountingGame(0))
+        .evaluator(CountingEvaluator)
+        .policy(UCTPolicy::new(0.5))
+        .transposition_table(())
+        .shared_stats(table.clone())
+        .build();
+    assert!(mcts.shared_stats().is_some());
+    mcts.playout_n(50);
+    let add = table.get(CountingMove::Add).0;
+    assert!(add + table.get(CountingMove::Sub).0 >= 50);
+}
+
+#[test]
+fn searches_without_a_table_record_nothing() {
+    let mcts = manager(0);
+    assert!(mcts.shared_stats().is_none());
+}
@@ -0,0 +1,108 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::thread;
+
+/// Every (path, visits) pair in the live tree, in node order.
+fn walk_tree(node: NodeHandle<FillMCTS>, path: &mut Vec<usize>, out: &mut Vec<(Vec<usize>, u64)>) {
+    for (mov, stats, child) in node.children() {
+        path.push(*mov);
+        out.push((path.clone(), stats.visits));
+        if let Some(child) = child {
+            walk_tree(child, path, out);
+        }
+        path.pop();
+    }
+}
+
+fn walk_snapshot(
+    node: SnapshotNode<FillMCTS>,
+    path: &mut Vec<usize>,
+    out: &mut Vec<(Vec<usize>, u64)>,
+) {
+    for (mov, stats, child) in node.children() {
+        path.push(*mov);
+        out.push((path.clone(), stats.visits));
+        if let Some(child) = child {
+            walk_snapshot(child, path, out);
+        }
+        path.pop();
+    }
+}
+
+#[test]
+fn snapshot_matches_the_tree() {
+    let tree = fill_tree(5, 3);
+    let mut tld = fill_thread_data(3);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    let snapshot = tree.snapshot();
+    let mut expected = Vec::new();
+    let mut actual = Vec::new();
+    walk_tree(tree.root_node(), &mut Vec::new(), &mut expected);
+    walk_snapshot(snapshot.root_node(), &mut Vec::new(), &mut actual);
+    assert_eq!(actual, expected);
+
+    let root = snapshot.root_node();
+    assert_eq!(root.visits(), 200);
+    assert_eq!(root.mean_value(), tree.root_node().mean_value());
+    assert_eq!(*root.state_evaluation(), 0);
+    for (copy, mov) in root.moves().zip(tree.root_node().moves()) {
+        assert_eq!(copy.get_move(), mov.get_move());
+        assert_eq!(copy.stats(), mov.stats());
+    }
+    let child = root.descend(&2).unwrap();
+    assert_eq!(*child.state_evaluation(), 2);
+    assert!(root.descend(&5).is_none());
+}
+
+#[test]
+fn transpositions_stay_shared() {
+    let tree = fill_tree(5, 3);
+    let mut tld = fill_thread_data(3);
+    for _ in 0..500 {
+        tree.playout(&mut tld);
+    }
+    let snapshot = tree.snapshot();
+    // 1 + 5 + 10 + 10 distinct sets of filled cells, against 86 paths.
+    assert!(snapshot.num_nodes() <= 26, "{} nodes", snapshot.num_nodes());
+    let root = snapshot.root_node();
+    let a = root.descend(&0).and_then(|x| x.descend(&1));
+    let b = root.descend(&1).and_then(|x| x.descend(&0));
+    if let (Some(a), Some(b)) = (a, b) {
+        assert_eq!(a.visits(), b.visits());
+    }
+}
+
+#[test]
+fn snapshot_is_unaffected_by_later_playouts() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(5, 3),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    let snapshot = mcts.tree().snapshot();
+    let mut before = Vec::new();
+    walk_snapshot(snapshot.root_node(), &mut Vec::new(), &mut before);
+
+    let analysis = thread::spawn(move || {
+        let mut out = Vec::new();
+        walk_snapshot(snapshot.root_node(), &mut Vec::new(), &mut out);
+        (snapshot.root_node().visits(), out)
+    });
+    mcts.playout_n(300);
+    mcts.tree().validate().unwrap();
+    let (visits, after) = analysis.join().unwrap();
+    assert_eq!(visits, 100);
+    assert_eq!(after, before);
+}
@@ -0,0 +1,157 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+#[cfg(not(feature = "wasm"))]
+use std::time::Duration;
+
+// The thread data alive, and the names of the threads that made it. Tests that
+// count them take `SERIAL` first.
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+static THREADS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static SERIAL: Mutex<()> = Mutex::new(());
+
+// A test that failed holding `SERIAL` leaves the count as the others expect it:
+// its thread data is dropped as it unwinds.
+fn serial() -> MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|x| x.into_inner())
+}
+
+struct Tracked;
+
+impl Default for Tracked {
+    fn default() -> Self {
+        LIVE.fetch_add(1, Ordering::SeqCst);
+        let name = std::thread::current().name().unwrap_or("").to_string();
+        THREADS.lock().unwrap().push(name);
+        Tracked
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        LIVE.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+struct TrackedMCTS;
+
+impl MCTS for TrackedMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = Tracked;
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+fn manager() -> MCTSManager<TrackedMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(5, 2),
+        TrackedMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    );
+    mcts.seed(Some(1));
+    mcts
+}
+
+// Runs the workers one after another on the caller's thread.
+#[derive(Default)]
+struct InPlace {
+    runs: Cell<usize>,
+}
+
+impl Spawner for InPlace {
+    fn run(&self, workers: usize, work: &(dyn Fn(usize) + Sync)) {
+        self.runs.set(self.runs.get() + 1);
+        for i in 0..workers {
+            work(i);
+        }
+    }
+}
+
+fn visits(mcts: &MCTSManager<TrackedMCTS>) -> Vec<u64> {
+    mcts.tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect()
+}
+
+#[test]
+fn workers_run_on_the_spawner() {
+    let _serial = serial();
+    let spawner = InPlace::default();
+    let mut mcts = manager();
+    let summary = mcts.playout_n_parallel_on(300, 3, &spawner);
+    assert_eq!(summary.playouts, 300);
+    assert_eq!(spawner.runs.get(), 1);
+    mcts.tree().validate().unwrap();
+    assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+
+    // One worker searches as one spawned thread does.
+    let mut other = manager();
+    other.playout_n_parallel(300, 1);
+    let mut mine = manager();
+    mine.playout_n_parallel_on(300, 1, &spawner);
+    assert_eq!(visits(&mine), visits(&other));
+}
+
+// Timed searches need a clock under the `wasm` feature.
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn timed_searches_count_each_worker() {
+    let _serial = serial();
+    let spawner = InPlace::default();
+    let mut mcts = manager();
+    let summary = mcts.playout_parallel_for_on(Duration::from_millis(20), 2, &spawner);
+    assert_eq!(summary.thread_playouts.len(), 2);
+    assert_eq!(
+        summary.thread_playouts.iter().sum::<u64>(),
+        summary.playouts
+    );
+    // Run in turn, the first worker used the whole budget.
+    assert!(summary.thread_playouts[0] > 0);
+    assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn spawned_threads_are_a_spawner() {
+    let _serial = serial();
+    let mut mcts = manager();
+    let summary = mcts.playout_n_parallel_on(500, 4, &SpawnThreads);
+    assert_eq!(summary.playouts, 500);
+    mcts.tree().validate().unwrap();
+    assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn rayon_pools_keep_no_thread_data() {
+    let _serial = serial();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .thread_name(|i| format!("pool {}", i))
+        .build()
+        .unwrap();
+    THREADS.lock().unwrap().clear();
+    let mut mcts = manager();
+    for _ in 0..5 {
+        let summary = mcts.playout_n_parallel_on(100, 4, &pool);
+        assert_eq!(summary.playouts, 100);
+        assert_eq!(LIVE.load(Ordering::SeqCst), 0);
+    }
+    mcts.tree().validate().unwrap();
+    let threads = THREADS.lock().unwrap();
+    assert_eq!(threads.len(), 20);
+    assert!(threads.iter().all(|x| x.starts_with("pool ")));
+}
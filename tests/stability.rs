@@ -0,0 +1,147 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// Eight plies of a single-player game with two moves each, scoring a hash of the
+// moves played after the first: both root moves lead to the same subtree, so
+// neither stays ahead for long.
+#[derive(Clone, Debug, Default, PartialEq, Hash)]
+struct CoinFlips(Vec<u8>);
+
+impl GameState for CoinFlips {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.0.len() == 8 {
+            vec![]
+        } else {
+            vec![0, 1]
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct CoinEvaluator;
+
+impl Evaluator<CoinMCTS> for CoinEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &CoinFlips,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<CoinMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let mut hasher = DefaultHasher::new();
+        state.0.get(1..).hash(&mut hasher);
+        let score = (hasher.finish() % 1000) as f64 / 1000.0;
+        (vec![(); moves.len()], score)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &CoinFlips,
+        evaln: &f64,
+        _: SearchHandle<CoinMCTS>,
+    ) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct CoinMCTS;
+
+impl MCTS for CoinMCTS {
+    type State = CoinFlips;
+    type Eval = CoinEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn a_forced_win_never_flips() {
+    // X wins at 2.
+    let state = TicTacToe {
+        cells: [1, 1, 0, 2, 2, 0, 0, 0, 0],
+    };
+    let mut mcts = TwoPlayerZeroSumSpec::<TicTacToe>::manager(state);
+    mcts.seed(Some(1));
+    mcts.track_stability_with(true, 100, 16);
+    mcts.playout_n(3000);
+    let report = mcts.stability_report();
+    assert_eq!(report.samples.len(), 16);
+    assert_eq!(report.samples[0].playouts, 1500);
+    assert_eq!(report.samples[15].playouts, 3000);
+    assert_eq!(report.flips, 0);
+    let best = mcts
+        .tree()
+        .root_node()
+        .moves()
+        .position(|x| *x.get_move() == 2);
+    assert!(report.samples.iter().all(|x| x.best_move_index == best));
+    assert!(report.value_variance < 0.01);
+}
+
+#[test]
+fn a_near_tie_flips() {
+    let mut mcts = MCTSManager::new(
+        CoinFlips::default(),
+        CoinMCTS,
+        CoinEvaluator,
+        UCTPolicy::new(2.0),
+        (),
+    );
+    mcts.track_stability_with(true, 7, 64);
+    mcts.playout_n(448);
+    let report = mcts.stability_report();
+    assert_eq!(report.samples.len(), 64);
+    assert!(report.flips >= 5, "{:?}", report);
+    assert!(report.value_variance > 0.0);
+}
+
+#[test]
+fn off_by_default_and_kept_by_reset() {
+    let mut mcts = TwoPlayerZeroSumSpec::<TicTacToe>::manager(TicTacToe::default());
+    mcts.playout_n(500);
+    let report = mcts.stability_report();
+    assert!(report.samples.is_empty());
+    assert_eq!(report.flips, 0);
+    assert!(report.value_variance.is_nan());
+
+    mcts.track_stability(true);
+    mcts.playout_n(640);
+    let samples = mcts.stability_report().samples;
+    assert_eq!(samples.len(), 10);
+    assert!(samples.iter().all(|x| x.playouts % 64 == 0));
+    // The totals count from the start of the tree.
+    assert_eq!(samples[9].playouts, 1088);
+
+    let mut mcts = mcts.reset();
+    assert!(mcts.stability_report().samples.is_empty());
+    mcts.playout_n(128);
+    assert_eq!(mcts.stability_report().samples.len(), 2);
+    mcts.track_stability(false);
+    mcts.playout_n(128);
+    assert_eq!(mcts.stability_report().samples.len(), 2);
+}
@@ -0,0 +1,45 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+fn best_move(cells: [u8; 9], seed: u64) -> Option<usize> {
+    let mut mcts = TwoPlayerZeroSumSpec::manager(TicTacToe { cells });
+    mcts.seed(Some(seed));
+    mcts.playout_n(3000);
+    mcts.tree().validate().unwrap();
+    mcts.best_move()
+}
+
+// The searches are seeded, so these moves are what the f64 build picks; the f32
+// build must agree.
+#[test]
+fn both_widths_choose_the_same_moves() {
+    // Player 1 wins at 2.
+    assert_eq!(best_move([1, 1, 0, 2, 2, 0, 0, 0, 0], 1), Some(2));
+    // Player 2 must block at 2.
+    assert_eq!(best_move([1, 1, 0, 0, 2, 0, 0, 0, 0], 2), Some(2));
+    assert_eq!(best_move([0; 9], 0), Some(4));
+    assert_eq!(best_move([1, 2, 0, 0, 0, 0, 0, 0, 0], 0), Some(4));
+}
+
+#[test]
+fn f32_stats_are_smaller() {
+    let mcts = TwoPlayerZeroSumSpec::manager(TicTacToe::default());
+    let bytes = mcts.memory_stats().bytes_per_move;
+    // A `usize` move, the child pointer, the visit count, the sum of the rewards and
+    // with `reward_variance` the sum of their squares, then the in-flight count and
+    // flags, padded to 8 bytes. Next to those 4 bytes, an f32 sum saves 8.
+    let expected = match (
+        cfg!(feature = "reward_variance"),
+        cfg!(feature = "f32_stats"),
+    ) {
+        (false, false) => 40,
+        (false, true) => 32,
+        (true, false) => 48,
+        (true, true) => 40,
+    };
+    assert_eq!(bytes, expected);
+}
@@ -0,0 +1,171 @@
+extern crate mcts;
+
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// A state holding a float can't derive Hash or Eq.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct FloatGame {
+    value: f64,
+    moves_left: u8,
+}
+
+impl GameState for FloatGame {
+    type Move = f64;
+    type Player = ();
+    type MoveList = Vec<f64>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<f64> {
+        if self.moves_left == 0 {
+            vec![]
+        } else {
+            vec![-0.5, 0.5]
+        }
+    }
+    fn make_move(&mut self, mov: &f64) -> Result<(), ()> {
+        self.value += mov;
+        self.moves_left -= 1;
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct FloatEvaluator;
+
+impl<Spec: MCTS<State = FloatGame>> Evaluator<Spec> for FloatEvaluator
+where
+    Spec::TreePolicy: TreePolicy<Spec, MoveEvaluation = ()>,
+{
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FloatGame,
+        moves: &Vec<f64>,
+        _: Option<SearchHandle<Spec>>,
+    ) -> (Vec<()>, f64) {
+        (vec![(); moves.len()], state.value)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &FloatGame, evaln: &f64, _: SearchHandle<Spec>) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct NoTableMCTS;
+
+impl MCTS for NoTableMCTS {
+    type State = FloatGame;
+    type Eval = FloatEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn unhashable_state_without_table() {
+    let game = FloatGame {
+        value: 0.0,
+        moves_left: 4,
+    };
+    let mut mcts = MCTSManager::new(game, NoTableMCTS, FloatEvaluator, UCTPolicy::new(1.0), ());
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.best_move(), Some(0.5));
+}
+
+// The same game, wrapped so it can be hashed, works with a transposition table.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct HashedFloatGame(FloatGame);
+
+impl std::hash::Hash for HashedFloatGame {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.value.to_bits().hash(state);
+        self.0.moves_left.hash(state);
+    }
+}
+
+impl GameState for HashedFloatGame {
+    type Move = f64;
+    type Player = ();
+    type MoveList = Vec<f64>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<f64> {
+        self.0.available_moves()
+    }
+    fn make_move(&mut self, mov: &f64) -> Result<(), ()> {
+        self.0.make_move(mov)
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+struct HashedEvaluator;
+
+impl Evaluator<TableMCTS> for HashedEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &HashedFloatGame,
+        moves: &Vec<f64>,
+        _: Option<SearchHandle<TableMCTS>>,
+    ) -> (Vec<()>, f64) {
+        (vec![(); moves.len()], state.0.value)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &HashedFloatGame,
+        evaln: &f64,
+        _: SearchHandle<TableMCTS>,
+    ) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct TableMCTS;
+
+impl MCTS for TableMCTS {
+    type State = HashedFloatGame;
+    type Eval = HashedEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ApproxTable<Self>;
+}
+
+#[test]
+fn hashable_state_with_table() {
+    let game = HashedFloatGame(FloatGame {
+        value: 0.0,
+        moves_left: 4,
+    });
+    let mut mcts = MCTSManager::new(
+        game,
+        TableMCTS,
+        HashedEvaluator,
+        UCTPolicy::new(1.0),
+        ApproxTable::new(1024),
+    );
+    mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    assert_eq!(mcts.best_move(), Some(0.5));
+}
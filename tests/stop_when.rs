@@ -0,0 +1,49 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+fn manager(cells: [u8; 9]) -> MCTSManager<Spec> {
+    let mut mcts = Spec::manager(TicTacToe { cells });
+    mcts.seed(Some(3));
+    mcts
+}
+
+#[test]
+fn a_forced_line_stops_early() {
+    // X to move completes the column 0, 3, 6.
+    let mut mcts = manager([1, 2, 0, 0, 2, 0, 1, 0, 0]);
+    let stop = StopWhen::pv_depth(4, 50).check_every(10);
+    let summary = mcts.playout_n_until(20_000, &stop);
+    assert_eq!(summary.stop_reason, StopReason::Condition);
+    assert!(summary.playouts < 2_000, "{}", summary.playouts);
+    assert_eq!(summary.playouts % 10, 0);
+    assert_eq!(mcts.best_move(), Some(3));
+    assert!(stop.is_met(mcts.tree()));
+    assert!(summary.to_string().contains("stop condition"));
+}
+
+#[test]
+fn a_balanced_opening_runs_out_the_budget() {
+    let mut mcts = manager([0; 9]);
+    let stop = StopWhen::pv_depth(9, 200);
+    let summary = mcts.playout_n_until(3_000, &stop);
+    assert_eq!(summary.stop_reason, StopReason::Budget);
+    assert_eq!(summary.playouts, 3_000);
+    assert!(!stop.is_met(mcts.tree()));
+}
+
+#[test]
+fn a_condition_met_at_the_start_runs_nothing() {
+    let mut mcts = manager([0; 9]);
+    mcts.playout_n(1_000);
+    let stop = StopWhen::pv_depth(1, 10);
+    assert_eq!(stop.interval(), StopWhen::DEFAULT_INTERVAL);
+    let summary = mcts.playout_n_until(1_000, &stop);
+    assert_eq!(summary.stop_reason, StopReason::Condition);
+    assert_eq!(summary.playouts, 0);
+}
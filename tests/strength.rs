@@ -0,0 +1,47 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::arena::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+const PLAYOUTS: usize = 100;
+
+fn uct_agent(exploration: f64) -> MCTSAgent<Spec, impl FnMut(TicTacToe) -> MCTSManager<Spec>> {
+    let make_manager = move |state| {
+        MCTSManager::builder(Spec::default())
+            .state(state)
+            .evaluator(WinDrawLossEvaluator)
+            .policy(UCTPolicy::new(exploration))
+            .transposition_table(())
+            .build()
+    };
+    MCTSAgent::new(make_manager, Budget::Playouts(PLAYOUTS))
+}
+
+// Every pair of first moves.
+fn openings() -> Vec<TicTacToe> {
+    let mut openings = Vec::new();
+    for first in 0..9 {
+        for second in (0..9).filter(|&x| x != first) {
+            let mut state = TicTacToe::default();
+            state.make_move(&first).unwrap();
+            state.make_move(&second).unwrap();
+            openings.push(state);
+        }
+    }
+    openings
+}
+
+// Guards against changes that weaken the search: with too little exploration, it
+// settles on a move before seeing the replies to the others.
+#[test]
+fn exploring_beats_greedy_search() {
+    let report = strength_test(&openings(), &mut uct_agent(4.4), &mut uct_agent(0.1), 6, 1);
+    assert_eq!(report.tally.games(), 864);
+    assert!(report.a_is_stronger(0.01), "{:?}", report);
+}
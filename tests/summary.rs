@@ -0,0 +1,88 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+#[cfg(not(feature = "wasm"))]
+use std::time::Duration;
+
+fn manager() -> MCTSManager<FillMCTS> {
+    MCTSManager::new(
+        FillGame::new(6, 3),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    )
+}
+
+#[test]
+fn summary_counts_playouts_and_depths() {
+    let mut mcts = manager();
+    let summary = mcts.playout_n(500);
+    mcts.tree().validate().unwrap();
+    assert_eq!(summary.playouts, 500);
+    assert_eq!(summary.nodes_created, mcts.tree().num_nodes() as u64 - 1);
+    let histogram = &summary.depth_histogram;
+    assert_eq!(histogram.total(), 500);
+    // Every playout stops at a new node or at the end of the game, 3 plies down.
+    assert_eq!(histogram.counts()[0], 0);
+    assert!(histogram.counts()[4..].iter().all(|&x| x == 0));
+    assert_eq!(histogram.percentile(1.0), Some(3));
+    assert_eq!(histogram.percentile(0.0), Some(1));
+    assert!(summary.playouts_per_second() > 0.0);
+
+    let second = mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    assert_eq!(second.playouts, 100);
+    assert_eq!(second.depth_histogram.percentile(0.5), Some(3));
+    assert_eq!(mcts.tree().depth_histogram().total(), 600);
+}
+
+#[test]
+// `playout_for` needs a clock under the `wasm` feature.
+#[cfg(not(feature = "wasm"))]
+fn parallel_and_timed_summaries() {
+    let mut mcts = manager();
+    assert_eq!(mcts.playout_n_parallel(1000, 4).playouts, 1000);
+    let summary = mcts.playout_for(Duration::from_millis(20));
+    mcts.tree().validate().unwrap();
+    assert!(summary.playouts > 0);
+    assert!(summary.elapsed >= Duration::from_millis(20));
+}
+
+#[test]
+fn totals_add_up_over_searches() {
+    let mut mcts = counting_manager(1.0);
+    let first = mcts.playout_n(1000);
+    let second = mcts.playout_n(1000);
+    assert_eq!(first.playouts, 1000);
+    assert_eq!(second.playouts, 1000);
+    let tree = mcts.tree();
+    assert_eq!(tree.total_playouts(), 2000);
+    let created = first.nodes_created + second.nodes_created;
+    assert_eq!(tree.total_nodes_created(), created);
+    assert_eq!(tree.total_nodes_created(), tree.num_nodes() as u64 - 1);
+    // Without a transposition table, every node created was evaluated once.
+    assert_eq!(tree.total_evaluations(), created);
+
+    let mcts = mcts.reset();
+    assert_eq!(mcts.tree().total_playouts(), 0);
+    assert_eq!(mcts.tree().total_nodes_created(), 0);
+}
+
+#[test]
+fn totals_count_transpositions_once() {
+    let mut mcts = manager();
+    mcts.playout_n(500);
+    let tree = mcts.tree();
+    assert_eq!(tree.total_playouts(), 500);
+    assert_eq!(tree.total_nodes_created(), tree.num_nodes() as u64 - 1);
+    // Playouts ending at the end of the game, or at a transposition, evaluate
+    // nothing new.
+    assert!(tree.total_evaluations() >= tree.total_nodes_created());
+    assert!(tree.total_evaluations() < 500);
+}
@@ -0,0 +1,53 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+#[test]
+fn sizes_round_up_to_a_power_of_two() {
+    for (size, capacity) in [(0, 1), (1, 1), (2, 2), (3, 4), (1000, 1024), (1024, 1024)] {
+        assert_eq!(ApproxTable::<FillMCTS>::new(size).capacity(), capacity);
+    }
+    assert_eq!(IncrementalTable::<FillMCTS>::new(1000).capacity(), 1024);
+}
+
+#[test]
+fn exact_sizes_must_be_powers_of_two() {
+    assert_eq!(
+        ApproxTable::<FillMCTS>::with_exact_size(512)
+            .unwrap()
+            .capacity(),
+        512
+    );
+    for size in [0, 3, 1000] {
+        let error = ApproxTable::<FillMCTS>::with_exact_size(size)
+            .err()
+            .unwrap();
+        assert_eq!(error, TableSizeError(size));
+        assert_eq!(
+            error.to_string(),
+            format!("transposition table size {} is not a power of 2", size)
+        );
+    }
+}
+
+#[test]
+fn rounded_tables_work() {
+    for size in [0, 1, 1000] {
+        let mut mcts = MCTSManager::new(
+            FillGame::new(6, 3),
+            FillMCTS,
+            FillEvaluator,
+            UCTPolicy::new(5.0),
+            ApproxTable::new(size),
+        );
+        mcts.playout_n(1000);
+        mcts.tree().validate().unwrap();
+        let states = mcts.principal_variation_states(3);
+        assert_eq!(states.last().unwrap().score(), 3 + 4 + 5);
+    }
+}
@@ -0,0 +1,98 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+type Spec = TwoPlayerZeroSumSpec<TicTacToe>;
+
+// X has 2 and 8, and wins at 5 unless O, to move, takes it.
+fn hanging() -> TicTacToe {
+    TicTacToe {
+        cells: [0, 0, 1, 0, 0, 0, 0, 2, 1],
+    }
+}
+
+fn searched(state: TicTacToe, playouts: usize) -> MCTSManager<Spec> {
+    let mut mcts = Spec::manager(state);
+    mcts.seed(Some(2));
+    mcts.playout_n(playouts);
+    mcts
+}
+
+#[test]
+fn rejects_a_loss_in_one() {
+    let mcts = searched(hanging(), 200);
+    // The unchecked search blunders.
+    assert_ne!(mcts.best_move(), Some(5));
+    let report = mcts.best_move_checked(&TacticalCheck::new().max_rejections(5));
+    assert_eq!(report.chosen, Some(5));
+    assert!(!report.all_refuted);
+    assert!(!report.rejected.is_empty());
+    assert_eq!(report.rejected[0].0, mcts.best_move().unwrap());
+    for (_, refutation) in &report.rejected {
+        assert_eq!(refutation, &Refutation::Loss(5));
+    }
+}
+
+#[test]
+fn keeps_a_sound_move() {
+    let mcts = searched(hanging(), 5000);
+    assert_eq!(mcts.best_move(), Some(5));
+    let report = mcts.best_move_checked(&TacticalCheck::new());
+    assert_eq!(report.chosen, Some(5));
+    assert!(report.rejected.is_empty());
+}
+
+#[test]
+fn gives_up_after_max_rejections() {
+    // X threatens 1, 6 and 8; O can't stop them all.
+    let state = TicTacToe {
+        cells: [1, 0, 1, 2, 1, 2, 0, 0, 0],
+    };
+    let mcts = searched(state, 500);
+    let report = mcts.best_move_checked(&TacticalCheck::new().max_rejections(2));
+    assert!(report.all_refuted);
+    assert_eq!(report.rejected.len(), 2);
+    assert_eq!(report.chosen, mcts.best_move());
+    let report = mcts.best_move_checked(&TacticalCheck::new().max_rejections(10));
+    assert!(report.all_refuted);
+    assert_eq!(report.rejected.len(), 4);
+    let report = mcts.best_move_checked(&TacticalCheck::new().max_rejections(0));
+    assert!(!report.all_refuted);
+    assert_eq!(report.chosen, mcts.best_move());
+}
+
+#[test]
+fn rejects_replies_the_tree_rates_highly() {
+    let mcts = searched(hanging(), 5000);
+    assert_eq!(mcts.best_move(), Some(5));
+    // Every visited reply is bad enough, so the best rated one refutes 5.
+    let check = TacticalCheck::new()
+        .max_rejections(1)
+        .reject_replies_above(-1.0, 1);
+    let report = mcts.best_move_checked(&check);
+    assert!(report.all_refuted);
+    let (mov, refutation) = &report.rejected[0];
+    assert_eq!(*mov, 5);
+    let child = mcts.principal_variation_info(1)[0].child().unwrap();
+    let best_reply = child
+        .moves()
+        .filter(|x| x.visits() > 0)
+        .max_by(|a, b| a.avg_reward().total_cmp(&b.avg_reward()))
+        .unwrap();
+    assert_eq!(
+        refutation,
+        &Refutation::Mean(*best_reply.get_move(), best_reply.avg_reward())
+    );
+    assert_eq!(refutation.reply(), best_reply.get_move());
+}
+
+#[test]
+fn no_move_before_a_search() {
+    let mcts = Spec::manager(hanging());
+    let report = mcts.best_move_checked(&TacticalCheck::new());
+    assert_eq!(report.chosen, None);
+    assert!(report.rejected.is_empty());
+}
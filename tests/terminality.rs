@@ -0,0 +1,168 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+
+// Pick two numbers from 0 to 3. The game forgets the moves after a 1, and claims to
+// be over after a 2 while still offering moves.
+#[derive(Clone, Debug, Default)]
+struct Broken(Vec<u8>);
+
+impl GameState for Broken {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        if self.0.len() == 2 || self.0 == [1] {
+            vec![]
+        } else {
+            vec![0, 1, 2, 3]
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0.push(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+    fn is_terminal(&self) -> bool {
+        self.0.len() == 2 || self.0 == [2]
+    }
+}
+
+struct MyEvaluator;
+
+impl Evaluator<MyMCTS> for MyEvaluator {
+    type StateEvaluation = ();
+
+    fn evaluate_new_state(
+        &self,
+        _: &Broken,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<MyMCTS>>,
+    ) -> (Vec<()>, ()) {
+        (vec![(); moves.len()], ())
+    }
+    fn interpret_evaluation_for_player(&self, _: &(), _: &()) -> f64 {
+        0.0
+    }
+    fn evaluate_existing_state(&self, _: &Broken, _: &(), _: SearchHandle<MyMCTS>) {}
+}
+
+/// With `panic`, states with no moves that aren't terminal panic instead of being
+/// treated as terminal.
+#[derive(Default)]
+struct MyMCTS {
+    reports: Mutex<Vec<String>>,
+    panic: bool,
+}
+
+impl MCTS for MyMCTS {
+    type State = Broken;
+    type Eval = MyEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn on_terminality_mismatch(&self, state: &Broken, path: &[u8], mismatch: TerminalityMismatch) {
+        self.reports
+            .lock()
+            .unwrap()
+            .push(mismatch.describe(state, path));
+    }
+    fn no_moves_behaviour(&self, state: &Broken) -> NoMovesBehaviour<Self> {
+        if self.panic {
+            NoMovesBehaviour::panic_with_state(state)
+        } else {
+            NoMovesBehaviour::Terminal
+        }
+    }
+}
+
+fn manager(spec: MyMCTS) -> MCTSManager<MyMCTS> {
+    MCTSManager::new(
+        Broken::default(),
+        spec,
+        MyEvaluator,
+        UCTPolicy::new(1.0),
+        (),
+    )
+}
+
+fn search() -> (MCTSManager<MyMCTS>, SearchSummary) {
+    let mut mcts = manager(MyMCTS::default());
+    let summary = mcts.playout_n(200);
+    mcts.tree().validate().unwrap();
+    (mcts, summary)
+}
+
+#[test]
+fn reports_moves_without_terminality_before_panicking() {
+    let mut mcts = manager(MyMCTS {
+        panic: true,
+        ..Default::default()
+    });
+    let result = panic::catch_unwind(AssertUnwindSafe(|| mcts.playout_n(200)));
+    assert!(result.is_err());
+    let reports = mcts.tree().spec().reports.lock().unwrap();
+    assert!(reports.contains(
+        &"available_moves() is empty but is_terminal() is false after moves [1] for state Broken([1])"
+            .to_string()
+    ));
+}
+
+#[test]
+fn reports_terminality_with_moves_in_debug() {
+    let (mcts, _) = search();
+    let reports = mcts.tree().spec().reports.lock().unwrap();
+    let report = "is_terminal() is true but available_moves() isn't empty after moves [2] for state Broken([2])"
+        .to_string();
+    assert_eq!(reports.contains(&report), cfg!(debug_assertions));
+}
+
+#[test]
+fn settled_states_are_not_mismatches() {
+    // `Broken([1])` has no moves and isn't terminal, but the spec treats it as
+    // terminal.
+    let (mcts, _) = search();
+    let reports = mcts.tree().spec().reports.lock().unwrap();
+    assert!(!reports.iter().any(|x| x.contains("Broken([1])")));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn counted_in_the_summary() {
+    let (mcts, summary) = search();
+    let expected = 1;
+    assert_eq!(summary.terminality_mismatches, expected);
+    assert_eq!(mcts.tree().terminality_mismatches(), expected as usize);
+    assert_eq!(
+        mcts.tree().spec().reports.lock().unwrap().len(),
+        expected as usize
+    );
+    assert!(summary
+        .to_string()
+        .contains(&format!("{} states where is_terminal()", expected)));
+}
+
+#[test]
+fn consistent_games_have_none() {
+    let mut mcts = MCTSManager::new(
+        Broken(vec![3]),
+        MyMCTS::default(),
+        MyEvaluator,
+        UCTPolicy::new(1.0),
+        (),
+    );
+    let summary = mcts.playout_n(100);
+    assert_eq!(summary.terminality_mismatches, 0);
+    assert!(mcts.tree().spec().reports.lock().unwrap().is_empty());
+}
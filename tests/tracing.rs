@@ -0,0 +1,83 @@
+#![cfg(feature = "tracing")]
+
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// The names of the events seen, with the `playouts` field of progress events, and
+// the number of `playout` spans.
+static EVENTS: Mutex<Vec<(&'static str, Option<u64>)>> = Mutex::new(Vec::new());
+static SPANS: AtomicU64 = AtomicU64::new(0);
+
+struct Capture;
+
+struct Playouts(Option<u64>);
+
+impl Visit for Playouts {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "playouts" {
+            self.0 = Some(value);
+        }
+    }
+    fn record_debug(&mut self, _: &Field, _: &dyn fmt::Debug) {}
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "mcts"
+    }
+    fn new_span(&self, attributes: &Attributes) -> Id {
+        assert_eq!(attributes.metadata().name(), "playout");
+        Id::from_u64(SPANS.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+    fn record(&self, _: &Id, _: &Record) {}
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+    fn event(&self, event: &Event) {
+        let mut playouts = Playouts(None);
+        event.record(&mut playouts);
+        let name = event.metadata().name();
+        EVENTS.lock().unwrap().push((name, playouts.0));
+    }
+    fn enter(&self, _: &Id) {}
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn search_reports_to_tracing() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(6, 3),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    tracing::subscriber::with_default(Capture, || {
+        mcts.playout_n(2048);
+    });
+    // Outside `with_default` nothing is reported.
+    mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+
+    assert_eq!(SPANS.load(Ordering::Relaxed), 2048);
+    let events = EVENTS.lock().unwrap();
+    let count = |name| events.iter().filter(|x| x.0 == name).count();
+    assert_eq!(count("expansion"), mcts.tree().num_nodes() - 1);
+    assert!(count("transposition_hit") > 0);
+    let progress: Vec<_> = events
+        .iter()
+        .filter(|x| x.0 == "search.progress")
+        .map(|x| x.1)
+        .collect();
+    assert_eq!(progress, [Some(1024), Some(2048)]);
+}
@@ -0,0 +1,94 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::*;
+
+fn searched_tree() -> SearchTree<FillMCTS> {
+    let tree = fill_tree(5, 3);
+    let mut tld = fill_thread_data(7);
+    for _ in 0..200 {
+        tree.playout(&mut tld);
+    }
+    tree.validate().unwrap();
+    tree
+}
+
+/// Every (path, visits) pair down to `depth`, in node order.
+fn walk(
+    node: NodeHandle<FillMCTS>,
+    path: &mut Vec<usize>,
+    depth: usize,
+    out: &mut Vec<(Vec<usize>, u64)>,
+) {
+    if depth == 0 {
+        return;
+    }
+    for (mov, stats, child) in node.children() {
+        path.push(*mov);
+        out.push((path.clone(), stats.visits));
+        if let Some(child) = child {
+            walk(child, path, depth - 1, out);
+        }
+        path.pop();
+    }
+}
+
+#[test]
+fn root_sums_its_children() {
+    let tree = searched_tree();
+    let root = tree.root_node();
+    assert_eq!(root.visits(), 200);
+    let stats: Vec<NodeStats> = root.children().map(|x| x.1).collect();
+    assert_eq!(stats.len(), 5);
+    let sum: f64 = stats.iter().map(|x| x.sum_rewards).sum();
+    assert!((root.mean_value() - sum / 200.0).abs() < 1e-9);
+    for (stats, mov) in stats.iter().zip(root.moves()) {
+        assert_eq!(*stats, mov.stats());
+        assert_eq!(stats.mean(), mov.avg_reward());
+    }
+}
+
+#[test]
+fn descend_to_depth_three() {
+    let tree = searched_tree();
+    let mut node = tree.root_node();
+    let mut path = Vec::new();
+    for _ in 0..3 {
+        let (mov, stats, child) = node.children().max_by_key(|x| x.1.visits).unwrap();
+        assert!(stats.visits > 0);
+        let next = node.descend(mov).unwrap();
+        assert_eq!(Some(next.into_raw()), child.map(|x| x.into_raw()));
+        path.push(*mov);
+        node = next;
+    }
+    // Three cells filled is the end of the game.
+    assert_eq!(node.children().count(), 0);
+    assert_eq!(node.visits(), 0);
+    assert!(node.mean_value().is_nan());
+    path.sort();
+    path.dedup();
+    assert_eq!(path.len(), 3);
+}
+
+#[test]
+fn descend_misses() {
+    let tree = searched_tree();
+    let root = tree.root_node();
+    assert!(root.descend(&5).is_none());
+    let fresh = fill_tree(5, 3);
+    assert!(fresh.root_node().descend(&0).is_none());
+    assert_eq!(fresh.root_node().visits(), 0);
+}
+
+#[test]
+fn seeded_walks_match() {
+    let mut a = Vec::new();
+    let mut b = Vec::new();
+    walk(searched_tree().root_node(), &mut Vec::new(), 3, &mut a);
+    walk(searched_tree().root_node(), &mut Vec::new(), 3, &mut b);
+    assert_eq!(a, b);
+    assert!(a.iter().any(|x| x.0.len() == 3));
+    assert!(a.iter().all(|x| x.0.len() <= 3));
+}
@@ -0,0 +1,50 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+type NimMCTS = TwoPlayerZeroSumSpec<Nim>;
+
+#[test]
+fn finds_the_winning_move() {
+    for (stones, best) in [(4, 1), (5, 2), (7, 1), (8, 2)] {
+        let mut mcts = NimMCTS::manager(Nim::new(stones));
+        mcts.playout_n(5000);
+        mcts.tree().validate().unwrap();
+        assert_eq!(mcts.best_move(), Some(best), "{} stones", stones);
+    }
+}
+
+#[test]
+fn the_winner_scores_one() {
+    let mut mcts = NimMCTS::manager(Nim::new(1));
+    mcts.playout_n(10);
+    mcts.tree().validate().unwrap();
+    let only = mcts.tree().root_node().moves().next().unwrap();
+    assert_eq!(only.avg_reward(), 1.0);
+}
+
+#[test]
+fn draws_are_worth_nothing() {
+    let eval = WinDrawLossEvaluator;
+    let interpret = |winner: Option<u8>, player: u8| {
+        <WinDrawLossEvaluator as Evaluator<NimMCTS>>::interpret_evaluation_for_player(
+            &eval, &winner, &player,
+        )
+    };
+    assert_eq!(interpret(None, 0), 0.0);
+    assert_eq!(interpret(Some(0), 0), 1.0);
+    assert_eq!(interpret(Some(0), 1), -1.0);
+}
+
+#[test]
+fn rollouts_reach_the_end() {
+    let mut rng = PolicyRng::new(3);
+    for _ in 0..20 {
+        let winner = WinDrawLossEvaluator::rollout(&Nim::new(10), &mut rng);
+        assert!(winner == Some(0) || winner == Some(1));
+    }
+}
@@ -0,0 +1,97 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::transposition_table::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+#[test]
+fn searched_trees_are_valid() {
+    // Filling the same cells in different orders reaches the same node through the
+    // transposition table.
+    let tree = fill_tree(6, 3);
+    let mut tld = fill_thread_data(0);
+    for _ in 0..500 {
+        tree.playout(&mut tld);
+    }
+    assert_eq!(tree.validate(), Ok(()));
+
+    let mut mcts = MCTSManager::new(
+        FillGame::new(8, 4),
+        FillMCTS,
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        ApproxTable::new(1024),
+    );
+    mcts.playout_n_parallel(2000, 4);
+    assert_eq!(mcts.tree().validate(), Ok(()));
+}
+
+#[test]
+fn empty_tree_is_valid() {
+    assert_eq!(fill_tree(4, 2).validate(), Ok(()));
+}
+
+/// Every reward is finite, but filling cell 2 is worth so much that two of them
+/// add up to infinity.
+struct HugeEvaluator;
+
+impl Evaluator<HugeMCTS> for HugeEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        _: Option<SearchHandle<HugeMCTS>>,
+    ) -> (Vec<()>, f64) {
+        let value = if state.cells[2] { f64::MAX } else { 0.0 };
+        (vec![(); moves.len()], value)
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &FillGame, evaln: &f64, _: SearchHandle<HugeMCTS>) -> f64 {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct HugeMCTS;
+
+impl MCTS for HugeMCTS {
+    type State = FillGame;
+    type Eval = HugeEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+}
+
+#[test]
+fn reports_infinite_rewards_with_their_path() {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(3, 1),
+        HugeMCTS,
+        HugeEvaluator,
+        UCTPolicy::new(1.0),
+        (),
+    );
+    mcts.playout_n(20);
+    let violations = mcts.tree().validate().unwrap_err();
+    // Cell 2's node, and the root's move to it.
+    assert_eq!(violations.len(), 2, "{:?}", violations);
+    for violation in &violations {
+        assert_eq!(
+            violation.violation,
+            Violation::NonFiniteReward {
+                sum_rewards: f64::INFINITY
+            }
+        );
+    }
+    assert_eq!(violations[0].path, Vec::<usize>::new());
+    assert_eq!(violations[1].path, vec![2]);
+    assert_eq!(violations[1].to_string(), "at [2]: sum of rewards is inf");
+}
@@ -0,0 +1,194 @@
+extern crate mcts;
+
+use mcts::tree_policy::*;
+use mcts::*;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Pick a digit, scoring a ninth of it. The priors favour 0, then 9, so a small
+/// search prefers 0 until it has seen enough of 9.
+#[derive(Clone, Default)]
+struct Digit(Option<u8>);
+
+impl GameState for Digit {
+    type Move = u8;
+    type Player = ();
+    type MoveList = Vec<u8>;
+
+    fn current_player(&self) -> &() {
+        &()
+    }
+    fn available_moves(&self) -> Vec<u8> {
+        match self.0 {
+            Some(_) => vec![],
+            None => (0..10).collect(),
+        }
+    }
+    fn make_move(&mut self, mov: &u8) -> Result<(), ()> {
+        self.0 = Some(*mov);
+        Ok(())
+    }
+    fn get_winner(&self) -> Option<()> {
+        None
+    }
+}
+
+/// With `panic` set, playouts reaching a node that already exists panic.
+#[derive(Default)]
+struct DigitEvaluator {
+    panic: AtomicBool,
+}
+
+impl Evaluator<DigitMCTS> for DigitEvaluator {
+    type StateEvaluation = f64;
+
+    fn evaluate_new_state(
+        &self,
+        state: &Digit,
+        moves: &Vec<u8>,
+        _: Option<SearchHandle<DigitMCTS>>,
+    ) -> (Vec<f64>, f64) {
+        let priors = moves
+            .iter()
+            .map(|&x| match x {
+                0 => 0.9,
+                9 => 0.09,
+                _ => 0.00125,
+            })
+            .collect();
+        (priors, state.0.map_or(0.0, |x| x as f64 / 9.0))
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &f64, _: &()) -> f64 {
+        *evaln
+    }
+    fn evaluate_existing_state(&self, _: &Digit, evaln: &f64, _: SearchHandle<DigitMCTS>) -> f64 {
+        assert!(!self.panic.load(Ordering::Relaxed), "evaluator failed");
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct DigitMCTS;
+
+impl MCTS for DigitMCTS {
+    type State = Digit;
+    type Eval = DigitEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = AlphaGoPolicy;
+    type TranspositionTable = ();
+}
+
+fn manager() -> MCTSManager<DigitMCTS> {
+    let mut mcts = MCTSManager::new(
+        Digit::default(),
+        DigitMCTS,
+        DigitEvaluator::default(),
+        AlphaGoPolicy::new(1.0),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts
+}
+
+fn searched(playouts: usize) -> MCTSManager<DigitMCTS> {
+    let mut mcts = manager();
+    mcts.playout_n(playouts);
+    mcts
+}
+
+fn root_visits(mcts: &MCTSManager<DigitMCTS>) -> Vec<u64> {
+    mcts.tree()
+        .root_node()
+        .moves()
+        .map(|x| x.visits())
+        .collect()
+}
+
+#[test]
+fn verification_playouts_only_reach_the_candidates() {
+    let mut mcts = searched(10);
+    let before = root_visits(&mcts);
+    let report = mcts.verify_best_move(300);
+    mcts.tree().validate().unwrap();
+    assert_eq!(report.playouts, 300);
+    let moves: Vec<u8> = report.candidates.iter().map(|x| x.mov).collect();
+    assert_eq!(moves, vec![0, 9]);
+    let gained: u64 = report
+        .candidates
+        .iter()
+        .map(|x| x.verification_visits())
+        .sum();
+    assert_eq!(gained, 300);
+    let after = root_visits(&mcts);
+    for digit in 1..9 {
+        assert_eq!(before[digit], after[digit]);
+    }
+    for candidate in &report.candidates {
+        assert_eq!(candidate.visits_before, before[candidate.mov as usize]);
+        assert_eq!(candidate.visits_after, after[candidate.mov as usize]);
+    }
+    let shares = |f: fn(&VerifiedMove<u8>) -> f64| report.candidates.iter().map(f).sum::<f64>();
+    assert!((shares(|x| x.share_before) - 1.0).abs() < 1e-9);
+    assert!((shares(|x| x.share_after) - 1.0).abs() < 1e-9);
+    // The other moves can be selected again afterwards.
+    assert!(mcts.tree().root_node().moves().all(|x| !x.is_excluded()));
+    mcts.min_root_visits(1).playout_n(20);
+    assert!(root_visits(&mcts).iter().all(|&x| x > 0));
+}
+
+#[test]
+fn flags_a_flipped_ranking() {
+    let mut mcts = searched(10);
+    assert_eq!(mcts.best_move(), Some(0));
+    let report = mcts.verify_best_move(500);
+    assert_eq!(report.best_before, Some(0));
+    assert_eq!(report.best_after, Some(9));
+    assert!(report.changed);
+    assert!(report.candidates[1].share_after > report.candidates[1].share_before);
+}
+
+#[test]
+fn confirms_a_settled_choice() {
+    let mut mcts = searched(3000);
+    assert_eq!(mcts.best_move(), Some(9));
+    let report = mcts.verify_best_move(200);
+    assert_eq!(report.best_before, Some(9));
+    assert_eq!(report.best_after, Some(9));
+    assert!(!report.changed);
+    assert_eq!(report.candidates[0].mov, 9);
+}
+
+#[test]
+fn searches_a_single_candidate() {
+    let mut mcts = searched(10);
+    let root = mcts.tree().root_node();
+    for i in 1..10 {
+        root.exclude_child(i);
+    }
+    let report = mcts.verify_best_move(100);
+    mcts.tree().validate().unwrap();
+    assert_eq!(report.playouts, 100);
+    assert_eq!(report.candidates.len(), 1);
+    assert_eq!(report.candidates[0].verification_visits(), 100);
+}
+
+#[test]
+fn leaves_the_search_report_alone() {
+    let mut mcts = manager();
+    mcts.tree().set_node_limit(3);
+    mcts.playout_n(20);
+    assert!(mcts.last_search_degraded());
+    mcts.tree().set_node_limit(usize::MAX);
+    mcts.verify_best_move(100);
+    assert!(mcts.last_search_degraded());
+}
+
+#[test]
+fn includes_the_moves_again_after_a_panic() {
+    let mut mcts = searched(10);
+    mcts.evaluator().panic.store(true, Ordering::Relaxed);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| mcts.verify_best_move(100)));
+    assert!(result.is_err());
+    assert!(mcts.tree().root_node().moves().all(|x| !x.is_excluded()));
+}
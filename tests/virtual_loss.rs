@@ -0,0 +1,233 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+
+// FillGame without a transposition table, so every node has a single parent.
+#[derive(Default)]
+struct VirtualVisitsMCTS(Option<VirtualLossMode>);
+
+impl MCTS for VirtualVisitsMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = UCTPolicy<()>;
+    type TranspositionTable = ();
+
+    fn virtual_loss_mode(&self) -> VirtualLossMode {
+        self.0.unwrap_or(VirtualLossMode::Reward(0.0))
+    }
+}
+
+fn search(mode: Option<VirtualLossMode>, threads: usize) -> MCTSManager<VirtualVisitsMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(7, 3),
+        VirtualVisitsMCTS(mode),
+        FillEvaluator,
+        UCTPolicy::new(5.0),
+        (),
+    );
+    mcts.playout_n_parallel(4000, threads);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+// Every visit to a move either stopped at its child or went on to one of the
+// child's moves, and every reward is a real score.
+fn check_counts(node: NodeHandle<VirtualVisitsMCTS>, state: &FillGame) {
+    for mov in node.moves() {
+        let mut state = state.clone();
+        state.make_move(mov.get_move()).unwrap();
+        if mov.visits() == 0 {
+            assert_eq!(mov.sum_rewards(), 0.0);
+            continue;
+        }
+        let child = mov.child().unwrap();
+        let (below, below_rewards) = child
+            .moves()
+            .fold((0, 0.0), |(v, r), x| (v + x.visits(), r + x.sum_rewards()));
+        let stopped = if state.filled() == state.target {
+            mov.visits()
+        } else {
+            1
+        };
+        assert_eq!(mov.visits(), stopped + below);
+        let expected = stopped as f64 * state.score() as f64 + below_rewards;
+        assert_eq!(mov.sum_rewards(), expected);
+        check_counts(child, &state);
+    }
+}
+
+#[test]
+fn reward_only_virtual_loss() {
+    let mcts = search(Some(VirtualLossMode::Reward(1.0)), 4);
+    check_counts(mcts.tree().root_node(), mcts.tree().root_state());
+}
+
+#[test]
+fn virtual_visits_are_removed() {
+    for threads in [1, 4] {
+        let mcts = search(Some(VirtualLossMode::VisitsAndReward(3, 1.0)), threads);
+        let total: u64 = mcts.tree().root_node().moves().map(|x| x.visits()).sum();
+        assert_eq!(total, 4000);
+        check_counts(mcts.tree().root_node(), mcts.tree().root_state());
+    }
+}
+
+#[test]
+fn virtual_visits_keep_the_search_working() {
+    let mcts = search(Some(VirtualLossMode::VisitsAndReward(3, 1.0)), 4);
+    let mut pv = mcts.principal_variation(3);
+    pv.sort();
+    assert_eq!(pv, vec![4, 5, 6]);
+}
+
+// A UCT policy that brings its own virtual loss, overriding the spec's huge one.
+struct LossyPolicy(Option<VirtualLossMode>);
+
+impl TreePolicy<PolicyLossMCTS> for LossyPolicy {
+    type MoveEvaluation = ();
+    type ThreadLocalData = PolicyRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<PolicyLossMCTS>,
+    ) -> &'a MoveInfo<PolicyLossMCTS>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<PolicyLossMCTS>> + Clone,
+    {
+        let parent_visits = moves.clone().map(|x| x.visits()).sum::<u64>() as f64;
+        handle
+            .thread_data()
+            .policy_data
+            .select_by_key(moves, |mov| {
+                let explore = (parent_visits.ln() / mov.visits() as f64).sqrt();
+                mov.mean_reward_or(f64::INFINITY) + 5.0 * explore
+            })
+            .unwrap()
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<PolicyRng> {
+        Some(PolicyRng::new(seed))
+    }
+
+    fn virtual_loss(&self) -> Option<VirtualLossMode> {
+        self.0
+    }
+}
+
+// Records the statistics of the moves of each new node's parent that have a
+// negative sum of rewards. Scores aren't negative, so only a playout in progress
+// can make them so.
+struct ObservingEvaluator;
+
+impl Evaluator<PolicyLossMCTS> for ObservingEvaluator {
+    type StateEvaluation = usize;
+
+    fn evaluate_new_state(
+        &self,
+        state: &FillGame,
+        moves: &Vec<usize>,
+        handle: Option<SearchHandle<PolicyLossMCTS>>,
+    ) -> (Vec<()>, usize) {
+        if let Some(handle) = handle {
+            let mut in_flight = handle.mcts().in_flight.lock().unwrap();
+            for mov in handle.node().moves().filter(|x| x.sum_rewards() < 0.0) {
+                in_flight.push((mov.visits(), mov.sum_rewards()));
+            }
+        }
+        (vec![(); moves.len()], state.score())
+    }
+    fn interpret_evaluation_for_player(&self, evaln: &usize, _: &()) -> f64 {
+        *evaln as f64
+    }
+    fn evaluate_existing_state(
+        &self,
+        _: &FillGame,
+        evaln: &usize,
+        _: SearchHandle<PolicyLossMCTS>,
+    ) -> usize {
+        *evaln
+    }
+}
+
+#[derive(Default)]
+struct PolicyLossMCTS {
+    in_flight: std::sync::Mutex<Vec<(u64, f64)>>,
+}
+
+impl MCTS for PolicyLossMCTS {
+    type State = FillGame;
+    type Eval = ObservingEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = LossyPolicy;
+    type TranspositionTable = ();
+
+    fn virtual_loss_mode(&self) -> VirtualLossMode {
+        VirtualLossMode::VisitsAndReward(100, 500.0)
+    }
+}
+
+fn search_with_policy_loss(loss: VirtualLossMode) -> MCTSManager<PolicyLossMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(7, 3),
+        PolicyLossMCTS::default(),
+        ObservingEvaluator,
+        LossyPolicy(Some(loss)),
+        (),
+    );
+    mcts.seed(Some(0));
+    mcts.playout_n(2000);
+    mcts.tree().validate().unwrap();
+    mcts
+}
+
+#[test]
+fn policy_virtual_loss_overrides_the_spec() {
+    for (loss, visits, reward) in [
+        (VirtualLossMode::Reward(2.0), 0, 2.0),
+        (VirtualLossMode::VisitsAndReward(3, 2.0), 3, 2.0),
+    ] {
+        let mcts = search_with_policy_loss(loss);
+        assert_eq!(mcts.tree().virtual_loss_mode(), loss);
+        // A move's first playout, in progress, shows the policy's loss.
+        let in_flight = mcts.spec().in_flight.lock().unwrap();
+        assert!(in_flight.contains(&(1 + visits, -reward)), "{:?}", loss);
+        assert!(in_flight.iter().all(|x| x.1 >= -reward), "{:?}", loss);
+    }
+}
+
+#[test]
+fn policy_virtual_loss_is_unwound() {
+    for loss in [
+        VirtualLossMode::Reward(2.0),
+        VirtualLossMode::VisitsAndReward(3, 2.0),
+    ] {
+        let mcts = search_with_policy_loss(loss);
+        let root = mcts.tree().root_node();
+        assert_eq!(root.moves().map(|x| x.visits()).sum::<u64>(), 2000);
+        assert!(root.moves().all(|x| x.sum_rewards() >= 0.0));
+        assert!(root.moves().all(|x| x.avg_reward() <= 15.0));
+    }
+}
+
+#[test]
+fn spec_virtual_loss_without_a_policy_override() {
+    let mcts = MCTSManager::new(
+        FillGame::new(7, 3),
+        PolicyLossMCTS::default(),
+        ObservingEvaluator,
+        LossyPolicy(None),
+        (),
+    );
+    assert_eq!(
+        mcts.tree().virtual_loss_mode(),
+        VirtualLossMode::VisitsAndReward(100, 500.0)
+    );
+}
@@ -0,0 +1,164 @@
+extern crate mcts;
+
+mod common;
+
+use common::*;
+use mcts::tree_policy::*;
+use mcts::*;
+use std::process::Command;
+
+fn choices(seed: u64, keys: &[f64]) -> Vec<usize> {
+    let mut rng = WeightedRng::new(seed);
+    (0..50)
+        .map(|_| rng.select_by_key(0..keys.len(), |&i| keys[i]).unwrap())
+        .collect()
+}
+
+#[test]
+fn infinite_keys_are_chosen_uniformly() {
+    let keys = [1.0, f64::INFINITY, 2.0, f64::INFINITY];
+    let mut rng = WeightedRng::new(1);
+    let chosen = (0..200)
+        .map(|_| rng.select_by_key(0..4, |&i| keys[i]).unwrap())
+        .collect::<Vec<_>>();
+    assert!(chosen.iter().all(|&i| i == 1 || i == 3));
+    assert!(chosen.contains(&1) && chosen.contains(&3));
+    assert_eq!(rng.fallbacks(), 200);
+    assert_eq!(rng.take_fallbacks(), 200);
+    assert_eq!(rng.take_fallbacks(), 0);
+    assert_eq!(rng.fallbacks(), 200);
+}
+
+#[test]
+fn nan_and_negative_infinity_count_as_lowest() {
+    for lowest in [f64::NAN, f64::NEG_INFINITY] {
+        let keys = [lowest, 1.0, 5.0];
+        let chosen = choices(2, &keys);
+        assert!(!chosen.contains(&0));
+        // The other keys are shifted to infinity, so they're equally likely.
+        assert!(chosen.contains(&1) && chosen.contains(&2));
+    }
+    let mut rng = WeightedRng::new(3);
+    assert!(rng.select_by_key(0..3, |_| f64::NAN).is_some());
+    assert_eq!(rng.fallbacks(), 1);
+}
+
+#[test]
+fn fallbacks_are_reproducible() {
+    let keys = [f64::NAN, f64::INFINITY, 3.0, f64::INFINITY, f64::INFINITY];
+    assert_eq!(choices(7, &keys), choices(7, &keys));
+    assert_ne!(choices(7, &keys), choices(8, &keys));
+}
+
+#[test]
+fn huge_weights_are_not_a_fallback() {
+    let keys = [f64::MAX, f64::MAX, 0.0];
+    let mut rng = WeightedRng::new(1);
+    let chosen = (0..100)
+        .map(|_| rng.select_by_key(0..3, |&i| keys[i]).unwrap())
+        .collect::<Vec<_>>();
+    assert!(!chosen.contains(&2));
+    assert!(chosen.contains(&0) && chosen.contains(&1));
+    assert_eq!(rng.fallbacks(), 0);
+}
+
+// Unvisited moves are worth infinitely much, so choosing among them falls back.
+struct OptimisticWeightedPolicy;
+
+impl<Spec: MCTS<TreePolicy = Self>> TreePolicy<Spec> for OptimisticWeightedPolicy {
+    type MoveEvaluation = ();
+    type ThreadLocalData = WeightedRng;
+
+    fn choose_child<'a, MoveIter>(
+        &self,
+        moves: MoveIter,
+        mut handle: SearchHandle<Spec>,
+    ) -> &'a MoveInfo<Spec>
+    where
+        MoveIter: Iterator<Item = &'a MoveInfo<Spec>> + Clone,
+    {
+        handle
+            .thread_data()
+            .policy_data
+            .select_by_key(moves, |mov| mov.mean_reward_or(f64::INFINITY))
+            .unwrap()
+    }
+
+    fn score_child(&self, child: &MoveInfo<Spec>, _parent_visits: u64) -> f64 {
+        child.mean_reward_or(f64::INFINITY)
+    }
+
+    fn seeded_thread_data(&self, seed: u64) -> Option<WeightedRng> {
+        Some(WeightedRng::new(seed))
+    }
+}
+
+#[derive(Default)]
+struct WeightedMCTS;
+
+impl MCTS for WeightedMCTS {
+    type State = FillGame;
+    type Eval = FillEvaluator;
+    type NodeData = ();
+    type ExtraThreadData = ();
+    type TreePolicy = OptimisticWeightedPolicy;
+    type TranspositionTable = ();
+}
+
+fn manager() -> MCTSManager<WeightedMCTS> {
+    let mut mcts = MCTSManager::new(
+        FillGame::new(5, 2),
+        WeightedMCTS,
+        FillEvaluator,
+        OptimisticWeightedPolicy,
+        (),
+    );
+    mcts.seed(Some(1));
+    mcts
+}
+
+#[test]
+fn summary_counts_fallbacks() {
+    let mut mcts = manager();
+    let first = mcts.playout_n(100);
+    mcts.tree().validate().unwrap();
+    assert!(first.selection_fallbacks > 0);
+    assert!(first
+        .to_string()
+        .contains("random choices fell back to uniform"));
+    // Once every node is expanded and visited, the weights are all finite.
+    let second = mcts.playout_n(100);
+    assert_eq!(second.selection_fallbacks, 0);
+    assert_eq!(
+        mcts.tree().selection_fallbacks() as u64,
+        first.selection_fallbacks
+    );
+    assert_eq!(mcts.best_move(), manager_best_move());
+}
+
+fn manager_best_move() -> Option<usize> {
+    let mut mcts = manager();
+    mcts.playout_n(200);
+    mcts.best_move()
+}
+
+#[test]
+fn fallbacks_print_nothing() {
+    if std::env::var_os("WEIGHTED_RNG_CHILD").is_some() {
+        println!("start");
+        let keys = [f64::NAN, f64::INFINITY, 1.0];
+        choices(1, &keys);
+        manager().playout_n(100);
+        println!("end");
+        return;
+    }
+    // Run this test again in a child process, with its output left uncaptured.
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "fallbacks_print_nothing", "--nocapture"])
+        .env("WEIGHTED_RNG_CHILD", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("start\nend\n"), "{}", stdout);
+}